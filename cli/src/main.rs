@@ -1,53 +1,121 @@
-use std::fs;
-use clap::{Parser, ArgAction};
+use std::{fs, path::Path};
+use clap::{Args, Parser, Subcommand, ArgAction};
 use rustclr::{
     RustClr,
     RuntimeVersion,
     error::ClrError, 
+    metadata::{Bundle, DependencyReport, Metadata, ResourceLocation},
 };
 
 /// The main command-line interface struct.
 #[derive(Parser)]
 #[clap(author="joaoviictorti", about="Host CLR and run .NET binaries using Rust", version="1.0")]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 pub struct Cli {
+    /// Inspects an assembly instead of running it.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// Path to the .NET assembly file to be executed.
     #[arg(short, long, required = true, help = "Path to the .NET assembly file")]
-    pub file: String,
+    pub file: Option<String>,
 
     /// Arguments for the .NET program (strings only).
     #[arg(short, long, action = ArgAction::Append, help = "String arguments for the .NET program")]
     pub inputs: Option<Vec<String>>,
 
-    /// Specify the .NET runtime version (e.g., "v2", "v3", "v4").
-    #[arg(short, long, default_value = "v4", help = "Specify .NET runtime version")]
-    pub runtime_version: String,
+    /// Specify the .NET runtime version (e.g., "v2", "4.0", "v4.0.30319", "auto").
+    #[arg(short, long, default_value = "v4", help = "Specify .NET runtime version (e.g. v2, 4.0, v4.0.30319, auto)")]
+    pub runtime_version: RuntimeVersion,
 
     /// Set a custom application domain name.
     #[arg(short = 'd', long, help = "Set custom application domain name")]
     pub domain: Option<String>,
+
+    /// Paths to assemblies the program depends on that are not in the GAC.
+    #[arg(short = 'D', long = "dependency", action = ArgAction::Append, help = "Path to a dependency assembly (repeatable)")]
+    pub dependencies: Vec<String>,
+
+    /// Serve the assemblies embedded as resources (e.g. by Costura) to the runtime.
+    #[arg(short = 'e', long, help = "Load the assemblies embedded as manifest resources as dependencies")]
+    pub embedded: bool,
+
+    /// Directory to write the manifest resources to, instead of running the assembly.
+    #[arg(long, value_name = "DIR", help = "Dump the manifest resources (decompressed) to a directory and exit")]
+    pub dump_resources: Option<String>,
+}
+
+/// Subcommands that work on the assembly without running it.
+#[derive(Subcommand)]
+pub enum Command {
+    /// Inspect the metadata of a .NET assembly without running it.
+    Inspect(InspectArgs),
+}
+
+/// Options of the `inspect` subcommand.
+#[derive(Args)]
+pub struct InspectArgs {
+    /// Path to the .NET assembly file to be inspected.
+    #[arg(short, long, required = true, help = "Path to the .NET assembly file")]
+    pub file: String,
+
+    /// Dump the `#Strings` and `#US` heaps.
+    #[arg(long, help = "Dump the #Strings and #US heaps, with the methods that load each literal")]
+    pub strings: bool,
+
+    /// Methods to disassemble, as `Namespace.Type::Method`.
+    #[arg(long, value_name = "TYPE::METHOD", action = ArgAction::Append, help = "Disassemble the IL of a method, e.g. Sample.Program::Main (repeatable)")]
+    pub il: Vec<String>,
 }
 
 fn main() -> Result<(), ClrError> {
     // Parse command-line arguments
     let cli = Cli::parse();
 
+    // Inspect the assembly instead of running it
+    if let Some(Command::Inspect(args)) = cli.command {
+        return inspect(&args);
+    }
+
     // Read the .NET assembly file
-    let data = fs::read(&cli.file)
+    let file = cli.file.ok_or(ClrError::ErrorClr("Missing assembly file"))?;
+    let data = fs::read(&file)
         .map_err(|_| ClrError::ErrorClr("Failed to read file"))?;
 
-    // Convert version string to RuntimeVersion enum
-    let runtime_version = match cli.runtime_version.as_str() {
-        "v2" => RuntimeVersion::V2,
-        "v3" => RuntimeVersion::V3,
-        "v4" => RuntimeVersion::V4,
-        _ => RuntimeVersion::UNKNOWN,
-    };
+    // Dump the manifest resources and stop there if requested
+    if let Some(directory) = cli.dump_resources {
+        return dump_resources(&data, Path::new(&directory));
+    }
+
+    // Read the dependency assemblies
+    let mut dependencies = cli.dependencies
+        .iter()
+        .map(|path| fs::read(path).map_err(|_| ClrError::ErrorClr("Failed to read dependency")))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Add the assemblies embedded in the program itself
+    if cli.embedded {
+        let embedded = Metadata::parse(&data)?.embedded_assemblies()?;
+        dependencies.extend(embedded.into_iter().map(|(_, assembly)| assembly));
+    }
+
+    // Report the references that are missing before running anything
+    let extra = dependencies.iter().map(Vec::as_slice).collect::<Vec<_>>();
+    match DependencyReport::build(&data, &extra) {
+        Ok(report) => print!("{report}"),
+        Err(err) => println!("Dependency report unavailable: {err}"),
+    }
 
     // Initialize and configure the RustClr instance
     let mut clr = RustClr::new(&data)?
-        .with_runtime_version(runtime_version)
+        .with_runtime_version(cli.runtime_version)
         .with_output_redirection(true);
 
+    // Serve the dependency assemblies from memory
+    for dependency in &dependencies {
+        clr = clr.with_dependency(dependency)?;
+    }
+
     // Set the custom application domain if provided
     if let Some(domain_name) = cli.domain {
         clr = clr.with_domain(&domain_name);
@@ -55,9 +123,7 @@ fn main() -> Result<(), ClrError> {
 
     // Set the string arguments for the .NET assembly if provided
     if let Some(inputs) = cli.inputs {
-        // Convert Vec<String> to Vec<&str>
-        let args = inputs.iter().map(|s| s.as_str()).collect::<Vec<&str>>();
-        clr = clr.with_args(args);
+        clr = clr.with_args(inputs);
     } else {
         clr = clr.with_args(vec![]);
     }
@@ -70,3 +136,123 @@ fn main() -> Result<(), ClrError> {
     
     Ok(())
 }
+
+/// Prints what the `inspect` options ask for, starting with the identity of the assembly.
+fn inspect(args: &InspectArgs) -> Result<(), ClrError> {
+    let data = fs::read(&args.file)
+        .map_err(|_| ClrError::ErrorClr("Failed to read file"))?;
+
+    // Single-file apphosts carry their assemblies in a bundle instead of metadata
+    if Bundle::is_bundle(&data) {
+        let bundle = Bundle::parse(&data)?;
+        println!("Single-file bundle {} (format {}.{})", bundle.id, bundle.major_version, bundle.minor_version);
+        for entry in &bundle.entries {
+            let compressed = if entry.is_compressed() { ", compressed" } else { "" };
+            println!("  {} ({:?}, {} bytes{compressed})", entry.path, entry.file_type, entry.size);
+        }
+
+        return Ok(());
+    }
+
+    let metadata = Metadata::parse(&data)?;
+    match metadata.assembly() {
+        Some(assembly) => println!("Assembly: {}", assembly.identity()),
+        None => println!("Module without an Assembly table"),
+    }
+
+    println!("Runtime: {}", metadata.version());
+    match metadata.target_framework() {
+        Some(framework) => println!("Framework: {} ({framework})", metadata.framework_family()),
+        None => println!("Framework: {}", metadata.framework_family()),
+    }
+    println!("Flags: {}", metadata.cli_header().flag_names());
+    if args.strings {
+        let identifiers = metadata.strings().iter().collect::<Vec<_>>();
+        println!("\n#Strings ({} entries)", identifiers.len());
+        for (offset, value) in identifiers {
+            println!("  0x{offset:08X}  {value:?}");
+        }
+
+        let literals = metadata.string_literals();
+        println!("\n#US ({} entries)", literals.len());
+        for literal in literals {
+            println!("  {}  {:?}", literal.token, literal.value);
+            for method in literal.references {
+                let name = metadata.method_name(method).unwrap_or_else(|| "?".to_string());
+                println!("      ldstr in {method} {name}");
+            }
+        }
+    }
+
+    for target in &args.il {
+        let (type_name, method_name) = target
+            .rsplit_once("::")
+            .ok_or(ClrError::ErrorClr("Expected a method as Type::Method"))?;
+
+        let ty = metadata
+            .find_type(type_name)?
+            .ok_or_else(|| ClrError::TypeNotFound(type_name.to_string()))?;
+
+        let methods = ty.methods.iter().filter(|method| method.name == method_name).collect::<Vec<_>>();
+        if methods.is_empty() {
+            return Err(ClrError::MethodNotFound);
+        }
+
+        // Every overload is listed
+        for method in methods {
+            println!("\n{}", metadata.disassemble(method)?);
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes every embedded manifest resource of `data` to `directory`, decompressing
+/// `.compressed` resources, and lists the ones stored elsewhere. The files of
+/// single-file bundles are extracted the same way.
+fn dump_resources(data: &[u8], directory: &Path) -> Result<(), ClrError> {
+    if Bundle::is_bundle(data) {
+        let bundle = Bundle::parse(data)?;
+        fs::create_dir_all(directory)
+            .map_err(|_| ClrError::ErrorClr("Failed to create output directory"))?;
+
+        for entry in &bundle.entries {
+            let contents = entry.contents()?;
+            let path = directory.join(entry.path.replace(['/', '\\'], "_"));
+            fs::write(&path, &contents)
+                .map_err(|_| ClrError::ErrorClr("Failed to write bundle file"))?;
+
+            println!("{} ({:?}, {} bytes) -> {}", entry.path, entry.file_type, contents.len(), path.display());
+        }
+
+        return Ok(());
+    }
+
+    let metadata = Metadata::parse(data)?;
+    let resources = metadata.manifest_resources();
+    if resources.is_empty() {
+        println!("No manifest resources found");
+        return Ok(());
+    }
+
+    fs::create_dir_all(directory)
+        .map_err(|_| ClrError::ErrorClr("Failed to create output directory"))?;
+
+    for resource in resources {
+        let visibility = format!("{:?}", resource.visibility).to_lowercase();
+        match &resource.location {
+            ResourceLocation::Embedded => {
+                let contents = resource.contents()?;
+                let path = directory.join(resource.file_name().replace(['/', '\\'], "_"));
+                fs::write(&path, &contents)
+                    .map_err(|_| ClrError::ErrorClr("Failed to write resource"))?;
+
+                println!("{} ({visibility}, {} bytes) -> {}", resource.name, contents.len(), path.display());
+            }
+            ResourceLocation::File(file) => println!("{} ({visibility}) is stored in file {file}", resource.name),
+            ResourceLocation::Assembly(assembly) => println!("{} ({visibility}) is stored in assembly {assembly}", resource.name),
+        }
+    }
+
+    Ok(())
+}
@@ -1,6 +1,6 @@
 use rustclr::{
-    RustClrEnv, ClrOutput, 
-    Invocation, Variant,
+    args, RustClrEnv, 
+    ClrOutput, Invocation,
 };
 
 fn sub() -> Result<(), Box<dyn std::error::Error>> {
@@ -14,11 +14,11 @@ fn sub() -> Result<(), Box<dyn std::error::Error>> {
     clr_output.redirect()?;
 
     // Prepare the arguments
-    let args = vec!["Hello World".to_variant()];
+    let args = args!["Hello World"];
 
     // Invoke the WriteLine method
-    console.invoke("WriteLine", None, Some(args), Invocation::Static)?;
-    console.invoke("WriteLine", None, Some(vec!["Hello World111".to_variant()]), Invocation::Static)?;
+    console.invoke("WriteLine", None, args, Invocation::Static)?;
+    console.invoke("WriteLine", None, ("Hello World111",), Invocation::Static)?;
 
 
     // Restore the original output and capture redirected content
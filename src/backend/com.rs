@@ -0,0 +1,267 @@
+use std::ptr::null_mut;
+use windows_core::{Interface, PCWSTR};
+use super::ClrBackend;
+use crate::com::{CLRCreateInstance, CLSID_CLRMETAHOST, CLSID_COR_RUNTIME_HOST};
+use crate::data::{ICLRMetaHost, ICLRRuntimeInfo, ICorRuntimeHost, _AppDomain, _Assembly, _Type};
+use crate::{
+    args, create_safe_array_args,
+    error::ClrError, metadata::{AssemblyIdentity, AssemblyStore},
+    AssemblyResolver, FromVariant, Invocation, OwnedVariant,
+    Result, RuntimeVersion, Value,
+};
+
+/// Hosts the .NET Framework CLR through `ICLRMetaHost` and `ICorRuntimeHost`.
+///
+/// This is the backend `RustClr::new` uses on Windows. The COM interfaces are created
+/// lazily, when the runtime is started or the installed runtimes are listed.
+#[derive(Debug, Clone, Default)]
+pub struct ComBackend {
+    /// MetaHost for accessing the installed CLR versions.
+    meta_host: Option<ICLRMetaHost>,
+
+    /// Runtime information for the started CLR version.
+    runtime_info: Option<ICLRRuntimeInfo>,
+
+    /// Host for the CLR runtime, once started.
+    cor_runtime_host: Option<ICorRuntimeHost>,
+}
+
+impl ComBackend {
+    /// Returns the MetaHost for accessing CLR components, once created.
+    pub fn meta_host(&self) -> Option<&ICLRMetaHost> {
+        self.meta_host.as_ref()
+    }
+
+    /// Returns the runtime information for the started CLR version.
+    pub fn runtime_info(&self) -> Option<&ICLRRuntimeInfo> {
+        self.runtime_info.as_ref()
+    }
+
+    /// Returns the host for the CLR runtime, once started.
+    pub fn cor_runtime_host(&self) -> Option<&ICorRuntimeHost> {
+        self.cor_runtime_host.as_ref()
+    }
+
+    /// Returns the `ICLRMetaHost` instance, creating it on first use.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(ICLRMetaHost)` - If the instance is available.
+    /// * `Err(ClrError)` - If the instance creation fails.
+    fn create_meta_host(&mut self) -> Result<ICLRMetaHost> {
+        if let Some(meta_host) = &self.meta_host {
+            return Ok(meta_host.clone());
+        }
+
+        let meta_host = CLRCreateInstance::<ICLRMetaHost>(&CLSID_CLRMETAHOST)
+            .map_err(|e| ClrError::MetaHostCreationError(format!("{e}")))?;
+
+        self.meta_host = Some(meta_host.clone());
+        Ok(meta_host)
+    }
+
+    /// Returns the runtime host started by `ClrBackend::start`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(&ICorRuntimeHost)` - If the runtime has been started.
+    /// * `Err(ClrError)` - If it has not.
+    fn runtime_host(&self) -> Result<&ICorRuntimeHost> {
+        self.cor_runtime_host.as_ref().ok_or(ClrError::RuntimeStartError)
+    }
+}
+
+impl ClrBackend for ComBackend {
+    type Domain = _AppDomain;
+    type Assembly = _Assembly;
+    type Type = _Type;
+    type Argument = Value;
+    type Resolver = AssemblyResolver;
+
+    fn installed_runtimes(&mut self) -> Result<Vec<String>> {
+        Ok(self.create_meta_host()?.runtimes()?.into_keys().collect())
+    }
+
+    fn start(&mut self, version: &RuntimeVersion) -> Result<()> {
+        // Gets information about the requested runtime version
+        let version_wide = version.to_vec();
+        let runtime_info = self.create_meta_host()?
+            .GetRuntime::<ICLRRuntimeInfo>(PCWSTR(version_wide.as_ptr()))
+            .map_err(|e| ClrError::RuntimeInfoError(format!("{e}")))?;
+
+        // Creates the runtime host
+        let cor_runtime_host = runtime_info.GetInterface::<ICorRuntimeHost>(&CLSID_COR_RUNTIME_HOST)
+            .map_err(|e| ClrError::RuntimeHostError(format!("{e}")))?;
+
+        // Starts the CLR runtime, unless it is already running in this process
+        if runtime_info.IsLoadable().is_ok() && !runtime_info.is_started() && cor_runtime_host.Start() != 0 {
+            return Err(ClrError::RuntimeStartError);
+        }
+
+        self.runtime_info = Some(runtime_info);
+        self.cor_runtime_host = Some(cor_runtime_host);
+        Ok(())
+    }
+
+    fn stop(&mut self) {
+        if let Some(cor_runtime_host) = self.cor_runtime_host.take() {
+            cor_runtime_host.Stop();
+        }
+    }
+
+    fn create_domain(&mut self, name: Option<&str>) -> Result<_AppDomain> {
+        let cor_runtime_host = self.runtime_host()?;
+        match name {
+            Some(name) => {
+                let wide_domain_name = name.encode_utf16().chain(Some(0)).collect::<Vec<u16>>();
+                cor_runtime_host.CreateDomain(PCWSTR(wide_domain_name.as_ptr()), null_mut())
+            }
+            None => cor_runtime_host.GetDefaultDomain(),
+        }
+    }
+
+    fn unload_domain(&mut self, domain: _AppDomain) -> Result<()> {
+        let Some(cor_runtime_host) = &self.cor_runtime_host else {
+            return Ok(());
+        };
+
+        cor_runtime_host.UnloadDomain(domain.cast::<windows_core::IUnknown>()
+            .map(|i| i.as_raw().cast())
+            .unwrap_or(null_mut())
+        )
+    }
+
+    fn install_resolver(&mut self, domain: &_AppDomain, store: &AssemblyStore) -> Result<AssemblyResolver> {
+        AssemblyResolver::install(domain, store)
+    }
+
+    fn register_dependency(&mut self, resolver: &AssemblyResolver, identity: &AssemblyIdentity, buffer: &[u8]) -> Result<()> {
+        resolver.register(identity, buffer)
+    }
+
+    fn load_assembly(&mut self, domain: &_AppDomain, buffer: &[u8]) -> Result<_Assembly> {
+        domain.load_assembly(buffer)
+    }
+
+    fn resolve_type(&mut self, assembly: &_Assembly, name: &str) -> Result<_Type> {
+        assembly.resolve_type(name)
+    }
+
+    fn run_entry_point(&mut self, assembly: &_Assembly, args: Option<&[String]>) -> Result<()> {
+        let parameters = args.map(|args| create_safe_array_args(args.to_vec())).transpose()?;
+        assembly.run(parameters.as_ref())?;
+        Ok(())
+    }
+
+    fn invoke_static(&mut self, ty: &_Type, signature: &str, args: &[Value]) -> Result<()> {
+        ty.method_signature(signature)?.invoke(None, args.to_vec())?;
+        Ok(())
+    }
+
+    fn capture_output(
+        &mut self,
+        domain: &_AppDomain,
+        run: &mut dyn FnMut(&mut Self) -> Result<()>,
+    ) -> Result<String> {
+        // Loads the mscorlib library for output redirection
+        let mscorlib = domain.get_assembly("mscorlib")?;
+        let mut output_manager = ClrOutput::new(&mscorlib);
+
+        // Redirecting output
+        output_manager.redirect()?;
+
+        // Invokes the method
+        run(self)?;
+
+        // Restores output if redirected
+        let output = output_manager.capture()?;
+        output_manager.restore()?;
+        Ok(output)
+    }
+}
+
+/// Manages output redirection in the CLR by using a `StringWriter`.
+///
+/// This struct handles the redirection of standard output and error streams
+/// to a `StringWriter` instance, enabling the capture of output produced
+/// by the .NET code.
+pub struct ClrOutput<'a> {
+    /// The `StringWriter` instance used to capture output.
+    string_writer: Option<OwnedVariant>,
+
+    /// Reference to the `mscorlib` assembly for creating types.
+    mscorlib: &'a _Assembly,
+}
+
+impl<'a> ClrOutput<'a> {
+    /// Creates a new `ClrOutput`.
+    ///
+    /// # Arguments
+    ///
+    /// * `mscorlib` - An instance of the `_Assembly` representing `mscorlib`.
+    ///
+    /// # Returns
+    ///
+    /// * A new instance of `ClrOutput`.
+    pub fn new(mscorlib: &'a _Assembly) -> Self {
+        Self {
+            string_writer: None,
+            mscorlib
+        }
+    }
+
+    /// Redirects standard output and error streams to a `StringWriter`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the redirection is successful.
+    /// * `Err(ClrError)` - If an error occurs while attempting to redirect the streams.
+    pub fn redirect(&mut self) -> Result<()> {
+        let console = self.mscorlib.resolve_type("System.Console")?;
+        let string_writer = self.mscorlib.create_instance("System.IO.StringWriter")?;
+
+        // Invokes the methods
+        console.invoke("SetOut", None, args![string_writer], Invocation::Static)?;
+        console.invoke("SetError", None, args![string_writer], Invocation::Static)?;
+
+        // Saves the StringWriter instance to retrieve the output later
+        self.string_writer = Some(string_writer);
+
+        Ok(())
+    }
+
+    /// Restores the original standard output and error streams.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the restoration is successful.
+    /// * `Err(ClrError)` - If an error occurs while restoring the streams.
+    pub fn restore(&mut self) -> Result<()> {
+        let console = self.mscorlib.resolve_type("System.Console")?;
+        console.method_signature("Void InitializeStdOutError(Boolean)")?
+            .invoke(None, (true,))?;
+
+        Ok(())
+    }
+
+    /// Captures the content of the `StringWriter` as a `String`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(String)` - The captured output as a string if successful.
+    /// * `Err(ClrError)` - If an error occurs while capturing the output.
+    pub fn capture(&self) -> Result<String> {
+        // Ensure that the StringWriter instance is available
+        let instance = self.string_writer.as_ref().ok_or(ClrError::ErrorClr("No StringWriter instance found"))?;
+
+        // Resolve the 'ToString' method on the StringWriter type
+        let string_writer = self.mscorlib.resolve_type("System.IO.StringWriter")?;
+        let to_string = string_writer.method("ToString")?;
+
+        // Invoke 'ToString' on the StringWriter instance
+        let result = to_string.invoke(Some(instance), ())?;
+
+        // Convert the BSTR to a UTF-8 String
+        String::from_variant(&result)
+    }
+}
@@ -0,0 +1,117 @@
+use std::{cell::RefCell, rc::Rc};
+use super::ClrBackend;
+use crate::{error::ClrError, metadata::{AssemblyIdentity, AssemblyStore}, Result, RuntimeVersion};
+
+/// An in-memory backend recording the calls `RustClr` and `RustClrEnv` make, for tests on any OS.
+///
+/// Domains are identified by name (`DefaultDomain` for the default one), assemblies by
+/// their size, types by name and arguments are plain integers. Each invocation "prints"
+/// `output`, which ends up in the captured output when redirection is enabled.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct FakeBackend {
+    /// Runtime versions reported as installed.
+    pub(crate) installed: Vec<String>,
+
+    /// Text printed by each invocation.
+    pub(crate) output: String,
+
+    /// Name of the call that fails with `ClrError::ErrorClr`, if any.
+    pub(crate) fail: Option<&'static str>,
+
+    /// Calls made so far, shared with the clones handed to the tests.
+    pub(crate) calls: Rc<RefCell<Vec<String>>>,
+
+    /// Output captured since `capture_output` redirected the console.
+    console: Option<String>,
+}
+
+impl FakeBackend {
+    /// Records a call, failing if it is the one set up to fail.
+    fn record(&mut self, name: &'static str, detail: String) -> Result<()> {
+        self.calls.borrow_mut().push(format!("{name} {detail}").trim_end().to_string());
+        match self.fail == Some(name) {
+            true => Err(ClrError::ErrorClr("fake failure")),
+            false => Ok(()),
+        }
+    }
+
+    /// Prints `output` to the redirected console, if any.
+    fn print(&mut self) {
+        if let Some(console) = &mut self.console {
+            console.push_str(&self.output);
+        }
+    }
+}
+
+impl ClrBackend for FakeBackend {
+    type Domain = String;
+    type Assembly = usize;
+    type Type = String;
+    type Argument = i32;
+    type Resolver = ();
+
+    fn installed_runtimes(&mut self) -> Result<Vec<String>> {
+        self.record("installed_runtimes", String::new())?;
+        Ok(self.installed.clone())
+    }
+
+    fn start(&mut self, version: &RuntimeVersion) -> Result<()> {
+        self.record("start", version.to_string())
+    }
+
+    fn stop(&mut self) {
+        let _ = self.record("stop", String::new());
+    }
+
+    fn create_domain(&mut self, name: Option<&str>) -> Result<String> {
+        let name = name.unwrap_or("DefaultDomain").to_string();
+        self.record("create_domain", name.clone())?;
+        Ok(name)
+    }
+
+    fn unload_domain(&mut self, domain: String) -> Result<()> {
+        self.record("unload_domain", domain)
+    }
+
+    fn install_resolver(&mut self, domain: &String, store: &AssemblyStore) -> Result<()> {
+        self.record("install_resolver", format!("{domain} {}", store.len()))
+    }
+
+    fn register_dependency(&mut self, _resolver: &(), identity: &AssemblyIdentity, _buffer: &[u8]) -> Result<()> {
+        self.record("register_dependency", identity.name.clone())
+    }
+
+    fn load_assembly(&mut self, domain: &String, buffer: &[u8]) -> Result<usize> {
+        self.record("load_assembly", domain.clone())?;
+        Ok(buffer.len())
+    }
+
+    fn resolve_type(&mut self, _assembly: &usize, name: &str) -> Result<String> {
+        self.record("resolve_type", name.to_string())?;
+        Ok(name.to_string())
+    }
+
+    fn run_entry_point(&mut self, _assembly: &usize, args: Option<&[String]>) -> Result<()> {
+        self.record("run_entry_point", args.map(|args| args.join(" ")).unwrap_or_default())?;
+        self.print();
+        Ok(())
+    }
+
+    fn invoke_static(&mut self, ty: &String, signature: &str, args: &[i32]) -> Result<()> {
+        self.record("invoke_static", format!("{ty}::{signature} {args:?}"))?;
+        self.print();
+        Ok(())
+    }
+
+    fn capture_output(
+        &mut self,
+        domain: &String,
+        run: &mut dyn FnMut(&mut Self) -> Result<()>,
+    ) -> Result<String> {
+        self.record("capture_output", domain.clone())?;
+        self.console = Some(String::new());
+        let result = run(self);
+        let output = self.console.take().unwrap_or_default();
+        result.map(|_| output)
+    }
+}
@@ -0,0 +1,94 @@
+//! # Runtime Hosting Backends
+//!
+//! `RustClr` and `RustClrEnv` drive the runtime through the `ClrBackend` trait, so the logic
+//! deciding what to start, load and invoke is independent of how the runtime is actually
+//! hosted. On Windows, `ComBackend` hosts the .NET Framework through `mscoree.dll` and COM; on other
+//! platforms, `UnsupportedBackend` fails with a clear error instead.
+
+#[cfg(windows)]
+mod com;
+
+#[cfg(not(windows))]
+mod unsupported;
+
+#[cfg(test)]
+pub(crate) mod fake;
+
+#[cfg(windows)]
+pub use com::*;
+
+#[cfg(not(windows))]
+pub use unsupported::*;
+
+use crate::{metadata::{AssemblyIdentity, AssemblyStore}, Result, RuntimeVersion};
+
+/// The backend `RustClr::new` uses on this platform.
+#[cfg(windows)]
+pub type DefaultBackend = ComBackend;
+
+/// The backend `RustClr::new` uses on this platform.
+#[cfg(not(windows))]
+pub type DefaultBackend = UnsupportedBackend;
+
+/// The operations `RustClr` needs from a hosted .NET runtime.
+///
+/// Handles to domains, assemblies and types are associated types, so each backend keeps
+/// its own representation (COM interfaces, identifiers of a test double, ...).
+pub trait ClrBackend {
+    /// Handle to an application domain.
+    type Domain: Clone;
+
+    /// Handle to an assembly loaded into a domain.
+    type Assembly;
+
+    /// Handle to a type resolved from an assembly.
+    type Type;
+
+    /// A typed argument passed to a static method, e.g. a `VARIANT`.
+    type Argument: Clone;
+
+    /// Guard serving in-memory dependencies to a domain until it is dropped.
+    type Resolver;
+
+    /// Lists the runtime versions installed on the system, e.g. `v4.0.30319`.
+    fn installed_runtimes(&mut self) -> Result<Vec<String>>;
+
+    /// Starts the runtime of the given version, or attaches to it if it is already running.
+    ///
+    /// `RuntimeVersion::Auto` is resolved by the caller, so `version` is always concrete.
+    fn start(&mut self, version: &RuntimeVersion) -> Result<()>;
+
+    /// Stops the runtime, if it was started. Errors are ignored, as this runs on drop.
+    fn stop(&mut self);
+
+    /// Creates a domain with the given name, or returns the default domain for `None`.
+    fn create_domain(&mut self, name: Option<&str>) -> Result<Self::Domain>;
+
+    /// Unloads a domain created by `create_domain`.
+    fn unload_domain(&mut self, domain: Self::Domain) -> Result<()>;
+
+    /// Serves the assemblies of `store` to `domain` when the runtime fails to resolve them.
+    fn install_resolver(&mut self, domain: &Self::Domain, store: &AssemblyStore) -> Result<Self::Resolver>;
+
+    /// Adds an assembly to a resolver installed by `install_resolver`.
+    fn register_dependency(&mut self, resolver: &Self::Resolver, identity: &AssemblyIdentity, buffer: &[u8]) -> Result<()>;
+
+    /// Loads an assembly from its bytes into `domain`.
+    fn load_assembly(&mut self, domain: &Self::Domain, buffer: &[u8]) -> Result<Self::Assembly>;
+
+    /// Resolves a type of `assembly` by its full name, e.g. `Namespace.Type`.
+    fn resolve_type(&mut self, assembly: &Self::Assembly, name: &str) -> Result<Self::Type>;
+
+    /// Invokes the entry point of `assembly`, passing `args` when `Main` takes a `string[]`.
+    fn run_entry_point(&mut self, assembly: &Self::Assembly, args: Option<&[String]>) -> Result<()>;
+
+    /// Invokes the static method of `ty` with the given signature, e.g. `Void Run(System.String)`.
+    fn invoke_static(&mut self, ty: &Self::Type, signature: &str, args: &[Self::Argument]) -> Result<()>;
+
+    /// Runs `run` with the console output of `domain` redirected, and returns what it printed.
+    fn capture_output(
+        &mut self,
+        domain: &Self::Domain,
+        run: &mut dyn FnMut(&mut Self) -> Result<()>,
+    ) -> Result<String>;
+}
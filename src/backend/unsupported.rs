@@ -0,0 +1,69 @@
+use core::convert::Infallible;
+use super::ClrBackend;
+use crate::{error::ClrError, metadata::{AssemblyIdentity, AssemblyStore}, Result, RuntimeVersion};
+
+/// Stands in for the COM backend on platforms without the .NET Framework.
+///
+/// Every operation fails with `ClrError::UnsupportedPlatform`, so code built on `RustClr`
+/// still compiles, and the metadata checks of `RustClr::new` still run, on Linux and macOS.
+/// Use `RustHostFxr` or `RustCoreClr` to host .NET 6+ there.
+#[derive(Debug, Clone, Default)]
+pub struct UnsupportedBackend;
+
+impl ClrBackend for UnsupportedBackend {
+    type Domain = Infallible;
+    type Assembly = Infallible;
+    type Type = Infallible;
+    type Argument = Infallible;
+    type Resolver = Infallible;
+
+    fn installed_runtimes(&mut self) -> Result<Vec<String>> {
+        Err(ClrError::UnsupportedPlatform)
+    }
+
+    fn start(&mut self, _version: &RuntimeVersion) -> Result<()> {
+        Err(ClrError::UnsupportedPlatform)
+    }
+
+    fn stop(&mut self) {}
+
+    fn create_domain(&mut self, _name: Option<&str>) -> Result<Infallible> {
+        Err(ClrError::UnsupportedPlatform)
+    }
+
+    fn unload_domain(&mut self, domain: Infallible) -> Result<()> {
+        match domain {}
+    }
+
+    fn install_resolver(&mut self, domain: &Infallible, _store: &AssemblyStore) -> Result<Infallible> {
+        match *domain {}
+    }
+
+    fn register_dependency(&mut self, resolver: &Infallible, _identity: &AssemblyIdentity, _buffer: &[u8]) -> Result<()> {
+        match *resolver {}
+    }
+
+    fn load_assembly(&mut self, domain: &Infallible, _buffer: &[u8]) -> Result<Infallible> {
+        match *domain {}
+    }
+
+    fn resolve_type(&mut self, assembly: &Infallible, _name: &str) -> Result<Infallible> {
+        match *assembly {}
+    }
+
+    fn run_entry_point(&mut self, assembly: &Infallible, _args: Option<&[String]>) -> Result<()> {
+        match *assembly {}
+    }
+
+    fn invoke_static(&mut self, ty: &Infallible, _signature: &str, _args: &[Infallible]) -> Result<()> {
+        match *ty {}
+    }
+
+    fn capture_output(
+        &mut self,
+        domain: &Infallible,
+        _run: &mut dyn FnMut(&mut Self) -> Result<()>,
+    ) -> Result<String> {
+        match *domain {}
+    }
+}
@@ -0,0 +1,1000 @@
+use std::fmt::{self, Display};
+use std::time::{Duration, UNIX_EPOCH};
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, EnumAccess,
+    MapAccess, SeqAccess, VariantAccess, Visitor,
+};
+use serde::ser::{self, Serialize};
+use serde::{forward_to_deserialize_any, Deserialize, Deserializer, Serializer};
+use crate::{error::ClrError, marshal::vt_name, vt, element_size, Decimal, Result, Value};
+#[cfg(windows)]
+use crate::FromVariant;
+
+/// The newtype name `Decimal` is serialized under, which `to_value` turns back into a `Value::Decimal`.
+const DECIMAL_NAME: &str = "$rustclr::Decimal";
+
+/// The struct name `SystemTime` is serialized under, which `to_value` turns into a `Value::Date`.
+const SYSTEM_TIME_NAME: &str = "SystemTime";
+
+/// The fields `SystemTime` is serialized with, which tell it apart from other structs of that name.
+const SYSTEM_TIME_FIELDS: [&str; 2] = ["secs_since_epoch", "nanos_since_epoch"];
+
+/// Converts a Rust value to a `Value` tree.
+///
+/// Sequences of values of one scalar type become typed arrays (`Vec<i32>` an `int[]`, `Vec<u8>`
+/// a `byte[]`) and anything else an `object[]`: tuples and structs hold their fields in order,
+/// maps hold `[key, value]` pairs and enum variants with data hold the variant name followed by
+/// the data. Unit variants are their name, `None` is `DBNull` and `()` is `null`.
+///
+/// An empty sequence has no element to type the array after, so it is an empty `object[]`
+/// whatever its Rust type. Serialize bytes with `serialize_bytes` (e.g. `serde_bytes`) to pass
+/// an empty `byte[]`, or build the `Value::Array` of the element type directly.
+///
+/// # Arguments
+///
+/// * `value` - The value to convert.
+///
+/// # Returns
+///
+/// * `Ok(Value)` - The converted value.
+/// * `Err(ClrError)` - If the `Serialize` implementation of the value fails.
+///
+/// # Examples
+///
+/// ```ignore
+/// #[derive(Serialize)]
+/// struct Point { x: i32, y: i32 }
+///
+/// // object[] { 1, 2 }
+/// let point = rustclr::to_value(&Point { x: 1, y: 2 })?;
+/// ```
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<Value> {
+    value.serialize(ValueSerializer { arguments: false })
+}
+
+/// Converts a Rust value to an argument list, e.g. for `_MethodInfo::invoke`.
+///
+/// The fields of a tuple or struct are spread over the parameters, `()` is no argument at all
+/// and any other value, sequences and enum variants included, is the only argument.
+///
+/// # Arguments
+///
+/// * `value` - The value to convert.
+///
+/// # Returns
+///
+/// * `Ok(Vec<Value>)` - The arguments.
+/// * `Err(ClrError)` - If the `Serialize` implementation of the value fails.
+///
+/// # Examples
+///
+/// ```ignore
+/// // static string Describe(string name, int age, string[] tags)
+/// let result = describe.invoke(None, rustclr::to_args(&person)?)?;
+/// ```
+pub fn to_args<T: Serialize + ?Sized>(value: &T) -> Result<Vec<Value>> {
+    Ok(match value.serialize(ValueSerializer { arguments: true })? {
+        Value::Empty => Vec::new(),
+        Value::Array(vt::VARIANT, items) => items,
+        value => vec![value],
+    })
+}
+
+/// Converts a `Value` tree back to a Rust value, following the rules of `to_value`.
+///
+/// # Arguments
+///
+/// * `value` - The value to convert, e.g. `Value::from_variant(&result)?` for the result of
+///   an invocation.
+///
+/// # Returns
+///
+/// * `Ok(T)` - The converted value.
+/// * `Err(ClrError)` - If the value does not have the shape `T` expects.
+pub fn from_value<T: DeserializeOwned>(value: Value) -> Result<T> {
+    T::deserialize(value)
+}
+
+/// Serializes a Rust value to JSON, to pass a complex object through a single `string` parameter.
+///
+/// # Arguments
+///
+/// * `value` - The value to serialize.
+///
+/// # Returns
+///
+/// * `Ok(Value)` - The `Value::String` holding the JSON text.
+/// * `Err(ClrError)` - If the value cannot be represented in JSON.
+#[cfg(feature = "json")]
+pub fn to_json<T: Serialize + ?Sized>(value: &T) -> Result<Value> {
+    serde_json::to_string(value)
+        .map(Value::String)
+        .map_err(|e| ClrError::SerializationError(e.to_string()))
+}
+
+/// Deserializes a Rust value from the JSON text held by a `Value`, e.g. returned by a .NET helper.
+///
+/// # Arguments
+///
+/// * `value` - The string holding the JSON text.
+///
+/// # Returns
+///
+/// * `Ok(T)` - The deserialized value.
+/// * `Err(ClrError)` - If the value is not a string, or not the JSON of a `T`.
+#[cfg(feature = "json")]
+pub fn from_json<T: DeserializeOwned>(value: Value) -> Result<T> {
+    let text = String::deserialize(value)?;
+    serde_json::from_str(&text).map_err(|e| ClrError::SerializationError(e.to_string()))
+}
+
+impl ser::Error for ClrError {
+    fn custom<T: Display>(msg: T) -> Self {
+        ClrError::SerializationError(msg.to_string())
+    }
+}
+
+impl de::Error for ClrError {
+    fn custom<T: Display>(msg: T) -> Self {
+        ClrError::SerializationError(msg.to_string())
+    }
+}
+
+impl Serialize for Decimal {
+    /// Serializes the decimal as its text, which `to_value` passes as a `System.Decimal`.
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct(DECIMAL_NAME, &self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Decimal {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        /// Parses the text of a decimal, possibly wrapped in its newtype.
+        struct DecimalVisitor;
+
+        impl<'de> Visitor<'de> for DecimalVisitor {
+            type Value = Decimal;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a decimal number")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> std::result::Result<Decimal, E> {
+                v.parse().map_err(E::custom)
+            }
+
+            fn visit_newtype_struct<D: Deserializer<'de>>(self, deserializer: D) -> std::result::Result<Decimal, D::Error> {
+                String::deserialize(deserializer)?.parse().map_err(de::Error::custom)
+            }
+        }
+
+        deserializer.deserialize_newtype_struct(DECIMAL_NAME, DecimalVisitor)
+    }
+}
+
+/// Serializes Rust values to `Value` trees.
+#[derive(Clone, Copy)]
+struct ValueSerializer {
+    /// Whether the value is the argument list of `to_args`, where only a tuple or struct is an
+    /// `object[]` to spread and any other compound value is wrapped into a one-element one.
+    arguments: bool,
+}
+
+impl ValueSerializer {
+    /// Starts collecting `len` elements of a compound value.
+    fn collect(self, kind: Kind, len: usize) -> Collect {
+        Collect { kind, items: Vec::with_capacity(len), key: None, serializer: self }
+    }
+
+    /// Starts collecting the fields of an enum variant, after its name.
+    fn variant(self, variant: &'static str, len: usize) -> Collect {
+        let mut collect = self.collect(Kind::Variant, len + 1);
+        collect.items.push(Value::String(variant.to_string()));
+        collect
+    }
+
+    /// Wraps a value that is a single argument of `to_args`, so that it is not spread.
+    fn argument(self, value: Value) -> Value {
+        match self.arguments {
+            true => Value::Array(vt::VARIANT, vec![value]),
+            false => value,
+        }
+    }
+}
+
+impl Serializer for ValueSerializer {
+    type Ok = Value;
+    type Error = ClrError;
+    type SerializeSeq = Collect;
+    type SerializeTuple = Collect;
+    type SerializeTupleStruct = Collect;
+    type SerializeTupleVariant = Collect;
+    type SerializeMap = Collect;
+    type SerializeStruct = Collect;
+    type SerializeStructVariant = Collect;
+
+    fn serialize_bool(self, v: bool) -> Result<Value> {
+        Ok(Value::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Value> {
+        Ok(Value::I8(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Value> {
+        Ok(Value::I16(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Value> {
+        Ok(Value::I32(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Value> {
+        Ok(Value::I64(v))
+    }
+
+    /// Passes integers beyond 64 bits as a `System.Decimal`.
+    fn serialize_i128(self, v: i128) -> Result<Value> {
+        match i64::try_from(v) {
+            Ok(v) => Ok(Value::I64(v)),
+            Err(_) => Decimal::new(v, 0).map(Value::Decimal),
+        }
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Value> {
+        Ok(Value::U8(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Value> {
+        Ok(Value::U16(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Value> {
+        Ok(Value::U32(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value> {
+        Ok(Value::U64(v))
+    }
+
+    /// Passes integers beyond 64 bits as a `System.Decimal`.
+    fn serialize_u128(self, v: u128) -> Result<Value> {
+        match u64::try_from(v) {
+            Ok(v) => Ok(Value::U64(v)),
+            Err(_) => i128::try_from(v)
+                .map_err(|_| ClrError::InvalidDecimal(v.to_string()))
+                .and_then(|v| Decimal::new(v, 0))
+                .map(Value::Decimal),
+        }
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Value> {
+        Ok(Value::F32(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Value> {
+        Ok(Value::F64(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value> {
+        Ok(Value::Char(v))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value> {
+        Ok(Value::Bytes(v.to_vec()))
+    }
+
+    fn serialize_none(self) -> Result<Value> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Value> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value> {
+        Ok(Value::Empty)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value> {
+        Ok(Value::Empty)
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<Value> {
+        Ok(Value::String(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, name: &'static str, value: &T) -> Result<Value> {
+        match value.serialize(self)? {
+            Value::String(text) if name == DECIMAL_NAME => text.parse().map(Value::Decimal),
+            value => Ok(value),
+        }
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value> {
+        let fields = vec![Value::String(variant.to_string()), to_value(value)?];
+        Ok(self.argument(Value::Array(vt::VARIANT, fields)))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Collect> {
+        Ok(self.collect(Kind::Seq, len.unwrap_or_default()))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Collect> {
+        Ok(self.collect(Kind::Fields, len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<Collect> {
+        Ok(self.collect(Kind::Fields, len))
+    }
+
+    fn serialize_tuple_variant(self, _name: &'static str, _index: u32, variant: &'static str, len: usize) -> Result<Collect> {
+        Ok(self.variant(variant, len))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Collect> {
+        Ok(self.collect(Kind::Map, len.unwrap_or_default()))
+    }
+
+    fn serialize_struct(self, name: &'static str, len: usize) -> Result<Collect> {
+        let kind = if name == SYSTEM_TIME_NAME && len == SYSTEM_TIME_FIELDS.len() { Kind::Time } else { Kind::Fields };
+        Ok(self.collect(kind, len))
+    }
+
+    fn serialize_struct_variant(self, _name: &'static str, _index: u32, variant: &'static str, len: usize) -> Result<Collect> {
+        Ok(self.variant(variant, len))
+    }
+}
+
+/// What a `Collect` builds.
+enum Kind {
+    /// A sequence, packed into a typed array when possible.
+    Seq,
+
+    /// The fields of a tuple or struct, packed into an `object[]`.
+    Fields,
+
+    /// The name and fields of an enum variant, packed into an `object[]`.
+    Variant,
+
+    /// The `[key, value]` pairs of a map, packed into an `object[]`.
+    Map,
+
+    /// The seconds and nanoseconds of a `SystemTime`, turned into a `Value::Date`. A struct of
+    /// the same name with other fields falls back to `Fields`.
+    Time,
+}
+
+/// Collects the elements of a compound value.
+struct Collect {
+    /// What the elements build.
+    kind: Kind,
+
+    /// The elements serialized so far.
+    items: Vec<Value>,
+
+    /// The key of a map entry, waiting for its value.
+    key: Option<Value>,
+
+    /// The serializer that started the collection.
+    serializer: ValueSerializer,
+}
+
+impl Collect {
+    /// Serializes the next element.
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.items.push(to_value(value)?);
+        Ok(())
+    }
+
+    /// Builds the compound value.
+    fn finish(self) -> Result<Value> {
+        let value = match self.kind {
+            Kind::Seq => typed_array(self.items),
+            Kind::Fields | Kind::Variant | Kind::Map => Value::Array(vt::VARIANT, self.items),
+            Kind::Time => match self.items.as_slice() {
+                [Value::U64(secs), Value::U32(nanos)] => Value::Date(UNIX_EPOCH + Duration::new(*secs, *nanos)),
+                _ => Value::Array(vt::VARIANT, self.items),
+            },
+        };
+
+        // The fields of a top-level tuple or struct are the arguments themselves
+        match self.kind {
+            Kind::Fields => Ok(value),
+            _ => Ok(self.serializer.argument(value)),
+        }
+    }
+}
+
+/// Packs the elements of a sequence into an array typed after them when they all have the
+/// same scalar type, e.g. `Vec<i32>` into an `int[]`, and into an `object[]` otherwise.
+///
+/// An empty sequence is always an `object[]`, as the Rust element type is not known here.
+fn typed_array(items: Vec<Value>) -> Value {
+    let element_vt = match items.first().map(Value::vt) {
+        Some(element_vt) if element_vt != vt::VARIANT && element_size(element_vt).is_ok() => element_vt,
+        _ => return Value::Array(vt::VARIANT, items),
+    };
+
+    if !items.iter().all(|item| item.vt() == element_vt) {
+        return Value::Array(vt::VARIANT, items);
+    }
+
+    if element_vt == vt::UI1 {
+        let bytes = items.iter().filter_map(|item| match item {
+            Value::U8(byte) => Some(*byte),
+            _ => None,
+        });
+
+        return Value::Bytes(bytes.collect());
+    }
+
+    Value::Array(element_vt, items)
+}
+
+impl ser::SerializeSeq for Collect {
+    type Ok = Value;
+    type Error = ClrError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for Collect {
+    type Ok = Value;
+    type Error = ClrError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for Collect {
+    type Ok = Value;
+    type Error = ClrError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for Collect {
+    type Ok = Value;
+    type Error = ClrError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeMap for Collect {
+    type Ok = Value;
+    type Error = ClrError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<()> {
+        self.key = Some(to_value(key)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let key = self.key.take().ok_or_else(|| ser::Error::custom("map value without a key"))?;
+        self.items.push(Value::Array(vt::VARIANT, vec![key, to_value(value)?]));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for Collect {
+    type Ok = Value;
+    type Error = ClrError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<()> {
+        if matches!(self.kind, Kind::Time) && SYSTEM_TIME_FIELDS.get(self.items.len()) != Some(&key) {
+            self.kind = Kind::Fields;
+        }
+
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for Collect {
+    type Ok = Value;
+    type Error = ClrError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, _key: &'static str, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value> {
+        self.finish()
+    }
+}
+
+impl Value {
+    /// Decodes a `VARIANT` passed as is, so that it is deserialized like any other value.
+    fn into_plain(self) -> Result<Value> {
+        match self {
+            #[cfg(windows)]
+            Value::Variant(variant) => Value::from_variant(&variant),
+            value => Ok(value),
+        }
+    }
+
+    /// Returns the error for a value that does not have the shape a `Deserialize` implementation expects.
+    fn invalid(&self, expected: &str) -> ClrError {
+        de::Error::custom(format!("expected {expected}, found {}", vt_name(self.vt())))
+    }
+}
+
+impl<'de> Deserializer<'de> for Value {
+    type Error = ClrError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.into_plain()? {
+            Value::Empty | Value::Null => visitor.visit_unit(),
+            Value::Bool(v) => visitor.visit_bool(v),
+            Value::I8(v) => visitor.visit_i8(v),
+            Value::I16(v) => visitor.visit_i16(v),
+            Value::I32(v) => visitor.visit_i32(v),
+            Value::I64(v) => visitor.visit_i64(v),
+            Value::U8(v) => visitor.visit_u8(v),
+            Value::U16(v) => visitor.visit_u16(v),
+            Value::U32(v) => visitor.visit_u32(v),
+            Value::U64(v) => visitor.visit_u64(v),
+            Value::F32(v) => visitor.visit_f32(v),
+            Value::F64(v) => visitor.visit_f64(v),
+            Value::Char(v) => visitor.visit_char(v),
+            Value::String(v) => visitor.visit_string(v),
+            Value::Decimal(v) => visitor.visit_string(v.to_string()),
+            Value::Guid(v) => visitor.visit_string(v.hyphenated().to_string()),
+            Value::Date(v) => visitor.visit_f64(match v.duration_since(UNIX_EPOCH) {
+                Ok(elapsed) => elapsed.as_secs_f64(),
+                Err(e) => -e.duration().as_secs_f64(),
+            }),
+            Value::Bytes(v) => visitor.visit_byte_buf(v),
+            Value::Array(_, items) => visitor.visit_seq(Seq(items.into_iter())),
+            value => Err(value.invalid("a value with a Rust equivalent")),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.into_plain()? {
+            Value::Empty | Value::Null => visitor.visit_none(),
+            value => visitor.visit_some(value),
+        }
+    }
+
+    /// Reads integers beyond 64 bits from a `System.Decimal`.
+    fn deserialize_i128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.into_plain()? {
+            Value::Decimal(v) if v.scale() == 0 => visitor.visit_i128(v.mantissa()),
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    /// Reads integers beyond 64 bits from a `System.Decimal`.
+    fn deserialize_u128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.into_plain()? {
+            Value::Decimal(v) if v.scale() == 0 && v.mantissa() >= 0 => visitor.visit_u128(v.mantissa() as u128),
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    /// Reads floating point numbers from a `System.Decimal` as well.
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_f64(visitor)
+    }
+
+    /// Reads floating point numbers from a `System.Decimal` as well.
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.into_plain()? {
+            Value::Decimal(v) => visitor.visit_f64(v.to_string().parse().map_err(de::Error::custom)?),
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    /// Reads characters from the `VT_UI2` a `System.Char` is passed as, too.
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.into_plain()? {
+            Value::U16(v) => match char::from_u32(v.into()) {
+                Some(v) => visitor.visit_char(v),
+                None => Err(Value::U16(v).invalid("a character")),
+            },
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    /// Reads sequences from byte arrays as well.
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.into_plain()? {
+            Value::Bytes(bytes) => visitor.visit_seq(Seq(bytes.into_iter().map(Value::U8))),
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(self, _name: &'static str, _len: usize, visitor: V) -> Result<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    /// Reads maps from arrays of `[key, value]` pairs.
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.into_plain()? {
+            Value::Array(_, items) => visitor.visit_map(Pairs { items: items.into_iter(), value: None }),
+            value => Err(value.invalid("an array of key/value pairs")),
+        }
+    }
+
+    /// Reads structs from arrays holding their fields in order.
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        match self.into_plain()? {
+            Value::Date(time) if name == SYSTEM_TIME_NAME && fields == SYSTEM_TIME_FIELDS => {
+                let elapsed = time.duration_since(UNIX_EPOCH).map_err(de::Error::custom)?;
+                let parts = [Value::U64(elapsed.as_secs()), Value::U32(elapsed.subsec_nanos())];
+                visitor.visit_seq(Seq(parts.into_iter()))
+            }
+            value @ Value::Array(..) => value.deserialize_seq(visitor),
+            value => Err(value.invalid(&format!("an array of the {} fields of {name}", fields.len()))),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, name: &'static str, visitor: V) -> Result<V::Value> {
+        match self.into_plain()? {
+            Value::Decimal(v) if name == DECIMAL_NAME => visitor.visit_newtype_struct(Value::String(v.to_string())),
+            value => visitor.visit_newtype_struct(value),
+        }
+    }
+
+    /// Reads unit variants from their name, and other variants from an array starting with it.
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        match self.into_plain()? {
+            Value::String(variant) => visitor.visit_enum(Enum { variant, fields: Vec::new() }),
+            Value::Array(element_vt, mut items) if matches!(items.first(), Some(Value::String(_))) => {
+                let Value::String(variant) = items.remove(0) else {
+                    return Err(Value::Array(element_vt, items).invalid(name));
+                };
+
+                visitor.visit_enum(Enum { variant, fields: items })
+            }
+            value => Err(value.invalid(&format!("a variant of {name}"))),
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 str string
+        bytes byte_buf unit unit_struct identifier
+    }
+}
+
+/// Hands the elements of an array to a `Visitor`.
+struct Seq<I>(I);
+
+impl<'de, I: Iterator<Item = Value>> SeqAccess<'de> for Seq<I> {
+    type Error = ClrError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>> {
+        self.0.next().map(|value| seed.deserialize(value)).transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.0.size_hint().0)
+    }
+}
+
+/// Hands the `[key, value]` pairs of an array to a `Visitor`.
+struct Pairs {
+    /// The pairs left.
+    items: std::vec::IntoIter<Value>,
+
+    /// The value of the pair whose key was read last.
+    value: Option<Value>,
+}
+
+impl<'de> MapAccess<'de> for Pairs {
+    type Error = ClrError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        match self.items.next().map(Value::into_plain).transpose()? {
+            Some(Value::Array(_, pair)) if pair.len() == 2 => {
+                let mut pair = pair.into_iter();
+                let key = pair.next();
+                self.value = pair.next();
+                key.map(|key| seed.deserialize(key)).transpose()
+            }
+            Some(value) => Err(value.invalid("a [key, value] pair")),
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<T::Value> {
+        let value = self.value.take().ok_or_else(|| de::Error::custom("map value without a key"))?;
+        seed.deserialize(value)
+    }
+}
+
+/// Hands an enum variant, read from its name and fields, to a `Visitor`.
+struct Enum {
+    /// The name of the variant.
+    variant: String,
+
+    /// The fields of the variant.
+    fields: Vec<Value>,
+}
+
+impl<'de> EnumAccess<'de> for Enum {
+    type Error = ClrError;
+    type Variant = Self;
+
+    fn variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<(T::Value, Self)> {
+        let variant = seed.deserialize(Value::String(self.variant.clone()))?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for Enum {
+    type Error = ClrError;
+
+    fn unit_variant(self) -> Result<()> {
+        match self.fields.len() {
+            0 => Ok(()),
+            len => Err(de::Error::invalid_length(len, &"no fields")),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value> {
+        let mut fields = self.fields.into_iter();
+        match (fields.next(), fields.len()) {
+            (Some(value), 0) => seed.deserialize(value),
+            (value, len) => Err(de::Error::invalid_length(len + usize::from(value.is_some()), &"one field")),
+        }
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        visitor.visit_seq(Seq(self.fields.into_iter()))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value> {
+        visitor.visit_seq(Seq(self.fields.into_iter()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+    use std::time::SystemTime;
+    use serde::Serialize;
+    use crate::{decode_variant, encode_variant, RawVariant, VariantArena};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Shape {
+        Empty,
+        Circle(f64),
+        Line(i32, i32),
+        Rect { width: u16, height: u16 },
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Order {
+        id: u64,
+        customer: String,
+        price: Decimal,
+        lines: Vec<i32>,
+        payload: Vec<u8>,
+        created: SystemTime,
+        note: Option<String>,
+        shapes: Vec<Shape>,
+        grade: char,
+    }
+
+    fn order() -> Order {
+        Order {
+            id: 7,
+            customer: "Contoso".to_string(),
+            price: "12.50".parse().unwrap(),
+            lines: vec![1, 2, 3],
+            payload: vec![0xde, 0xad],
+            created: UNIX_EPOCH + Duration::from_millis(1_700_000_000_123),
+            note: None,
+            shapes: vec![Shape::Empty, Shape::Circle(1.5), Shape::Line(1, -1), Shape::Rect { width: 2, height: 3 }],
+            grade: 'A',
+        }
+    }
+
+    #[test]
+    fn serializes_structs_positionally() {
+        let value = to_value(&order()).unwrap();
+        let Value::Array(vt::VARIANT, fields) = &value else {
+            panic!("expected an object[], found {value:?}");
+        };
+
+        assert_eq!(fields[..5], [
+            Value::U64(7),
+            Value::String("Contoso".to_string()),
+            Value::Decimal("12.50".parse().unwrap()),
+            Value::Array(vt::I4, vec![Value::I32(1), Value::I32(2), Value::I32(3)]),
+            Value::Bytes(vec![0xde, 0xad]),
+        ]);
+
+        assert_eq!(fields[5], Value::Date(UNIX_EPOCH + Duration::from_millis(1_700_000_000_123)));
+        assert_eq!(fields[6], Value::Null);
+        assert_eq!(fields[7], Value::Array(vt::VARIANT, vec![
+            Value::String("Empty".to_string()),
+            Value::Array(vt::VARIANT, vec![Value::String("Circle".to_string()), Value::F64(1.5)]),
+            Value::Array(vt::VARIANT, vec![Value::String("Line".to_string()), Value::I32(1), Value::I32(-1)]),
+            Value::Array(vt::VARIANT, vec![Value::String("Rect".to_string()), Value::U16(2), Value::U16(3)]),
+        ]));
+        assert_eq!(fields[8], Value::Char('A'));
+
+        assert_eq!(from_value::<Order>(value).unwrap(), order());
+    }
+
+    #[test]
+    fn round_trips_through_variants() {
+        // The same path as an invocation: to a VARIANT, and back from the one returned
+        let mut arena = VariantArena::default();
+        let mut variant = RawVariant::default();
+        encode_variant(&to_value(&order()).unwrap(), &mut variant, &mut arena).unwrap();
+
+        let decoded = unsafe { decode_variant(&variant) }.unwrap();
+        assert_eq!(from_value::<Order>(decoded).unwrap(), order());
+    }
+
+    #[test]
+    fn serializes_maps_and_scalars() {
+        let map = BTreeMap::from([("a".to_string(), 1u8), ("b".to_string(), 2)]);
+        let value = to_value(&map).unwrap();
+        assert_eq!(value, Value::Array(vt::VARIANT, vec![
+            Value::Array(vt::VARIANT, vec![Value::String("a".to_string()), Value::U8(1)]),
+            Value::Array(vt::VARIANT, vec![Value::String("b".to_string()), Value::U8(2)]),
+        ]));
+
+        assert_eq!(from_value::<BTreeMap<String, u8>>(value).unwrap(), map);
+        assert_eq!(to_value(&i128::MAX).unwrap_err().to_string(), format!("Invalid decimal: {}e-0", i128::MAX));
+        assert_eq!(from_value::<u128>(to_value(&(u64::MAX as u128 + 1)).unwrap()).unwrap(), u64::MAX as u128 + 1);
+        assert_eq!(from_value::<i64>(Value::I32(-5)).unwrap(), -5);
+        assert_eq!(from_value::<f64>(Value::Decimal("0.25".parse().unwrap())).unwrap(), 0.25);
+        assert_eq!(from_value::<Option<bool>>(Value::Null).unwrap(), None);
+        assert_eq!(from_value::<char>(Value::U16(0x3b1)).unwrap(), 'α');
+        assert!(from_value::<char>(Value::U16(0xd800)).is_err());
+        assert!(from_value::<u8>(Value::I32(300)).is_err());
+    }
+
+    #[test]
+    fn round_trips_empty_sequences() {
+        /// Bytes serialized as such rather than as a sequence, like `serde_bytes` does.
+        struct Raw<'a>(&'a [u8]);
+
+        impl Serialize for Raw<'_> {
+            fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+                serializer.serialize_bytes(self.0)
+            }
+        }
+
+        let value = to_value(&Vec::<i32>::new()).unwrap();
+        assert_eq!(value, Value::Array(vt::VARIANT, Vec::new()));
+        assert_eq!(to_value(&Raw(&[])).unwrap(), Value::Bytes(Vec::new()));
+
+        let mut arena = VariantArena::default();
+        let mut variant = RawVariant::default();
+        encode_variant(&value, &mut variant, &mut arena).unwrap();
+
+        let decoded = unsafe { decode_variant(&variant) }.unwrap();
+        assert_eq!(decoded, Value::Array(vt::VARIANT, Vec::new()));
+        assert_eq!(from_value::<Vec<i32>>(decoded).unwrap(), Vec::<i32>::new());
+
+        encode_variant(&to_value(&Raw(&[])).unwrap(), &mut variant, &mut arena).unwrap();
+        let decoded = unsafe { decode_variant(&variant) }.unwrap();
+        assert_eq!(from_value::<Vec<u8>>(decoded).unwrap(), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn keeps_other_system_time_structs() {
+        /// A user type that only shares its name with `std::time::SystemTime`.
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        #[serde(rename = "SystemTime")]
+        struct Clock {
+            hours: u8,
+            minutes: u8,
+        }
+
+        let clock = Clock { hours: 9, minutes: 30 };
+        let value = to_value(&clock).unwrap();
+        assert_eq!(value, Value::Array(vt::VARIANT, vec![Value::U8(9), Value::U8(30)]));
+        assert_eq!(from_value::<Clock>(value).unwrap(), clock);
+        assert!(from_value::<Clock>(Value::Date(UNIX_EPOCH)).is_err());
+    }
+
+    #[test]
+    fn spreads_arguments() {
+        assert_eq!(to_args(&()).unwrap(), []);
+        assert_eq!(to_args(&("name", 5)).unwrap(), [Value::String("name".to_string()), Value::I32(5)]);
+        assert_eq!(to_args(&Shape::Rect { width: 2, height: 3 }).unwrap(), [Value::Array(vt::VARIANT, vec![
+            Value::String("Rect".to_string()),
+            Value::U16(2),
+            Value::U16(3),
+        ])]);
+        assert_eq!(to_args(&order()).unwrap().len(), 9);
+        assert_eq!(to_args(&vec!["a", "b"]).unwrap(), [Value::Array(vt::BSTR, vec![
+            Value::String("a".to_string()),
+            Value::String("b".to_string()),
+        ])]);
+
+        // Sequences of compound values are one argument too, not spread like their object[]
+        assert_eq!(to_args(&vec![(1, "a"), (2, "b")]).unwrap(), [Value::Array(vt::VARIANT, vec![
+            Value::Array(vt::VARIANT, vec![Value::I32(1), Value::String("a".to_string())]),
+            Value::Array(vt::VARIANT, vec![Value::I32(2), Value::String("b".to_string())]),
+        ])]);
+        assert_eq!(to_args(&vec![Some(1), None]).unwrap(), [Value::Array(vt::VARIANT, vec![Value::I32(1), Value::Null])]);
+        assert_eq!(to_args(&BTreeMap::from([(1, 2)])).unwrap().len(), 1);
+        assert_eq!(to_args(&Shape::Circle(1.5)).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn rejects_mismatched_shapes() {
+        let error = from_value::<Order>(Value::I32(1)).unwrap_err();
+        assert_eq!(error.to_string(), "Serialization error: expected an array of the 9 fields of Order, found VT_I4");
+        assert!(from_value::<Shape>(Value::String("Triangle".to_string())).is_err());
+        assert!(from_value::<BTreeMap<String, u8>>(Value::Array(vt::VARIANT, vec![Value::I32(1)])).is_err());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn round_trips_json() {
+        let json = to_json(&order()).unwrap();
+        assert!(matches!(&json, Value::String(text) if text.contains("\"customer\":\"Contoso\"")));
+        assert_eq!(from_json::<Order>(json).unwrap(), order());
+        assert!(from_json::<Order>(Value::I32(1)).is_err());
+    }
+}
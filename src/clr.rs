@@ -1,28 +1,21 @@
-use std::ptr::null_mut;
-use windows_core::{Interface, PCWSTR};
-use windows_sys::Win32::System::Variant::{VariantClear, VARIANT};
-use crate::Variant;
 use crate::{
-    WinStr, Result,
-    file::validate_file,
-    create_safe_array_args,
-    Invocation, error::ClrError,
-};
-use crate::com::{
-    CLRCreateInstance, 
-    CLSID_CLRMETAHOST, 
-    CLSID_COR_RUNTIME_HOST
-};
-use crate::data::{
-    ICLRMetaHost, ICLRRuntimeInfo, ICorRuntimeHost, _AppDomain, _Assembly
+    Result,
+    file::{validate_assembly, Bitness},
+    metadata::{AssemblyStore, Bundle, EntryPoint, Metadata},
+    backend::{ClrBackend, DefaultBackend},
+    error::ClrError,
+    RuntimeVersion, RuntimeSelection,
+    select_runtime,
 };
 
 /// Represents a Rust interface to the Common Language Runtime (CLR).
 /// 
 /// This structure allows loading and executing .NET assemblies with specific runtime versions, 
 /// application domains, and arguments.
+/// 
+/// The runtime itself is driven through a `ClrBackend`, which is `ComBackend` on Windows.
 #[derive(Debug, Clone)]
-pub struct RustClr<'a> {
+pub struct RustClr<'a, B: ClrBackend = DefaultBackend> {
     /// Buffer containing the .NET assembly in bytes.
     buffer: &'a [u8],
 
@@ -35,17 +28,29 @@ pub struct RustClr<'a> {
     /// .NET runtime version to use.
     runtime_version: Option<RuntimeVersion>,
 
+    /// Runtime chosen when `RuntimeVersion::Auto` is used.
+    runtime_selection: Option<RuntimeSelection>,
+
     /// Arguments to pass to the .NET assembly's `Main` method.
     args: Option<Vec<String>>,
 
+    /// Entry point resolved from the assembly metadata.
+    entry_point: Option<EntryPoint>,
+
+    /// Static method to invoke instead of the entry point.
+    entry: Option<EntryMethod<B>>,
+
+    /// Dependent assemblies served from memory when the runtime cannot resolve them.
+    dependencies: AssemblyStore,
+
     /// Current application domain where the assembly is loaded.
-    app_domain: Option<_AppDomain>,
+    app_domain: Option<B::Domain>,
 
-    /// Host for the CLR runtime.
-    cor_runtime_host: Option<ICorRuntimeHost>,
+    /// Backend hosting the runtime.
+    backend: B,
 }
 
-impl<'a> Default for RustClr<'a> {
+impl<'a, B: ClrBackend + Default> Default for RustClr<'a, B> {
     /// Provides a default-initialized `RustClr`.
     ///
     /// # Returns
@@ -55,11 +60,15 @@ impl<'a> Default for RustClr<'a> {
         Self { 
             buffer: &[], 
             runtime_version: None,
+            runtime_selection: None,
             redirect_output: false,
             domain_name: None,
             args: None, 
+            entry_point: None,
+            entry: None,
+            dependencies: AssemblyStore::new(),
             app_domain: None,
-            cor_runtime_host: None
+            backend: B::default(),
         }
     }
 }
@@ -67,6 +76,10 @@ impl<'a> Default for RustClr<'a> {
 impl<'a> RustClr<'a> {
     /// Creates a new `RustClr` instance with the specified assembly buffer.
     /// 
+    /// The entry point is resolved from the assembly metadata and validated before
+    /// the runtime is touched, so assemblies that cannot be run are rejected early.
+    /// Class libraries (DLLs) have no entry point; use `with_entry` to pick the method to invoke.
+    /// 
     /// # Arguments
     /// 
     /// * `buffer` - A reference to a byte slice representing the .NET assembly.
@@ -74,7 +87,10 @@ impl<'a> RustClr<'a> {
     /// # Returns
     /// 
     /// * `Ok(Self)` - If the buffer is valid and the `RustClr` instance is created successfully.
-    /// * `Err(ClrError)` - If the buffer validation fails (e.g., not a valid .NET assembly).
+    /// * `Err(ClrError)` - If the buffer validation fails (e.g., not a valid .NET assembly),
+    ///   the image cannot run in this process (mixed-mode, or built for another bitness),
+    ///   the assembly targets .NET Core or is a single-file bundle,
+    ///   or the entry point of an executable is missing or unsupported.
     /// 
     /// # Examples
     /// 
@@ -94,22 +110,71 @@ impl<'a> RustClr<'a> {
     /// }
     /// ```
     pub fn new(buffer: &'a [u8]) -> Result<Self> {
-        // Checks if it is a valid .NET and EXE file
-        validate_file(buffer)?;
+        Self::new_with_backend(buffer, DefaultBackend::default())
+    }
+}
+
+impl<'a, B: ClrBackend> RustClr<'a, B> {
+    /// Creates a new `RustClr` instance driving the runtime through the given backend.
+    ///
+    /// The assembly goes through the same checks as in `RustClr::new`.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - A reference to a byte slice representing the .NET assembly.
+    /// * `backend` - The backend hosting the runtime.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Self)` - If the buffer is valid and the `RustClr` instance is created successfully.
+    /// * `Err(ClrError)` - If the buffer fails the checks of `RustClr::new`.
+    pub fn new_with_backend(buffer: &'a [u8], backend: B) -> Result<Self> {
+        // Single-file apphosts are native executables with the assemblies appended
+        if Bundle::is_bundle(buffer) {
+            return Err(ClrError::UnsupportedFramework("single-file bundle".to_string()));
+        }
+
+        // Checks if it is a valid .NET EXE or DLL file
+        validate_assembly(buffer)?;
+
+        // Rejects images the runtime cannot load into this process, such as x86-only
+        // assemblies in a 64-bit host, before they fail with an opaque HRESULT
+        let metadata = Metadata::parse(buffer)?;
+        metadata.check_host(Bitness::host())?;
+
+        // .NET Core assemblies pass the PE checks but fail inside the .NET Framework CLR
+        let family = metadata.framework_family();
+        if !family.runs_on_net_framework() {
+            let framework = metadata.target_framework().unwrap_or_else(|| family.to_string());
+            return Err(ClrError::UnsupportedFramework(framework));
+        }
+
+        // Resolves the entry point of executables before loading anything into the runtime
+        let entry_point = match metadata.pe().is_dll() {
+            true => None,
+            false => Some(metadata.entry_point()?),
+        };
 
         Ok(Self { 
             buffer, 
             redirect_output: false,
             runtime_version: None,
+            runtime_selection: None,
             domain_name: None, 
             args: None, 
+            entry_point,
+            entry: None,
+            dependencies: AssemblyStore::new(),
             app_domain: None,
-            cor_runtime_host: None
+            backend,
         })
     }
 
     /// Sets the .NET runtime version to use.
     /// 
+    /// With `RuntimeVersion::Auto`, the runtime is chosen from the assembly's metadata
+    /// version and `TargetFrameworkAttribute`, among the runtimes installed on the system.
+    /// 
     /// # Arguments
     /// 
     /// * `version` - The `RuntimeVersion` enum representing the .NET version.
@@ -209,6 +274,178 @@ impl<'a> RustClr<'a> {
         self
     }
 
+    /// Invokes a static method instead of the assembly's entry point.
+    /// 
+    /// This is how class libraries (DLLs) are run. The method is looked up in the assembly
+    /// metadata when `run` is called, before the assembly is loaded, so a misspelled type or
+    /// method fails without touching the runtime.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `type_name` - Full name of the type declaring the method, e.g. `Namespace.Type`.
+    /// * `method` - The method name, or its full signature as printed by `MethodInfo.ToString()`
+    ///   (e.g. `Void Run(System.String, Int32)`) to choose among overloads.
+    /// * `args` - The arguments to pass, e.g. built with `args!`.
+    /// 
+    /// # Returns
+    /// 
+    /// * Returns the modified `RustClr` instance.
+    /// 
+    /// # Examples
+    /// 
+    /// ```ignore
+    /// use rustclr::{args, RustClr};
+    /// use std::fs;
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let buffer = fs::read("examples/library.dll")?;
+    ///
+    ///     // Invoke `static void Tools.Runner.Run(string, int)`
+    ///     let output = RustClr::new(&buffer)?
+    ///         .with_entry("Tools.Runner", "Run", args!["target", 5])
+    ///         .with_output_redirection(true)
+    ///         .run()?;
+    ///
+    ///     println!("Output: {}", output);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn with_entry(mut self, type_name: &str, method: &str, args: Vec<B::Argument>) -> Self {
+        self.entry = Some(EntryMethod {
+            type_name: type_name.to_string(),
+            method: method.to_string(),
+            args: EntryArgs(args),
+        });
+        self
+    }
+
+    /// Registers a dependent assembly to serve from memory.
+    /// 
+    /// When the runtime fails to resolve a reference of the assembly (e.g. a library that is
+    /// not in the GAC), an `AppDomain.AssemblyResolve` handler loads the registered assembly
+    /// with the requested display name, or the one registered last with the same simple name, so
+    /// multi-assembly tools can run without touching the disk.
+    /// 
+    /// # Arguments
+    /// 
+    /// * `buffer` - The bytes of the dependent assembly.
+    /// 
+    /// # Returns
+    /// 
+    /// * `Ok(Self)` - The modified `RustClr` instance.
+    /// * `Err(ClrError)` - If the buffer is not a .NET assembly or has no `Assembly` table.
+    /// 
+    /// # Examples
+    /// 
+    /// ```ignore
+    /// use rustclr::RustClr;
+    /// use std::fs;
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let buffer = fs::read("examples/sample.exe")?;
+    ///     let library = fs::read("examples/library.dll")?;
+    ///
+    ///     // Serve `library.dll` to the runtime when `sample.exe` references it
+    ///     let clr = RustClr::new(&buffer)?
+    ///         .with_dependency(&library)?;
+    ///
+    ///     println!("Dependency registered successfully.");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn with_dependency(mut self, buffer: &[u8]) -> Result<Self> {
+        self.dependencies.insert(buffer)?;
+        Ok(self)
+    }
+
+    /// Registers the assemblies embedded as manifest resources of the assembly as dependencies.
+    /// 
+    /// Packers such as Costura.Fody store the dependencies of a tool as (optionally
+    /// deflate-compressed) resources and unpack them from a module initializer. Registering
+    /// them up front serves them through the same `AssemblyResolve` handler as `with_dependency`.
+    /// 
+    /// # Returns
+    /// 
+    /// * `Ok(Self)` - The modified `RustClr` instance.
+    /// * `Err(ClrError)` - If the metadata cannot be read or a compressed resource is corrupt.
+    /// 
+    /// # Examples
+    /// 
+    /// ```ignore
+    /// use rustclr::RustClr;
+    /// use std::fs;
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let buffer = fs::read("examples/packed.exe")?;
+    ///
+    ///     // Serve the `costura.*.dll.compressed` resources of `packed.exe` from memory
+    ///     let clr = RustClr::new(&buffer)?
+    ///         .with_embedded_dependencies()?;
+    ///
+    ///     println!("Embedded dependencies registered successfully.");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn with_embedded_dependencies(mut self) -> Result<Self> {
+        for (_, assembly) in Metadata::parse(self.buffer)?.embedded_assemblies()? {
+            self.dependencies.insert(&assembly)?;
+        }
+
+        Ok(self)
+    }
+
+    /// Returns the entry point resolved from the assembly metadata.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(&EntryPoint)` - The entry point, including whether it takes arguments.
+    /// * `None` - If the assembly is a DLL or the instance was not created through `RustClr::new`.
+    pub fn entry_point(&self) -> Option<&EntryPoint> {
+        self.entry_point.as_ref()
+    }
+
+    /// Parses the metadata of the assembly buffer, without loading it into a runtime.
+    /// 
+    /// This gives access to everything the offline reader exposes, e.g. the `#Strings`
+    /// identifiers, the `#US` literals with the methods that load them, or the manifest resources.
+    /// 
+    /// # Returns
+    /// 
+    /// * `Ok(Metadata)` - The parsed metadata, borrowing the assembly buffer.
+    /// * `Err(ClrError)` - If the metadata is malformed.
+    /// 
+    /// # Examples
+    /// 
+    /// ```ignore
+    /// use rustclr::RustClr;
+    /// use std::fs;
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let buffer = fs::read("examples/sample.exe")?;
+    ///     let clr = RustClr::new(&buffer)?;
+    ///
+    ///     // List the string literals before running anything
+    ///     for literal in clr.metadata()?.string_literals() {
+    ///         println!("{} {:?}", literal.token, literal.value);
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn metadata(&self) -> Result<Metadata<'a>> {
+        Metadata::parse(self.buffer)
+    }
+
+    /// Returns the runtime chosen by `RuntimeVersion::Auto`.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(&RuntimeSelection)` - The chosen version and the rule that chose it, once the runtime is prepared.
+    /// * `None` - If `RuntimeVersion::Auto` is not in use or the runtime has not been prepared yet.
+    pub fn runtime_selection(&self) -> Option<&RuntimeSelection> {
+        self.runtime_selection.as_ref()
+    }
+
     /// Enables or disables output redirection.
     ///
     /// # Arguments
@@ -221,7 +458,7 @@ impl<'a> RustClr<'a> {
     /// 
     /// # Examples
     ///
-    /// ```rust
+    /// ```ignore
     /// use rustclr::RustClr;
     /// use std::fs;
     ///
@@ -243,31 +480,34 @@ impl<'a> RustClr<'a> {
 
     /// Prepares the CLR environment by initializing the runtime and application domain.
     /// 
+    /// `RuntimeVersion::Auto` is resolved against the installed runtimes and the
+    /// selection is recorded for `RustClr::runtime_selection`.
+    /// 
     /// # Returns
     /// 
     /// * `Ok(())` - If the environment is successfully prepared.
     /// * `Err(ClrError)` - If any error occurs during the preparation process.
     fn prepare(&mut self) -> Result<()> {
-        // Creates the MetaHost to access the available CLR versions
-        let meta_host = self.create_meta_host()?;
-
-        // Gets information about the specified (or default) runtime version
-        let runtime_info = self.get_runtime_info(&meta_host)?;
-
-        // Creates the runtime host
-        let cor_runtime_host = self.get_runtime_host(&runtime_info)?;
+        // Resolves the specified (or default) runtime version
+        let version = match self.runtime_version.unwrap_or(RuntimeVersion::V4) {
+            RuntimeVersion::Auto => {
+                let metadata = Metadata::parse(self.buffer)?;
+                let installed = self.backend.installed_runtimes()?;
+                let selection = select_runtime(Some(metadata.version()), metadata.target_framework().as_deref(), &installed)
+                    .ok_or_else(|| ClrError::RuntimeInfoError(format!("no installed runtime can run {}", metadata.version())))?;
+
+                let version = RuntimeVersion::custom(&selection.version);
+                self.runtime_selection = Some(selection);
+                version
+            }
+            version => version,
+        };
 
-        // Checks if the runtime is started
-        if runtime_info.IsLoadable().is_ok() && !runtime_info.is_started() {
-            // Starts the CLR runtime
-            self.start_runtime(&cor_runtime_host)?;
-        }
+        // Starts the runtime, unless it is already running
+        self.backend.start(&version)?;
 
         // Initializes the specified application domain or the default
-        self.init_app_domain(&cor_runtime_host)?;
-
-        // Saves the runtime host for future use
-        self.cor_runtime_host = Some(cor_runtime_host);
+        self.app_domain = Some(self.backend.create_domain(self.domain_name.as_deref())?);
 
         Ok(())
     }
@@ -303,46 +543,62 @@ impl<'a> RustClr<'a> {
     /// }
     /// ```
     pub fn run(&mut self) -> Result<String> {
+        // Checks that the chosen method exists, or that `Main` gets its arguments, before starting the runtime
+        let signature = match &self.entry {
+            Some(entry) => {
+                let metadata = Metadata::parse(self.buffer)?;
+                let (_, method) = metadata.find_static_method(&entry.type_name, &entry.method, entry.args.0.len())?;
+                Some(method.to_string())
+            }
+            None => {
+                match &self.entry_point {
+                    Some(entry_point) if entry_point.takes_args && self.args.is_none() => return Err(ClrError::MissingArguments),
+                    Some(_) => {}
+                    None => return Err(ClrError::EntryPointNotFound),
+                }
+
+                None
+            }
+        };
+
         // Prepare the CLR environment
         self.prepare()?;
 
         // Gets the current application domain
         let domain = self.get_app_domain()?;
 
-        // Loads the .NET assembly specified by the buffer
-        let assembly = domain.load_assembly(self.buffer)?;
+        // Serves the registered dependencies from memory, for as long as the assembly runs
+        let resolver = match self.dependencies.is_empty() {
+            true => None,
+            false => Some(self.backend.install_resolver(&domain, &self.dependencies)?),
+        };
 
-        // Prepares the parameters for the `Main` method
-        let parameters = self.args.as_ref().map_or_else(
-            || Ok(null_mut()),
-            |args| create_safe_array_args(args.to_vec())
-        )?;
+        // Loads the .NET assembly specified by the buffer
+        let assembly = self.backend.load_assembly(&domain, self.buffer)?;
+
+        // Invokes the chosen static method, or the `Main` method of the assembly
+        let (entry, args) = (&self.entry, self.args.as_deref());
+        let mut invoke = |backend: &mut B| match (entry, &signature) {
+            (Some(entry), Some(signature)) => {
+                let ty = backend.resolve_type(&assembly, &entry.type_name)?;
+                backend.invoke_static(&ty, signature, &entry.args.0)
+            }
+            _ => backend.run_entry_point(&assembly, args),
+        };
 
         // Redirects output if enabled
         let output = if self.redirect_output {
-            // Loads the mscorlib library for output redirection
-            let mscorlib = domain.get_assembly("mscorlib")?;
-            let mut output_manager = ClrOutput::new(&mscorlib);
-            
-            // Redirecting output
-            output_manager.redirect()?;
-
-            // Invokes the `Main` method of the assembly
-            assembly.run(parameters)?;
-
-            // Restores output if redirected
-            let output = output_manager.capture()?;
-            output_manager.restore()?;
-            output
+            self.backend.capture_output(&domain, &mut invoke)?
         } else {
-            // Invokes the `Main` method of the assembly
-            assembly.run(parameters)?;
+            // Invokes the method
+            invoke(&mut self.backend)?;
 
             // Empty output
             String::new()
         };
 
         // Unload Domain
+        drop(resolver);
         self.unload_domain()?;
         Ok(output)
     }
@@ -351,99 +607,12 @@ impl<'a> RustClr<'a> {
     /// 
     /// # Returns
     /// 
-    /// * `Ok(_AppDomain)` - If the application domain is available.
+    /// * `Ok(B::Domain)` - If the application domain is available.
     /// * `Err(ClrError)` - If no application domain is available.
-    fn get_app_domain(&mut self) -> Result<_AppDomain> {
+    fn get_app_domain(&mut self) -> Result<B::Domain> {
         self.app_domain.clone().ok_or(ClrError::NoDomainAvailable)
     }
 
-    /// Creates an instance of `ICLRMetaHost`.
-    /// 
-    /// # Returns
-    /// 
-    /// * `Ok(ICLRMetaHost)` - If the instance is created successfully.
-    /// * `Err(ClrError)` - If the instance creation fails.
-    fn create_meta_host(&self) -> Result<ICLRMetaHost> {
-        CLRCreateInstance::<ICLRMetaHost>(&CLSID_CLRMETAHOST)
-            .map_err(|e| ClrError::MetaHostCreationError(format!("{e}")))
-    }
-
-    /// Retrieves runtime information based on the selected .NET version.
-    /// 
-    /// # Arguments
-    /// 
-    /// * `meta_host` - Reference to the `ICLRMetaHost` instance.
-    /// 
-    /// # Returns
-    /// 
-    /// * `Ok(ICLRRuntimeInfo)` - If runtime information is retrieved successfully.
-    /// * `Err(ClrError)` - If the retrieval fails.
-    fn get_runtime_info(&self, meta_host: &ICLRMetaHost) -> Result<ICLRRuntimeInfo> {
-        let runtime_version = self.runtime_version.unwrap_or(RuntimeVersion::V4);
-        let version_wide = runtime_version.to_vec();
-        let version = PCWSTR(version_wide.as_ptr());
-
-        meta_host.GetRuntime::<ICLRRuntimeInfo>(version)
-            .map_err(|e| ClrError::RuntimeInfoError(format!("{e}")))
-    }
-
-    /// Gets the runtime host interface from the provided runtime information.
-    /// 
-    /// # Arguments
-    /// 
-    /// * `runtime_info` - Reference to the `ICLRRuntimeInfo` instance.
-    /// 
-    /// # Returns
-    /// 
-    /// * `Ok(ICorRuntimeHost)` - If the interface is obtained successfully.
-    /// * `Err(ClrError)` - If the retrieval fails.
-    fn get_runtime_host(&self, runtime_info: &ICLRRuntimeInfo) -> Result<ICorRuntimeHost> {
-        runtime_info.GetInterface::<ICorRuntimeHost>(&CLSID_COR_RUNTIME_HOST)
-            .map_err(|e| ClrError::RuntimeHostError(format!("{e}")))
-    }
-
-    /// Starts the CLR runtime using the provided runtime host.
-    /// 
-    /// # Arguments
-    /// 
-    /// * `cor_runtime_host` - Reference to the `ICorRuntimeHost` instance.
-    /// 
-    /// # Returns
-    /// 
-    /// * `Ok(())` - If the runtime starts successfully.
-    /// * `Err(ClrError)` - If the runtime fails to start.
-    fn start_runtime(&self, cor_runtime_host: &ICorRuntimeHost) -> Result<()> {
-        if cor_runtime_host.Start() != 0 {
-            return Err(ClrError::RuntimeStartError);
-        }
-
-        Ok(())
-    }
-
-    /// Initializes the application domain with the specified name or uses the default domain.
-    /// 
-    /// # Arguments
-    /// 
-    /// * `cor_runtime_host` - Reference to the `ICorRuntimeHost` instance.
-    /// 
-    /// # Returns
-    /// 
-    /// * `Ok(())` - If the application domain is successfully initialized.
-    /// * `Err(ClrError)` - If the initialization fails.
-    fn init_app_domain(&mut self, cor_runtime_host: &ICorRuntimeHost) -> Result<()> {
-        // Creates the application domain based on the specified name or uses the default domain
-        let app_domain = if let Some(domain_name) = &self.domain_name {
-            let wide_domain_name = domain_name.encode_utf16().chain(Some(0)).collect::<Vec<u16>>();
-            cor_runtime_host.CreateDomain(PCWSTR(wide_domain_name.as_ptr()), null_mut())?
-        } else {
-            cor_runtime_host.GetDefaultDomain()?
-        };
-        // Saves the created application domain
-        self.app_domain = Some(app_domain);
-
-        Ok(())
-    }
-
     /// Unloads the current application domain.
     ///
     /// This method is used to properly unload a custom AppDomain created by `RustClr`.
@@ -456,353 +625,164 @@ impl<'a> RustClr<'a> {
         if self.domain_name.is_none() {
             return Ok(())
         }
-        if let Some(cor_runtime_host) = &self.cor_runtime_host{
-            if let Some(app_domain) = self.app_domain.take(){
-                // Attempt to unload the AppDomain, log error if it fails
-                cor_runtime_host.UnloadDomain(app_domain.cast::<windows_core::IUnknown>()
-                    .map(|i| i.as_raw().cast())
-                    .unwrap_or(null_mut())
-                )?
-            }
-        
+        if let Some(app_domain) = self.app_domain.take() {
+            self.backend.unload_domain(app_domain)?;
         }
         Ok(())
     }
 }
 
 /// Implements the `Drop` trait to release memory when `RustClr` goes out of scope.
-impl<'a> Drop for RustClr<'a> {
+impl<'a, B: ClrBackend> Drop for RustClr<'a, B> {
     fn drop(&mut self) {
         let _ = self.unload_domain();
-        if let Some(cor_runtime_host) = &self.cor_runtime_host {
-            // Attempt to stop the CLR runtime
-            cor_runtime_host.Stop();
-        }
-    }
-}
-
-/// Manages output redirection in the CLR by using a `StringWriter`.
-///
-/// This struct handles the redirection of standard output and error streams
-/// to a `StringWriter` instance, enabling the capture of output produced
-/// by the .NET code.
-pub struct ClrOutput<'a> {
-    /// The `StringWriter` instance used to capture output.
-    string_writer: Option<VARIANT>,
-
-    /// Reference to the `mscorlib` assembly for creating types.
-    mscorlib: &'a _Assembly,
-}
-
-impl<'a> ClrOutput<'a> {
-    /// Creates a new `ClrOutput`.
-    ///
-    /// # Arguments
-    ///
-    /// * `mscorlib` - An instance of the `_Assembly` representing `mscorlib`.
-    ///
-    /// # Returns
-    ///
-    /// * A new instance of `ClrOutput`.
-    pub fn new(mscorlib: &'a _Assembly) -> Self {
-        Self {
-            string_writer: None,
-            mscorlib
-        }
-    }
-
-    /// Redirects standard output and error streams to a `StringWriter`.
-    ///
-    /// # Returns
-    ///
-    /// * `Ok(())` - If the redirection is successful.
-    /// * `Err(ClrError)` - If an error occurs while attempting to redirect the streams.
-    pub fn redirect(&mut self) -> Result<()> {
-        let console = self.mscorlib.resolve_type("System.Console")?;
-        let string_writer = self.mscorlib.create_instance("System.IO.StringWriter")?;
-
-        // Invokes the methods
-        console.invoke("SetOut", None, Some(vec![string_writer]), Invocation::Static)?;
-        console.invoke("SetError", None, Some(vec![string_writer]), Invocation::Static)?;
-
-        // Saves the StringWriter instance to retrieve the output later
-
-        // console.invoke("WriteLine", None, Some(vec!["Hello World111".to_variant()]), Invocation::Static)?;
 
-        self.string_writer = Some(string_writer);
-
-        Ok(())
-    }
-
-    /// Restores the original standard output and error streams.
-    ///
-    /// # Returns
-    ///
-    /// * `Ok(())` - If the restoration is successful.
-    /// * `Err(ClrError)` - If an error occurs while restoring the streams.
-    pub fn restore(&mut self) -> Result<()> {
-        let console = self.mscorlib.resolve_type("System.Console")?;
-        console.method_signature("Void InitializeStdOutError(Boolean)")?
-            .invoke(None, Some(crate::create_safe_args(vec![true.to_variant()])?))?;
-
-        Ok(())
-    }
-
-    /// Captures the content of the `StringWriter` as a `String`.
-    ///
-    /// # Returns
-    ///
-    /// * `Ok(String)` - The captured output as a string if successful.
-    /// * `Err(ClrError)` - If an error occurs while capturing the output.
-    pub fn capture(&self) -> Result<String> {
-        // Ensure that the StringWriter instance is available
-        let mut instance = self.string_writer.ok_or(ClrError::ErrorClr("No StringWriter instance found"))?;
-        
-        // Resolve the 'ToString' method on the StringWriter type
-        let string_writer = self.mscorlib.resolve_type("System.IO.StringWriter")?;
-        let to_string = string_writer.method("ToString")?;
-        
-        // Invoke 'ToString' on the StringWriter instance
-        let result = to_string.invoke(Some(instance), None)?;
-
-        // Extract the BSTR from the result
-        let bstr = unsafe { result.Anonymous.Anonymous.Anonymous.bstrVal };
-
-        // Clean Variant
-        unsafe { VariantClear(&mut instance as *mut _) };
-
-        // Convert the BSTR to a UTF-8 String
-        Ok(bstr.to_string())
+        // Attempt to stop the CLR runtime
+        self.backend.stop();
     }
 }
 
-/// Represents a simplified interface to the CLR components without loading assemblies.
-#[derive(Debug)]
-pub struct RustClrEnv {
-    /// .NET runtime version to use.
-    pub runtime_version: RuntimeVersion,
-
-    /// MetaHost for accessing CLR components.
-    pub meta_host: ICLRMetaHost,
-
-    /// Runtime information for the specified CLR version.
-    pub runtime_info: ICLRRuntimeInfo,
+/// A static method chosen with `RustClr::with_entry`.
+#[derive(Debug, Clone)]
+struct EntryMethod<B: ClrBackend> {
+    /// Full name of the declaring type.
+    type_name: String,
 
-    /// Host for the CLR runtime.
-    pub cor_runtime_host: ICorRuntimeHost,
+    /// Method name or full signature.
+    method: String,
 
-    /// Current application domain.
-    pub app_domain: _AppDomain,
+    /// Arguments passed to the method.
+    args: EntryArgs<B>,
 }
 
-impl RustClrEnv {
-    /// Creates a new `RustClrEnv` instance with the specified runtime version.
-    ///
-    /// # Arguments
-    ///
-    /// * `runtime_version` - The .NET runtime version to use.
-    ///
-    /// # Returns
-    ///
-    /// * `Ok(Self)` - If the components are initialized successfully.
-    /// * `Err(ClrError)` - If initialization fails at any step.
-    ///
-    /// # Examples
-    ///
-    /// ```ignore
-    /// use rustclr::{RustClrEnv, RuntimeVersion};
-    ///
-    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
-    ///     // Create a new RustClrEnv with a specific runtime version
-    ///     let clr_env = RustClrEnv::new(Some(RuntimeVersion::V4))?;
-    ///
-    ///     println!("CLR initialized successfully.");
-    ///     Ok(())
-    /// }
-    /// ```
-    pub fn new(runtime_version: Option<RuntimeVersion>) -> Result<Self> {
-        // Initialize MetaHost
-        let meta_host = CLRCreateInstance::<ICLRMetaHost>(&CLSID_CLRMETAHOST)
-            .map_err(|e| ClrError::MetaHostCreationError(format!("{e}")))?;
-
-        // Initialize RuntimeInfo
-        let version_str = runtime_version.unwrap_or(RuntimeVersion::V4).to_vec();
-        let version = PCWSTR(version_str.as_ptr());
-
-        let runtime_info = meta_host.GetRuntime::<ICLRRuntimeInfo>(version)
-            .map_err(|e| ClrError::RuntimeInfoError(format!("{e}")))?;
-
-        // Initialize CorRuntimeHost
-        let cor_runtime_host = runtime_info.GetInterface::<ICorRuntimeHost>(&CLSID_COR_RUNTIME_HOST)
-            .map_err(|e| ClrError::RuntimeHostError(format!("{e}")))?;
-        
-        if cor_runtime_host.Start() != 0 {
-            return Err(ClrError::RuntimeStartError);
-        }
+/// Arguments of an `EntryMethod`, kept apart since backend arguments such as `VARIANT`
+/// do not implement `Debug`.
+#[derive(Clone)]
+struct EntryArgs<B: ClrBackend>(Vec<B::Argument>);
 
-        // Initialize AppDomain
-        let uuid = uuid::Uuid::new_v4()
-            .to_string()
-            .encode_utf16()
-            .chain(Some(0))
-            .collect::<Vec<u16>>();
-
-        let app_domain = cor_runtime_host.CreateDomain(PCWSTR(uuid.as_ptr()), null_mut())
-            .map_err(|_| ClrError::NoDomainAvailable)?;
-
-        // Return the initialized instance
-        Ok(Self {
-            runtime_version: runtime_version.unwrap_or(RuntimeVersion::V4),
-            meta_host,
-            runtime_info,
-            cor_runtime_host,
-            app_domain,
-        })
+impl<B: ClrBackend> core::fmt::Debug for EntryArgs<B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "EntryArgs({} arguments)", self.0.len())
     }
 }
 
-impl Drop for RustClrEnv {
-    fn drop(&mut self) {
-        // Attempt to unload the AppDomain, log error if it fails
-        if let Err(e) = self.cor_runtime_host.UnloadDomain(
-            self.app_domain.cast::<windows_core::IUnknown>()
-                        .map(|i| i.as_raw().cast())
-                        .unwrap_or(null_mut()))
-        {
-            eprintln!("Failed to unload AppDomain: {:?}", e);
-        }
-
-        // Attempt to stop the CLR runtime
-        self.cor_runtime_host.Stop();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell::RefCell, rc::Rc};
+    use crate::backend::fake::FakeBackend;
+    use crate::metadata::{builder::AssemblyBuilder, TableId};
+
+    /// Builds an executable whose `Program.Main` has the given signature.
+    fn executable(main_sig: &[u8]) -> Vec<u8> {
+        let mut b = AssemblyBuilder::new();
+        let program = b.string("Program");
+        let main = b.string("Main");
+        let sig = b.blob(main_sig);
+        b.row(TableId::TypeDef, &[0x0010_0001, program, 0, 0, 1, 1]);
+        b.row(TableId::MethodDef, &[0x2050, 0, 0x0016, main, sig, 1]);
+        b.entry_point = 0x0600_0001;
+        b.build()
     }
-}
-
-/// Represents the .NET runtime versions supported by RustClr.
-#[derive(Debug, Clone, Copy)]
-pub enum RuntimeVersion {
-    /// .NET Framework 2.0, identified by version `v2.0.50727`.
-    V2,
-    
-    /// .NET Framework 3.0, identified by version `v3.0`.
-    V3,
-    
-    /// .NET Framework 4.0, identified by version `v4.0.30319`.
-    V4,
-
-    /// Represents an unknown or unsupported .NET runtime version.
-    UNKNOWN,
-}
 
-impl RuntimeVersion {
-    /// Converts the `RuntimeVersion` to a wide string representation as a `Vec<u16>`.
-    ///
-    /// # Returns
-    ///
-    /// A `Vec<u16>` containing the .NET runtime version as a null-terminated wide string.
-    fn to_vec(self) -> Vec<u16> {
-        let runtime_version = match self {
-            RuntimeVersion::V2 => "v2.0.50727",
-            RuntimeVersion::V3 => "v3.0",
-            RuntimeVersion::V4 => "v4.0.30319",
-            RuntimeVersion::UNKNOWN => "UNKNOWN",
-        };
-
-        runtime_version.encode_utf16().chain(Some(0)).collect::<Vec<u16>>()
+    /// Builds a library declaring `static void Tools.Runner.Run(int)`.
+    fn library() -> Vec<u8> {
+        let mut b = AssemblyBuilder::new();
+        b.characteristics |= 0x2000;
+        let tools = b.string("Tools");
+        let runner = b.string("Runner");
+        let run = b.string("Run");
+        let sig = b.blob(&[0x00, 0x01, 0x01, 0x08]);
+        b.row(TableId::TypeDef, &[0x0010_0001, runner, tools, 0, 1, 1]);
+        b.row(TableId::MethodDef, &[0x2050, 0, 0x0096, run, sig, 1]);
+        b.build()
     }
-}
-
-/// Provides a persistent interface for executing PowerShell commands
-/// from a .NET runtime hosted inside a Rust application.
-pub struct PowerShell {
-    /// The loaded .NET automation assembly (`System.Management.Automation`),
-    /// used to resolve types like `Runspace`, `Pipeline`, `PSObject`, etc.
-    automation: _Assembly,
-
-    /// CLR environment used to host the .NET runtime.
-    /// This is kept alive to ensure assemblies and types remain valid.
-    _clr: RustClrEnv,
-}
 
-impl PowerShell {
-    /// Creates a new PowerShell session by initializing the .NET CLR
-    /// and loading the `System.Management.Automation` assembly.
-    ///
-    /// # Returns
-    ///
-    /// A new `PowerShell` instance ready to execute commands.
-    pub fn new() -> Result<Self> {
-        // Initialize .NET runtime (v4.0).
-        let clr = RustClrEnv::new(None)?;
+    /// Returns a fake backend printing `output`, along with its call log.
+    fn backend(output: &str) -> (FakeBackend, Rc<RefCell<Vec<String>>>) {
+        let mut backend = FakeBackend::default();
+        backend.output = output.to_string();
+        let calls = backend.calls.clone();
+        (backend, calls)
+    }
 
-        // Load `mscorlib` and resolve `System.Reflection.Assembly`.
-        let mscorlib = clr.app_domain.get_assembly("mscorlib")?;
-        let reflection_assembly = mscorlib.resolve_type("System.Reflection.Assembly")?;
+    #[test]
+    fn runs_entry_point_with_redirected_output() {
+        let buffer = executable(&[0x00, 0x01, 0x01, 0x1D, 0x0E]);
+        let (backend, calls) = backend("Hello\n");
+        let mut clr = RustClr::new_with_backend(&buffer, backend).unwrap()
+            .with_domain("Custom")
+            .with_args(vec!["a".to_string(), "b".to_string()])
+            .with_output_redirection(true);
+
+        assert_eq!(clr.run().unwrap(), "Hello\n");
+        drop(clr);
+        assert_eq!(*calls.borrow(), [
+            "start v4.0.30319",
+            "create_domain Custom",
+            "load_assembly Custom",
+            "capture_output Custom",
+            "run_entry_point a b",
+            "unload_domain Custom",
+            "stop",
+        ]);
+    }
 
-        // Resolve and invoke `LoadWithPartialName` method.
-        let load_partial_name = reflection_assembly.method_signature("System.Reflection.Assembly LoadWithPartialName(System.String)")?;
-        let param = crate::create_safe_args(vec!["System.Management.Automation".to_variant()])?;
-        let result = load_partial_name.invoke(None, Some(param))?;
+    #[test]
+    fn invokes_static_methods_in_default_domain() {
+        let buffer = library();
+        let (backend, calls) = backend("ignored");
+        let mut clr = RustClr::new_with_backend(&buffer, backend).unwrap()
+            .with_entry("Tools.Runner", "Run", vec![5]);
+
+        assert_eq!(clr.run().unwrap(), "");
+        drop(clr);
+        assert_eq!(*calls.borrow(), [
+            "start v4.0.30319",
+            "create_domain DefaultDomain",
+            "load_assembly DefaultDomain",
+            "resolve_type Tools.Runner",
+            "invoke_static Tools.Runner::Void Run(Int32) [5]",
+            "stop",
+        ]);
+    }
 
-        // Convert result to `_Assembly`.
-        let automation = _Assembly::from_raw(unsafe { result.Anonymous.Anonymous.Anonymous.byref })?;
+    #[test]
+    fn selects_installed_runtime() {
+        let buffer = executable(&[0x00, 0x00, 0x01]);
+        let (mut backend, calls) = backend("");
+        backend.installed = vec!["v2.0.50727".to_string(), "v4.0.30319".to_string()];
+        let mut clr = RustClr::new_with_backend(&buffer, backend).unwrap()
+            .with_runtime_version(RuntimeVersion::Auto);
+
+        clr.run().unwrap();
+        assert_eq!(clr.runtime_selection().map(|selection| selection.version.as_str()), Some("v4.0.30319"));
+        assert_eq!(calls.borrow()[..2], ["installed_runtimes", "start v4.0.30319"]);
+    }
 
-        Ok(Self {
-            automation,
-            _clr: clr,
-        })
+    #[test]
+    fn rejects_missing_entries_before_starting() {
+        let buffer = executable(&[0x00, 0x01, 0x01, 0x1D, 0x0E]);
+        let (backend, calls) = backend("");
+        let mut clr = RustClr::new_with_backend(&buffer, backend).unwrap();
+        assert!(matches!(clr.run(), Err(ClrError::MissingArguments)));
+
+        let buffer = library();
+        let mut clr = RustClr::new_with_backend(&buffer, FakeBackend::default()).unwrap();
+        assert!(matches!(clr.run(), Err(ClrError::EntryPointNotFound)));
+        assert!(calls.borrow().is_empty());
     }
-    /// Executes a PowerShell command and returns its output as a string.
-    ///
-    /// This method creates a new temporary `Runspace` and `Pipeline` for
-    /// each invocation. The result is captured via `PSObject.ToString()`.
-    ///
-    /// # Arguments
-    ///
-    /// * `command` - A PowerShell command to be executed.
-    ///
-    /// # Returns
-    ///
-    /// * Returns the textual output of the PowerShell command.
-    pub fn execute(&self, command: &str) -> Result<String> {
-        // Invoke `CreateRunspace` method.
-        let runspace_factory = self.automation.resolve_type("System.Management.Automation.Runspaces.RunspaceFactory")?;
-        let create_runspace = runspace_factory.method_signature("System.Management.Automation.Runspaces.Runspace CreateRunspace()")?;
-        let runspace = create_runspace.invoke(None, None)?;
-
-        // Invoke `CreatePipeline` method.
-        let assembly_runspace = self.automation.resolve_type("System.Management.Automation.Runspaces.Runspace")?;
-        assembly_runspace.invoke("Open", Some(runspace), None, Invocation::Instance)?;
-        let create_pipeline = assembly_runspace.method_signature("System.Management.Automation.Runspaces.Pipeline CreatePipeline()")?;
-        let pipe = create_pipeline.invoke(Some(runspace), None)?;
-
-        // Invoke `get_Commands` method.
-        let pipeline = self.automation.resolve_type("System.Management.Automation.Runspaces.Pipeline")?;
-        let get_command = pipeline.invoke("get_Commands", Some(pipe), None, Invocation::Instance)?;
-
-        // Invoke `AddScript` method.
-        let command_collection = self.automation.resolve_type("System.Management.Automation.Runspaces.CommandCollection")?;
-        let cmd= vec![format!("{} | Out-String", command).to_variant()];
-        let args = crate::create_safe_args(cmd)?;
-        let add_script = command_collection.method_signature("Void AddScript(System.String)")?;
-        add_script.invoke(Some(get_command), Some(args))?;
-
-        // Invoke `InvokeAsync` method.
-        pipeline.invoke("InvokeAsync", Some(pipe), None, Invocation::Instance)?;
-
-        // Invoke `get_Output` method.
-        let get_output = pipeline.invoke("get_Output", Some(pipe), None, Invocation::Instance)?;
-
-        // Invoke `Read` method.
-        let pipeline_reader = self.automation.resolve_type("System.Management.Automation.Runspaces.PipelineReader`1[System.Management.Automation.PSObject]")?;
-        let read = pipeline_reader.method_signature("System.Management.Automation.PSObject Read()")?;
-        let ps_object_instance = read.invoke(Some(get_output), None)?;
-
-        // Invoke `ToString` method.
-        let ps_object = self.automation.resolve_type("System.Management.Automation.PSObject")?;
-        let to_string = ps_object.method_signature("System.String ToString()")?;
-        let output = to_string.invoke(Some(ps_object_instance), None)?;
-
-        assembly_runspace.invoke("Close", Some(runspace), None, Invocation::Instance)?;
-        Ok(unsafe { output.Anonymous.Anonymous.Anonymous.bstrVal.to_string() })
+
+    #[test]
+    fn unloads_domain_when_dropped_after_failure() {
+        let buffer = executable(&[0x00, 0x00, 0x01]);
+        let (mut backend, calls) = backend("");
+        backend.fail = Some("run_entry_point");
+        let mut clr = RustClr::new_with_backend(&buffer, backend).unwrap()
+            .with_domain("Custom");
+
+        assert!(clr.run().is_err());
+        drop(clr);
+        assert_eq!(calls.borrow()[calls.borrow().len() - 2..], ["unload_domain Custom", "stop"]);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,655 @@
+use std::{
+    collections::HashSet,
+    ffi::{c_char, c_void, CString},
+    fmt, fs,
+    path::{Path, PathBuf},
+    ptr::null_mut,
+};
+use crate::{
+    error::ClrError, Result,
+    file::is_dotnet,
+    hostfxr::{dotnet_roots, latest_version_dir},
+    library::Library,
+    metadata::Metadata,
+    runtimeconfig::NETCORE_APP,
+};
+
+/// File name of the `coreclr` library.
+#[cfg(windows)]
+const CORECLR_LIBRARY: &str = "coreclr.dll";
+#[cfg(target_os = "macos")]
+const CORECLR_LIBRARY: &str = "libcoreclr.dylib";
+#[cfg(all(unix, not(target_os = "macos")))]
+const CORECLR_LIBRARY: &str = "libcoreclr.so";
+
+/// Separator of the path lists passed as runtime properties.
+const PATH_LIST_SEPARATOR: char = if cfg!(windows) { ';' } else { ':' };
+
+/// Name of the AppDomain created when none is given.
+const DEFAULT_DOMAIN: &str = "rustclr";
+
+/// `coreclr_initialize`.
+type InitializeFn = unsafe extern "system" fn(
+    exe_path: *const c_char,
+    app_domain_friendly_name: *const c_char,
+    property_count: i32,
+    property_keys: *const *const c_char,
+    property_values: *const *const c_char,
+    host_handle: *mut *mut c_void,
+    domain_id: *mut u32,
+) -> i32;
+
+/// `coreclr_execute_assembly`.
+type ExecuteAssemblyFn = unsafe extern "system" fn(
+    host_handle: *mut c_void,
+    domain_id: u32,
+    argc: i32,
+    argv: *const *const c_char,
+    managed_assembly_path: *const c_char,
+    exit_code: *mut u32,
+) -> i32;
+
+/// `coreclr_create_delegate`.
+type CreateDelegateFn = unsafe extern "system" fn(
+    host_handle: *mut c_void,
+    domain_id: u32,
+    assembly_name: *const c_char,
+    type_name: *const c_char,
+    method_name: *const c_char,
+    delegate: *mut *mut c_void,
+) -> i32;
+
+/// `coreclr_shutdown_2`.
+type ShutdownFn = unsafe extern "system" fn(host_handle: *mut c_void, domain_id: u32, latched_exit_code: *mut i32) -> i32;
+
+/// The Trusted Platform Assemblies (TPA) list: the assemblies CoreCLR may load without probing.
+///
+/// Assemblies are identified by file name, so the first path added for a name wins,
+/// which lets an application override a framework assembly by adding its copy first.
+///
+/// # Examples
+///
+/// ```ignore
+/// use rustclr::TrustedPlatformAssemblies;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let tpa = TrustedPlatformAssemblies::new()
+///         .with_assembly("app/Library.dll")
+///         .with_shared_framework(None)?;
+///
+///     println!("{} assemblies", tpa.len());
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrustedPlatformAssemblies {
+    /// The assembly paths, in the order they were added.
+    paths: Vec<PathBuf>,
+
+    /// Lower-case file names of `paths`, used to skip duplicates.
+    names: HashSet<String>,
+}
+
+impl TrustedPlatformAssemblies {
+    /// Creates an empty list.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an assembly, unless one with the same file name is already listed.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Full path of the assembly.
+    ///
+    /// # Returns
+    ///
+    /// * Returns the modified list.
+    pub fn with_assembly(mut self, path: impl AsRef<Path>) -> Self {
+        self.add(path.as_ref());
+        self
+    }
+
+    /// Adds every `.dll` of a directory, e.g. a shared framework or an application directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory to scan (not recursively).
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Self)` - The list with the assemblies of the directory appended, in file name order.
+    /// * `Err(ClrError)` - `ClrError::HostNotFound` if the directory cannot be read.
+    pub fn with_directory(mut self, dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref();
+        let entries = fs::read_dir(dir).map_err(|_| ClrError::HostNotFound(dir.display().to_string()))?;
+        let mut paths = entries
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| {
+                path.is_file() && path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("dll"))
+            })
+            .collect::<Vec<_>>();
+
+        paths.sort();
+        for path in paths {
+            self.add(&path);
+        }
+
+        Ok(self)
+    }
+
+    /// Adds the assemblies of the newest `Microsoft.NETCore.App` shared framework.
+    ///
+    /// # Arguments
+    ///
+    /// * `dotnet_root` - The installation to use, instead of `DOTNET_ROOT` or the default locations.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Self)` - The list with the framework assemblies appended.
+    /// * `Err(ClrError)` - `ClrError::HostNotFound` if no shared framework is installed.
+    pub fn with_shared_framework(self, dotnet_root: Option<&Path>) -> Result<Self> {
+        self.with_directory(shared_framework_dir(dotnet_root)?)
+    }
+
+    /// Returns the assembly paths, in the order they were added.
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /// Returns the number of assemblies in the list.
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    /// Returns `true` if the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// Adds `path` if no assembly with the same file name is listed.
+    fn add(&mut self, path: &Path) {
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            return;
+        };
+
+        if self.names.insert(name.to_lowercase()) {
+            self.paths.push(path.to_path_buf());
+        }
+    }
+}
+
+impl fmt::Display for TrustedPlatformAssemblies {
+    /// Formats the list the way the `TRUSTED_PLATFORM_ASSEMBLIES` property expects it.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, path) in self.paths.iter().enumerate() {
+            if i > 0 {
+                write!(f, "{PATH_LIST_SEPARATOR}")?;
+            }
+
+            write!(f, "{}", path.display())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A started CoreCLR runtime and the exports used to drive it.
+#[derive(Debug)]
+struct CoreRuntime {
+    /// Handle returned by `coreclr_initialize`.
+    handle: *mut c_void,
+
+    /// Identifier of the AppDomain created by `coreclr_initialize`.
+    domain_id: u32,
+
+    /// `coreclr_execute_assembly`.
+    execute_assembly: ExecuteAssemblyFn,
+
+    /// `coreclr_create_delegate`.
+    create_delegate: CreateDelegateFn,
+
+    /// `coreclr_shutdown_2`.
+    shutdown: ShutdownFn,
+}
+
+/// Hosts CoreCLR directly through `coreclr_initialize`, without `hostfxr` or a `runtimeconfig.json`.
+///
+/// The caller decides which assemblies the runtime trusts (see `TrustedPlatformAssemblies`)
+/// and where it probes for the others. By default the newest shared framework is used,
+/// along with the directory of the assembly.
+///
+/// # Examples
+///
+/// ```ignore
+/// use rustclr::RustCoreClr;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let mut clr = RustCoreClr::new("app/App.dll")?
+///         .with_args(vec!["--verbose".to_string()]);
+///
+///     let exit_code = clr.run()?;
+///     println!("Exited with {exit_code}");
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct RustCoreClr {
+    /// Absolute path of the assembly.
+    assembly_path: PathBuf,
+
+    /// Bytes of the assembly, used to check entry points and methods before calling the runtime.
+    buffer: Vec<u8>,
+
+    /// Simple name of the assembly, passed to `coreclr_create_delegate`.
+    assembly_name: String,
+
+    /// Root of the .NET installation providing the shared framework.
+    dotnet_root: Option<PathBuf>,
+
+    /// Path of the `coreclr` library, defaulting to the one of the shared framework.
+    coreclr_path: Option<PathBuf>,
+
+    /// Trusted Platform Assemblies, defaulting to the shared framework.
+    tpa: Option<TrustedPlatformAssemblies>,
+
+    /// Directories probed for the application's assemblies (`APP_PATHS`).
+    app_paths: Vec<PathBuf>,
+
+    /// Additional runtime properties.
+    properties: Vec<(String, String)>,
+
+    /// Name of the AppDomain.
+    domain_name: Option<String>,
+
+    /// Arguments passed to the entry point by `run`.
+    args: Vec<String>,
+
+    /// The runtime, once started.
+    runtime: Option<CoreRuntime>,
+}
+
+impl RustCoreClr {
+    /// Creates a new `RustCoreClr` for the assembly at `path`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the .NET assembly.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Self)` - If the file is a .NET assembly.
+    /// * `Err(ClrError)` - If the file cannot be read or is not a .NET assembly.
+    pub fn new(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let buffer = fs::read(path).map_err(|_| ClrError::ErrorClr("Failed to read the assembly"))?;
+        if !is_dotnet(&buffer) {
+            return Err(ClrError::NotDotNet);
+        }
+
+        let assembly_name = Metadata::parse(&buffer)?
+            .assembly()
+            .ok_or(ClrError::MetadataError("the module does not define an assembly"))?
+            .name;
+
+        Ok(Self {
+            assembly_path: std::path::absolute(path).map_err(|_| ClrError::ErrorClr("Invalid assembly path"))?,
+            buffer,
+            assembly_name,
+            dotnet_root: None,
+            coreclr_path: None,
+            tpa: None,
+            app_paths: Vec::new(),
+            properties: Vec::new(),
+            domain_name: None,
+            args: Vec::new(),
+            runtime: None,
+        })
+    }
+
+    /// Sets the root of the .NET installation whose shared framework is used by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The directory containing `shared/Microsoft.NETCore.App`.
+    ///
+    /// # Returns
+    ///
+    /// * Returns the modified `RustCoreClr` instance.
+    pub fn with_dotnet_root(mut self, path: impl AsRef<Path>) -> Self {
+        self.dotnet_root = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Sets the path of the `coreclr` library.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Full path of `coreclr.dll`, `libcoreclr.so` or `libcoreclr.dylib`.
+    ///
+    /// # Returns
+    ///
+    /// * Returns the modified `RustCoreClr` instance.
+    pub fn with_coreclr(mut self, path: impl AsRef<Path>) -> Self {
+        self.coreclr_path = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Sets the Trusted Platform Assemblies instead of using the shared framework.
+    ///
+    /// The assembly itself is always appended to the list.
+    ///
+    /// # Arguments
+    ///
+    /// * `tpa` - The assemblies the runtime may load.
+    ///
+    /// # Returns
+    ///
+    /// * Returns the modified `RustCoreClr` instance.
+    pub fn with_tpa(mut self, tpa: TrustedPlatformAssemblies) -> Self {
+        self.tpa = Some(tpa);
+        self
+    }
+
+    /// Adds a directory probed for the application's assemblies (`APP_PATHS`).
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The directory. The directory of the assembly is used when none is given.
+    ///
+    /// # Returns
+    ///
+    /// * Returns the modified `RustCoreClr` instance.
+    pub fn with_app_path(mut self, path: impl AsRef<Path>) -> Self {
+        self.app_paths.push(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Sets a runtime property (an `AppContext` switch such as `System.GC.Server`).
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the property.
+    /// * `value` - Its value.
+    ///
+    /// # Returns
+    ///
+    /// * Returns the modified `RustCoreClr` instance.
+    pub fn with_property(mut self, name: &str, value: &str) -> Self {
+        self.properties.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Sets the name of the AppDomain created by the runtime.
+    ///
+    /// # Arguments
+    ///
+    /// * `domain_name` - The friendly name of the domain.
+    ///
+    /// # Returns
+    ///
+    /// * Returns the modified `RustCoreClr` instance.
+    pub fn with_domain(mut self, domain_name: &str) -> Self {
+        self.domain_name = Some(domain_name.to_string());
+        self
+    }
+
+    /// Sets the arguments passed to the entry point by `run`.
+    ///
+    /// # Arguments
+    ///
+    /// * `args` - The command-line arguments.
+    ///
+    /// # Returns
+    ///
+    /// * Returns the modified `RustCoreClr` instance.
+    pub fn with_args(mut self, args: Vec<String>) -> Self {
+        self.args = args;
+        self
+    }
+
+    /// Loads `coreclr` and starts the runtime with the configured properties.
+    ///
+    /// Called automatically by `run` and `get_function`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the runtime is started.
+    /// * `Err(ClrError)` - If `coreclr` or the shared framework cannot be found, or initialization fails.
+    pub fn prepare(&mut self) -> Result<()> {
+        if self.runtime.is_some() {
+            return Ok(());
+        }
+
+        // The shared framework is only needed for what the caller did not provide
+        let framework_dir = match (&self.coreclr_path, &self.tpa) {
+            (Some(_), Some(_)) => None,
+            _ => Some(shared_framework_dir(self.dotnet_root.as_deref())?),
+        };
+
+        let coreclr_path = self
+            .coreclr_path
+            .clone()
+            .or_else(|| framework_dir.as_ref().map(|dir| dir.join(CORECLR_LIBRARY)))
+            .ok_or_else(|| ClrError::HostNotFound(CORECLR_LIBRARY.to_string()))?;
+
+        let tpa = match (&self.tpa, &framework_dir) {
+            (Some(tpa), _) => tpa.clone(),
+            (None, Some(dir)) => TrustedPlatformAssemblies::new().with_directory(dir)?,
+            (None, None) => TrustedPlatformAssemblies::new(),
+        };
+
+        let app_dir = self.assembly_path.parent().map(Path::to_path_buf).unwrap_or_default();
+        let tpa = tpa.with_assembly(&self.assembly_path);
+        let app_paths = match self.app_paths.is_empty() {
+            true => vec![app_dir.clone()],
+            false => self.app_paths.clone(),
+        };
+
+        let mut native_dirs = app_paths.clone();
+        native_dirs.extend(framework_dir);
+
+        let mut properties = vec![
+            ("TRUSTED_PLATFORM_ASSEMBLIES".to_string(), tpa.to_string()),
+            ("APP_PATHS".to_string(), join_paths(&app_paths)),
+            ("APP_CONTEXT_BASE_DIRECTORY".to_string(), format!("{}{}", app_dir.display(), std::path::MAIN_SEPARATOR)),
+            ("NATIVE_DLL_SEARCH_DIRECTORIES".to_string(), join_paths(&native_dirs)),
+        ];
+
+        properties.extend(self.properties.iter().cloned());
+
+        let keys = properties.iter().map(|(key, _)| c_string(key)).collect::<Result<Vec<_>>>()?;
+        let values = properties.iter().map(|(_, value)| c_string(value)).collect::<Result<Vec<_>>>()?;
+        let keys = keys.iter().map(|key| key.as_ptr()).collect::<Vec<_>>();
+        let values = values.iter().map(|value| value.as_ptr()).collect::<Vec<_>>();
+        let exe_path = c_string(&self.assembly_path.display().to_string())?;
+        let domain_name = c_string(self.domain_name.as_deref().unwrap_or(DEFAULT_DOMAIN))?;
+
+        let library = Library::load(&coreclr_path)?;
+        unsafe {
+            let initialize = library.symbol::<InitializeFn>("coreclr_initialize")?;
+            let execute_assembly = library.symbol::<ExecuteAssemblyFn>("coreclr_execute_assembly")?;
+            let create_delegate = library.symbol::<CreateDelegateFn>("coreclr_create_delegate")?;
+            let shutdown = library.symbol::<ShutdownFn>("coreclr_shutdown_2")?;
+
+            let (mut handle, mut domain_id) = (null_mut(), 0);
+            let code = initialize(
+                exe_path.as_ptr(),
+                domain_name.as_ptr(),
+                keys.len() as i32,
+                keys.as_ptr(),
+                values.as_ptr(),
+                &mut handle,
+                &mut domain_id,
+            );
+
+            if code < 0 || handle.is_null() {
+                return Err(ClrError::ApiError("coreclr_initialize", code));
+            }
+
+            self.runtime = Some(CoreRuntime {
+                handle,
+                domain_id,
+                execute_assembly,
+                create_delegate,
+                shutdown,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Runs the entry point of the assembly.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(i32)` - The exit code returned by `Main` (zero for `void Main`).
+    /// * `Err(ClrError)` - If the assembly has no entry point or the runtime fails to run it.
+    pub fn run(&mut self) -> Result<i32> {
+        Metadata::parse(&self.buffer)?.entry_point()?;
+        self.prepare()?;
+
+        let runtime = self.runtime.as_ref().ok_or(ClrError::ErrorClr("The runtime is not started"))?;
+        let args = self.args.iter().map(|arg| c_string(arg)).collect::<Result<Vec<_>>>()?;
+        let argv = args.iter().map(|arg| arg.as_ptr()).collect::<Vec<_>>();
+        let assembly_path = c_string(&self.assembly_path.display().to_string())?;
+
+        let mut exit_code = 0;
+        let code = unsafe {
+            (runtime.execute_assembly)(
+                runtime.handle,
+                runtime.domain_id,
+                argv.len() as i32,
+                argv.as_ptr(),
+                assembly_path.as_ptr(),
+                &mut exit_code,
+            )
+        };
+
+        if code < 0 {
+            return Err(ClrError::ApiError("coreclr_execute_assembly", code));
+        }
+
+        Ok(exit_code as i32)
+    }
+
+    /// Returns a function pointer to a static method, through `coreclr_create_delegate`.
+    ///
+    /// The type and method are checked against the assembly metadata before the runtime loads it.
+    ///
+    /// # Arguments
+    ///
+    /// * `type_name` - The full name of the type, e.g. `Namespace.Type` or `Namespace.Outer+Inner`.
+    /// * `method` - The name of the method.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(F)` - The method, as the function pointer type `F`.
+    /// * `Err(ClrError)` - If the method does not exist or the runtime fails to bind it.
+    ///
+    /// # Safety
+    ///
+    /// `F` must be an `extern "system"` function pointer type matching the marshalled managed signature.
+    pub unsafe fn get_function<F: Copy>(&mut self, type_name: &str, method: &str) -> Result<F> {
+        assert_eq!(size_of::<F>(), size_of::<*mut c_void>(), "methods must be cast to function pointers");
+
+        let metadata = Metadata::parse(&self.buffer)?;
+        let ty = metadata
+            .find_type(type_name)?
+            .ok_or_else(|| ClrError::TypeNotFound(type_name.to_string()))?;
+
+        if !ty.methods.iter().any(|candidate| candidate.name == method) {
+            return Err(ClrError::MethodNotFound);
+        }
+
+        self.prepare()?;
+        let runtime = self.runtime.as_ref().ok_or(ClrError::ErrorClr("The runtime is not started"))?;
+
+        let mut function = null_mut();
+        let code = (runtime.create_delegate)(
+            runtime.handle,
+            runtime.domain_id,
+            c_string(&self.assembly_name)?.as_ptr(),
+            c_string(type_name)?.as_ptr(),
+            c_string(method)?.as_ptr(),
+            &mut function,
+        );
+
+        if code < 0 || function.is_null() {
+            return Err(ClrError::ApiError("coreclr_create_delegate", code));
+        }
+
+        Ok(core::mem::transmute_copy::<*mut c_void, F>(&function))
+    }
+}
+
+impl Drop for RustCoreClr {
+    /// Shuts the runtime down. CoreCLR cannot be started again in the same process afterwards.
+    fn drop(&mut self) {
+        if let Some(runtime) = self.runtime.take() {
+            let mut exit_code = 0;
+            unsafe { (runtime.shutdown)(runtime.handle, runtime.domain_id, &mut exit_code) };
+        }
+    }
+}
+
+/// Returns the directory of the newest `Microsoft.NETCore.App` shared framework.
+fn shared_framework_dir(dotnet_root: Option<&Path>) -> Result<PathBuf> {
+    dotnet_roots(dotnet_root)
+        .into_iter()
+        .find_map(|root| latest_version_dir(&root.join("shared").join(NETCORE_APP)))
+        .ok_or_else(|| ClrError::HostNotFound(NETCORE_APP.to_string()))
+}
+
+/// Joins paths with the platform's list separator.
+fn join_paths(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(&PATH_LIST_SEPARATOR.to_string())
+}
+
+/// Converts a string to a `CString`, rejecting interior null bytes.
+fn c_string(value: &str) -> Result<CString> {
+    CString::new(value).map_err(|_| ClrError::ErrorClr("Strings passed to coreclr cannot contain null bytes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_the_tpa_list() {
+        let root = std::env::temp_dir().join(format!("rustclr-tpa-{}", std::process::id()));
+        let framework = root.join("shared").join(NETCORE_APP);
+        for version in ["6.0.25", "8.0.1"] {
+            fs::create_dir_all(framework.join(version)).unwrap();
+            for name in ["System.Runtime.dll", "System.Private.CoreLib.dll", "libcoreclr.so"] {
+                fs::write(framework.join(version).join(name), b"").unwrap();
+            }
+        }
+
+        let tpa = TrustedPlatformAssemblies::new()
+            .with_assembly(root.join("app").join("System.Runtime.DLL"))
+            .with_shared_framework(Some(&root))
+            .unwrap();
+
+        let latest = framework.join("8.0.1");
+        assert_eq!(tpa.paths(), &[
+            root.join("app").join("System.Runtime.DLL"),
+            latest.join("System.Private.CoreLib.dll"),
+        ]);
+
+        let expected = format!(
+            "{}{PATH_LIST_SEPARATOR}{}",
+            root.join("app").join("System.Runtime.DLL").display(),
+            latest.join("System.Private.CoreLib.dll").display(),
+        );
+
+        assert_eq!(tpa.to_string(), expected);
+        assert!(TrustedPlatformAssemblies::new().with_shared_framework(Some(&root.join("missing"))).is_err());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
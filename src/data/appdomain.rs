@@ -7,18 +7,15 @@ use super::{_Type, _Assembly};
 use crate::{
     create_safe_array_buffer,
     WinStr, error::ClrError,
-    Result
+    metadata::AssemblyIdentity,
+    Result, SafeArray
 };
 use windows_core::{IUnknown, Interface, GUID};
 use windows_sys::{
     core::{BSTR, HRESULT},
     Win32::System::{
         Com::SAFEARRAY, 
-        Ole::{
-            SafeArrayGetElement, 
-            SafeArrayGetLBound, 
-            SafeArrayGetUBound
-        }
+        Ole::SafeArrayGetElement
     }
 };
 
@@ -39,7 +36,7 @@ impl _AppDomain {
     /// Loads an assembly into the current application domain from a byte slice.
     ///
     /// This method creates a `SAFEARRAY` from the given byte buffer and loads it using 
-    /// the `Load_3` method.
+    /// the `Load_3` method. The array is destroyed once the assembly is loaded.
     ///
     /// # Arguments
     ///
@@ -50,8 +47,8 @@ impl _AppDomain {
     /// * `Ok(_Assembly)` - If successful, returns an `_Assembly` instance.
     /// * `Err(ClrError)` - If loading fails, returns a `ClrError`.
     pub fn load_assembly(&self, buffer: &[u8]) -> Result<_Assembly> {
-        let safe_array = create_safe_array_buffer(&buffer)?;
-        self.Load_3(safe_array)
+        let safe_array = create_safe_array_buffer(buffer)?;
+        self.Load_3(&safe_array)
     }
 
     /// Loads an assembly by its name in the current application domain.
@@ -89,9 +86,14 @@ impl _AppDomain {
 
     /// Searches for an assembly by name within the current AppDomain.
     ///
+    /// The name is matched against each assembly's identity, so `mscorlib` finds `mscorlib`
+    /// but not an assembly that merely contains it in its name, and a full display name
+    /// such as `System, Version=4.0.0.0, Culture=neutral, PublicKeyToken=b77a5c561934e089`
+    /// only finds that exact version.
+    ///
     /// # Arguments
     ///
-    /// * `assembly_name` – A substring to look for in the assembly's full display name.
+    /// * `assembly_name` – A simple name or a full or partial display name.
     ///
     /// # Returns
     ///
@@ -100,7 +102,8 @@ impl _AppDomain {
     pub fn get_assembly(&self, assembly_name: &str) -> Result<_Assembly> {
         let assemblies = self.assemblies()?;
         for (name, assembly) in assemblies {
-            if name.contains(assembly_name) {
+            let identity = name.parse::<AssemblyIdentity>();
+            if identity.is_ok_and(|identity| identity.matches(assembly_name)) {
                 return Ok(assembly);
             }
         }
@@ -116,20 +119,12 @@ impl _AppDomain {
     /// * `Err(ClrError)` – If any error occurs during retrieval or conversion.
     pub fn assemblies(&self) -> Result<Vec<(String, _Assembly)>> {
         let sa_assemblies = self.GetAssemblies()?;
-        if sa_assemblies.is_null() {
-            return Err(ClrError::NullPointerError("GetAssemblies"));
-        }
-
+        let (lbound, ubound) = sa_assemblies.bounds()?;
         let mut assemblies = Vec::new();
-        let mut lbound = 0;
-        let mut ubound = 0;
         unsafe {
-            SafeArrayGetLBound(sa_assemblies, 1, &mut lbound);
-            SafeArrayGetUBound(sa_assemblies, 1, &mut ubound);
-            
             for i in lbound..=ubound {
                 let mut p_assembly = null_mut::<_Assembly>();
-                let hr = SafeArrayGetElement(sa_assemblies, &i, &mut p_assembly as *mut _ as *mut _);
+                let hr = SafeArrayGetElement(sa_assemblies.as_ptr(), &i, &mut p_assembly as *mut _ as *mut _);
                 if hr != 0 || p_assembly.is_null() {
                     return Err(ClrError::ApiError("SafeArrayGetElement", hr));
                 }
@@ -152,15 +147,15 @@ impl _AppDomain {
     ///
     /// # Arguments
     /// 
-    /// * `rawAssembly` - The raw assembly data as a `SAFEARRAY` pointer.
+    /// * `rawAssembly` - The raw assembly data as a `SAFEARRAY`.
     /// 
     /// # Returns
     /// 
     /// * `Ok(_Assembly)` - If successful, returns a `_Assembly` instance.
     /// * `Err(ClrError)` - If loading fails, returns a `ClrError`.
-    pub fn Load_3(&self, rawAssembly: *mut SAFEARRAY) -> Result<_Assembly> {
+    pub fn Load_3(&self, rawAssembly: &SafeArray) -> Result<_Assembly> {
         let mut result = null_mut();
-        let hr = unsafe { (Interface::vtable(self).Load_3)(Interface::as_raw(self), rawAssembly, &mut result) };
+        let hr = unsafe { (Interface::vtable(self).Load_3)(Interface::as_raw(self), rawAssembly.as_ptr(), &mut result) };
         if hr == 0 {
             _Assembly::from_raw(result as *mut c_void)
         } else {
@@ -224,13 +219,13 @@ impl _AppDomain {
     ///
     /// # Returns
     ///
-    /// * `Ok(*mut SAFEARRAY)` – Pointer to a COM SAFEARRAY of `_Assembly` references.
+    /// * `Ok(SafeArray)` – A COM SAFEARRAY of `_Assembly` references.
     /// * `Err(ClrError)` – If the COM call fails or returns an error HRESULT.
-    pub fn GetAssemblies(&self) -> Result<*mut SAFEARRAY> {
+    pub fn GetAssemblies(&self) -> Result<SafeArray> {
         let mut result = null_mut();
         let hr: i32 = unsafe { (Interface::vtable(self).GetAssemblies)(Interface::as_raw(self), &mut result) };
         if hr == 0 {
-            Ok(result)
+            unsafe { SafeArray::from_raw(result) }.ok_or(ClrError::NullPointerError("GetAssemblies"))
         } else {
             Err(ClrError::ApiError("GetAssemblies", hr))
         }
@@ -5,7 +5,7 @@ use std::{
 };
 use {
     super::{_MethodInfo, _Type},
-    crate::{error::ClrError, WinStr, Result},
+    crate::{error::ClrError, WinStr, Result, OwnedVariant, SafeArray},
 };
 use windows_core::{IUnknown, Interface, GUID};
 use windows_sys::{
@@ -15,11 +15,7 @@ use windows_sys::{
         System::{
             Com::SAFEARRAY, 
             Variant::VARIANT,
-            Ole::{
-                SafeArrayGetElement, 
-                SafeArrayGetLBound, 
-                SafeArrayGetUBound
-            }
+            Ole::SafeArrayGetElement
         }
     }
 };
@@ -59,24 +55,24 @@ impl _Assembly {
     ///
     /// # Arguments
     ///
-    /// * `args` - An `*mut SAFEARRAY` containing arguments to be passed to
+    /// * `args` - A `SafeArray` containing arguments to be passed to
     ///   `Main(System.String[])`. If `Main()` is invoked, this should be `None`.
     ///
     /// # Returns
     ///
-    /// * `Ok(VARIANT)` - On successful invocation, returns the result as a `VARIANT`.
+    /// * `Ok(OwnedVariant)` - On successful invocation, returns the result as a `VARIANT`.
     /// * `Err(ClrError)` - Returns an error if the entry point cannot be resolved or invoked.
-    pub fn run(&self, args: *mut SAFEARRAY) -> Result<VARIANT> {
+    pub fn run(&self, args: Option<&SafeArray>) -> Result<OwnedVariant> {
         let entrypoint = self.get_EntryPoint()?;
         let str = entrypoint.ToString()?;
         match str.as_str() {
-            str if str.ends_with("Main()") => entrypoint.invoke(None, None),
+            str if str.ends_with("Main()") => entrypoint.invoke(None, ()),
             str if str.ends_with("Main(System.String[])") =>  {
-                if args.is_null() {
+                if args.is_none() {
                     return Err(ClrError::MissingArguments)
                 }
 
-                entrypoint.invoke(None, Some(args))
+                entrypoint.Invoke_3(&OwnedVariant::default(), args)
             }
             _ => Err(ClrError::MethodNotFound)
         }
@@ -90,9 +86,9 @@ impl _Assembly {
     ///
     /// # Returns
     ///
-    /// * `Ok(VARIANT)` - If successful, returns a `VARIANT` containing the created instance.
+    /// * `Ok(OwnedVariant)` - If successful, returns a `VARIANT` containing the created instance.
     /// * `Err(ClrError)` - If creation fails, returns a `ClrError`.
-    pub fn create_instance(&self, name: &str) -> Result<VARIANT> {
+    pub fn create_instance(&self, name: &str) -> Result<OwnedVariant> {
         let type_name = name.to_bstr();
         self.CreateInstance(type_name)
     }
@@ -105,20 +101,12 @@ impl _Assembly {
     /// * `Err(ClrError)` - On failure, returns an appropriate `ClrError`.
     pub fn types(&self) -> Result<Vec<String>> {
         let sa_types = self.GetTypes()?;
-        if sa_types.is_null() {
-            return Err(ClrError::NullPointerError("GetTypes"));
-        }
-
+        let (lbound, ubound) = sa_types.bounds()?;
         let mut types = Vec::new();
-        let mut lbound = 0;
-        let mut ubound = 0;
         unsafe {
-            SafeArrayGetLBound(sa_types, 1, &mut lbound);
-            SafeArrayGetUBound(sa_types, 1, &mut ubound);
-            
             for i in lbound..=ubound {
                 let mut p_type = null_mut::<_Type>();
-                let hr = SafeArrayGetElement(sa_types, &i, &mut p_type as *mut _ as *mut _);
+                let hr = SafeArrayGetElement(sa_types.as_ptr(), &i, &mut p_type as *mut _ as *mut _);
                 if hr != 0 || p_type.is_null() {
                     return Err(ClrError::ApiError("SafeArrayGetElement", hr));
                 }
@@ -235,13 +223,13 @@ impl _Assembly {
     ///
     /// # Returns
     ///
-    /// * `Ok(*mut SAFEARRAY)` - If successful, returns a pointer to the `SAFEARRAY`.
+    /// * `Ok(SafeArray)` - If successful, returns the `SAFEARRAY`.
     /// * `Err(ClrError)` - If retrieval fails, returns a `ClrError`.
-    pub fn GetTypes(&self) -> Result<*mut SAFEARRAY> {
+    pub fn GetTypes(&self) -> Result<SafeArray> {
         let mut result = null_mut();
         let hr = unsafe { (Interface::vtable(self).GetTypes)(Interface::as_raw(self), &mut result) };
         if hr == 0 {
-            Ok(result)
+            unsafe { SafeArray::from_raw(result) }.ok_or(ClrError::NullPointerError("GetTypes"))
         } else {
             Err(ClrError::ApiError("GetTypes", hr))
         }
@@ -255,11 +243,11 @@ impl _Assembly {
     ///
     /// # Returns
     ///
-    /// * `Ok(VARIANT)` - If successful, returns the created instance as a `VARIANT`.
+    /// * `Ok(OwnedVariant)` - If successful, returns the created instance as a `VARIANT`.
     /// * `Err(ClrError)` - If creation fails, returns a `ClrError`.
-    pub fn CreateInstance(&self, typeName: BSTR) -> Result<VARIANT> {
-        let mut result = unsafe { std::mem::zeroed::<VARIANT>() };
-        let hr = unsafe { (Interface::vtable(self).CreateInstance)(Interface::as_raw(self), typeName, &mut result) };
+    pub fn CreateInstance(&self, typeName: BSTR) -> Result<OwnedVariant> {
+        let mut result = OwnedVariant::default();
+        let hr = unsafe { (Interface::vtable(self).CreateInstance)(Interface::as_raw(self), typeName, result.as_mut_ptr()) };
         if hr == 0 {
             Ok(result)
         } else {
@@ -0,0 +1,142 @@
+use crate::error::ClrError;
+use crate::Result;
+use windows_sys::core::HRESULT;
+use windows_sys::Win32::System::Ole::GetRecordInfoFromGuids;
+use windows_core::{GUID, Interface};
+use std::{
+    ops::Deref, ptr::null_mut,
+    ffi::c_void
+};
+
+/// Represents the COM `IRecordInfo` interface, which describes a user-defined type (UDT)
+/// such as `System.Guid` so that it can be passed in a `VT_RECORD` VARIANT.
+#[repr(C)]
+#[derive(Debug, Clone)]
+pub struct IRecordInfo(windows_core::IUnknown);
+
+impl IRecordInfo {
+    /// Retrieves the `IRecordInfo` of a type described in a registered type library.
+    ///
+    /// # Arguments
+    ///
+    /// * `libid` - The GUID of the type library, e.g. the one of `mscorlib.tlb`.
+    /// * `major` - The major version of the type library.
+    /// * `minor` - The minor version of the type library.
+    /// * `type_guid` - The GUID of the type.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(IRecordInfo)` - If the type is found.
+    /// * `Err(ClrError)` - If the type library or the type is not registered.
+    pub fn from_guids(libid: &GUID, major: u32, minor: u32, type_guid: &GUID) -> Result<Self> {
+        let mut result = null_mut();
+        let hr = unsafe {
+            GetRecordInfoFromGuids(
+                libid as *const GUID as _,
+                major,
+                minor,
+                0,
+                type_guid as *const GUID as _,
+                &mut result
+            )
+        };
+
+        if hr == 0 && !result.is_null() {
+            Ok(unsafe { IRecordInfo::from_raw(result) })
+        } else {
+            Err(ClrError::ApiError("GetRecordInfoFromGuids", hr))
+        }
+    }
+}
+
+/// Implementation of the original `IRecordInfo` COM interface methods.
+///
+/// These methods are direct FFI bindings to the corresponding functions in the COM interface.
+impl IRecordInfo {
+    /// Allocates a new record and copies an existing one into it.
+    ///
+    /// # Arguments
+    ///
+    /// * `psource` - Pointer to the record to copy, e.g. a `GUID`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(*mut c_void)` - The new record, freed by `VariantClear` once it is stored in a VARIANT.
+    /// * `Err(ClrError)` - If the record cannot be allocated.
+    pub fn RecordCreateCopy(&self, psource: *const c_void) -> Result<*mut c_void> {
+        let mut result = null_mut();
+        let hr = unsafe { (Interface::vtable(self).RecordCreateCopy)(Interface::as_raw(self), psource, &mut result) };
+        if hr == 0 {
+            Ok(result)
+        } else {
+            Err(ClrError::ApiError("RecordCreateCopy", hr))
+        }
+    }
+}
+
+unsafe impl Interface for IRecordInfo {
+    type Vtable = IRecordInfo_Vtbl;
+
+    /// The interface identifier (IID) for the `IRecordInfo` COM interface.
+    ///
+    /// This GUID is used to identify the `IRecordInfo` interface when calling
+    /// COM methods like `QueryInterface`.
+    const IID: GUID = GUID::from_u128(0x0000002f_0000_0000_c000_000000000046);
+}
+
+impl Deref for IRecordInfo {
+    type Target = windows_core::IUnknown;
+
+    /// Provides a reference to the underlying `IUnknown` interface.
+    ///
+    /// This implementation allows `IRecordInfo` to be used as an `IUnknown`
+    /// pointer, enabling access to basic COM methods like `AddRef`, `Release`,
+    /// and `QueryInterface`.
+    fn deref(&self) -> &Self::Target {
+        unsafe { core::mem::transmute(self) }
+    }
+}
+
+#[repr(C)]
+pub struct IRecordInfo_Vtbl {
+    /// Base vtable inherited from the `IUnknown` interface.
+    ///
+    /// This field contains the basic methods for reference management,
+    /// like `AddRef`, `Release`, and `QueryInterface`.
+    pub base__: windows_core::IUnknown_Vtbl,
+
+    /// Placeholder for the methods. Not used directly.
+    RecordInit: *const c_void,
+    RecordClear: *const c_void,
+    RecordCopy: *const c_void,
+    GetGuid: *const c_void,
+    GetName: *const c_void,
+    GetSize: *const c_void,
+    GetTypeInfo: *const c_void,
+    GetField: *const c_void,
+    GetFieldNoCopy: *const c_void,
+    PutField: *const c_void,
+    PutFieldNoCopy: *const c_void,
+    GetFieldNames: *const c_void,
+    IsMatchingType: *const c_void,
+    RecordCreate: *const c_void,
+
+    /// Allocates a new record and copies an existing one into it.
+    ///
+    /// # Arguments
+    ///
+    /// * `pvSource` - Pointer to the record to copy.
+    /// * `ppvDest` - Pointer receiving the new record.
+    ///
+    /// # Returns
+    ///
+    /// * Returns an HRESULT indicating success or failure.
+    pub RecordCreateCopy: unsafe extern "system" fn(
+        *mut c_void,
+        pvSource: *const c_void,
+        ppvDest: *mut *mut c_void
+    ) -> HRESULT,
+
+    /// Placeholder for the methods. Not used directly.
+    RecordDestroy: *const c_void,
+}
@@ -7,7 +7,8 @@ use crate::{
     error::ClrError, 
     data::_MethodInfo,
     WinStr, Invocation,
-    create_safe_args, Result
+    IntoArgs, Result,
+    OwnedVariant, SafeArray
 };
 use windows_core::{IUnknown, Interface, GUID};
 use windows_sys::{
@@ -15,11 +16,7 @@ use windows_sys::{
     Win32::System::{
         Com::SAFEARRAY, 
         Variant::VARIANT,
-        Ole::{
-            SafeArrayGetElement, 
-            SafeArrayGetLBound, 
-            SafeArrayGetUBound
-        }, 
+        Ole::SafeArrayGetElement, 
     }
 };
 
@@ -78,33 +75,31 @@ impl _Type {
     ///
     /// * `name` - The name of the method to invoke.
     /// * `instance` - An optional `VARIANT` representing the instance.
-    /// * `args` - Optional vector of `VARIANT` arguments.
+    /// * `args` - The arguments, e.g. `()`, a tuple or a list built with `args!`.
     /// * `invocation_type` - The `Invocation`, indicating if it's a static or instance method.
     ///
     /// # Returns
     ///
-    /// * `Ok(VARIANT)` - On success, returns the result as `VARIANT`.
+    /// * `Ok(OwnedVariant)` - On success, returns the result as `VARIANT`.
     /// * `Err(ClrError)` - On failure, returns `ClrError`.
     pub fn invoke(
         &self, 
         name: &str, 
-        instance: Option<VARIANT>, 
-        args: Option<Vec<VARIANT>>, 
+        instance: Option<&OwnedVariant>, 
+        args: impl IntoArgs, 
         invocation_type: Invocation
-    ) -> Result<VARIANT> {
+    ) -> Result<OwnedVariant> {
         let flags = match invocation_type {
             Invocation::Static => BindingFlags::NonPublic | BindingFlags::Public | BindingFlags::Static | BindingFlags::InvokeMethod,
             Invocation::Instance => BindingFlags::NonPublic | BindingFlags::Public | BindingFlags::Instance | BindingFlags::InvokeMethod,
         };
 
         let method_name = name.to_bstr();
-        let args = args.as_ref().map_or_else(
-            || Ok(null_mut()),
-            |args| create_safe_args(args.to_vec())
-        )?;
-
-        let instance = instance.unwrap_or(unsafe { std::mem::zeroed::<VARIANT>() });
-        self.InvokeMember_3(method_name, flags, instance, args)
+        let args = args.into_safe_array()?;
+        match instance {
+            Some(instance) => self.InvokeMember_3(method_name, flags, instance, args.as_ref()),
+            None => self.InvokeMember_3(method_name, flags, &OwnedVariant::default(), args.as_ref()),
+        }
     }
 
     /// Retrieves all methods of the type.
@@ -118,20 +113,12 @@ impl _Type {
             BindingFlags::Static | BindingFlags::FlattenHierarchy | BindingFlags::NonPublic;
         
         let sa_methods = self.GetMethods(binding_flags)?;
-        if sa_methods.is_null() {
-            return Err(ClrError::NullPointerError("GetMethods"));
-        }
-
-        let mut lbound = 0;
-        let mut ubound = 0;
+        let (lbound, ubound) = sa_methods.bounds()?;
         let mut methods = Vec::new();
         unsafe {
-            SafeArrayGetLBound(sa_methods, 1, &mut lbound);
-            SafeArrayGetUBound(sa_methods, 1, &mut ubound);
-            
             let mut p_method = null_mut::<_MethodInfo>();
             for i in lbound..=ubound {
-                let hr = SafeArrayGetElement(sa_methods, &i, &mut p_method as *mut _ as *mut _);
+                let hr = SafeArrayGetElement(sa_methods.as_ptr(), &i, &mut p_method as *mut _ as *mut _);
                 if hr != 0 || p_method.is_null() {
                     return Err(ClrError::ApiError("SafeArrayGetElement", hr));
                 }
@@ -200,14 +187,14 @@ impl _Type {
     ///
     /// # Returns
     ///
-    /// * `Ok(*mut SAFEARRAY)` - On success, returns a pointer to a `SAFEARRAY` of methods.
+    /// * `Ok(SafeArray)` - On success, returns a `SAFEARRAY` of methods.
     /// * `Err(ClrError)` - On failure, returns a `ClrError`.
-    pub fn GetMethods(&self, bindingAttr: BindingFlags) -> Result<*mut SAFEARRAY> {
+    pub fn GetMethods(&self, bindingAttr: BindingFlags) -> Result<SafeArray> {
         unsafe {
             let mut result = null_mut();
             let hr = (Interface::vtable(self).GetMethods)(Interface::as_raw(self), bindingAttr, &mut result);
             if hr == 0 {
-                Ok(result)
+                SafeArray::from_raw(result).ok_or(ClrError::NullPointerError("GetMethods"))
             } else {
                 Err(ClrError::ApiError("GetMethods", hr))
             }
@@ -244,17 +231,18 @@ impl _Type {
     /// * `invoke_attr` - `BindingFlags` that specify invocation options (such as
     ///   whether to target a static or instance method).
     /// * `instance` - A `VARIANT` representing the object instance on which to invoke
-    ///   the member, or a `null`/default value for static members.
-    /// * `args` - A pointer to a `SAFEARRAY` containing the arguments for the method invocation.
+    ///   the member, or a `VT_EMPTY` value for static members.
+    /// * `args` - A `SAFEARRAY` containing the arguments for the method invocation, if any.
     ///
     /// # Returns
     ///
-    /// * `Ok(VARIANT)` - On success, returns the result of the invocation as a `VARIANT`.
+    /// * `Ok(OwnedVariant)` - On success, returns the result of the invocation as a `VARIANT`.
     /// * `Err(ClrError)` - If invocation fails, returns an appropriate `ClrError`.
-    pub fn InvokeMember_3(&self, name: BSTR, invoke_attr: BindingFlags, instance: VARIANT, args: *mut SAFEARRAY) -> Result<VARIANT> {
+    pub fn InvokeMember_3(&self, name: BSTR, invoke_attr: BindingFlags, instance: &OwnedVariant, args: Option<&SafeArray>) -> Result<OwnedVariant> {
         unsafe {
-            let mut result = std::mem::zeroed();
-            let hr = (Interface::vtable(self).InvokeMember_3)(Interface::as_raw(self), name, invoke_attr, null_mut(), instance, args, &mut result);
+            let mut result = OwnedVariant::default();
+            let args = args.map_or(null_mut(), SafeArray::as_ptr);
+            let hr = (Interface::vtable(self).InvokeMember_3)(Interface::as_raw(self), name, invoke_attr, null_mut(), **instance, args, result.as_mut_ptr());
             if hr == 0 {
                 Ok(result)
             } else {
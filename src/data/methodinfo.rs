@@ -5,14 +5,14 @@ use std::{
 }; 
 use {
     super::_Type, crate::Result,
-    crate::error::ClrError, 
+    crate::{error::ClrError, IntoArgs, OwnedVariant, SafeArray}, 
 };
 use windows_core::{IUnknown, Interface, GUID};
 use windows_sys::{
     core::{BSTR, HRESULT}, 
     Win32::System::{
         Com::SAFEARRAY,
-        Variant::{VariantClear, VARIANT}
+        Variant::VARIANT
     }
 };
 
@@ -34,15 +34,18 @@ impl _MethodInfo {
     /// # Arguments
     /// 
     /// * `obj` - An optional `VARIANT` representing the target object for instance methods.
-    /// * `parameters` - An optional pointer to a `SAFEARRAY` containing the parameters for the method.
+    /// * `args` - The arguments, e.g. `()`, a tuple or a list built with `args!`.
     ///
     /// # Returns
     ///
-    /// * `Ok(VARIANT)` - On successful invocation, returns the result as a `VARIANT`.
+    /// * `Ok(OwnedVariant)` - On successful invocation, returns the result as a `VARIANT`.
     /// * `Err(ClrError)` - Returns an error if the entry point cannot be resolved or invoked.
-    pub fn invoke(&self, obj: Option<VARIANT>, parameters: Option<*mut SAFEARRAY>) -> Result<VARIANT> {
-        let variant_obj = unsafe { obj.unwrap_or(std::mem::zeroed::<VARIANT>()) };
-        self.Invoke_3(variant_obj, parameters.unwrap_or(null_mut()))
+    pub fn invoke(&self, obj: Option<&OwnedVariant>, args: impl IntoArgs) -> Result<OwnedVariant> {
+        let parameters = args.into_safe_array()?;
+        match obj {
+            Some(obj) => self.Invoke_3(obj, parameters.as_ref()),
+            None => self.Invoke_3(&OwnedVariant::default(), parameters.as_ref()),
+        }
     }
 
     /// Creates an `_MethodInfo` instance from a raw COM interface pointer.
@@ -120,23 +123,21 @@ impl _MethodInfo {
     ///
     /// # Arguments
     /// 
-    /// * `obj` - A `VARIANT` representing the target instance or null for static methods.
-    /// * `parameters` - A pointer to a `SAFEARRAY` containing the parameters for the method.
+    /// * `obj` - A `VARIANT` representing the target instance or `VT_EMPTY` for static methods.
+    /// * `parameters` - A `SAFEARRAY` containing the parameters for the method, if any.
     ///
     /// # Returns
     ///
-    /// * `Ok(VARIANT)` - The result of the method invocation.
+    /// * `Ok(OwnedVariant)` - The result of the method invocation.
     /// * `Err(ClrError)` - Returns an error if the invocation fails.
-    pub fn Invoke_3(&self, obj: VARIANT, parameters: *mut SAFEARRAY) -> Result<VARIANT> {
-        unsafe {
-            let mut result = std::mem::zeroed();
-            let hr = (Interface::vtable(self).Invoke_3)(Interface::as_raw(self), obj, parameters, &mut result);
-            if hr == 0 {
-                Ok(result)
-            } else {
-                VariantClear(&mut result);
-                Err(ClrError::ApiError("Invoke_3", hr))
-            }
+    pub fn Invoke_3(&self, obj: &OwnedVariant, parameters: Option<&SafeArray>) -> Result<OwnedVariant> {
+        let mut result = OwnedVariant::default();
+        let parameters = parameters.map_or(null_mut(), SafeArray::as_ptr);
+        let hr = unsafe { (Interface::vtable(self).Invoke_3)(Interface::as_raw(self), **obj, parameters, result.as_mut_ptr()) };
+        if hr == 0 {
+            Ok(result)
+        } else {
+            Err(ClrError::ApiError("Invoke_3", hr))
         }
     }
 
@@ -144,13 +145,13 @@ impl _MethodInfo {
     ///
     /// # Returns
     ///
-    /// * `Ok(*mut SAFEARRAY)` - A pointer to the `SAFEARRAY` containing the method's parameters.
+    /// * `Ok(SafeArray)` - The `SAFEARRAY` containing the method's parameters.
     /// * `Err(ClrError)` - Returns an error if the parameters cannot be retrieved.
-    pub fn GetParameters(&self) -> Result<*mut SAFEARRAY> {
+    pub fn GetParameters(&self) -> Result<SafeArray> {
         let mut result = null_mut();
         let hr = unsafe { (Interface::vtable(self).GetParameters)(Interface::as_raw(self), &mut result) };
         if hr == 0 {
-            Ok(result)
+            unsafe { SafeArray::from_raw(result) }.ok_or(ClrError::NullPointerError("GetParameters"))
         } else {
             Err(ClrError::ApiError("GetParameters", hr))
         }
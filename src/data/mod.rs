@@ -9,6 +9,7 @@ mod iclrmetahost;
 mod iclrruntimeinfo;
 mod icorruntimehost;
 mod ienumunknown;
+mod irecordinfo;
 mod methodinfo;
 mod itype;
 
@@ -16,6 +17,7 @@ pub use itype::*;
 pub use assembly::*;
 pub use appdomain::*;
 pub use ienumunknown::*;
+pub use irecordinfo::*;
 pub use iclrmetahost::*;
 pub use iclrruntimeinfo::*;
 pub use icorruntimehost::*;
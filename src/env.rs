@@ -0,0 +1,315 @@
+use crate::{
+    Result,
+    metadata::{AssemblyIdentity, AssemblyStore},
+    backend::{ClrBackend, DefaultBackend},
+    error::ClrError,
+    RuntimeVersion, select_runtime,
+};
+#[cfg(windows)]
+use crate::{args, data::_Assembly, FromVariant, Invocation};
+
+/// Represents a simplified interface to the CLR components without loading assemblies.
+///
+/// The runtime is driven through a `ClrBackend`, which is `ComBackend` on Windows. Its COM
+/// interfaces (`ICLRMetaHost`, `ICLRRuntimeInfo` and `ICorRuntimeHost`) are available through
+/// `backend()`, e.g. `env.backend().cor_runtime_host()`.
+#[derive(Debug)]
+pub struct RustClrEnv<B: ClrBackend = DefaultBackend> {
+    /// .NET runtime version to use.
+    pub runtime_version: RuntimeVersion,
+
+    /// Current application domain.
+    pub app_domain: B::Domain,
+
+    /// Dependent assemblies served from memory.
+    dependencies: AssemblyStore,
+
+    /// Handler serving `dependencies`, installed with the first one.
+    resolver: Option<B::Resolver>,
+
+    /// Backend hosting the runtime.
+    backend: B,
+}
+
+impl RustClrEnv {
+    /// Creates a new `RustClrEnv` instance with the specified runtime version.
+    ///
+    /// Since there is no assembly to inspect, `RuntimeVersion::Auto` picks the newest installed runtime.
+    ///
+    /// # Arguments
+    ///
+    /// * `runtime_version` - The .NET runtime version to use.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Self)` - If the components are initialized successfully.
+    /// * `Err(ClrError)` - If initialization fails at any step.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// use rustclr::{RustClrEnv, RuntimeVersion};
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     // Create a new RustClrEnv with a specific runtime version
+    ///     let clr_env = RustClrEnv::new(Some(RuntimeVersion::V4))?;
+    ///
+    ///     println!("CLR initialized successfully.");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn new(runtime_version: Option<RuntimeVersion>) -> Result<Self> {
+        Self::new_with_backend(runtime_version, DefaultBackend::default())
+    }
+}
+
+impl<B: ClrBackend> RustClrEnv<B> {
+    /// Creates a new `RustClrEnv` instance driving the runtime through the given backend.
+    ///
+    /// # Arguments
+    ///
+    /// * `runtime_version` - The .NET runtime version to use.
+    /// * `backend` - The backend hosting the runtime.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Self)` - If the runtime is started and the AppDomain created.
+    /// * `Err(ClrError)` - If initialization fails at any step.
+    pub fn new_with_backend(runtime_version: Option<RuntimeVersion>, mut backend: B) -> Result<Self> {
+        // Resolves the runtime version, the newest installed one for `Auto`
+        let runtime_version = runtime_version.unwrap_or(RuntimeVersion::V4);
+        let version = match runtime_version {
+            RuntimeVersion::Auto => {
+                let installed = backend.installed_runtimes()?;
+                let selection = select_runtime(None, None, &installed)
+                    .ok_or_else(|| ClrError::RuntimeInfoError("no installed runtime".to_string()))?;
+
+                RuntimeVersion::custom(&selection.version)
+            }
+            version => version,
+        };
+
+        backend.start(&version)?;
+
+        // Initializes an AppDomain with a unique name
+        let name = uuid::Uuid::new_v4().to_string();
+        let app_domain = backend.create_domain(Some(&name))
+            .map_err(|_| ClrError::NoDomainAvailable)?;
+
+        Ok(Self {
+            runtime_version,
+            app_domain,
+            dependencies: AssemblyStore::new(),
+            resolver: None,
+            backend,
+        })
+    }
+
+    /// Returns the backend hosting the runtime.
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    /// Registers a dependent assembly to serve from memory, like `RustClr::with_dependency`.
+    ///
+    /// The `AppDomain.AssemblyResolve` handler is installed on the environment's AppDomain
+    /// with the first dependency, and later ones are added to it.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - The bytes of the dependent assembly.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(AssemblyIdentity)` - The identity the assembly was registered under.
+    /// * `Err(ClrError)` - If the buffer is not a .NET assembly or the handler cannot be installed.
+    pub fn add_dependency(&mut self, buffer: &[u8]) -> Result<AssemblyIdentity> {
+        let identity = self.dependencies.insert(buffer)?;
+        match &self.resolver {
+            Some(resolver) => self.backend.register_dependency(resolver, &identity, buffer)?,
+            None => self.resolver = Some(self.backend.install_resolver(&self.app_domain, &self.dependencies)?),
+        }
+
+        Ok(identity)
+    }
+
+    /// Registers a dependent assembly to serve from memory, consuming and returning the environment.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - The bytes of the dependent assembly.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Self)` - The environment with the dependency registered.
+    /// * `Err(ClrError)` - If the buffer is not a .NET assembly or the handler cannot be installed.
+    pub fn with_dependency(mut self, buffer: &[u8]) -> Result<Self> {
+        self.add_dependency(buffer)?;
+        Ok(self)
+    }
+}
+
+impl<B: ClrBackend> Drop for RustClrEnv<B> {
+    fn drop(&mut self) {
+        // Releases the resolve handler's table while the AppDomain is still alive
+        self.resolver.take();
+
+        // Attempt to unload the AppDomain, log error if it fails
+        if let Err(e) = self.backend.unload_domain(self.app_domain.clone()) {
+            eprintln!("Failed to unload AppDomain: {:?}", e);
+        }
+
+        // Attempt to stop the CLR runtime
+        self.backend.stop();
+    }
+}
+
+/// Provides a persistent interface for executing PowerShell commands
+/// from a .NET runtime hosted inside a Rust application.
+///
+/// Unlike `RustClrEnv`, it works on the COM interfaces directly, so it is only available on Windows.
+#[cfg(windows)]
+pub struct PowerShell {
+    /// The loaded .NET automation assembly (`System.Management.Automation`),
+    /// used to resolve types like `Runspace`, `Pipeline`, `PSObject`, etc.
+    automation: _Assembly,
+
+    /// CLR environment used to host the .NET runtime.
+    /// This is kept alive to ensure assemblies and types remain valid.
+    _clr: RustClrEnv,
+}
+
+#[cfg(windows)]
+impl PowerShell {
+    /// Creates a new PowerShell session by initializing the .NET CLR
+    /// and loading the `System.Management.Automation` assembly.
+    ///
+    /// # Returns
+    ///
+    /// A new `PowerShell` instance ready to execute commands.
+    pub fn new() -> Result<Self> {
+        // Initialize .NET runtime (v4.0).
+        let clr = RustClrEnv::new(None)?;
+
+        // Load `mscorlib` and resolve `System.Reflection.Assembly`.
+        let mscorlib = clr.app_domain.get_assembly("mscorlib")?;
+        let reflection_assembly = mscorlib.resolve_type("System.Reflection.Assembly")?;
+
+        // Resolve and invoke `LoadWithPartialName` method.
+        let load_partial_name = reflection_assembly.method_signature("System.Reflection.Assembly LoadWithPartialName(System.String)")?;
+        let result = load_partial_name.invoke(None, args!["System.Management.Automation"])?;
+
+        // Convert result to `_Assembly`, which takes over the reference held by the variant.
+        let automation = _Assembly::from_raw(unsafe { result.into_raw().Anonymous.Anonymous.Anonymous.byref })?;
+
+        Ok(Self {
+            automation,
+            _clr: clr,
+        })
+    }
+    /// Executes a PowerShell command and returns its output as a string.
+    ///
+    /// This method creates a new temporary `Runspace` and `Pipeline` for
+    /// each invocation. The result is captured via `PSObject.ToString()`.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - A PowerShell command to be executed.
+    ///
+    /// # Returns
+    ///
+    /// * Returns the textual output of the PowerShell command.
+    pub fn execute(&self, command: &str) -> Result<String> {
+        // Invoke `CreateRunspace` method.
+        let runspace_factory = self.automation.resolve_type("System.Management.Automation.Runspaces.RunspaceFactory")?;
+        let create_runspace = runspace_factory.method_signature("System.Management.Automation.Runspaces.Runspace CreateRunspace()")?;
+        let runspace = create_runspace.invoke(None, ())?;
+
+        // Invoke `CreatePipeline` method.
+        let assembly_runspace = self.automation.resolve_type("System.Management.Automation.Runspaces.Runspace")?;
+        assembly_runspace.invoke("Open", Some(&runspace), (), Invocation::Instance)?;
+        let create_pipeline = assembly_runspace.method_signature("System.Management.Automation.Runspaces.Pipeline CreatePipeline()")?;
+        let pipe = create_pipeline.invoke(Some(&runspace), ())?;
+
+        // Invoke `get_Commands` method.
+        let pipeline = self.automation.resolve_type("System.Management.Automation.Runspaces.Pipeline")?;
+        let get_command = pipeline.invoke("get_Commands", Some(&pipe), (), Invocation::Instance)?;
+
+        // Invoke `AddScript` method.
+        let command_collection = self.automation.resolve_type("System.Management.Automation.Runspaces.CommandCollection")?;
+        let add_script = command_collection.method_signature("Void AddScript(System.String)")?;
+        add_script.invoke(Some(&get_command), args![format!("{} | Out-String", command)])?;
+
+        // Invoke `InvokeAsync` method.
+        pipeline.invoke("InvokeAsync", Some(&pipe), (), Invocation::Instance)?;
+
+        // Invoke `get_Output` method.
+        let get_output = pipeline.invoke("get_Output", Some(&pipe), (), Invocation::Instance)?;
+
+        // Invoke `Read` method.
+        let pipeline_reader = self.automation.resolve_type("System.Management.Automation.Runspaces.PipelineReader`1[System.Management.Automation.PSObject]")?;
+        let read = pipeline_reader.method_signature("System.Management.Automation.PSObject Read()")?;
+        let ps_object_instance = read.invoke(Some(&get_output), ())?;
+
+        // Invoke `ToString` method.
+        let ps_object = self.automation.resolve_type("System.Management.Automation.PSObject")?;
+        let to_string = ps_object.method_signature("System.String ToString()")?;
+        let output = to_string.invoke(Some(&ps_object_instance), ())?;
+
+        assembly_runspace.invoke("Close", Some(&runspace), (), Invocation::Instance)?;
+        String::from_variant(&output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::fake::FakeBackend;
+    use crate::metadata::{builder::AssemblyBuilder, TableId};
+
+    /// Builds a library named `name`.
+    fn library(name: &str) -> Vec<u8> {
+        let mut b = AssemblyBuilder::new();
+        b.characteristics |= 0x2000;
+        let name = b.string(name);
+        b.row(TableId::Assembly, &[0x8004, 1, 0, 0, 0, 0, 0, name, 0]);
+        b.build()
+    }
+
+    #[test]
+    fn prepares_and_unloads_domain() {
+        let mut backend = FakeBackend::default();
+        backend.installed = vec!["v2.0.50727".to_string(), "v4.0.30319".to_string()];
+        let calls = backend.calls.clone();
+
+        let mut env = RustClrEnv::new_with_backend(Some(RuntimeVersion::Auto), backend).unwrap();
+        let domain = env.app_domain.clone();
+        assert_eq!(env.runtime_version, RuntimeVersion::Auto);
+
+        let (first, second) = (library("First"), library("Second"));
+        assert_eq!(env.add_dependency(&first).unwrap().name, "First");
+        assert_eq!(env.add_dependency(&second).unwrap().name, "Second");
+        drop(env);
+
+        assert_eq!(*calls.borrow(), [
+            "installed_runtimes".to_string(),
+            "start v4.0.30319".to_string(),
+            format!("create_domain {domain}"),
+            format!("install_resolver {domain} 1"),
+            "register_dependency Second".to_string(),
+            format!("unload_domain {domain}"),
+            "stop".to_string(),
+        ]);
+    }
+
+    #[test]
+    fn reports_missing_domain() {
+        let mut backend = FakeBackend::default();
+        backend.fail = Some("create_domain");
+        let calls = backend.calls.clone();
+
+        let result = RustClrEnv::new_with_backend(None, backend);
+        assert!(matches!(result, Err(ClrError::NoDomainAvailable)));
+        assert_eq!(calls.borrow()[0], "start v4.0.30319");
+    }
+}
@@ -33,10 +33,59 @@ pub enum ClrError {
     #[error("Method not found")]
     MethodNotFound,
 
+    /// Raised when a type is not found in the .NET assembly.
+    ///
+    /// # Arguments
+    ///
+    /// * `{0}` - The name of the type that was looked up.
+    #[error("Type not found: {0}")]
+    TypeNotFound(String),
+
     /// Raised when the buffer does not contain a .NET application.
     #[error("The executable is not a .NET application")]
     NotDotNet,
 
+    /// Raised when an x86-only assembly is loaded into a 64-bit process.
+    #[error("The assembly requires a 32-bit process, but the host is 64-bit")]
+    Requires32BitProcess,
+
+    /// Raised when a 64-bit-only assembly is loaded into a 32-bit process.
+    #[error("The assembly requires a 64-bit process, but the host is 32-bit")]
+    Requires64BitProcess,
+
+    /// Raised when the assembly is a mixed-mode (C++/CLI) image, which cannot be loaded from memory.
+    #[error("The assembly contains native code (mixed-mode C++/CLI) and cannot be loaded from memory")]
+    MixedModeAssembly,
+
+    /// Raised when the assembly targets a runtime the .NET Framework CLR cannot host.
+    ///
+    /// # Arguments
+    ///
+    /// * `{0}` - The target framework or the kind of image that was detected.
+    #[error("Unsupported framework: {0} (only .NET Framework and .NET Standard assemblies can be hosted)")]
+    UnsupportedFramework(String),
+
+    /// Raised when a .NET Framework assembly is given to the .NET (Core) hosts, which cannot run it.
+    ///
+    /// # Arguments
+    ///
+    /// * `{0}` - The target framework of the assembly.
+    #[error("The assembly targets {0}, which .NET (Core) cannot host (use `RustClr` for .NET Framework assemblies)")]
+    NetFrameworkAssembly(String),
+
+    /// Raised when a component of the .NET host (`hostfxr`, `nethost`, `coreclr`) or one of
+    /// its exports cannot be found.
+    ///
+    /// # Arguments
+    ///
+    /// * `{0}` - The library path or export that is missing.
+    #[error("Failed to locate the .NET host component: {0}")]
+    HostNotFound(String),
+
+    /// Raised when the .NET Framework CLR is used on a platform other than Windows.
+    #[error("The .NET Framework CLR can only be hosted on Windows")]
+    UnsupportedPlatform,
+
     /// Raised when there is a failure creating the .NET MetaHost.
     ///
     /// # Arguments
@@ -104,7 +153,76 @@ pub enum ClrError {
     /// Raised when the type of a VARIANT is unsupported by the current context.
     #[error("Type of VARIANT not supported")]
     VariantUnsupported,
-    
+
+    /// Raised when a VARIANT does not hold the type it is converted to.
+    ///
+    /// # Arguments
+    ///
+    /// * `{0}` - The Rust type that was expected.
+    /// * `{1}` - The `vt` of the VARIANT, e.g. `VT_ARRAY | VT_BSTR`.
+    #[error("Expected a VARIANT holding {0}, found {1}")]
+    VariantTypeMismatch(&'static str, String),
+
+    /// Raised when a value cannot be represented as a `System.Decimal`.
+    ///
+    /// # Arguments
+    ///
+    /// * `{0}` - The rejected value.
+    #[error("Invalid decimal: {0}")]
+    InvalidDecimal(String),
+
+    /// Raised when a Rust value cannot be serialized to, or deserialized from, a `Value`.
+    ///
+    /// # Arguments
+    ///
+    /// * `{0}` - The message reported by serde.
+    #[error("Serialization error: {0}")]
+    SerializationError(String),
+
+    /// Raised when the assembly does not define a managed entry point.
+    #[error("The assembly does not define a managed entry point")]
+    EntryPointNotFound,
+
+    /// Raised when the entry point of the assembly cannot be invoked.
+    ///
+    /// # Arguments
+    ///
+    /// * `{0}` - A message describing why the entry point is unsupported.
+    #[error("Unsupported entry point: {0}")]
+    InvalidEntryPoint(String),
+
+    /// Raised when a runtime version string cannot be parsed.
+    ///
+    /// # Arguments
+    ///
+    /// * `{0}` - The rejected version string.
+    #[error("Invalid runtime version: {0:?} (expected e.g. \"v4\", \"4.0\" or \"v4.0.30319\")")]
+    InvalidRuntimeVersion(String),
+
+    /// Raised when an assembly display name cannot be parsed.
+    ///
+    /// # Arguments
+    ///
+    /// * `{0}` - A message describing the invalid component.
+    #[error("Invalid assembly name: {0}")]
+    InvalidAssemblyName(String),
+
+    /// Raised when a compressed resource cannot be decompressed.
+    ///
+    /// # Arguments
+    ///
+    /// * `{0}` - A message describing why the deflate stream is invalid.
+    #[error("Failed to decompress resource: {0}")]
+    DecompressionError(&'static str),
+
+    /// Raised when the metadata of an assembly is malformed or truncated.
+    ///
+    /// # Arguments
+    ///
+    /// * `{0}` - A message describing which part of the metadata is invalid.
+    #[error("Invalid metadata: {0}")]
+    MetadataError(&'static str),
+
     /// Represents a generic error specific to the CLR.
     ///
     /// # Arguments
@@ -0,0 +1,591 @@
+use std::{
+    env, fs,
+    ffi::c_void,
+    path::{Path, PathBuf},
+    ptr::{null, null_mut},
+};
+use crate::{
+    error::ClrError, Result,
+    file::is_dotnet,
+    library::Library,
+    metadata::Metadata,
+    version::parse_version,
+    RuntimeConfig,
+};
+
+/// Character type of the hosting APIs: UTF-16 on Windows, UTF-8 elsewhere.
+#[cfg(windows)]
+pub(crate) type CharT = u16;
+
+/// Character type of the hosting APIs: UTF-16 on Windows, UTF-8 elsewhere.
+#[cfg(not(windows))]
+pub(crate) type CharT = std::ffi::c_char;
+
+/// File name of the `hostfxr` library.
+#[cfg(windows)]
+const HOSTFXR_LIBRARY: &str = "hostfxr.dll";
+#[cfg(target_os = "macos")]
+const HOSTFXR_LIBRARY: &str = "libhostfxr.dylib";
+#[cfg(all(unix, not(target_os = "macos")))]
+const HOSTFXR_LIBRARY: &str = "libhostfxr.so";
+
+/// File name of the `nethost` library, which knows where `hostfxr` is installed.
+#[cfg(windows)]
+const NETHOST_LIBRARY: &str = "nethost.dll";
+#[cfg(target_os = "macos")]
+const NETHOST_LIBRARY: &str = "libnethost.dylib";
+#[cfg(all(unix, not(target_os = "macos")))]
+const NETHOST_LIBRARY: &str = "libnethost.so";
+
+/// `hdt_load_assembly_and_get_function_pointer`, the delegate type requested from `hostfxr`.
+const HDT_LOAD_ASSEMBLY_AND_GET_FUNCTION_POINTER: i32 = 5;
+
+/// `UNMANAGEDCALLERSONLY_METHOD`: the target is marked `[UnmanagedCallersOnly]` and needs no delegate type.
+const UNMANAGED_CALLERS_ONLY_METHOD: *const CharT = usize::MAX as *const CharT;
+
+/// Size of the buffer handed to `get_hostfxr_path`, in characters.
+const MAX_PATH: usize = 4096;
+
+/// `hostfxr_initialize_parameters`.
+#[repr(C)]
+struct InitializeParameters {
+    size: usize,
+    host_path: *const CharT,
+    dotnet_root: *const CharT,
+}
+
+/// `get_hostfxr_parameters` of `nethost`.
+#[repr(C)]
+struct GetHostFxrParameters {
+    size: usize,
+    assembly_path: *const CharT,
+    dotnet_root: *const CharT,
+}
+
+/// `get_hostfxr_path` of `nethost`, which is `__stdcall` on Windows unlike the `hostfxr_*` exports.
+type GetHostFxrPathFn = unsafe extern "system" fn(*mut CharT, *mut usize, *const GetHostFxrParameters) -> i32;
+
+/// `hostfxr_initialize_for_runtime_config`.
+type InitializeForRuntimeConfigFn = unsafe extern "C" fn(*const CharT, *const InitializeParameters, *mut *mut c_void) -> i32;
+
+/// `hostfxr_set_runtime_property_value`.
+type SetRuntimePropertyValueFn = unsafe extern "C" fn(*mut c_void, *const CharT, *const CharT) -> i32;
+
+/// `hostfxr_get_runtime_delegate`.
+type GetRuntimeDelegateFn = unsafe extern "C" fn(*mut c_void, i32, *mut *mut c_void) -> i32;
+
+/// `hostfxr_close`.
+type CloseFn = unsafe extern "C" fn(*mut c_void) -> i32;
+
+/// `load_assembly_and_get_function_pointer_fn`.
+type LoadAssemblyAndGetFunctionPointerFn = unsafe extern "system" fn(
+    assembly_path: *const CharT,
+    type_name: *const CharT,
+    method_name: *const CharT,
+    delegate_type_name: *const CharT,
+    reserved: *mut c_void,
+    delegate: *mut *mut c_void,
+) -> i32;
+
+/// Signature of the methods called by `RustHostFxr::invoke`:
+/// `[UnmanagedCallersOnly] static int Method(IntPtr args, int size)`.
+pub type ComponentEntryPoint = unsafe extern "system" fn(args: *mut c_void, size: i32) -> i32;
+
+/// Hosts .NET 6+ (CoreCLR) through `hostfxr`, the library behind the `dotnet` muxer.
+///
+/// The runtime is started from the assembly's `runtimeconfig.json`, and static methods marked
+/// `[UnmanagedCallersOnly]` are then called directly. Unlike `RustClr`, this works on Linux
+/// and macOS as well as Windows.
+///
+/// # Examples
+///
+/// ```ignore
+/// use rustclr::RustHostFxr;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let mut host = RustHostFxr::new("bin/Library.dll")?
+///         .with_property("System.GC.Server", "false");
+///
+///     let mut args = *b"payload";
+///     let code = host.invoke("Library.Exports", "Run", &mut args)?;
+///     println!("Returned {code}");
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct RustHostFxr {
+    /// Absolute path of the assembly to load.
+    assembly_path: PathBuf,
+
+    /// Bytes of the assembly, used to check types and methods before calling into the runtime.
+    buffer: Vec<u8>,
+
+    /// Simple name of the assembly, used to qualify type names.
+    assembly_name: String,
+
+    /// Path of the `runtimeconfig.json`, defaulting to the one next to the assembly.
+    runtime_config: Option<PathBuf>,
+
+    /// Path of the `hostfxr` library, located through `nethost` when unset.
+    hostfxr_path: Option<PathBuf>,
+
+    /// Root of the .NET installation (the directory of the `dotnet` executable).
+    dotnet_root: Option<PathBuf>,
+
+    /// Runtime properties set before the runtime starts.
+    properties: Vec<(String, String)>,
+
+    /// `load_assembly_and_get_function_pointer`, once the runtime is started.
+    load_assembly: Option<LoadAssemblyAndGetFunctionPointerFn>,
+
+    /// Temporary directory holding an assembly given as bytes, removed on drop.
+    staging_dir: Option<PathBuf>,
+}
+
+impl RustHostFxr {
+    /// Creates a new `RustHostFxr` for the assembly at `path`.
+    ///
+    /// Nothing is loaded until `prepare` or the first call into the assembly.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the .NET assembly (typically a class library).
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Self)` - If the file is a .NET assembly.
+    /// * `Err(ClrError)` - If the file cannot be read or is not a .NET assembly.
+    pub fn new(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let buffer = fs::read(path).map_err(|_| ClrError::ErrorClr("Failed to read the assembly"))?;
+        if !is_dotnet(&buffer) {
+            return Err(ClrError::NotDotNet);
+        }
+
+        let assembly_name = Metadata::parse(&buffer)?
+            .assembly()
+            .ok_or(ClrError::MetadataError("the module does not define an assembly"))?
+            .name;
+
+        Ok(Self {
+            assembly_path: std::path::absolute(path).map_err(|_| ClrError::ErrorClr("Invalid assembly path"))?,
+            buffer,
+            assembly_name,
+            runtime_config: None,
+            hostfxr_path: None,
+            dotnet_root: None,
+            properties: Vec::new(),
+            load_assembly: None,
+            staging_dir: None,
+        })
+    }
+
+    /// Creates a new `RustHostFxr` for an assembly held in memory.
+    ///
+    /// `hostfxr` only loads assemblies and configurations from disk, so the assembly is written,
+    /// along with the `runtimeconfig.json` and `deps.json` rendered from `config`, to a fresh
+    /// temporary directory. The directory is removed when the host is dropped, where the
+    /// platform allows deleting loaded files.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - A reference to a byte slice representing the .NET assembly.
+    /// * `config` - The runtime configuration, typically from `RuntimeConfig::from_assembly`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Self)` - If the assembly and its configuration are staged.
+    /// * `Err(ClrError)` - If the buffer is not a .NET assembly or the files cannot be written.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// use rustclr::{RollForward, RuntimeConfig, RustHostFxr};
+    /// use std::fs;
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let buffer = fs::read("bin/Library.dll")?;
+    ///     let config = RuntimeConfig::from_assembly(&buffer)?
+    ///         .with_roll_forward(RollForward::LatestMajor);
+    ///
+    ///     let mut host = RustHostFxr::from_bytes(&buffer, &config)?;
+    ///     let code = host.invoke("Library.Exports", "Run", &mut [])?;
+    ///     println!("Returned {code}");
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn from_bytes(buffer: &[u8], config: &RuntimeConfig) -> Result<Self> {
+        if !is_dotnet(buffer) {
+            return Err(ClrError::NotDotNet);
+        }
+
+        let staging_dir = env::temp_dir().join(format!("rustclr-{}", uuid::Uuid::new_v4()));
+        let staged = (|| {
+            fs::create_dir_all(&staging_dir).map_err(|_| ClrError::ErrorClr("Failed to create the staging directory"))?;
+            let assembly_path = staging_dir.join(config.file_name());
+            fs::write(&assembly_path, buffer).map_err(|_| ClrError::ErrorClr("Failed to write the assembly"))?;
+            let runtime_config = config.write(&staging_dir)?;
+
+            let mut host = Self::new(&assembly_path)?.with_runtime_config(runtime_config);
+            host.staging_dir = Some(staging_dir.clone());
+            Ok(host)
+        })();
+
+        if staged.is_err() {
+            let _ = fs::remove_dir_all(&staging_dir);
+        }
+
+        staged
+    }
+
+    /// Sets the `runtimeconfig.json` used to start the runtime.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the file. Defaults to `<assembly>.runtimeconfig.json` next to the assembly.
+    ///
+    /// # Returns
+    ///
+    /// * Returns the modified `RustHostFxr` instance.
+    pub fn with_runtime_config(mut self, path: impl AsRef<Path>) -> Self {
+        self.runtime_config = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Sets the path of the `hostfxr` library instead of locating it.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Full path of `hostfxr.dll`, `libhostfxr.so` or `libhostfxr.dylib`.
+    ///
+    /// # Returns
+    ///
+    /// * Returns the modified `RustHostFxr` instance.
+    pub fn with_hostfxr(mut self, path: impl AsRef<Path>) -> Self {
+        self.hostfxr_path = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Sets the root of the .NET installation used to locate `hostfxr` and the frameworks.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The directory containing the `dotnet` executable, e.g. `/usr/share/dotnet`.
+    ///
+    /// # Returns
+    ///
+    /// * Returns the modified `RustHostFxr` instance.
+    pub fn with_dotnet_root(mut self, path: impl AsRef<Path>) -> Self {
+        self.dotnet_root = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Sets a runtime property (an `AppContext` switch such as `System.GC.Server`).
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the property.
+    /// * `value` - Its value.
+    ///
+    /// # Returns
+    ///
+    /// * Returns the modified `RustHostFxr` instance.
+    pub fn with_property(mut self, name: &str, value: &str) -> Self {
+        self.properties.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Loads `hostfxr` and starts the runtime.
+    ///
+    /// Called automatically by `get_function` and `invoke`. Only one CoreCLR runtime can live in
+    /// a process: if one is already running, its configuration must be compatible.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the runtime is started.
+    /// * `Err(ClrError)` - If `hostfxr` cannot be found or the runtime fails to start.
+    pub fn prepare(&mut self) -> Result<()> {
+        if self.load_assembly.is_some() {
+            return Ok(());
+        }
+
+        let hostfxr_path = match &self.hostfxr_path {
+            Some(path) => path.clone(),
+            None => locate_hostfxr(self.dotnet_root.as_deref())?,
+        };
+
+        let runtime_config = match &self.runtime_config {
+            Some(path) => path.clone(),
+            None => self.assembly_path.with_extension("runtimeconfig.json"),
+        };
+
+        let library = Library::load(&hostfxr_path)?;
+        unsafe {
+            let initialize = library.symbol::<InitializeForRuntimeConfigFn>("hostfxr_initialize_for_runtime_config")?;
+            let set_property = library.symbol::<SetRuntimePropertyValueFn>("hostfxr_set_runtime_property_value")?;
+            let get_delegate = library.symbol::<GetRuntimeDelegateFn>("hostfxr_get_runtime_delegate")?;
+            let close = library.symbol::<CloseFn>("hostfxr_close")?;
+
+            let config = path_to_char_t(&runtime_config)?;
+            let dotnet_root = self.dotnet_root.as_deref().map(path_to_char_t).transpose()?;
+            let parameters = dotnet_root.as_ref().map(|root| InitializeParameters {
+                size: size_of::<InitializeParameters>(),
+                host_path: null(),
+                dotnet_root: root.as_ptr(),
+            });
+
+            let parameters = parameters.as_ref().map_or(null(), |parameters| parameters as *const _);
+            let mut handle = null_mut();
+            let code = initialize(config.as_ptr(), parameters, &mut handle);
+            if code < 0 || handle.is_null() {
+                return Err(ClrError::ApiError("hostfxr_initialize_for_runtime_config", code));
+            }
+
+            // The context only needs to live until the delegate is obtained
+            let result = (|| {
+                for (name, value) in &self.properties {
+                    let code = set_property(handle, to_char_t(name).as_ptr(), to_char_t(value).as_ptr());
+                    if code < 0 {
+                        return Err(ClrError::ApiError("hostfxr_set_runtime_property_value", code));
+                    }
+                }
+
+                let mut delegate = null_mut();
+                let code = get_delegate(handle, HDT_LOAD_ASSEMBLY_AND_GET_FUNCTION_POINTER, &mut delegate);
+                if code < 0 || delegate.is_null() {
+                    return Err(ClrError::ApiError("hostfxr_get_runtime_delegate", code));
+                }
+
+                Ok(core::mem::transmute::<*mut c_void, LoadAssemblyAndGetFunctionPointerFn>(delegate))
+            })();
+
+            close(handle);
+            self.load_assembly = Some(result?);
+        }
+
+        Ok(())
+    }
+
+    /// Returns a function pointer to a static method marked `[UnmanagedCallersOnly]`.
+    ///
+    /// The type and method are checked against the assembly metadata before the runtime loads it.
+    ///
+    /// # Arguments
+    ///
+    /// * `type_name` - The full name of the type, e.g. `Namespace.Type` or `Namespace.Outer+Inner`.
+    /// * `method` - The name of the method.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(F)` - The method, as the function pointer type `F`.
+    /// * `Err(ClrError)` - If the method does not exist or the runtime fails to resolve it.
+    ///
+    /// # Safety
+    ///
+    /// `F` must be an `extern "system"` function pointer type matching the managed signature.
+    pub unsafe fn get_function<F: Copy>(&mut self, type_name: &str, method: &str) -> Result<F> {
+        assert_eq!(size_of::<F>(), size_of::<*mut c_void>(), "methods must be cast to function pointers");
+
+        let metadata = Metadata::parse(&self.buffer)?;
+        let ty = metadata
+            .find_type(type_name)?
+            .ok_or_else(|| ClrError::TypeNotFound(type_name.to_string()))?;
+
+        if !ty.methods.iter().any(|candidate| candidate.name == method) {
+            return Err(ClrError::MethodNotFound);
+        }
+
+        self.prepare()?;
+        let load_assembly = self.load_assembly.ok_or(ClrError::ErrorClr("The runtime is not started"))?;
+
+        let mut function = null_mut();
+        let code = load_assembly(
+            path_to_char_t(&self.assembly_path)?.as_ptr(),
+            to_char_t(&format!("{type_name}, {}", self.assembly_name)).as_ptr(),
+            to_char_t(method).as_ptr(),
+            UNMANAGED_CALLERS_ONLY_METHOD,
+            null_mut(),
+            &mut function,
+        );
+
+        if code < 0 || function.is_null() {
+            return Err(ClrError::ApiError("load_assembly_and_get_function_pointer", code));
+        }
+
+        Ok(core::mem::transmute_copy::<*mut c_void, F>(&function))
+    }
+
+    /// Calls `[UnmanagedCallersOnly] static int Method(IntPtr args, int size)`.
+    ///
+    /// # Arguments
+    ///
+    /// * `type_name` - The full name of the type, e.g. `Namespace.Type`.
+    /// * `method` - The name of the method.
+    /// * `args` - The buffer passed to the method, which may write back into it.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(i32)` - The value returned by the method.
+    /// * `Err(ClrError)` - If the method cannot be resolved.
+    pub fn invoke(&mut self, type_name: &str, method: &str, args: &mut [u8]) -> Result<i32> {
+        let size = i32::try_from(args.len()).map_err(|_| ClrError::ErrorClr("The argument buffer is too large"))?;
+        unsafe {
+            let function = self.get_function::<ComponentEntryPoint>(type_name, method)?;
+            Ok(function(args.as_mut_ptr().cast(), size))
+        }
+    }
+}
+
+impl Drop for RustHostFxr {
+    fn drop(&mut self) {
+        if let Some(dir) = self.staging_dir.take() {
+            let _ = fs::remove_dir_all(dir);
+        }
+    }
+}
+
+/// Locates the `hostfxr` library of a .NET installation.
+///
+/// `nethost` is asked first when it can be loaded; otherwise the newest `host/fxr/<version>`
+/// directory is taken from `dotnet_root`, `DOTNET_ROOT` or the default install locations.
+///
+/// # Arguments
+///
+/// * `dotnet_root` - The installation to use, instead of searching for one.
+///
+/// # Returns
+///
+/// * `Ok(PathBuf)` - The full path of the library.
+/// * `Err(ClrError)` - `ClrError::HostNotFound` if no installation contains it.
+pub fn locate_hostfxr(dotnet_root: Option<&Path>) -> Result<PathBuf> {
+    if let Some(path) = nethost_hostfxr_path(dotnet_root) {
+        return Ok(path);
+    }
+
+    dotnet_roots(dotnet_root)
+        .into_iter()
+        .find_map(|root| latest_version_dir(&root.join("host").join("fxr")))
+        .map(|dir| dir.join(HOSTFXR_LIBRARY))
+        .filter(|path| path.is_file())
+        .ok_or_else(|| ClrError::HostNotFound(HOSTFXR_LIBRARY.to_string()))
+}
+
+/// Asks `nethost`, when it is on the library search path, where `hostfxr` lives.
+fn nethost_hostfxr_path(dotnet_root: Option<&Path>) -> Option<PathBuf> {
+    let library = Library::load(Path::new(NETHOST_LIBRARY)).ok()?;
+    unsafe {
+        let get_hostfxr_path = library.symbol::<GetHostFxrPathFn>("get_hostfxr_path").ok()?;
+        let root = dotnet_root.map(path_to_char_t).transpose().ok()?;
+        let parameters = GetHostFxrParameters {
+            size: size_of::<GetHostFxrParameters>(),
+            assembly_path: null(),
+            dotnet_root: root.as_ref().map_or(null(), |root| root.as_ptr()),
+        };
+
+        let mut buffer = vec![0 as CharT; MAX_PATH];
+        let mut size = buffer.len();
+        if get_hostfxr_path(buffer.as_mut_ptr(), &mut size, &parameters) != 0 {
+            return None;
+        }
+
+        buffer.truncate(buffer.iter().position(|&c| c == 0)?);
+        Some(PathBuf::from(from_char_t(&buffer)))
+    }
+}
+
+/// Lists the candidate roots of a .NET installation, most specific first.
+pub(crate) fn dotnet_roots(dotnet_root: Option<&Path>) -> Vec<PathBuf> {
+    if let Some(root) = dotnet_root {
+        return vec![root.to_path_buf()];
+    }
+
+    let mut roots = env::var_os("DOTNET_ROOT").map(PathBuf::from).into_iter().collect::<Vec<_>>();
+
+    #[cfg(windows)]
+    roots.extend(env::var_os("ProgramFiles").map(|dir| PathBuf::from(dir).join("dotnet")));
+
+    #[cfg(unix)]
+    {
+        roots.extend(env::var_os("HOME").map(|home| PathBuf::from(home).join(".dotnet")));
+        roots.extend(["/usr/share/dotnet", "/usr/lib/dotnet", "/usr/local/share/dotnet", "/opt/dotnet"].map(PathBuf::from));
+    }
+
+    roots
+}
+
+/// Returns the subdirectory of `dir` named after the highest version, e.g. `host/fxr/8.0.1`.
+///
+/// Prerelease versions such as `9.0.0-rc.1` rank below the release they precede.
+pub(crate) fn latest_version_dir(dir: &Path) -> Option<PathBuf> {
+    fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let name = entry.file_name().into_string().ok()?;
+            let (version, prerelease) = match name.split_once('-') {
+                Some((version, _)) => (version, true),
+                None => (name.as_str(), false),
+            };
+
+            let key = (parse_version(version)?, !prerelease);
+            entry.path().is_dir().then(|| (key, entry.path()))
+        })
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, path)| path)
+}
+
+/// Encodes a string as a null-terminated `char_t` string.
+pub(crate) fn to_char_t(value: &str) -> Vec<CharT> {
+    #[cfg(windows)]
+    return value.encode_utf16().chain(Some(0)).collect();
+
+    #[cfg(not(windows))]
+    return value.bytes().map(|byte| byte as CharT).chain(Some(0)).collect();
+}
+
+/// Encodes a path as a null-terminated `char_t` string.
+pub(crate) fn path_to_char_t(path: &Path) -> Result<Vec<CharT>> {
+    path.to_str()
+        .map(to_char_t)
+        .ok_or_else(|| ClrError::HostNotFound(path.display().to_string()))
+}
+
+/// Decodes a `char_t` string without its terminator.
+pub(crate) fn from_char_t(value: &[CharT]) -> String {
+    #[cfg(windows)]
+    return String::from_utf16_lossy(value);
+
+    #[cfg(not(windows))]
+    return String::from_utf8_lossy(&value.iter().map(|&c| c as u8).collect::<Vec<_>>()).into_owned();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_the_latest_version_directory() {
+        let dir = env::temp_dir().join(format!("rustclr-fxr-{}", std::process::id()));
+        for version in ["6.0.25", "8.0.1", "8.0.10", "9.0.0-rc.1", "not-a-version"] {
+            fs::create_dir_all(dir.join(version)).unwrap();
+        }
+
+        fs::write(dir.join("10.0.0"), b"a file, not a directory").unwrap();
+        assert_eq!(latest_version_dir(&dir), Some(dir.join("9.0.0-rc.1")));
+
+        fs::create_dir_all(dir.join("9.0.0")).unwrap();
+        assert_eq!(latest_version_dir(&dir), Some(dir.join("9.0.0")));
+        assert_eq!(latest_version_dir(&dir.join("missing")), None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn encodes_host_strings() {
+        let encoded = to_char_t("fxr-é");
+        assert_eq!(encoded.last(), Some(&0));
+        assert_eq!(from_char_t(&encoded[..encoded.len() - 1]), "fxr-é");
+        assert_eq!(dotnet_roots(Some(Path::new("/opt/custom"))), vec![PathBuf::from("/opt/custom")]);
+    }
+}
@@ -3,21 +3,70 @@
 #![allow(clippy::not_unsafe_ptr_arg_deref)]
 
 /// Defines data structures and descriptions for manipulating and interacting with the CLR.
+#[cfg(windows)]
 pub mod data;
 
 /// Contains definitions for COM interoperability, making it easier to call methods and manipulate COM interfaces.
+#[cfg(windows)]
 pub mod com;
 
 /// Manages specific error types used when interacting with the CLR and COM APIs.
 pub mod error;
 
+/// Offline reader for the ECMA-335 metadata of .NET assemblies, independent of the CLR.
+pub mod metadata;
+
+/// Backends hosting the runtime for `RustClr`: COM on Windows, or a stand-in elsewhere.
+pub mod backend;
+
 /// Main CLR module, providing functions and structures for working with the Common Language Runtime.
 mod clr;
 
+/// Low-level CoreCLR hosting through `coreclr_initialize`, with a caller-supplied TPA list.
+mod coreclr;
+
+/// CoreCLR (.NET 6+) hosting through `hostfxr`, available on Windows, Linux and macOS.
+mod hostfxr;
+
+/// Wrappers over a started CLR, such as `RustClrEnv` and the Windows-only `PowerShell`.
+mod env;
+
+/// Generation of the `runtimeconfig.json` and `deps.json` of an assembly from its metadata.
+mod runtimeconfig;
+
+/// In-memory resolution of dependent assemblies through `AppDomain.AssemblyResolve`.
+#[cfg(windows)]
+mod resolver;
+
+/// .NET runtime versions and the selection of an installed runtime for an assembly.
+mod version;
+
+/// Arguments passed to .NET methods, independent of their COM representation.
+mod value;
+
+/// Exact encoding of `Value`s in the `VARIANT` and `SAFEARRAY` memory layouts, independent of OLE.
+mod marshal;
+
+/// Conversion of `serde` types to and from `Value` trees, and optionally JSON strings.
+#[cfg(feature = "serde")]
+mod bridge;
+
 /// Auxiliary functions for common manipulations and conversions needed when interacting with the CLR and COM.
 mod utils;
 
+pub use backend::*;
+#[cfg(feature = "serde")]
+pub use bridge::*;
 pub use clr::*;
+pub use coreclr::*;
+pub use hostfxr::*;
+pub use env::*;
+#[cfg(windows)]
+pub use resolver::*;
+pub use runtimeconfig::*;
 pub use utils::*;
+pub use marshal::*;
+pub use value::*;
+pub use version::*;
 
 type Result<T> = core::result::Result<T, error::ClrError>;
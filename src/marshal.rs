@@ -0,0 +1,1111 @@
+use std::{
+    ffi::c_void, fmt,
+    mem::{size_of, ManuallyDrop},
+    ptr::{self, null_mut, NonNull},
+    slice,
+};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use uuid::Uuid;
+use crate::{error::ClrError, vt, Decimal, Result, Value};
+#[cfg(windows)]
+use crate::OwnedVariant;
+
+/// The Unix epoch as an OLE Automation date.
+const UNIX_EPOCH_DAYS: f64 = 25569.0;
+
+/// The sign bit of a `DECIMAL`.
+const DECIMAL_NEG: u8 = 0x80;
+
+/// The type GUID of the `System.Guid` record in `mscorlib.tlb`, reported by its `IRecordInfo`.
+pub(crate) const SYSTEM_GUID_TYPE: u128 = 0x9c5923e9_de52_33ea_88de_7ebc8633b9cc;
+
+/// `SAFEARRAY` feature flags, describing the type of the elements.
+const FADF_RECORD: u16 = 0x0020;
+const FADF_HAVEVARTYPE: u16 = 0x0080;
+const FADF_BSTR: u16 = 0x0100;
+const FADF_UNKNOWN: u16 = 0x0200;
+const FADF_DISPATCH: u16 = 0x0400;
+const FADF_VARIANT: u16 = 0x0800;
+
+/// The memory layout of a `VARIANT`.
+///
+/// The `vt` tag is followed by three reserved words and a payload holding a scalar, a pointer
+/// (`BSTR`, `SAFEARRAY`, interface or, with `VT_BYREF`, the referenced value) or a record.
+/// `VT_DECIMAL` is the exception: its `DECIMAL` overlays the whole structure but `vt`.
+#[repr(C, align(8))]
+#[derive(Clone, Copy)]
+pub struct RawVariant {
+    /// The `VARENUM` type of the payload.
+    pub vt: u16,
+
+    /// Reserved words, which hold the scale, sign and high bits of a `VT_DECIMAL`.
+    pub reserved: [u16; 3],
+
+    /// The payload.
+    pub data: RawVariantData,
+}
+
+/// The payload of a `RawVariant`.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub union RawVariantData {
+    /// The bits of a scalar, of at most 8 bytes.
+    pub bits: u64,
+
+    /// A `BSTR`, `SAFEARRAY`, interface or `VT_BYREF` pointer.
+    pub pointer: *mut c_void,
+
+    /// A `VT_RECORD`: the record data and its `IRecordInfo`.
+    pub record: [*mut c_void; 2],
+}
+
+impl Default for RawVariant {
+    /// Returns a `VT_EMPTY` variant.
+    fn default() -> Self {
+        Self {
+            vt: vt::EMPTY,
+            reserved: [0; 3],
+            data: RawVariantData { record: [null_mut(); 2] },
+        }
+    }
+}
+
+impl fmt::Debug for RawVariant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RawVariant").field(&vt_name(self.vt)).finish()
+    }
+}
+
+/// The memory layout of a one-dimensional `SAFEARRAY`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct RawSafeArray {
+    /// The number of dimensions.
+    pub dims: u16,
+
+    /// The `FADF_*` flags describing the allocation and the type of the elements.
+    pub features: u16,
+
+    /// The size of one element, in bytes.
+    pub element_size: u32,
+
+    /// The number of times the array is locked.
+    pub locks: u32,
+
+    /// The elements.
+    pub data: *mut c_void,
+
+    /// The number of elements.
+    pub len: u32,
+
+    /// The index of the first element.
+    pub lower_bound: i32,
+}
+
+/// The memory layout of a `DECIMAL`.
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct RawDecimal {
+    reserved: u16,
+    scale: u8,
+    sign: u8,
+    hi: u32,
+    lo: u64,
+}
+
+/// The start of the vtable of every COM interface.
+#[repr(C)]
+struct IUnknownVtbl {
+    query_interface: unsafe extern "system" fn(*mut c_void, *const c_void, *mut *mut c_void) -> i32,
+    add_ref: unsafe extern "system" fn(*mut c_void) -> u32,
+    release: unsafe extern "system" fn(*mut c_void) -> u32,
+}
+
+/// The start of the vtable of `IRecordInfo`, up to `GetGuid`.
+#[repr(C)]
+struct IRecordInfoVtbl {
+    base: IUnknownVtbl,
+    record_init: Option<unsafe extern "system" fn()>,
+    record_clear: Option<unsafe extern "system" fn()>,
+    record_copy: Option<unsafe extern "system" fn()>,
+    get_guid: unsafe extern "system" fn(*mut c_void, *mut [u8; 16]) -> i32,
+}
+
+/// A static `IRecordInfo` describing `System.Guid`, attached to the records of `VariantArena`
+/// so that they decode like the ones the OLE functions create.
+#[repr(C)]
+struct GuidRecordInfo {
+    vtable: &'static IRecordInfoVtbl,
+}
+
+/// The only `GuidRecordInfo`, which is never freed.
+static GUID_RECORD_INFO: GuidRecordInfo = GuidRecordInfo {
+    vtable: &IRecordInfoVtbl {
+        base: IUnknownVtbl {
+            query_interface: GuidRecordInfo::query_interface,
+            add_ref: GuidRecordInfo::add_ref,
+            release: GuidRecordInfo::add_ref,
+        },
+        record_init: None,
+        record_clear: None,
+        record_copy: None,
+        get_guid: GuidRecordInfo::get_guid,
+    },
+};
+
+impl GuidRecordInfo {
+    /// Returns the pointer to `GUID_RECORD_INFO` stored next to the records.
+    fn as_raw() -> *mut c_void {
+        &GUID_RECORD_INFO as *const GuidRecordInfo as *mut c_void
+    }
+
+    unsafe extern "system" fn query_interface(_: *mut c_void, _: *const c_void, object: *mut *mut c_void) -> i32 {
+        *object = null_mut();
+        0x80004002u32 as i32
+    }
+
+    unsafe extern "system" fn add_ref(_: *mut c_void) -> u32 {
+        1
+    }
+
+    unsafe extern "system" fn get_guid(_: *mut c_void, guid: *mut [u8; 16]) -> i32 {
+        *guid = Uuid::from_u128(SYSTEM_GUID_TYPE).to_bytes_le();
+        0
+    }
+}
+
+/// A counted reference to a COM object, such as a .NET object passed as `VT_UNKNOWN`.
+///
+/// Cloning it calls `AddRef` and dropping it calls `Release`, through the vtable of the object.
+pub struct ObjectRef(NonNull<c_void>);
+
+impl ObjectRef {
+    /// Takes ownership of a reference to a COM object.
+    ///
+    /// # Arguments
+    ///
+    /// * `object` - The interface pointer.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(ObjectRef)` - The reference, released when it is dropped.
+    /// * `None` - If `object` is null.
+    ///
+    /// # Safety
+    ///
+    /// `object` must be null or a COM interface pointer whose reference is handed over.
+    pub unsafe fn from_raw(object: *mut c_void) -> Option<Self> {
+        NonNull::new(object).map(Self)
+    }
+
+    /// Takes a new reference to a COM object, calling `AddRef`.
+    ///
+    /// # Arguments
+    ///
+    /// * `object` - The interface pointer.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(ObjectRef)` - The new reference.
+    /// * `None` - If `object` is null.
+    ///
+    /// # Safety
+    ///
+    /// `object` must be null or a valid COM interface pointer.
+    pub unsafe fn from_raw_borrowed(object: *mut c_void) -> Option<Self> {
+        let object = ManuallyDrop::new(Self::from_raw(object)?);
+        Some(ObjectRef::clone(&object))
+    }
+
+    /// Returns the interface pointer, without taking a reference.
+    pub fn as_raw(&self) -> *mut c_void {
+        self.0.as_ptr()
+    }
+
+    /// Hands the reference over to the caller, which must release it.
+    pub fn into_raw(self) -> *mut c_void {
+        ManuallyDrop::new(self).as_raw()
+    }
+
+    /// Returns the `IUnknown` methods of the object.
+    fn vtable(&self) -> &IUnknownVtbl {
+        unsafe { &**(self.as_raw() as *const *const IUnknownVtbl) }
+    }
+}
+
+#[cfg(windows)]
+impl<T: windows_core::Interface> From<&T> for ObjectRef {
+    /// Takes a new reference to a COM interface.
+    fn from(object: &T) -> Self {
+        Self(NonNull::new(object.clone().into_raw()).expect("interface pointers are not null"))
+    }
+}
+
+impl Clone for ObjectRef {
+    fn clone(&self) -> Self {
+        unsafe { (self.vtable().add_ref)(self.as_raw()) };
+        Self(self.0)
+    }
+}
+
+impl Drop for ObjectRef {
+    fn drop(&mut self) {
+        unsafe { (self.vtable().release)(self.as_raw()) };
+    }
+}
+
+impl PartialEq for ObjectRef {
+    /// Compares the interface pointers.
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl fmt::Debug for ObjectRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ObjectRef").field(&self.0).finish()
+    }
+}
+
+/// Allocates the memory encoded `VARIANT`s point to: strings, arrays and records.
+///
+/// `VariantArena` keeps it in Rust-owned buffers, while the Windows adapter behind
+/// `OwnedVariant::try_from` uses the OLE functions, so that `VariantClear` frees it.
+///
+/// # Safety
+///
+/// The pointers returned must be valid allocations of the requested layouts, since
+/// `encode_variant` writes the elements of the arrays in place.
+pub unsafe trait VariantAllocator {
+    /// Allocates a `BSTR`.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The UTF-16 characters, without a terminator.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(*mut u16)` - The `BSTR`, pointing to its first character.
+    /// * `Err(ClrError)` - If the allocation fails.
+    fn alloc_string(&mut self, text: &[u16]) -> Result<*mut u16>;
+
+    /// Allocates a one-dimensional `SAFEARRAY` of zeroed elements, starting at index 0.
+    ///
+    /// # Arguments
+    ///
+    /// * `vt` - The type of the elements.
+    /// * `len` - The number of elements.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(NonNull<RawSafeArray>)` - The array descriptor.
+    /// * `Err(ClrError)` - If the allocation fails.
+    fn alloc_array(&mut self, vt: u16, len: u32) -> Result<NonNull<RawSafeArray>>;
+
+    /// Allocates a `System.Guid` record.
+    ///
+    /// # Arguments
+    ///
+    /// * `guid` - The GUID, in its memory layout.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok([*mut c_void; 2])` - The record data and its `IRecordInfo`.
+    /// * `Err(ClrError)` - If the record cannot be created.
+    fn alloc_guid(&mut self, guid: &[u8; 16]) -> Result<[*mut c_void; 2]>;
+
+    /// Returns the pointer a `VARIANT` referencing `object` holds, taking the reference it owns.
+    fn retain(&mut self, object: &ObjectRef) -> *mut c_void;
+
+    /// Copies a `VARIANT` passed as is.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The `VARIANT` to copy.
+    /// * `variant` - The `VT_EMPTY` variant receiving the copy.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the copy is made.
+    /// * `Err(ClrError)` - If it fails, or the allocator cannot free what it references.
+    #[cfg(windows)]
+    fn copy_variant(&mut self, source: &OwnedVariant, variant: &mut RawVariant) -> Result<()> {
+        let _ = (source, variant);
+        Err(ClrError::VariantUnsupported)
+    }
+}
+
+/// A `VariantAllocator` keeping everything in Rust-owned buffers, freed when it is dropped.
+///
+/// Strings and arrays are laid out as `SysAllocString` and `SafeArrayCreateVector` lay them
+/// out, so values can be encoded, inspected and decoded on any platform.
+#[derive(Debug, Default)]
+pub struct VariantArena {
+    /// The allocations, aligned on 8 bytes.
+    blocks: Vec<Box<[u64]>>,
+
+    /// The objects referenced by the encoded values.
+    objects: Vec<ObjectRef>,
+}
+
+impl VariantArena {
+    /// Allocates `size` zeroed bytes, aligned on 8 bytes.
+    fn alloc(&mut self, size: usize) -> *mut u8 {
+        let mut block = vec![0u64; size.div_ceil(8)].into_boxed_slice();
+        let ptr = block.as_mut_ptr() as *mut u8;
+        self.blocks.push(block);
+        ptr
+    }
+}
+
+unsafe impl VariantAllocator for VariantArena {
+    fn alloc_string(&mut self, text: &[u16]) -> Result<*mut u16> {
+        // The length in bytes precedes the characters, which are followed by a terminator
+        let bytes = size_of_val(text);
+        let block = self.alloc(4 + bytes + 2);
+        unsafe {
+            write(block, bytes as u32);
+            let bstr = block.add(4) as *mut u16;
+            ptr::copy_nonoverlapping(text.as_ptr(), bstr, text.len());
+            Ok(bstr)
+        }
+    }
+
+    fn alloc_array(&mut self, vt: u16, len: u32) -> Result<NonNull<RawSafeArray>> {
+        let size = element_size(vt)?;
+        let features = match vt {
+            vt::RECORD => FADF_RECORD,
+            vt::BSTR => FADF_HAVEVARTYPE | FADF_BSTR,
+            vt::UNKNOWN => FADF_HAVEVARTYPE | FADF_UNKNOWN,
+            vt::DISPATCH => FADF_HAVEVARTYPE | FADF_DISPATCH,
+            vt::VARIANT => FADF_HAVEVARTYPE | FADF_VARIANT,
+            _ => FADF_HAVEVARTYPE,
+        };
+
+        // The element type is kept in the 4 bytes preceding the descriptor, the elements follow it
+        let header = size_of::<RawSafeArray>().next_multiple_of(8);
+        let block = self.alloc(8 + header + size * len as usize);
+        unsafe {
+            // Record arrays keep their `IRecordInfo` there instead
+            if features & FADF_HAVEVARTYPE != 0 {
+                write(block.add(4), u32::from(vt));
+            } else if features & FADF_RECORD != 0 {
+                write(block.add(8 - size_of::<*mut c_void>()), GuidRecordInfo::as_raw());
+            }
+
+            let array = block.add(8) as *mut RawSafeArray;
+            array.write(RawSafeArray {
+                dims: 1,
+                features,
+                element_size: size as u32,
+                locks: 0,
+                data: block.add(8 + header).cast(),
+                len,
+                lower_bound: 0,
+            });
+
+            Ok(NonNull::new_unchecked(array))
+        }
+    }
+
+    fn alloc_guid(&mut self, guid: &[u8; 16]) -> Result<[*mut c_void; 2]> {
+        let record = self.alloc(guid.len());
+        unsafe { ptr::copy_nonoverlapping(guid.as_ptr(), record, guid.len()) };
+        Ok([record.cast(), GuidRecordInfo::as_raw()])
+    }
+
+    fn retain(&mut self, object: &ObjectRef) -> *mut c_void {
+        self.objects.push(object.clone());
+        object.as_raw()
+    }
+}
+
+/// Returns the size of an element of type `vt` in a `SAFEARRAY`, which is also the size of
+/// the value a `VT_BYREF` of that type points to.
+///
+/// # Arguments
+///
+/// * `vt` - The type of the element. `VT_RECORD` elements are `System.Guid`s.
+///
+/// # Returns
+///
+/// * `Ok(usize)` - The size in bytes.
+/// * `Err(ClrError)` - If `vt` cannot be stored in an array.
+pub fn element_size(vt: u16) -> Result<usize> {
+    Ok(match vt {
+        vt::I1 | vt::UI1 => 1,
+        vt::I2 | vt::UI2 | vt::BOOL => 2,
+        vt::I4 | vt::UI4 | vt::INT | vt::UINT | vt::R4 => 4,
+        vt::I8 | vt::UI8 | vt::R8 | vt::DATE => 8,
+        vt::BSTR | vt::UNKNOWN | vt::DISPATCH => size_of::<*mut c_void>(),
+        vt::DECIMAL | vt::RECORD => 16,
+        vt::VARIANT => size_of::<RawVariant>(),
+        other => return Err(unsupported(other)),
+    })
+}
+
+/// Encodes a `Value` into the `VARIANT` layout.
+///
+/// The type of `variant` is set as soon as the array it points to is allocated, so when an
+/// element fails to encode, `variant` describes the partly filled array and clearing it frees
+/// everything allocated so far.
+///
+/// # Arguments
+///
+/// * `value` - The value to encode.
+/// * `variant` - The variant receiving it, overwritten without being cleared.
+/// * `allocator` - Allocates the strings, arrays and records `variant` points to.
+///
+/// # Returns
+///
+/// * `Ok(())` - If the value is encoded.
+/// * `Err(ClrError)` - If an allocation fails, or an array holds elements of another type.
+pub fn encode_variant(value: &Value, variant: &mut RawVariant, allocator: &mut dyn VariantAllocator) -> Result<()> {
+    *variant = RawVariant::default();
+    match value {
+        Value::Decimal(decimal) => {
+            // The DECIMAL overlays the whole VARIANT, so `vt` is set afterwards
+            unsafe { write_decimal(variant as *mut RawVariant as *mut u8, decimal) };
+        }
+        Value::Guid(guid) => variant.data.record = allocator.alloc_guid(&guid.to_bytes_le())?,
+        Value::Bytes(bytes) => {
+            let data = alloc_array(variant, vt::UI1, bytes.len(), allocator)?;
+            unsafe { ptr::copy_nonoverlapping(bytes.as_ptr(), data, bytes.len()) };
+        }
+        Value::Array(element_vt, items) => {
+            let size = element_size(*element_vt)?;
+            let data = alloc_array(variant, *element_vt, items.len(), allocator)?;
+            for (i, item) in items.iter().enumerate() {
+                unsafe { encode_element(item, *element_vt, data.add(i * size), allocator)? };
+            }
+        }
+        #[cfg(windows)]
+        Value::Variant(source) => return allocator.copy_variant(source, variant),
+        _ => unsafe {
+            let payload = &mut variant.data as *mut RawVariantData as *mut u8;
+            encode_element(value, value.vt(), payload, allocator)?
+        },
+    }
+
+    variant.vt = value.vt();
+    Ok(())
+}
+
+/// Allocates the array of a `VT_ARRAY` variant and sets its type.
+///
+/// # Returns
+///
+/// * `Ok(*mut u8)` - The storage of the elements.
+/// * `Err(ClrError)` - If the array cannot be allocated.
+fn alloc_array(variant: &mut RawVariant, element_vt: u16, len: usize, allocator: &mut dyn VariantAllocator) -> Result<*mut u8> {
+    let len = u32::try_from(len)
+        .map_err(|_| ClrError::SafeArrayError(format!("{len} elements do not fit in a SAFEARRAY")))?;
+
+    let array = allocator.alloc_array(element_vt, len)?;
+    variant.data.pointer = array.as_ptr().cast();
+    variant.vt = vt::ARRAY | element_vt;
+    Ok(unsafe { array.as_ref().data.cast() })
+}
+
+/// Writes a value as an element of type `vt`, as stored in a `SAFEARRAY` or behind a `VT_BYREF`.
+///
+/// # Safety
+///
+/// `slot` must be valid for writes of `element_size(vt)` bytes.
+unsafe fn encode_element(value: &Value, vt: u16, slot: *mut u8, allocator: &mut dyn VariantAllocator) -> Result<()> {
+    if vt == vt::VARIANT {
+        return encode_variant(value, &mut *(slot as *mut RawVariant), allocator);
+    }
+
+    if value.vt() != vt {
+        return Err(ClrError::SafeArrayError(format!(
+            "expected {} elements, found {}",
+            vt_name(vt),
+            vt_name(value.vt())
+        )));
+    }
+
+    match value {
+        Value::Empty | Value::Null => {}
+        Value::Bool(value) => write(slot, if *value { -1i16 } else { 0 }),
+        Value::I8(value) => write(slot, *value),
+        Value::I16(value) => write(slot, *value),
+        Value::I32(value) => write(slot, *value),
+        Value::I64(value) => write(slot, *value),
+        Value::U8(value) => write(slot, *value),
+        Value::U16(value) => write(slot, *value),
+        Value::U32(value) => write(slot, *value),
+        Value::U64(value) => write(slot, *value),
+        Value::F32(value) => write(slot, *value),
+        Value::F64(value) => write(slot, *value),
+        Value::Char(value) => write(slot, u16::try_from(u32::from(*value)).unwrap_or(0xfffd)),
+        Value::String(value) => {
+            let text = value.encode_utf16().collect::<Vec<u16>>();
+            write(slot, allocator.alloc_string(&text)?);
+        }
+        Value::Decimal(value) => write_decimal(slot, value),
+        Value::Date(value) => write(slot, ole_date(*value)),
+        Value::Guid(value) => write(slot, value.to_bytes_le()),
+        Value::Object(value) => write(slot, allocator.retain(value)),
+        _ => return Err(unsupported(vt)),
+    }
+
+    Ok(())
+}
+
+/// Decodes a `VARIANT` to a `Value`, copying everything it points to.
+///
+/// `VT_BYREF` variants decode to the value they reference. Decoding is the inverse of
+/// `encode_variant`, except that `VT_UI2` decodes to `Value::U16`, `VT_INT` and `VT_UINT` to
+/// `Value::I32` and `Value::U32`, `VT_DISPATCH` to `Value::Object`, a null array to
+/// `Value::Empty`, and dates are rounded to the millisecond.
+///
+/// # Arguments
+///
+/// * `variant` - The variant to decode.
+///
+/// # Returns
+///
+/// * `Ok(Value)` - The decoded value.
+/// * `Err(ClrError)` - If the variant holds a type `Value` has no equivalent for, such as a
+///   `VT_RECORD` whose `IRecordInfo` describes another structure than `System.Guid`.
+///
+/// # Safety
+///
+/// Everything `variant` points to must be valid for its `vt`, including the `IRecordInfo`
+/// of records and record arrays.
+pub unsafe fn decode_variant(variant: &RawVariant) -> Result<Value> {
+    let vt = variant.vt;
+
+    // Records keep the same pair with `VT_BYREF`, and are only read once their type is known
+    if vt & !vt::BYREF == vt::RECORD {
+        let [record, record_info] = variant.data.record;
+        check_guid_record(record_info)?;
+        if record.is_null() {
+            return Err(ClrError::NullPointerError("VARIANT payload"));
+        }
+
+        return Ok(Value::Guid(Uuid::from_bytes_le(read(record as *const u8))));
+    }
+
+    let payload = match vt {
+        _ if vt & vt::BYREF != 0 => variant.data.pointer as *const u8,
+        vt::DECIMAL => variant as *const RawVariant as *const u8,
+        _ => &variant.data as *const RawVariantData as *const u8,
+    };
+
+    if payload.is_null() {
+        return Err(ClrError::NullPointerError("VARIANT payload"));
+    }
+
+    decode_element(vt & !vt::BYREF, payload)
+}
+
+/// Reads an element of type `vt`, as stored in a `SAFEARRAY` or behind a `VT_BYREF`.
+///
+/// # Safety
+///
+/// `slot` must point to a valid element of type `vt`. For `VT_RECORD`, the caller checks
+/// that the records are `System.Guid`s.
+unsafe fn decode_element(vt: u16, slot: *const u8) -> Result<Value> {
+    if vt & vt::ARRAY != 0 {
+        return decode_array(vt & !vt::ARRAY, read(slot));
+    }
+
+    Ok(match vt {
+        vt::EMPTY => Value::Empty,
+        vt::NULL => Value::Null,
+        vt::BOOL => Value::Bool(read::<i16>(slot) != 0),
+        vt::I1 => Value::I8(read(slot)),
+        vt::I2 => Value::I16(read(slot)),
+        vt::I4 | vt::INT => Value::I32(read(slot)),
+        vt::I8 => Value::I64(read(slot)),
+        vt::UI1 => Value::U8(read(slot)),
+        vt::UI2 => Value::U16(read(slot)),
+        vt::UI4 | vt::UINT => Value::U32(read(slot)),
+        vt::UI8 => Value::U64(read(slot)),
+        vt::R4 => Value::F32(read(slot)),
+        vt::R8 => Value::F64(read(slot)),
+        vt::DATE => Value::Date(system_time(read(slot))),
+        vt::BSTR => Value::String(read_bstr(read(slot))),
+        vt::RECORD => Value::Guid(Uuid::from_bytes_le(read(slot))),
+        vt::VARIANT => decode_variant(&*(slot as *const RawVariant))?,
+        vt::UNKNOWN | vt::DISPATCH => match ObjectRef::from_raw_borrowed(read(slot)) {
+            Some(object) => Value::Object(object),
+            None => Value::Empty,
+        },
+        vt::DECIMAL => {
+            let decimal = read::<RawDecimal>(slot);
+            let magnitude = (i128::from(decimal.hi) << 64) | i128::from(decimal.lo);
+            let mantissa = if decimal.sign & DECIMAL_NEG != 0 { -magnitude } else { magnitude };
+            Value::Decimal(Decimal::new(mantissa, decimal.scale)?)
+        }
+        other => return Err(unsupported(other)),
+    })
+}
+
+/// Reads a one-dimensional `SAFEARRAY` of elements of type `vt`.
+///
+/// # Safety
+///
+/// `array` must be null or point to a valid `SAFEARRAY` of elements of type `vt`.
+unsafe fn decode_array(vt: u16, array: *const RawSafeArray) -> Result<Value> {
+    let Some(array) = array.as_ref() else {
+        return Ok(Value::Empty);
+    };
+
+    let size = element_size(vt)?;
+    if vt == vt::RECORD {
+        // The `IRecordInfo` of the elements precedes the descriptor
+        let record_info = match array.features & FADF_RECORD {
+            0 => null_mut(),
+            _ => read((array as *const RawSafeArray as *const u8).sub(size_of::<*mut c_void>())),
+        };
+
+        check_guid_record(record_info)?;
+    }
+
+    if array.dims != 1 || array.element_size as usize != size {
+        return Err(ClrError::VariantTypeMismatch("Value", format!(
+            "{} with {} dimensions of {}-byte elements",
+            vt_name(vt::ARRAY | vt),
+            array.dims,
+            array.element_size
+        )));
+    }
+
+    let len = array.len as usize;
+    let data = array.data as *const u8;
+    if len == 0 {
+        return Ok(if vt == vt::UI1 { Value::Bytes(Vec::new()) } else { Value::Array(vt, Vec::new()) });
+    }
+
+    if vt == vt::UI1 {
+        return Ok(Value::Bytes(slice::from_raw_parts(data, len).to_vec()));
+    }
+
+    let items = (0..len)
+        .map(|i| decode_element(vt, data.add(i * size)))
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Value::Array(vt, items))
+}
+
+/// Checks that records are `System.Guid`s, the only structure `Value` has an equivalent for.
+///
+/// # Safety
+///
+/// `record_info` must be null or point to a valid `IRecordInfo`.
+unsafe fn check_guid_record(record_info: *mut c_void) -> Result<()> {
+    let mismatch = |found: String| ClrError::VariantTypeMismatch("Value", found);
+    if record_info.is_null() {
+        return Err(mismatch("VT_RECORD without IRecordInfo".to_string()));
+    }
+
+    let vtable = &**(record_info as *const *const IRecordInfoVtbl);
+    let mut guid = [0u8; 16];
+    if (vtable.get_guid)(record_info, &mut guid) != 0 {
+        return Err(mismatch("VT_RECORD of an unknown type".to_string()));
+    }
+
+    match Uuid::from_bytes_le(guid) {
+        guid if guid == Uuid::from_u128(SYSTEM_GUID_TYPE) => Ok(()),
+        guid => Err(mismatch(format!("VT_RECORD {{{guid}}}"))),
+    }
+}
+
+/// Reads the text of a `BSTR`, whose length in bytes precedes its first character.
+///
+/// # Safety
+///
+/// `bstr` must be null or a valid `BSTR`.
+unsafe fn read_bstr(bstr: *const u16) -> String {
+    if bstr.is_null() {
+        return String::new();
+    }
+
+    let bytes = read::<u32>((bstr as *const u8).sub(4));
+    String::from_utf16_lossy(slice::from_raw_parts(bstr, bytes as usize / 2))
+}
+
+/// Writes the `DECIMAL` layout of `decimal`.
+///
+/// # Safety
+///
+/// `slot` must be valid for writes of 16 bytes.
+unsafe fn write_decimal(slot: *mut u8, decimal: &Decimal) {
+    let magnitude = decimal.mantissa().unsigned_abs();
+    write(slot, RawDecimal {
+        reserved: 0,
+        scale: decimal.scale(),
+        sign: if decimal.mantissa() < 0 { DECIMAL_NEG } else { 0 },
+        hi: (magnitude >> 64) as u32,
+        lo: magnitude as u64,
+    });
+}
+
+/// Writes a value at a possibly unaligned address.
+unsafe fn write<T>(slot: *mut u8, value: T) {
+    ptr::write_unaligned(slot as *mut T, value)
+}
+
+/// Reads a value from a possibly unaligned address.
+unsafe fn read<T>(slot: *const u8) -> T {
+    ptr::read_unaligned(slot as *const T)
+}
+
+/// Returns the error for a type `Value` has no equivalent for.
+fn unsupported(vt: u16) -> ClrError {
+    ClrError::VariantTypeMismatch("Value", vt_name(vt))
+}
+
+/// Converts a point in time to an OLE Automation date, the number of days since
+/// 1899-12-30 00:00 UTC.
+///
+/// # Arguments
+///
+/// * `time` - The point in time.
+///
+/// # Returns
+///
+/// * The `VT_DATE` value.
+pub(crate) fn ole_date(time: SystemTime) -> f64 {
+    let seconds = match time.duration_since(UNIX_EPOCH) {
+        Ok(elapsed) => elapsed.as_secs_f64(),
+        Err(e) => -e.duration().as_secs_f64(),
+    };
+
+    let days = seconds / 86_400.0 + UNIX_EPOCH_DAYS;
+    if days >= 0.0 {
+        return days;
+    }
+
+    // Before 1899-12-30 the whole days count backwards, but the fraction still counts the time of day forwards
+    let whole = days.floor();
+    whole - (days - whole)
+}
+
+/// Converts an OLE Automation date back to a point in time, rounded to the millisecond.
+///
+/// # Arguments
+///
+/// * `date` - The `VT_DATE` value.
+///
+/// # Returns
+///
+/// * The point in time.
+pub(crate) fn system_time(date: f64) -> SystemTime {
+    let whole = date.trunc();
+    let days = whole + (date - whole).abs();
+    let millis = ((days - UNIX_EPOCH_DAYS) * 86_400_000.0).round();
+    if millis >= 0.0 {
+        UNIX_EPOCH + Duration::from_millis(millis as u64)
+    } else {
+        UNIX_EPOCH - Duration::from_millis(-millis as u64)
+    }
+}
+
+/// Returns the name of a `VARENUM` type, e.g. `VT_ARRAY | VT_BSTR`, for error messages.
+pub(crate) fn vt_name(vt: u16) -> String {
+    let base = match vt & 0x0FFF {
+        vt::EMPTY => "VT_EMPTY".to_string(),
+        vt::NULL => "VT_NULL".to_string(),
+        vt::I1 => "VT_I1".to_string(),
+        vt::I2 => "VT_I2".to_string(),
+        vt::I4 => "VT_I4".to_string(),
+        vt::I8 => "VT_I8".to_string(),
+        vt::UI1 => "VT_UI1".to_string(),
+        vt::UI2 => "VT_UI2".to_string(),
+        vt::UI4 => "VT_UI4".to_string(),
+        vt::UI8 => "VT_UI8".to_string(),
+        vt::INT => "VT_INT".to_string(),
+        vt::UINT => "VT_UINT".to_string(),
+        vt::R4 => "VT_R4".to_string(),
+        vt::R8 => "VT_R8".to_string(),
+        vt::BOOL => "VT_BOOL".to_string(),
+        vt::BSTR => "VT_BSTR".to_string(),
+        vt::VARIANT => "VT_VARIANT".to_string(),
+        vt::DATE => "VT_DATE".to_string(),
+        vt::DECIMAL => "VT_DECIMAL".to_string(),
+        vt::RECORD => "VT_RECORD".to_string(),
+        vt::UNKNOWN => "VT_UNKNOWN".to_string(),
+        vt::DISPATCH => "VT_DISPATCH".to_string(),
+        other => format!("vt {other:#06x}"),
+    };
+
+    let mut name = String::new();
+    if vt & vt::BYREF != 0 {
+        name.push_str("VT_BYREF | ");
+    }
+
+    if vt & vt::ARRAY != 0 {
+        name.push_str("VT_ARRAY | ");
+    }
+
+    name + &base
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quickcheck::{Arbitrary, Gen, QuickCheck};
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// The element types of the generated typed arrays. Arrays of `VT_UI1` are `Value::Bytes`.
+    const ARRAY_TYPES: [u16; 15] = [
+        vt::BOOL, vt::I1, vt::I2, vt::I4, vt::I8, vt::UI2, vt::UI4, vt::UI8,
+        vt::R4, vt::R8, vt::BSTR, vt::DECIMAL, vt::DATE, vt::RECORD, vt::VARIANT,
+    ];
+
+    /// A value that decodes back to itself: no `Char`, since it decodes to `U16`, no objects,
+    /// no NaNs and no dates more precise than the millisecond.
+    #[derive(Debug, Clone)]
+    struct Canonical(Value);
+
+    impl Arbitrary for Canonical {
+        fn arbitrary(g: &mut Gen) -> Self {
+            Canonical(value(g, 2))
+        }
+    }
+
+    /// Generates a value, nesting `VARIANT` arrays at most `depth` times.
+    fn value(g: &mut Gen, depth: u32) -> Value {
+        match u8::arbitrary(g) % if depth == 0 { 3 } else { 5 } {
+            0 => if bool::arbitrary(g) { Value::Empty } else { Value::Null },
+            1 => scalar(g, vt::UI1),
+            2 => {
+                let vt = *g.choose(&ARRAY_TYPES[..14]).unwrap();
+                scalar(g, vt)
+            }
+            3 => Value::Bytes(Vec::arbitrary(g)),
+            _ => {
+                let vt = *g.choose(&ARRAY_TYPES).unwrap();
+                let len = usize::arbitrary(g) % 6;
+                let items = (0..len)
+                    .map(|_| if vt == vt::VARIANT { value(g, depth - 1) } else { scalar(g, vt) })
+                    .collect();
+
+                Value::Array(vt, items)
+            }
+        }
+    }
+
+    /// Generates a scalar of type `vt`.
+    fn scalar(g: &mut Gen, vt: u16) -> Value {
+        match vt {
+            vt::BOOL => Value::Bool(bool::arbitrary(g)),
+            vt::I1 => Value::I8(i8::arbitrary(g)),
+            vt::I2 => Value::I16(i16::arbitrary(g)),
+            vt::I4 => Value::I32(i32::arbitrary(g)),
+            vt::I8 => Value::I64(i64::arbitrary(g)),
+            vt::UI1 => Value::U8(u8::arbitrary(g)),
+            vt::UI2 => Value::U16(u16::arbitrary(g)),
+            vt::UI4 => Value::U32(u32::arbitrary(g)),
+            vt::UI8 => Value::U64(u64::arbitrary(g)),
+            vt::R4 => Value::F32(Some(f32::arbitrary(g)).filter(|f| !f.is_nan()).unwrap_or_default()),
+            vt::R8 => Value::F64(Some(f64::arbitrary(g)).filter(|f| !f.is_nan()).unwrap_or_default()),
+            vt::BSTR => Value::String(String::arbitrary(g)),
+            vt::RECORD => Value::Guid(Uuid::from_u128(u128::arbitrary(g))),
+            vt::DECIMAL => {
+                let mantissa = i128::arbitrary(g) % (Decimal::MAX_MANTISSA + 1);
+                Value::Decimal(Decimal::new(mantissa, u8::arbitrary(g) % 29).unwrap())
+            }
+            vt::DATE => {
+                // Within about 250 years of 1970, so before the OLE epoch as well
+                let millis = i64::arbitrary(g) % 8_000_000_000_000;
+                let offset = Duration::from_millis(millis.unsigned_abs());
+                Value::Date(if millis < 0 { UNIX_EPOCH - offset } else { UNIX_EPOCH + offset })
+            }
+            _ => unreachable!("not a scalar type: {vt}"),
+        }
+    }
+
+    /// Encodes a value in `arena`.
+    fn encode(value: &Value, arena: &mut VariantArena) -> RawVariant {
+        let mut variant = RawVariant::default();
+        encode_variant(value, &mut variant, arena).unwrap();
+        variant
+    }
+
+    #[test]
+    fn round_trips_values() {
+        fn round_trip(value: Canonical) -> bool {
+            let mut arena = VariantArena::default();
+            let variant = encode(&value.0, &mut arena);
+            unsafe { decode_variant(&variant) }.unwrap() == value.0
+        }
+
+        QuickCheck::new().tests(500).quickcheck(round_trip as fn(Canonical) -> bool);
+    }
+
+    #[test]
+    fn decodes_by_reference() {
+        fn by_reference(value: Canonical) -> bool {
+            let mut arena = VariantArena::default();
+            let mut variant = encode(&value.0, &mut arena);
+
+            // VT_BYREF | VT_VARIANT points to the variant
+            let mut reference = RawVariant { vt: vt::BYREF | vt::VARIANT, ..RawVariant::default() };
+            reference.data.pointer = &mut variant as *mut RawVariant as *mut c_void;
+            if unsafe { decode_variant(&reference) }.unwrap() != value.0 {
+                return false;
+            }
+
+            // Any other VT_BYREF points to the payload, which records and empty values cannot be referenced as
+            reference.vt = vt::BYREF | variant.vt;
+            reference.data.pointer = match variant.vt {
+                vt::EMPTY | vt::NULL | vt::RECORD => return true,
+                vt::DECIMAL => &mut variant as *mut RawVariant as *mut c_void,
+                _ => &mut variant.data as *mut RawVariantData as *mut c_void,
+            };
+
+            unsafe { decode_variant(&reference) }.unwrap() == value.0
+        }
+
+        QuickCheck::new().tests(500).quickcheck(by_reference as fn(Canonical) -> bool);
+    }
+
+    #[test]
+    fn lays_out_variants_exactly() {
+        assert_eq!(size_of::<RawVariant>(), 8 + 2 * size_of::<usize>());
+        assert_eq!(size_of::<RawSafeArray>(), if size_of::<usize>() == 8 { 32 } else { 24 });
+
+        let mut arena = VariantArena::default();
+        let flag = encode(&Value::Bool(true), &mut arena);
+        assert_eq!((flag.vt, unsafe { flag.data.bits }), (vt::BOOL, 0xffff));
+
+        let decimal = encode(&Value::Decimal("-1.5".parse().unwrap()), &mut arena);
+        let bytes = unsafe { slice::from_raw_parts(&decimal as *const RawVariant as *const u8, 16) };
+        assert_eq!(bytes, [14, 0, 1, 0x80, 0, 0, 0, 0, 15, 0, 0, 0, 0, 0, 0, 0]);
+
+        let text = encode(&Value::String("hé".to_string()), &mut arena);
+        unsafe {
+            let bstr = text.data.pointer as *const u8;
+            assert_eq!(slice::from_raw_parts(bstr.sub(4), 10), [4, 0, 0, 0, b'h', 0, 0xe9, 0, 0, 0]);
+        }
+
+        let array = encode(&Value::Array(vt::I2, vec![Value::I16(1), Value::I16(-2)]), &mut arena);
+        assert_eq!(array.vt, vt::ARRAY | vt::I2);
+        unsafe {
+            let descriptor = &*(array.data.pointer as *const RawSafeArray);
+            assert_eq!((descriptor.dims, descriptor.element_size, descriptor.len), (1, 2, 2));
+            assert_eq!(descriptor.features, FADF_HAVEVARTYPE);
+            assert_eq!(read::<u32>((descriptor as *const RawSafeArray as *const u8).sub(4)), u32::from(vt::I2));
+            assert_eq!(slice::from_raw_parts(descriptor.data as *const i16, 2), [1, -2]);
+        }
+    }
+
+    #[test]
+    fn rejects_mismatched_elements() {
+        let mut arena = VariantArena::default();
+        let mut variant = RawVariant::default();
+        let error = encode_variant(&Value::Array(vt::I4, vec![Value::I32(1), Value::Bool(true)]), &mut variant, &mut arena)
+            .unwrap_err();
+
+        assert_eq!(error.to_string(), "Error creating SafeArray: expected VT_I4 elements, found VT_BOOL");
+        assert_eq!(variant.vt, vt::ARRAY | vt::I4);
+
+        variant.vt = 6;
+        assert!(matches!(unsafe { decode_variant(&variant) }, Err(ClrError::VariantTypeMismatch("Value", found)) if found == "vt 0x0006"));
+    }
+
+    #[test]
+    fn rejects_foreign_records() {
+        // An 8-byte `Point` record, whose `IRecordInfo` reports its own type GUID
+        unsafe extern "system" fn get_point_guid(_: *mut c_void, guid: *mut [u8; 16]) -> i32 {
+            *guid = Uuid::from_u128(0x0123_4567_89ab_cdef_0123_4567_89ab_cdef).to_bytes_le();
+            0
+        }
+
+        static POINT_RECORD_INFO: GuidRecordInfo = GuidRecordInfo {
+            vtable: &IRecordInfoVtbl {
+                base: IUnknownVtbl {
+                    query_interface: GuidRecordInfo::query_interface,
+                    add_ref: GuidRecordInfo::add_ref,
+                    release: GuidRecordInfo::add_ref,
+                },
+                record_init: None,
+                record_clear: None,
+                record_copy: None,
+                get_guid: get_point_guid,
+            },
+        };
+
+        let point_record_info = &POINT_RECORD_INFO as *const GuidRecordInfo as *mut c_void;
+        let mut point = [1i32, 2];
+        let mut variant = RawVariant { vt: vt::RECORD, ..RawVariant::default() };
+        variant.data.record = [point.as_mut_ptr().cast(), point_record_info];
+
+        let error = unsafe { decode_variant(&variant) }.unwrap_err();
+        assert_eq!(error.to_string(), ClrError::VariantTypeMismatch(
+            "Value",
+            "VT_RECORD {01234567-89ab-cdef-0123-456789abcdef}".to_string(),
+        ).to_string());
+
+        variant.data.record = [point.as_mut_ptr().cast(), null_mut()];
+        assert!(matches!(unsafe { decode_variant(&variant) }, Err(ClrError::VariantTypeMismatch(..))));
+
+        // Arrays of 16-byte records of another type are not read as GUIDs either
+        let mut arena = VariantArena::default();
+        let array = encode(&Value::Array(vt::RECORD, vec![Value::Guid(Uuid::nil())]), &mut arena);
+        assert_eq!(unsafe { decode_variant(&array) }.unwrap(), Value::Array(vt::RECORD, vec![Value::Guid(Uuid::nil())]));
+        unsafe {
+            let descriptor = array.data.pointer as *mut u8;
+            write(descriptor.sub(size_of::<*mut c_void>()), point_record_info);
+        }
+
+        assert!(matches!(unsafe { decode_variant(&array) }, Err(ClrError::VariantTypeMismatch(..))));
+    }
+
+    #[test]
+    fn counts_object_references() {
+        #[repr(C)]
+        struct Object {
+            vtable: *const IUnknownVtbl,
+            references: AtomicU32,
+        }
+
+        unsafe extern "system" fn query_interface(_: *mut c_void, _: *const c_void, _: *mut *mut c_void) -> i32 {
+            0x80004002u32 as i32
+        }
+
+        unsafe extern "system" fn add_ref(this: *mut c_void) -> u32 {
+            (*(this as *const Object)).references.fetch_add(1, Ordering::SeqCst) + 1
+        }
+
+        unsafe extern "system" fn release(this: *mut c_void) -> u32 {
+            (*(this as *const Object)).references.fetch_sub(1, Ordering::SeqCst) - 1
+        }
+
+        static VTABLE: IUnknownVtbl = IUnknownVtbl { query_interface, add_ref, release };
+        let object = Object { vtable: &VTABLE, references: AtomicU32::new(1) };
+        let references = || object.references.load(Ordering::SeqCst);
+
+        let reference = unsafe { ObjectRef::from_raw(&object as *const Object as *mut c_void) }.unwrap();
+        {
+            let value = Value::Object(reference.clone());
+            let mut arena = VariantArena::default();
+            let variant = encode(&value, &mut arena);
+            assert_eq!((variant.vt, references()), (vt::UNKNOWN, 3));
+
+            let decoded = unsafe { decode_variant(&variant) }.unwrap();
+            assert_eq!((decoded, references()), (value, 4));
+        }
+
+        assert_eq!(references(), 1);
+        drop(reference);
+        assert_eq!(references(), 0);
+    }
+
+    #[test]
+    fn converts_dates() {
+        assert_eq!(ole_date(UNIX_EPOCH), 25569.0);
+        assert_eq!(ole_date(UNIX_EPOCH + Duration::from_secs(43_200)), 25569.5);
+
+        // 1899-12-29 06:00 is one day before the OLE epoch, plus a quarter of a day
+        let before = UNIX_EPOCH - Duration::from_secs(25569 * 86_400 + 64_800);
+        assert_eq!(ole_date(before), -1.25);
+        assert_eq!(system_time(-1.25), before);
+        assert_eq!(system_time(25569.5), UNIX_EPOCH + Duration::from_secs(43_200));
+    }
+}
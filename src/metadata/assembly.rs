@@ -0,0 +1,174 @@
+use super::{identity::public_key_token, AssemblyIdentity, AssemblyVersion, Metadata, TableId, Token};
+
+/// `AssemblyFlags.PublicKey`: the blob holds the full public key rather than its token.
+const ASSEMBLY_PUBLIC_KEY: u32 = 0x0001;
+
+/// The row of the `Assembly` table describing the assembly itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssemblyDefinition {
+    /// `AssemblyHashAlgorithm` used for the files of the assembly.
+    pub hash_algorithm: u32,
+
+    /// `AssemblyFlags` of the assembly.
+    pub flags: u32,
+
+    /// Simple name of the assembly.
+    pub name: String,
+
+    /// Version of the assembly.
+    pub version: AssemblyVersion,
+
+    /// Culture of the assembly, `None` when neutral.
+    pub culture: Option<String>,
+
+    /// Full public key of strong-named assemblies, empty otherwise.
+    pub public_key: Vec<u8>,
+}
+
+impl AssemblyDefinition {
+    /// Returns `true` if the assembly carries a public key.
+    pub fn is_strong_named(&self) -> bool {
+        !self.public_key.is_empty()
+    }
+
+    /// Returns the identity of the assembly, deriving the public key token from the public key.
+    pub fn identity(&self) -> AssemblyIdentity {
+        AssemblyIdentity {
+            name: self.name.clone(),
+            version: self.version,
+            culture: self.culture.clone(),
+            public_key_token: self.is_strong_named().then(|| public_key_token(&self.public_key)),
+        }
+    }
+}
+
+/// A row of the `AssemblyRef` table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssemblyReference {
+    /// Metadata token of the reference.
+    pub token: Token,
+
+    /// `AssemblyFlags` of the reference.
+    pub flags: u32,
+
+    /// Simple name of the referenced assembly.
+    pub name: String,
+
+    /// Version of the referenced assembly.
+    pub version: AssemblyVersion,
+
+    /// Culture of the referenced assembly, `None` when neutral.
+    pub culture: Option<String>,
+
+    /// The public key token, or the full key when `AssemblyFlags.PublicKey` is set.
+    pub public_key_or_token: Vec<u8>,
+}
+
+impl AssemblyReference {
+    /// Returns the identity the reference asks the loader for.
+    ///
+    /// References carrying a full public key get the token derived from it.
+    pub fn identity(&self) -> AssemblyIdentity {
+        let public_key_token = match self.flags & ASSEMBLY_PUBLIC_KEY {
+            _ if self.public_key_or_token.is_empty() => None,
+            0 => self.public_key_or_token.as_slice().try_into().ok(),
+            _ => Some(public_key_token(&self.public_key_or_token)),
+        };
+
+        AssemblyIdentity {
+            name: self.name.clone(),
+            version: self.version,
+            culture: self.culture.clone(),
+            public_key_token,
+        }
+    }
+}
+
+impl<'a> Metadata<'a> {
+    /// Reads the `Assembly` table, which only assemblies (not modules) have.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(AssemblyDefinition)` - The name, version, culture and public key of the assembly.
+    /// * `None` - If the image is a module without an `Assembly` row.
+    pub fn assembly(&self) -> Option<AssemblyDefinition> {
+        let row = self.tables().table(TableId::Assembly).row(1)?;
+        Some(AssemblyDefinition {
+            hash_algorithm: row[0],
+            version: AssemblyVersion::new(row[1] as u16, row[2] as u16, row[3] as u16, row[4] as u16),
+            flags: row[5],
+            public_key: self.blobs().get(row[6]).unwrap_or_default().to_vec(),
+            name: self.strings().get(row[7]).unwrap_or_default().to_string(),
+            culture: self.culture(row[8]),
+        })
+    }
+
+    /// Lists the assemblies referenced through the `AssemblyRef` table.
+    ///
+    /// # Returns
+    ///
+    /// * The references in table order.
+    pub fn assembly_references(&self) -> Vec<AssemblyReference> {
+        let table = self.tables().table(TableId::AssemblyRef);
+        (1..=table.len())
+            .filter_map(|index| {
+                let row = table.row(index)?;
+                Some(AssemblyReference {
+                    token: Token::new(TableId::AssemblyRef, index),
+                    version: AssemblyVersion::new(row[0] as u16, row[1] as u16, row[2] as u16, row[3] as u16),
+                    flags: row[4],
+                    public_key_or_token: self.blobs().get(row[5]).unwrap_or_default().to_vec(),
+                    name: self.strings().get(row[6])?.to_string(),
+                    culture: self.culture(row[7]),
+                })
+            })
+            .collect()
+    }
+
+    /// Reads a culture name, mapping the empty string and `neutral` to `None`.
+    fn culture(&self, offset: u32) -> Option<String> {
+        self.strings()
+            .get(offset)
+            .filter(|culture| !culture.is_empty() && !culture.eq_ignore_ascii_case("neutral"))
+            .map(str::to_string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::builder::AssemblyBuilder;
+
+    #[test]
+    fn reads_assembly_identities() {
+        let mut b = AssemblyBuilder::new();
+        let name = b.string("Sample");
+        let key = b.blob(&[0, 0, 0, 0, 0, 0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0]);
+        let mscorlib = b.string("mscorlib");
+        let resources = b.string("Sample.resources");
+        let de = b.string("de-DE");
+        let token = b.blob(&[0xB7, 0x7A, 0x5C, 0x56, 0x19, 0x34, 0xE0, 0x89]);
+        b.row(TableId::Assembly, &[0x8004, 1, 2, 3, 4, 0x0001, key, name, 0]);
+        b.row(TableId::AssemblyRef, &[4, 0, 0, 0, 0, token, mscorlib, 0, 0]);
+        b.row(TableId::AssemblyRef, &[1, 0, 0, 0, 0, 0, resources, de, 0]);
+
+        let buffer = b.build();
+        let metadata = Metadata::parse(&buffer).unwrap();
+
+        let assembly = metadata.assembly().unwrap();
+        assert_eq!(assembly.name, "Sample");
+        assert_eq!(assembly.version, AssemblyVersion::new(1, 2, 3, 4));
+        assert!(assembly.is_strong_named());
+        assert_eq!(assembly.identity().to_string(), "Sample, Version=1.2.3.4, Culture=neutral, PublicKeyToken=b77a5c561934e089");
+
+        let references = metadata.assembly_references();
+        assert_eq!(references.len(), 2);
+        assert_eq!(references[0].token, Token::new(TableId::AssemblyRef, 1));
+        assert_eq!(
+            references[0].identity().to_string(),
+            "mscorlib, Version=4.0.0.0, Culture=neutral, PublicKeyToken=b77a5c561934e089"
+        );
+        assert_eq!(references[1].identity().culture.as_deref(), Some("de-DE"));
+        assert_eq!(references[1].identity().public_key_token, None);
+    }
+}
@@ -0,0 +1,147 @@
+use super::{reader::Cursor, CodedIndex, Metadata, TableId, Token, TypeName};
+
+/// Prolog that starts every custom attribute value blob (ECMA-335 II.23.3).
+const CUSTOM_ATTRIBUTE_PROLOG: u16 = 0x0001;
+
+/// A custom attribute applied to a metadata entity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomAttribute<'a> {
+    /// The entity the attribute is applied to.
+    pub parent: Token,
+
+    /// The `MethodDef` or `MemberRef` token of the attribute constructor.
+    pub constructor: Token,
+
+    /// The attribute type, resolved from the constructor.
+    pub type_name: TypeName,
+
+    /// The raw value blob, holding the constructor arguments and named arguments.
+    pub value: &'a [u8],
+}
+
+impl CustomAttribute<'_> {
+    /// Reads the first fixed constructor argument as a string.
+    ///
+    /// This covers attributes such as `TargetFrameworkAttribute(string)` without
+    /// decoding the constructor signature.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(String)` - The value of the first argument.
+    /// * `None` - If the blob is malformed or the argument is a null string.
+    pub fn string_argument(&self) -> Option<String> {
+        let mut cursor = Cursor::new(self.value);
+        if cursor.u16()? != CUSTOM_ATTRIBUTE_PROLOG || cursor.peek_u8()? == 0xFF {
+            return None;
+        }
+
+        let len = cursor.compressed_u32()? as usize;
+        Some(String::from_utf8_lossy(cursor.bytes(len)?).into_owned())
+    }
+}
+
+impl<'a> Metadata<'a> {
+    /// Lists the custom attributes applied to `parent`.
+    ///
+    /// # Arguments
+    ///
+    /// * `parent` - Token of the entity, e.g. `Token::new(TableId::Assembly, 1)`.
+    ///
+    /// # Returns
+    ///
+    /// * The attributes whose constructor could be resolved, in table order.
+    pub fn custom_attributes(&self, parent: Token) -> Vec<CustomAttribute<'a>> {
+        let Some(encoded) = parent.table().and_then(|table| CodedIndex::HasCustomAttribute.encode(table, parent.row())) else {
+            return Vec::new();
+        };
+
+        let table = self.tables().table(TableId::CustomAttribute);
+        table
+            .equal_range(0, encoded)
+            .filter_map(|row| {
+                let (ctor_table, ctor_row) = CodedIndex::CustomAttributeType.decode(table.get(row, 1)?)?;
+                let constructor = Token::new(ctor_table, ctor_row);
+                Some(CustomAttribute {
+                    parent,
+                    constructor,
+                    type_name: self.member_parent(constructor)?,
+                    value: self.blobs().get(table.get(row, 2)?)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the value of the assembly's `TargetFrameworkAttribute`.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(String)` - The framework moniker, e.g. `.NETFramework,Version=v4.7.2`.
+    /// * `None` - If the assembly does not carry the attribute.
+    pub fn target_framework(&self) -> Option<String> {
+        self.custom_attributes(Token::new(TableId::Assembly, 1))
+            .into_iter()
+            .find(|attribute| {
+                attribute.type_name.namespace == "System.Runtime.Versioning"
+                    && attribute.type_name.name == "TargetFrameworkAttribute"
+            })
+            .and_then(|attribute| attribute.string_argument())
+    }
+
+    /// Resolves the type declaring a `MethodDef` or `MemberRef`.
+    pub(crate) fn member_parent(&self, member: Token) -> Option<TypeName> {
+        match member.table()? {
+            TableId::MethodDef => {
+                let row = self.declaring_type(member.row())?;
+                self.type_name(Token::new(TableId::TypeDef, row))
+            }
+            TableId::MemberRef => {
+                let class = self.tables().table(TableId::MemberRef).get(member.row(), 0)?;
+                let (table, row) = CodedIndex::MemberRefParent.decode(class)?;
+                self.type_name(Token::new(table, row))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::builder::AssemblyBuilder;
+
+    #[test]
+    fn reads_target_framework() {
+        let mut b = AssemblyBuilder::new();
+        let name = b.string("Sample");
+        let mscorlib = b.string("mscorlib");
+        b.row(TableId::Assembly, &[0x8004, 1, 0, 0, 0, 0, 0, name, 0]);
+        b.row(TableId::AssemblyRef, &[4, 0, 0, 0, 0, 0, mscorlib, 0, 0]);
+        b.target_framework(".NETFramework,Version=v4.7.2");
+
+        let buffer = b.build();
+        let metadata = Metadata::parse(&buffer).unwrap();
+        let attributes = metadata.custom_attributes(Token::new(TableId::Assembly, 1));
+        assert_eq!(attributes.len(), 1);
+        assert_eq!(attributes[0].type_name.to_string(), "System.Runtime.Versioning.TargetFrameworkAttribute");
+        assert_eq!(attributes[0].constructor, Token::new(TableId::MemberRef, 1));
+        assert_eq!(metadata.target_framework().as_deref(), Some(".NETFramework,Version=v4.7.2"));
+
+        assert!(metadata.custom_attributes(Token::new(TableId::TypeDef, 1)).is_empty());
+    }
+
+    #[test]
+    fn reads_long_target_framework() {
+        // Monikers of 128 bytes or more have a two-byte length
+        let moniker = format!(".NETCoreApp,Version=v8.0,Profile={}", "x".repeat(120));
+        let mut b = AssemblyBuilder::new();
+        let name = b.string("Sample");
+        let mscorlib = b.string("mscorlib");
+        b.row(TableId::Assembly, &[0x8004, 1, 0, 0, 0, 0, 0, name, 0]);
+        b.row(TableId::AssemblyRef, &[4, 0, 0, 0, 0, 0, mscorlib, 0, 0]);
+        b.target_framework(&moniker);
+
+        let buffer = b.build();
+        let metadata = Metadata::parse(&buffer).unwrap();
+        assert_eq!(metadata.target_framework(), Some(moniker));
+    }
+}
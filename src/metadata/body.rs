@@ -0,0 +1,288 @@
+use super::{cil::{decode_instructions, Instruction}, reader::Cursor, Metadata, TableId, Token};
+use crate::{error::ClrError, Result};
+
+/// `CorILMethod_TinyFormat`, flagging a one-byte header.
+const TINY_FORMAT: u8 = 0x2;
+
+/// `CorILMethod_FatFormat`, flagging a twelve-byte header.
+const FAT_FORMAT: u8 = 0x3;
+
+/// `CorILMethod_MoreSects`, flagging data sections after the code.
+const MORE_SECTS: u16 = 0x0008;
+
+/// `CorILMethod_InitLocals`.
+const INIT_LOCALS: u16 = 0x0010;
+
+/// `CorILMethod_Sect_*` flags of a data section header.
+const SECT_EH_TABLE: u8 = 0x01;
+const SECT_FAT_FORMAT: u8 = 0x40;
+const SECT_MORE_SECTS: u8 = 0x80;
+
+/// `COR_ILEXCEPTION_CLAUSE_*` kinds.
+const CLAUSE_FILTER: u32 = 0x0001;
+const CLAUSE_FINALLY: u32 = 0x0002;
+const CLAUSE_FAULT: u32 = 0x0004;
+
+/// `MethodImplAttributes.CodeTypeMask`; zero means the body is IL.
+pub(crate) const CODE_TYPE_MASK: u16 = 0x0003;
+
+/// How an exception handler is entered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExceptionHandler {
+    /// A `catch` block for exceptions of the given type.
+    Catch(Token),
+
+    /// A filter block starting at the given code offset, followed by its handler.
+    Filter(u32),
+
+    /// A `finally` block.
+    Finally,
+
+    /// A `fault` block, run only when an exception leaves the protected block.
+    Fault,
+}
+
+/// A clause of the exception-handling table of a method body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExceptionClause {
+    /// The kind of handler.
+    pub handler: ExceptionHandler,
+
+    /// Code offset of the protected block.
+    pub try_offset: u32,
+
+    /// Length in bytes of the protected block.
+    pub try_length: u32,
+
+    /// Code offset of the handler block.
+    pub handler_offset: u32,
+
+    /// Length in bytes of the handler block.
+    pub handler_length: u32,
+}
+
+/// The header and IL code of a method (ECMA-335 II.25.4).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MethodBody<'a> {
+    /// Maximum number of items on the evaluation stack.
+    pub max_stack: u16,
+
+    /// Whether locals are zero-initialized.
+    pub init_locals: bool,
+
+    /// `StandAloneSig` token of the locals signature, null when the method has no locals.
+    pub local_var_sig: Token,
+
+    /// The IL code.
+    pub code: &'a [u8],
+
+    /// The exception-handling clauses, innermost first.
+    pub exception_clauses: Vec<ExceptionClause>,
+}
+
+impl MethodBody<'_> {
+    /// Decodes the IL code into instructions.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<Instruction>)` - The instructions in code order.
+    /// * `Err(ClrError)` - If the code contains an undefined opcode or a truncated operand.
+    pub fn instructions(&self) -> Result<Vec<Instruction>> {
+        decode_instructions(self.code)
+    }
+}
+
+impl<'a> Metadata<'a> {
+    /// Reads the method body stored at `rva`.
+    ///
+    /// # Arguments
+    ///
+    /// * `rva` - The RVA of the body, as found in `MethodDefinition::rva`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(MethodBody)` - The header fields and the IL code.
+    /// * `Err(ClrError)` - If the RVA is not mapped or the header is malformed.
+    pub fn method_body(&self, rva: u32) -> Result<MethodBody<'a>> {
+        let malformed = || ClrError::MetadataError("malformed method body");
+        let data = self.rva(rva).ok_or(ClrError::MetadataError("method body is out of bounds"))?;
+        let mut cursor = Cursor::new(data);
+        let first = cursor.peek_u8().ok_or_else(malformed)?;
+        match first & 0x3 {
+            TINY_FORMAT => {
+                cursor.u8();
+                Ok(MethodBody {
+                    max_stack: 8,
+                    init_locals: false,
+                    local_var_sig: Token(0),
+                    code: cursor.bytes((first >> 2) as usize).ok_or_else(malformed)?,
+                    exception_clauses: Vec::new(),
+                })
+            }
+            FAT_FORMAT => {
+                let flags = cursor.u16().ok_or_else(malformed)?;
+                let max_stack = cursor.u16().ok_or_else(malformed)?;
+                let code_size = cursor.u32().ok_or_else(malformed)?;
+                let local_var_sig = Token(cursor.u32().ok_or_else(malformed)?);
+
+                // The size of the header, in dwords, lives in the top four bits of the flags
+                cursor.seek((flags >> 12) as usize * 4);
+                let code = cursor.bytes(code_size as usize).ok_or_else(malformed)?;
+                let exception_clauses = match flags & MORE_SECTS {
+                    0 => Vec::new(),
+                    _ => exception_clauses(&mut cursor).ok_or(ClrError::MetadataError("malformed method data section"))?,
+                };
+
+                Ok(MethodBody {
+                    max_stack,
+                    init_locals: flags & INIT_LOCALS != 0,
+                    local_var_sig,
+                    code,
+                    exception_clauses,
+                })
+            }
+            _ => Err(ClrError::MetadataError("invalid method body header")),
+        }
+    }
+
+    /// Lists the `MethodDef` rows whose body is IL stored in the image, with their RVAs.
+    ///
+    /// Abstract, runtime-provided and native methods are left out.
+    pub(crate) fn il_methods(&self) -> impl Iterator<Item = (Token, u32)> + '_ {
+        let table = self.tables().table(TableId::MethodDef);
+        (1..=table.len()).filter_map(move |row| {
+            let rva = table.get(row, 0)?;
+            let impl_flags = table.get(row, 1)? as u16;
+            (rva != 0 && impl_flags & CODE_TYPE_MASK == 0).then(|| (Token::new(TableId::MethodDef, row), rva))
+        })
+    }
+}
+
+/// Reads the data sections following the code of a fat body, keeping the exception clauses.
+fn exception_clauses(cursor: &mut Cursor) -> Option<Vec<ExceptionClause>> {
+    let mut clauses = Vec::new();
+    loop {
+        cursor.align(4);
+        let kind = cursor.u8()?;
+        let fat = kind & SECT_FAT_FORMAT != 0;
+        let (size, clause_size) = match fat {
+            true => {
+                let bytes = cursor.bytes(3)?;
+                (u32::from_le_bytes([bytes[0], bytes[1], bytes[2], 0]), 24)
+            }
+            false => {
+                let size = u32::from(cursor.u8()?);
+                cursor.u16()?;
+                (size, 12)
+            }
+        };
+
+        // The size covers the section header
+        let data_size = size.checked_sub(4)?;
+        if kind & SECT_EH_TABLE == 0 {
+            cursor.bytes(data_size as usize)?;
+        } else {
+            for _ in 0..data_size / clause_size {
+                let (flags, try_offset, try_length, handler_offset, handler_length, extra) = match fat {
+                    true => (cursor.u32()?, cursor.u32()?, cursor.u32()?, cursor.u32()?, cursor.u32()?, cursor.u32()?),
+                    false => (
+                        u32::from(cursor.u16()?),
+                        u32::from(cursor.u16()?),
+                        u32::from(cursor.u8()?),
+                        u32::from(cursor.u16()?),
+                        u32::from(cursor.u8()?),
+                        cursor.u32()?,
+                    ),
+                };
+
+                let handler = match flags {
+                    CLAUSE_FILTER => ExceptionHandler::Filter(extra),
+                    CLAUSE_FINALLY => ExceptionHandler::Finally,
+                    CLAUSE_FAULT => ExceptionHandler::Fault,
+                    _ => ExceptionHandler::Catch(Token(extra)),
+                };
+
+                clauses.push(ExceptionClause { handler, try_offset, try_length, handler_offset, handler_length });
+            }
+        }
+
+        if kind & SECT_MORE_SECTS == 0 {
+            return Some(clauses);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::{builder::AssemblyBuilder, OpCode, Operand};
+
+    #[test]
+    fn reads_tiny_and_fat_bodies() {
+        let mut b = AssemblyBuilder::new();
+        let name = b.string("Main");
+        let sig = b.blob(&[0x00, 0x00, 0x01]);
+        let tiny = b.body(&[0x72, 0x01, 0x00, 0x00, 0x70, 0x2A]);
+        let fat = b.body(&[0x00; 70]);
+        b.row(TableId::MethodDef, &[tiny, 0, 0x16, name, sig, 1]);
+        b.row(TableId::MethodDef, &[fat, 0, 0x16, name, sig, 1]);
+        b.row(TableId::MethodDef, &[0, 0, 0x0416, name, sig, 1]);
+
+        let buffer = b.build();
+        let metadata = Metadata::parse(&buffer).unwrap();
+
+        let body = metadata.method_body(tiny).unwrap();
+        assert_eq!(body.max_stack, 8);
+        assert_eq!(body.code.len(), 6);
+        let instructions = body.instructions().unwrap();
+        assert_eq!(instructions[0].opcode.value, OpCode::LDSTR);
+        assert_eq!(instructions[0].operand, Operand::Token(Token(0x7000_0001)));
+
+        let body = metadata.method_body(fat).unwrap();
+        assert_eq!(body.code.len(), 70);
+        assert!(body.init_locals);
+        assert_eq!(body.instructions().unwrap().len(), 70);
+
+        let methods = metadata.il_methods().collect::<Vec<_>>();
+        assert_eq!(methods, vec![(Token::new(TableId::MethodDef, 1), tiny), (Token::new(TableId::MethodDef, 2), fat)]);
+        assert!(metadata.method_body(0x9000).is_err());
+    }
+
+    #[test]
+    fn reads_exception_clauses() {
+        // A small catch clause followed by a fat finally clause
+        let mut sections = vec![0x81, 16, 0, 0];
+        sections.extend([0x00, 0x00, 0x00, 0x00, 0x04, 0x04, 0x00, 0x02]);
+        sections.extend(0x0100_0001u32.to_le_bytes());
+        sections.extend([0x41, 28, 0, 0]);
+        for value in [2u32, 0, 6, 6, 2, 0] {
+            sections.extend(value.to_le_bytes());
+        }
+
+        let mut b = AssemblyBuilder::new();
+        let code = [0x00, 0x00, 0x00, 0xDE, 0x02, 0x26, 0xDC, 0x2A];
+        let rva = b.fat_body(&code, 0x1100_0001, &sections);
+        let buffer = b.build();
+        let metadata = Metadata::parse(&buffer).unwrap();
+
+        let body = metadata.method_body(rva).unwrap();
+        assert_eq!(body.code, &code);
+        assert_eq!(body.local_var_sig, Token::new(TableId::StandAloneSig, 1));
+        assert_eq!(body.exception_clauses, vec![
+            ExceptionClause {
+                handler: ExceptionHandler::Catch(Token::new(TableId::TypeRef, 1)),
+                try_offset: 0,
+                try_length: 4,
+                handler_offset: 4,
+                handler_length: 2,
+            },
+            ExceptionClause {
+                handler: ExceptionHandler::Finally,
+                try_offset: 0,
+                try_length: 6,
+                handler_offset: 6,
+                handler_length: 2,
+            },
+        ]);
+    }
+}
@@ -0,0 +1,328 @@
+//! Test-only writer that assembles synthetic .NET images used as fixtures.
+
+use super::tables::{CodedIndex, TableId, Widths};
+use crate::file::{
+    tests::build_pe, DataDirectory,
+    IMAGE_NT_OPTIONAL_HDR32_MAGIC, IMAGE_FILE_MACHINE_I386,
+};
+
+/// RVA at which the `.text` section of the fixture is mapped.
+const TEXT_RVA: u32 = 0x2000;
+
+/// Size of the CLI header at the start of the `.text` section.
+const CLI_HEADER_SIZE: u32 = 72;
+
+/// Encodes an unsigned integer using the ECMA-335 compressed format.
+pub(crate) fn compress(value: u32) -> Vec<u8> {
+    match value {
+        0..=0x7F => vec![value as u8],
+        0x80..=0x3FFF => vec![(value >> 8) as u8 | 0x80, value as u8],
+        _ => vec![(value >> 24) as u8 | 0xC0, (value >> 16) as u8, (value >> 8) as u8, value as u8],
+    }
+}
+
+/// Builds a synthetic assembly out of heaps, table rows and method bodies.
+pub(crate) struct AssemblyBuilder {
+    /// Version string written to the metadata root.
+    pub(crate) version: String,
+
+    /// CLI header flags.
+    pub(crate) flags: u32,
+
+    /// CLI header entry point token.
+    pub(crate) entry_point: u32,
+
+    /// Optional header magic.
+    pub(crate) magic: u16,
+
+    /// File header machine.
+    pub(crate) machine: u16,
+
+    /// File header characteristics.
+    pub(crate) characteristics: u16,
+
+    strings: Vec<u8>,
+    user_strings: Vec<u8>,
+    blobs: Vec<u8>,
+    guids: Vec<u8>,
+    rows: Vec<Vec<Vec<u32>>>,
+    bodies: Vec<u8>,
+    resources: Vec<u8>,
+}
+
+impl AssemblyBuilder {
+    /// Creates a builder for an ILONLY x86 executable targeting `v4.0.30319`.
+    pub(crate) fn new() -> Self {
+        Self {
+            version: "v4.0.30319".to_string(),
+            flags: 0x0000_0001,
+            entry_point: 0,
+            magic: IMAGE_NT_OPTIONAL_HDR32_MAGIC,
+            machine: IMAGE_FILE_MACHINE_I386,
+            characteristics: 0x0102,
+            strings: vec![0],
+            user_strings: vec![0],
+            blobs: vec![0],
+            guids: Vec::new(),
+            rows: vec![Vec::new(); TableId::ALL.len()],
+            bodies: Vec::new(),
+            resources: Vec::new(),
+        }
+    }
+
+    /// Adds a string to the `#Strings` heap and returns its offset.
+    pub(crate) fn string(&mut self, value: &str) -> u32 {
+        if value.is_empty() {
+            return 0;
+        }
+
+        let needle = [value.as_bytes(), &[0]].concat();
+        if let Some(pos) = self.strings.windows(needle.len()).position(|w| w == needle.as_slice()) {
+            if pos == 0 || self.strings[pos - 1] == 0 {
+                return pos as u32;
+            }
+        }
+
+        let offset = self.strings.len() as u32;
+        self.strings.extend_from_slice(&needle);
+        offset
+    }
+
+    /// Adds a string literal to the `#US` heap and returns its offset.
+    pub(crate) fn user_string(&mut self, value: &str) -> u32 {
+        let offset = self.user_strings.len() as u32;
+        let units = value.encode_utf16().collect::<Vec<u16>>();
+        self.user_strings.extend(compress(units.len() as u32 * 2 + 1));
+        units.iter().for_each(|u| self.user_strings.extend(u.to_le_bytes()));
+        self.user_strings.push(units.iter().any(|&u| u >= 0x80) as u8);
+        offset
+    }
+
+    /// Adds a blob to the `#Blob` heap and returns its offset.
+    pub(crate) fn blob(&mut self, value: &[u8]) -> u32 {
+        if value.is_empty() {
+            return 0;
+        }
+
+        let offset = self.blobs.len() as u32;
+        self.blobs.extend(compress(value.len() as u32));
+        self.blobs.extend_from_slice(value);
+        offset
+    }
+
+    /// Adds a GUID to the `#GUID` heap and returns its 1-based index.
+    pub(crate) fn guid(&mut self, value: [u8; 16]) -> u32 {
+        self.guids.extend_from_slice(&value);
+        (self.guids.len() / 16) as u32
+    }
+
+    /// Adds a method body and returns its RVA.
+    ///
+    /// Code shorter than 64 bytes gets a tiny header, longer code a fat header with `InitLocals`.
+    pub(crate) fn body(&mut self, code: &[u8]) -> u32 {
+        if code.len() < 64 {
+            let rva = TEXT_RVA + CLI_HEADER_SIZE + self.bodies.len() as u32;
+            self.bodies.push((code.len() as u8) << 2 | 0x2);
+            self.bodies.extend_from_slice(code);
+            return rva;
+        }
+
+        self.fat_body(code, 0, &[])
+    }
+
+    /// Adds a method body with a fat header and returns its RVA.
+    ///
+    /// `sections` holds the raw data sections (e.g. the exception table) that follow the code.
+    pub(crate) fn fat_body(&mut self, code: &[u8], local_var_sig: u32, sections: &[u8]) -> u32 {
+        while !self.bodies.len().is_multiple_of(4) {
+            self.bodies.push(0);
+        }
+
+        let flags = if sections.is_empty() { 0x3013u16 } else { 0x301Bu16 };
+        let rva = TEXT_RVA + CLI_HEADER_SIZE + self.bodies.len() as u32;
+        self.bodies.extend(flags.to_le_bytes());
+        self.bodies.extend(8u16.to_le_bytes());
+        self.bodies.extend((code.len() as u32).to_le_bytes());
+        self.bodies.extend(local_var_sig.to_le_bytes());
+        self.bodies.extend_from_slice(code);
+        if !sections.is_empty() {
+            while !self.bodies.len().is_multiple_of(4) {
+                self.bodies.push(0);
+            }
+
+            self.bodies.extend_from_slice(sections);
+        }
+
+        rva
+    }
+
+    /// Adds a length-prefixed entry to the managed resources and returns its offset.
+    pub(crate) fn resource(&mut self, value: &[u8]) -> u32 {
+        let offset = self.resources.len() as u32;
+        self.resources.extend((value.len() as u32).to_le_bytes());
+        self.resources.extend_from_slice(value);
+        while !self.resources.len().is_multiple_of(8) {
+            self.resources.push(0);
+        }
+
+        offset
+    }
+
+    /// Appends a row to a table and returns its 1-based index.
+    pub(crate) fn row(&mut self, table: TableId, values: &[u32]) -> u32 {
+        assert_eq!(values.len(), table.columns().len(), "wrong column count for {table:?}");
+        let rows = &mut self.rows[table as usize];
+        rows.push(values.to_vec());
+        rows.len() as u32
+    }
+
+    /// Applies `[assembly: TargetFramework(moniker)]` to the `Assembly` row.
+    ///
+    /// The attribute type is resolved through `AssemblyRef` row 1, which the caller adds.
+    pub(crate) fn target_framework(&mut self, moniker: &str) {
+        let versioning = self.string("System.Runtime.Versioning");
+        let attribute = self.string("TargetFrameworkAttribute");
+        let ctor = self.string(".ctor");
+        let ctor_sig = self.blob(&[0x20, 0x01, 0x01, 0x0E]);
+        let value = self.blob(&[&[0x01, 0x00][..], &compress(moniker.len() as u32), moniker.as_bytes(), &[0x00, 0x00]].concat());
+
+        let scope = CodedIndex::ResolutionScope.encode(TableId::AssemblyRef, 1).unwrap();
+        let type_ref = self.row(TableId::TypeRef, &[scope, attribute, versioning]);
+        let parent = CodedIndex::MemberRefParent.encode(TableId::TypeRef, type_ref).unwrap();
+        let member_ref = self.row(TableId::MemberRef, &[parent, ctor, ctor_sig]);
+        self.row(TableId::CustomAttribute, &[
+            CodedIndex::HasCustomAttribute.encode(TableId::Assembly, 1).unwrap(),
+            CodedIndex::CustomAttributeType.encode(TableId::MemberRef, member_ref).unwrap(),
+            value,
+        ]);
+    }
+
+    /// Serializes the `#~` stream.
+    fn tables_stream(&self) -> Vec<u8> {
+        let mut widths = Widths {
+            rows: [0; 64],
+            strings: if self.strings.len() >= 1 << 16 { 4 } else { 2 },
+            guid: if self.guids.len() / 16 >= 1 << 16 { 4 } else { 2 },
+            blob: if self.blobs.len() >= 1 << 16 { 4 } else { 2 },
+        };
+
+        let mut valid = 0u64;
+        for id in TableId::ALL {
+            let rows = self.rows[id as usize].len() as u32;
+            widths.rows[id as usize] = rows;
+            if rows != 0 {
+                valid |= 1 << id as u64;
+            }
+        }
+
+        let heap_sizes = (widths.strings == 4) as u8 | ((widths.guid == 4) as u8) << 1 | ((widths.blob == 4) as u8) << 2;
+        let mut stream = Vec::new();
+        stream.extend(0u32.to_le_bytes());
+        stream.extend([2, 0, heap_sizes, 1]);
+        stream.extend(valid.to_le_bytes());
+        stream.extend(0x0000_1600_3301_FA00u64.to_le_bytes());
+        for id in TableId::ALL {
+            if valid & (1 << id as u64) != 0 {
+                stream.extend(widths.rows[id as usize].to_le_bytes());
+            }
+        }
+
+        for id in TableId::ALL {
+            for row in &self.rows[id as usize] {
+                for (value, column) in row.iter().zip(id.columns()) {
+                    match widths.column(*column) {
+                        2 => stream.extend((*value as u16).to_le_bytes()),
+                        _ => stream.extend(value.to_le_bytes()),
+                    }
+                }
+            }
+        }
+
+        stream
+    }
+
+    /// Serializes the metadata root and its streams.
+    fn metadata(&self) -> Vec<u8> {
+        let pad = |mut data: Vec<u8>| {
+            while !data.len().is_multiple_of(4) {
+                data.push(0);
+            }
+            data
+        };
+
+        let streams = [
+            ("#~", pad(self.tables_stream())),
+            ("#Strings", pad(self.strings.clone())),
+            ("#US", pad(self.user_strings.clone())),
+            ("#GUID", self.guids.clone()),
+            ("#Blob", pad(self.blobs.clone())),
+        ];
+
+        let version = pad([self.version.as_bytes(), &[0]].concat());
+        let mut headers_size = 0;
+        for (name, _) in &streams {
+            headers_size += 8 + (name.len() + 1).div_ceil(4) * 4;
+        }
+
+        let mut root = Vec::new();
+        root.extend(0x424A_5342u32.to_le_bytes());
+        root.extend(1u16.to_le_bytes());
+        root.extend(1u16.to_le_bytes());
+        root.extend(0u32.to_le_bytes());
+        root.extend((version.len() as u32).to_le_bytes());
+        root.extend(&version);
+        root.extend(0u16.to_le_bytes());
+        root.extend((streams.len() as u16).to_le_bytes());
+
+        let mut offset = root.len() + headers_size;
+        for (name, data) in &streams {
+            root.extend((offset as u32).to_le_bytes());
+            root.extend((data.len() as u32).to_le_bytes());
+            root.extend(pad([name.as_bytes(), &[0]].concat()));
+            offset += data.len();
+        }
+
+        for (_, data) in &streams {
+            root.extend(data);
+        }
+
+        root
+    }
+
+    /// Produces the final PE image.
+    pub(crate) fn build(&self) -> Vec<u8> {
+        let mut text = vec![0u8; CLI_HEADER_SIZE as usize];
+        text.extend(&self.bodies);
+        while !text.len().is_multiple_of(8) {
+            text.push(0);
+        }
+
+        let resources = DataDirectory {
+            virtual_address: if self.resources.is_empty() { 0 } else { TEXT_RVA + text.len() as u32 },
+            size: self.resources.len() as u32,
+        };
+        text.extend(&self.resources);
+
+        let metadata = self.metadata();
+        let root = DataDirectory {
+            virtual_address: TEXT_RVA + text.len() as u32,
+            size: metadata.len() as u32,
+        };
+        text.extend(&metadata);
+
+        let mut header = Vec::new();
+        header.extend(CLI_HEADER_SIZE.to_le_bytes());
+        header.extend(2u16.to_le_bytes());
+        header.extend(5u16.to_le_bytes());
+        header.extend(root.virtual_address.to_le_bytes());
+        header.extend(root.size.to_le_bytes());
+        header.extend(self.flags.to_le_bytes());
+        header.extend(self.entry_point.to_le_bytes());
+        header.extend(resources.virtual_address.to_le_bytes());
+        header.extend(resources.size.to_le_bytes());
+        text[..header.len()].copy_from_slice(&header);
+
+        let cli = DataDirectory { virtual_address: TEXT_RVA, size: CLI_HEADER_SIZE };
+        build_pe(self.magic, self.machine, self.characteristics, cli, &text)
+    }
+}
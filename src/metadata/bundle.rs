@@ -0,0 +1,310 @@
+use std::borrow::Cow;
+use super::{inflate::inflate, reader::Cursor, Metadata};
+use crate::{error::ClrError, file::is_dotnet, Result};
+
+/// Marker the SDK embeds in single-file apphosts, right after the 8-byte offset of the bundle header.
+const BUNDLE_SIGNATURE: [u8; 32] = [
+    0x8B, 0x12, 0x02, 0xB9, 0x6A, 0x61, 0x20, 0x38, 0x72, 0x7B, 0x93, 0x02, 0x14, 0xD7, 0xA0, 0x32,
+    0x13, 0xF5, 0xB9, 0xE6, 0xEF, 0xAE, 0x33, 0x18, 0xEE, 0x3B, 0x2D, 0xCE, 0x24, 0xB3, 0x6A, 0xAE,
+];
+
+/// Kind of a file stored in a single-file bundle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BundleFileType {
+    /// A file of unknown type.
+    Unknown,
+
+    /// A managed assembly.
+    Assembly,
+
+    /// A native library.
+    NativeBinary,
+
+    /// The `*.deps.json` of the application.
+    DepsJson,
+
+    /// The `*.runtimeconfig.json` of the application.
+    RuntimeConfigJson,
+
+    /// Debug symbols (`*.pdb`).
+    Symbols,
+}
+
+impl From<u8> for BundleFileType {
+    fn from(value: u8) -> Self {
+        match value {
+            1 => BundleFileType::Assembly,
+            2 => BundleFileType::NativeBinary,
+            3 => BundleFileType::DepsJson,
+            4 => BundleFileType::RuntimeConfigJson,
+            5 => BundleFileType::Symbols,
+            _ => BundleFileType::Unknown,
+        }
+    }
+}
+
+/// A file listed in the manifest of a single-file bundle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BundleEntry<'a> {
+    /// Path of the file relative to the application directory, e.g. `Library.dll`.
+    pub path: String,
+
+    /// Kind of the file.
+    pub file_type: BundleFileType,
+
+    /// Offset of the file in the bundle.
+    pub offset: u64,
+
+    /// Size of the file once extracted.
+    pub size: u64,
+
+    /// Size of the deflate-compressed file, zero when it is stored as is.
+    pub compressed_size: u64,
+
+    /// The bytes stored in the bundle, compressed or not.
+    pub data: &'a [u8],
+}
+
+impl<'a> BundleEntry<'a> {
+    /// Returns `true` if the file is stored deflate-compressed (bundles of .NET 6 and later).
+    pub fn is_compressed(&self) -> bool {
+        self.compressed_size != 0
+    }
+
+    /// Returns the contents of the file, decompressing it as needed.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Cow<[u8]>)` - The stored bytes, or the inflated bytes of compressed files.
+    /// * `Err(ClrError)` - If the file fails to decompress.
+    pub fn contents(&self) -> Result<Cow<'a, [u8]>> {
+        match self.is_compressed() {
+            true => inflate(self.data).map(Cow::Owned),
+            false => Ok(Cow::Borrowed(self.data)),
+        }
+    }
+}
+
+/// The manifest of a .NET single-file bundle, as produced by `PublishSingleFile`.
+///
+/// Such apphosts are native executables with the application's assemblies appended,
+/// so they cannot be handed to `RustClr` directly. Their assemblies can however be
+/// extracted and inspected offline.
+///
+/// # Examples
+///
+/// ```ignore
+/// use rustclr::metadata::Bundle;
+/// use std::fs;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let buffer = fs::read("examples/app.exe")?;
+///     let bundle = Bundle::parse(&buffer)?;
+///     for entry in &bundle.entries {
+///         println!("{} ({:?}, {} bytes)", entry.path, entry.file_type, entry.size);
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bundle<'a> {
+    /// Major version of the bundle format (1 for .NET Core 3, 2 for .NET 5, 6 for .NET 6+).
+    pub major_version: u32,
+
+    /// Minor version of the bundle format.
+    pub minor_version: u32,
+
+    /// Unique identifier of the bundle, used to name its extraction directory.
+    pub id: String,
+
+    /// The files of the bundle, in manifest order.
+    pub entries: Vec<BundleEntry<'a>>,
+}
+
+impl<'a> Bundle<'a> {
+    /// Returns `true` if the buffer is an apphost with a single-file bundle attached.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - A reference to a byte slice representing the potential bundle.
+    pub fn is_bundle(buffer: &[u8]) -> bool {
+        header_offset(buffer).is_some()
+    }
+
+    /// Parses the bundle manifest of a single-file apphost.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - A reference to a byte slice representing the bundle.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Bundle)` - The manifest, with each entry borrowing its bytes from `buffer`.
+    /// * `Err(ClrError)` - If the buffer is not a bundle or its manifest is malformed.
+    pub fn parse(buffer: &'a [u8]) -> Result<Self> {
+        let offset = header_offset(buffer).ok_or(ClrError::MetadataError("not a single-file bundle"))?;
+        Self::read_manifest(buffer, offset).ok_or(ClrError::MetadataError("malformed bundle manifest"))
+    }
+
+    /// Extracts the .NET assemblies of the bundle, decompressing them as needed.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<(String, Vec<u8>)>)` - The relative path and bytes of each assembly.
+    /// * `Err(ClrError)` - If a compressed file fails to decompress.
+    pub fn assemblies(&self) -> Result<Vec<(String, Vec<u8>)>> {
+        let mut assemblies = Vec::new();
+        for entry in self.entries.iter().filter(|entry| entry.file_type == BundleFileType::Assembly) {
+            let contents = entry.contents()?;
+            let is_assembly = is_dotnet(&contents)
+                && Metadata::parse(&contents).is_ok_and(|metadata| metadata.assembly().is_some());
+
+            if is_assembly {
+                assemblies.push((entry.path.clone(), contents.into_owned()));
+            }
+        }
+
+        Ok(assemblies)
+    }
+
+    /// Reads the manifest header and its file entries.
+    fn read_manifest(buffer: &'a [u8], offset: usize) -> Option<Self> {
+        let mut cursor = Cursor::new(buffer);
+        cursor.seek(offset);
+
+        let major_version = cursor.u32()?;
+        let minor_version = cursor.u32()?;
+        let count = cursor.u32()?;
+        let id = string(&mut cursor)?;
+
+        // Version 2 added the locations of the json files and the bundle flags
+        if major_version >= 2 {
+            cursor.bytes(4 * 8 + 8)?;
+        }
+
+        let entries = (0..count)
+            .map(|_| {
+                let offset = cursor.u64()?;
+                let size = cursor.u64()?;
+                let compressed_size = if major_version >= 6 { cursor.u64()? } else { 0 };
+                let file_type = BundleFileType::from(cursor.u8()?);
+                let path = string(&mut cursor)?;
+
+                let stored = if compressed_size != 0 { compressed_size } else { size };
+                let start = usize::try_from(offset).ok()?;
+                let end = start.checked_add(usize::try_from(stored).ok()?)?;
+                Some(BundleEntry {
+                    path,
+                    file_type,
+                    offset,
+                    size,
+                    compressed_size,
+                    data: buffer.get(start..end)?,
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Self { major_version, minor_version, id, entries })
+    }
+}
+
+/// Finds the bundle marker and returns the offset of the bundle header it points to.
+///
+/// An apphost that is not bundled still carries the marker, with a zero offset.
+fn header_offset(buffer: &[u8]) -> Option<usize> {
+    let position = buffer.windows(BUNDLE_SIGNATURE.len()).position(|window| window == BUNDLE_SIGNATURE)?;
+    let bytes = buffer.get(position.checked_sub(8)?..position)?;
+    let offset = usize::try_from(u64::from_le_bytes(bytes.try_into().ok()?)).ok()?;
+    (offset != 0 && offset < buffer.len()).then_some(offset)
+}
+
+/// Reads a string written by `BinaryWriter`: a 7-bit encoded length followed by UTF-8 bytes.
+fn string(cursor: &mut Cursor) -> Option<String> {
+    let mut length = 0usize;
+    for shift in (0..35).step_by(7) {
+        let byte = cursor.u8()?;
+        length |= usize::from(byte & 0x7F) << shift;
+        if byte & 0x80 == 0 {
+            let bytes = cursor.bytes(length)?;
+            return String::from_utf8(bytes.to_vec()).ok();
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::{builder::AssemblyBuilder, TableId};
+
+    /// Writes a `BinaryWriter` string.
+    fn push_string(out: &mut Vec<u8>, value: &str) {
+        out.push(value.len() as u8);
+        out.extend(value.as_bytes());
+    }
+
+    #[test]
+    fn reads_bundle_manifest() {
+        let mut library = AssemblyBuilder::new();
+        let name = library.string("Library");
+        library.row(TableId::Assembly, &[0x8004, 1, 0, 0, 0, 0, 0, name, 0]);
+        let library = library.build();
+
+        // A fake apphost carrying the marker, then the files and the manifest
+        let mut buffer = b"MZ native apphost code".to_vec();
+        let marker = buffer.len();
+        buffer.extend([0; 8]);
+        buffer.extend(BUNDLE_SIGNATURE);
+
+        let config = br#"{"runtimeOptions":{}}"#;
+        let config_offset = buffer.len();
+        buffer.extend(config);
+
+        let library_offset = buffer.len();
+        buffer.extend([0x01]);
+        buffer.extend((library.len() as u16).to_le_bytes());
+        buffer.extend((!(library.len() as u16)).to_le_bytes());
+        buffer.extend(&library);
+        let compressed_size = buffer.len() - library_offset;
+
+        let header = buffer.len();
+        buffer[marker..marker + 8].copy_from_slice(&(header as u64).to_le_bytes());
+        buffer.extend(6u32.to_le_bytes());
+        buffer.extend(0u32.to_le_bytes());
+        buffer.extend(2u32.to_le_bytes());
+        push_string(&mut buffer, "bundle-id");
+        buffer.extend([0; 40]);
+
+        for (offset, size, compressed, kind, path) in [
+            (config_offset, config.len(), 0, 4u8, "App.runtimeconfig.json"),
+            (library_offset, library.len(), compressed_size, 1u8, "Library.dll"),
+        ] {
+            buffer.extend((offset as u64).to_le_bytes());
+            buffer.extend((size as u64).to_le_bytes());
+            buffer.extend((compressed as u64).to_le_bytes());
+            buffer.push(kind);
+            push_string(&mut buffer, path);
+        }
+
+        assert!(Bundle::is_bundle(&buffer));
+        let bundle = Bundle::parse(&buffer).unwrap();
+        assert_eq!((bundle.major_version, bundle.id.as_str()), (6, "bundle-id"));
+        assert_eq!(bundle.entries.len(), 2);
+        assert_eq!(bundle.entries[0].file_type, BundleFileType::RuntimeConfigJson);
+        assert_eq!(bundle.entries[0].contents().unwrap().as_ref(), config);
+        assert!(bundle.entries[1].is_compressed());
+        assert_eq!(bundle.assemblies().unwrap(), vec![("Library.dll".to_string(), library)]);
+    }
+
+    #[test]
+    fn ignores_unbundled_apphosts() {
+        let mut buffer = b"MZ".to_vec();
+        buffer.extend([0; 8]);
+        buffer.extend(BUNDLE_SIGNATURE);
+        assert!(!Bundle::is_bundle(&buffer));
+        assert!(Bundle::parse(&buffer).is_err());
+        assert!(!Bundle::is_bundle(&AssemblyBuilder::new().build()));
+    }
+}
@@ -0,0 +1,514 @@
+//! Decoder for the CIL instruction stream of method bodies (ECMA-335 Partition III).
+
+use core::fmt;
+use super::{reader::Cursor, Token};
+use crate::{error::ClrError, Result};
+
+/// Prefix of the two-byte opcodes.
+const TWO_BYTE_PREFIX: u8 = 0xFE;
+
+/// Kind of inline operand that follows an opcode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperandType {
+    /// No operand.
+    None,
+
+    /// An unsigned 8-bit value (`unaligned.`, `no.`).
+    UInt8,
+
+    /// A signed 8-bit constant (`ldc.i4.s`).
+    Int8,
+
+    /// A 32-bit constant.
+    Int32,
+
+    /// A 64-bit constant.
+    Int64,
+
+    /// A 32-bit float.
+    Float32,
+
+    /// A 64-bit float.
+    Float64,
+
+    /// An 8-bit argument or local index.
+    Var8,
+
+    /// A 16-bit argument or local index.
+    Var16,
+
+    /// An 8-bit branch offset relative to the next instruction.
+    Branch8,
+
+    /// A 32-bit branch offset relative to the next instruction.
+    Branch32,
+
+    /// A count followed by that many 32-bit branch offsets.
+    Switch,
+
+    /// A metadata token (or a `#US` offset for `ldstr`).
+    Token,
+}
+
+/// A CIL opcode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpCode {
+    /// Encoded value, with the `0xFE` prefix in the high byte for two-byte opcodes.
+    pub value: u16,
+
+    /// Mnemonic, e.g. `ldstr`.
+    pub name: &'static str,
+
+    /// Kind of the inline operand.
+    pub operand: OperandType,
+}
+
+impl OpCode {
+    /// `ldstr`, which loads a string literal from the `#US` heap.
+    pub const LDSTR: u16 = 0x72;
+
+    /// Looks an opcode up by its encoded value.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(OpCode)` - The opcode.
+    /// * `None` - If the value is not a defined opcode.
+    pub fn from_value(value: u16) -> Option<Self> {
+        let entry = match value >> 8 {
+            0 => ONE_BYTE.get(value as usize),
+            0xFE => TWO_BYTE.get((value & 0xFF) as usize),
+            _ => None,
+        };
+
+        let (name, operand) = (*entry?)?;
+        Some(Self { value, name, operand })
+    }
+
+    /// Returns the size of the encoded opcode, without its operand.
+    pub fn size(&self) -> u32 {
+        if self.value >> 8 == 0 { 1 } else { 2 }
+    }
+}
+
+/// A decoded inline operand.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operand {
+    /// No operand.
+    None,
+
+    /// An integer constant (`ldc.i4*`, `ldc.i8`, `unaligned.`, `no.`).
+    Int(i64),
+
+    /// A floating point constant (`ldc.r4`, `ldc.r8`).
+    Float(f64),
+
+    /// An argument or local index.
+    Variable(u16),
+
+    /// A metadata token, or a `#US` token for `ldstr`.
+    Token(Token),
+
+    /// Absolute code offset of a branch target.
+    Target(u32),
+
+    /// Absolute code offsets of the `switch` targets.
+    Switch(Vec<u32>),
+}
+
+/// A single decoded instruction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Instruction {
+    /// Offset of the instruction within the method code.
+    pub offset: u32,
+
+    /// The opcode.
+    pub opcode: OpCode,
+
+    /// The inline operand.
+    pub operand: Operand,
+}
+
+/// Formats the instruction as `IL_0000: ldstr 0x70000001`, with branch targets as labels.
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "IL_{:04x}: {}", self.offset, self.opcode.name)?;
+        match &self.operand {
+            Operand::None => Ok(()),
+            Operand::Int(value) => write!(f, " {value}"),
+            Operand::Float(value) => write!(f, " {value}"),
+            Operand::Variable(index) => write!(f, " {index}"),
+            Operand::Token(token) => write!(f, " {token}"),
+            Operand::Target(target) => write!(f, " IL_{target:04x}"),
+            Operand::Switch(targets) => {
+                let labels = targets.iter().map(|target| format!("IL_{target:04x}")).collect::<Vec<_>>();
+                write!(f, " ({})", labels.join(", "))
+            }
+        }
+    }
+}
+
+/// Decodes the instructions of a method body.
+///
+/// # Arguments
+///
+/// * `code` - The IL bytes, as returned by `MethodBody::code`.
+///
+/// # Returns
+///
+/// * `Ok(Vec<Instruction>)` - The instructions in code order.
+/// * `Err(ClrError)` - If an opcode is undefined or an operand is truncated.
+pub fn decode_instructions(code: &[u8]) -> Result<Vec<Instruction>> {
+    let truncated = || ClrError::MetadataError("truncated IL instruction");
+    let mut cursor = Cursor::new(code);
+    let mut instructions = Vec::new();
+    while cursor.position() < code.len() {
+        let offset = cursor.position() as u32;
+        let value = match cursor.u8().ok_or_else(truncated)? {
+            TWO_BYTE_PREFIX => 0xFE00 | u16::from(cursor.u8().ok_or_else(truncated)?),
+            byte => u16::from(byte),
+        };
+
+        let opcode = OpCode::from_value(value).ok_or(ClrError::MetadataError("invalid IL opcode"))?;
+        let operand = operand(&mut cursor, opcode.operand).ok_or_else(truncated)?;
+        instructions.push(Instruction { offset, opcode, operand });
+    }
+
+    Ok(instructions)
+}
+
+/// Reads the inline operand of an opcode, resolving branch offsets into absolute targets.
+fn operand(cursor: &mut Cursor, kind: OperandType) -> Option<Operand> {
+    let target = |cursor: &Cursor, delta: i32| (cursor.position() as i64 + i64::from(delta)) as u32;
+    Some(match kind {
+        OperandType::None => Operand::None,
+        OperandType::UInt8 => Operand::Int(i64::from(cursor.u8()?)),
+        OperandType::Int8 => Operand::Int(i64::from(cursor.u8()? as i8)),
+        OperandType::Int32 => Operand::Int(i64::from(cursor.u32()? as i32)),
+        OperandType::Int64 => Operand::Int(cursor.u64()? as i64),
+        OperandType::Float32 => Operand::Float(f64::from(f32::from_bits(cursor.u32()?))),
+        OperandType::Float64 => Operand::Float(f64::from_bits(cursor.u64()?)),
+        OperandType::Var8 => Operand::Variable(u16::from(cursor.u8()?)),
+        OperandType::Var16 => Operand::Variable(cursor.u16()?),
+        OperandType::Token => Operand::Token(Token(cursor.u32()?)),
+        OperandType::Branch8 => {
+            let delta = i32::from(cursor.u8()? as i8);
+            Operand::Target(target(cursor, delta))
+        }
+        OperandType::Branch32 => {
+            let delta = cursor.u32()? as i32;
+            Operand::Target(target(cursor, delta))
+        }
+        OperandType::Switch => {
+            let count = cursor.u32()? as usize;
+            let deltas = (0..count).map(|_| cursor.u32().map(|delta| delta as i32)).collect::<Option<Vec<_>>>()?;
+
+            // Targets are relative to the end of the whole instruction
+            Operand::Switch(deltas.into_iter().map(|delta| target(cursor, delta)).collect())
+        }
+    })
+}
+
+/// Single-byte opcodes `0x00..=0xE0`, `None` marking unused values.
+const ONE_BYTE: [Option<(&str, OperandType)>; 225] = [
+    /* 0x00 */ Some(("nop", OperandType::None)),
+    /* 0x01 */ Some(("break", OperandType::None)),
+    /* 0x02 */ Some(("ldarg.0", OperandType::None)),
+    /* 0x03 */ Some(("ldarg.1", OperandType::None)),
+    /* 0x04 */ Some(("ldarg.2", OperandType::None)),
+    /* 0x05 */ Some(("ldarg.3", OperandType::None)),
+    /* 0x06 */ Some(("ldloc.0", OperandType::None)),
+    /* 0x07 */ Some(("ldloc.1", OperandType::None)),
+    /* 0x08 */ Some(("ldloc.2", OperandType::None)),
+    /* 0x09 */ Some(("ldloc.3", OperandType::None)),
+    /* 0x0A */ Some(("stloc.0", OperandType::None)),
+    /* 0x0B */ Some(("stloc.1", OperandType::None)),
+    /* 0x0C */ Some(("stloc.2", OperandType::None)),
+    /* 0x0D */ Some(("stloc.3", OperandType::None)),
+    /* 0x0E */ Some(("ldarg.s", OperandType::Var8)),
+    /* 0x0F */ Some(("ldarga.s", OperandType::Var8)),
+    /* 0x10 */ Some(("starg.s", OperandType::Var8)),
+    /* 0x11 */ Some(("ldloc.s", OperandType::Var8)),
+    /* 0x12 */ Some(("ldloca.s", OperandType::Var8)),
+    /* 0x13 */ Some(("stloc.s", OperandType::Var8)),
+    /* 0x14 */ Some(("ldnull", OperandType::None)),
+    /* 0x15 */ Some(("ldc.i4.m1", OperandType::None)),
+    /* 0x16 */ Some(("ldc.i4.0", OperandType::None)),
+    /* 0x17 */ Some(("ldc.i4.1", OperandType::None)),
+    /* 0x18 */ Some(("ldc.i4.2", OperandType::None)),
+    /* 0x19 */ Some(("ldc.i4.3", OperandType::None)),
+    /* 0x1A */ Some(("ldc.i4.4", OperandType::None)),
+    /* 0x1B */ Some(("ldc.i4.5", OperandType::None)),
+    /* 0x1C */ Some(("ldc.i4.6", OperandType::None)),
+    /* 0x1D */ Some(("ldc.i4.7", OperandType::None)),
+    /* 0x1E */ Some(("ldc.i4.8", OperandType::None)),
+    /* 0x1F */ Some(("ldc.i4.s", OperandType::Int8)),
+    /* 0x20 */ Some(("ldc.i4", OperandType::Int32)),
+    /* 0x21 */ Some(("ldc.i8", OperandType::Int64)),
+    /* 0x22 */ Some(("ldc.r4", OperandType::Float32)),
+    /* 0x23 */ Some(("ldc.r8", OperandType::Float64)),
+    /* 0x24 */ None,
+    /* 0x25 */ Some(("dup", OperandType::None)),
+    /* 0x26 */ Some(("pop", OperandType::None)),
+    /* 0x27 */ Some(("jmp", OperandType::Token)),
+    /* 0x28 */ Some(("call", OperandType::Token)),
+    /* 0x29 */ Some(("calli", OperandType::Token)),
+    /* 0x2A */ Some(("ret", OperandType::None)),
+    /* 0x2B */ Some(("br.s", OperandType::Branch8)),
+    /* 0x2C */ Some(("brfalse.s", OperandType::Branch8)),
+    /* 0x2D */ Some(("brtrue.s", OperandType::Branch8)),
+    /* 0x2E */ Some(("beq.s", OperandType::Branch8)),
+    /* 0x2F */ Some(("bge.s", OperandType::Branch8)),
+    /* 0x30 */ Some(("bgt.s", OperandType::Branch8)),
+    /* 0x31 */ Some(("ble.s", OperandType::Branch8)),
+    /* 0x32 */ Some(("blt.s", OperandType::Branch8)),
+    /* 0x33 */ Some(("bne.un.s", OperandType::Branch8)),
+    /* 0x34 */ Some(("bge.un.s", OperandType::Branch8)),
+    /* 0x35 */ Some(("bgt.un.s", OperandType::Branch8)),
+    /* 0x36 */ Some(("ble.un.s", OperandType::Branch8)),
+    /* 0x37 */ Some(("blt.un.s", OperandType::Branch8)),
+    /* 0x38 */ Some(("br", OperandType::Branch32)),
+    /* 0x39 */ Some(("brfalse", OperandType::Branch32)),
+    /* 0x3A */ Some(("brtrue", OperandType::Branch32)),
+    /* 0x3B */ Some(("beq", OperandType::Branch32)),
+    /* 0x3C */ Some(("bge", OperandType::Branch32)),
+    /* 0x3D */ Some(("bgt", OperandType::Branch32)),
+    /* 0x3E */ Some(("ble", OperandType::Branch32)),
+    /* 0x3F */ Some(("blt", OperandType::Branch32)),
+    /* 0x40 */ Some(("bne.un", OperandType::Branch32)),
+    /* 0x41 */ Some(("bge.un", OperandType::Branch32)),
+    /* 0x42 */ Some(("bgt.un", OperandType::Branch32)),
+    /* 0x43 */ Some(("ble.un", OperandType::Branch32)),
+    /* 0x44 */ Some(("blt.un", OperandType::Branch32)),
+    /* 0x45 */ Some(("switch", OperandType::Switch)),
+    /* 0x46 */ Some(("ldind.i1", OperandType::None)),
+    /* 0x47 */ Some(("ldind.u1", OperandType::None)),
+    /* 0x48 */ Some(("ldind.i2", OperandType::None)),
+    /* 0x49 */ Some(("ldind.u2", OperandType::None)),
+    /* 0x4A */ Some(("ldind.i4", OperandType::None)),
+    /* 0x4B */ Some(("ldind.u4", OperandType::None)),
+    /* 0x4C */ Some(("ldind.i8", OperandType::None)),
+    /* 0x4D */ Some(("ldind.i", OperandType::None)),
+    /* 0x4E */ Some(("ldind.r4", OperandType::None)),
+    /* 0x4F */ Some(("ldind.r8", OperandType::None)),
+    /* 0x50 */ Some(("ldind.ref", OperandType::None)),
+    /* 0x51 */ Some(("stind.ref", OperandType::None)),
+    /* 0x52 */ Some(("stind.i1", OperandType::None)),
+    /* 0x53 */ Some(("stind.i2", OperandType::None)),
+    /* 0x54 */ Some(("stind.i4", OperandType::None)),
+    /* 0x55 */ Some(("stind.i8", OperandType::None)),
+    /* 0x56 */ Some(("stind.r4", OperandType::None)),
+    /* 0x57 */ Some(("stind.r8", OperandType::None)),
+    /* 0x58 */ Some(("add", OperandType::None)),
+    /* 0x59 */ Some(("sub", OperandType::None)),
+    /* 0x5A */ Some(("mul", OperandType::None)),
+    /* 0x5B */ Some(("div", OperandType::None)),
+    /* 0x5C */ Some(("div.un", OperandType::None)),
+    /* 0x5D */ Some(("rem", OperandType::None)),
+    /* 0x5E */ Some(("rem.un", OperandType::None)),
+    /* 0x5F */ Some(("and", OperandType::None)),
+    /* 0x60 */ Some(("or", OperandType::None)),
+    /* 0x61 */ Some(("xor", OperandType::None)),
+    /* 0x62 */ Some(("shl", OperandType::None)),
+    /* 0x63 */ Some(("shr", OperandType::None)),
+    /* 0x64 */ Some(("shr.un", OperandType::None)),
+    /* 0x65 */ Some(("neg", OperandType::None)),
+    /* 0x66 */ Some(("not", OperandType::None)),
+    /* 0x67 */ Some(("conv.i1", OperandType::None)),
+    /* 0x68 */ Some(("conv.i2", OperandType::None)),
+    /* 0x69 */ Some(("conv.i4", OperandType::None)),
+    /* 0x6A */ Some(("conv.i8", OperandType::None)),
+    /* 0x6B */ Some(("conv.r4", OperandType::None)),
+    /* 0x6C */ Some(("conv.r8", OperandType::None)),
+    /* 0x6D */ Some(("conv.u4", OperandType::None)),
+    /* 0x6E */ Some(("conv.u8", OperandType::None)),
+    /* 0x6F */ Some(("callvirt", OperandType::Token)),
+    /* 0x70 */ Some(("cpobj", OperandType::Token)),
+    /* 0x71 */ Some(("ldobj", OperandType::Token)),
+    /* 0x72 */ Some(("ldstr", OperandType::Token)),
+    /* 0x73 */ Some(("newobj", OperandType::Token)),
+    /* 0x74 */ Some(("castclass", OperandType::Token)),
+    /* 0x75 */ Some(("isinst", OperandType::Token)),
+    /* 0x76 */ Some(("conv.r.un", OperandType::None)),
+    /* 0x77 */ None,
+    /* 0x78 */ None,
+    /* 0x79 */ Some(("unbox", OperandType::Token)),
+    /* 0x7A */ Some(("throw", OperandType::None)),
+    /* 0x7B */ Some(("ldfld", OperandType::Token)),
+    /* 0x7C */ Some(("ldflda", OperandType::Token)),
+    /* 0x7D */ Some(("stfld", OperandType::Token)),
+    /* 0x7E */ Some(("ldsfld", OperandType::Token)),
+    /* 0x7F */ Some(("ldsflda", OperandType::Token)),
+    /* 0x80 */ Some(("stsfld", OperandType::Token)),
+    /* 0x81 */ Some(("stobj", OperandType::Token)),
+    /* 0x82 */ Some(("conv.ovf.i1.un", OperandType::None)),
+    /* 0x83 */ Some(("conv.ovf.i2.un", OperandType::None)),
+    /* 0x84 */ Some(("conv.ovf.i4.un", OperandType::None)),
+    /* 0x85 */ Some(("conv.ovf.i8.un", OperandType::None)),
+    /* 0x86 */ Some(("conv.ovf.u1.un", OperandType::None)),
+    /* 0x87 */ Some(("conv.ovf.u2.un", OperandType::None)),
+    /* 0x88 */ Some(("conv.ovf.u4.un", OperandType::None)),
+    /* 0x89 */ Some(("conv.ovf.u8.un", OperandType::None)),
+    /* 0x8A */ Some(("conv.ovf.i.un", OperandType::None)),
+    /* 0x8B */ Some(("conv.ovf.u.un", OperandType::None)),
+    /* 0x8C */ Some(("box", OperandType::Token)),
+    /* 0x8D */ Some(("newarr", OperandType::Token)),
+    /* 0x8E */ Some(("ldlen", OperandType::None)),
+    /* 0x8F */ Some(("ldelema", OperandType::Token)),
+    /* 0x90 */ Some(("ldelem.i1", OperandType::None)),
+    /* 0x91 */ Some(("ldelem.u1", OperandType::None)),
+    /* 0x92 */ Some(("ldelem.i2", OperandType::None)),
+    /* 0x93 */ Some(("ldelem.u2", OperandType::None)),
+    /* 0x94 */ Some(("ldelem.i4", OperandType::None)),
+    /* 0x95 */ Some(("ldelem.u4", OperandType::None)),
+    /* 0x96 */ Some(("ldelem.i8", OperandType::None)),
+    /* 0x97 */ Some(("ldelem.i", OperandType::None)),
+    /* 0x98 */ Some(("ldelem.r4", OperandType::None)),
+    /* 0x99 */ Some(("ldelem.r8", OperandType::None)),
+    /* 0x9A */ Some(("ldelem.ref", OperandType::None)),
+    /* 0x9B */ Some(("stelem.i", OperandType::None)),
+    /* 0x9C */ Some(("stelem.i1", OperandType::None)),
+    /* 0x9D */ Some(("stelem.i2", OperandType::None)),
+    /* 0x9E */ Some(("stelem.i4", OperandType::None)),
+    /* 0x9F */ Some(("stelem.i8", OperandType::None)),
+    /* 0xA0 */ Some(("stelem.r4", OperandType::None)),
+    /* 0xA1 */ Some(("stelem.r8", OperandType::None)),
+    /* 0xA2 */ Some(("stelem.ref", OperandType::None)),
+    /* 0xA3 */ Some(("ldelem", OperandType::Token)),
+    /* 0xA4 */ Some(("stelem", OperandType::Token)),
+    /* 0xA5 */ Some(("unbox.any", OperandType::Token)),
+    /* 0xA6 */ None,
+    /* 0xA7 */ None,
+    /* 0xA8 */ None,
+    /* 0xA9 */ None,
+    /* 0xAA */ None,
+    /* 0xAB */ None,
+    /* 0xAC */ None,
+    /* 0xAD */ None,
+    /* 0xAE */ None,
+    /* 0xAF */ None,
+    /* 0xB0 */ None,
+    /* 0xB1 */ None,
+    /* 0xB2 */ None,
+    /* 0xB3 */ Some(("conv.ovf.i1", OperandType::None)),
+    /* 0xB4 */ Some(("conv.ovf.u1", OperandType::None)),
+    /* 0xB5 */ Some(("conv.ovf.i2", OperandType::None)),
+    /* 0xB6 */ Some(("conv.ovf.u2", OperandType::None)),
+    /* 0xB7 */ Some(("conv.ovf.i4", OperandType::None)),
+    /* 0xB8 */ Some(("conv.ovf.u4", OperandType::None)),
+    /* 0xB9 */ Some(("conv.ovf.i8", OperandType::None)),
+    /* 0xBA */ Some(("conv.ovf.u8", OperandType::None)),
+    /* 0xBB */ None,
+    /* 0xBC */ None,
+    /* 0xBD */ None,
+    /* 0xBE */ None,
+    /* 0xBF */ None,
+    /* 0xC0 */ None,
+    /* 0xC1 */ None,
+    /* 0xC2 */ Some(("refanyval", OperandType::Token)),
+    /* 0xC3 */ Some(("ckfinite", OperandType::None)),
+    /* 0xC4 */ None,
+    /* 0xC5 */ None,
+    /* 0xC6 */ Some(("mkrefany", OperandType::Token)),
+    /* 0xC7 */ None,
+    /* 0xC8 */ None,
+    /* 0xC9 */ None,
+    /* 0xCA */ None,
+    /* 0xCB */ None,
+    /* 0xCC */ None,
+    /* 0xCD */ None,
+    /* 0xCE */ None,
+    /* 0xCF */ None,
+    /* 0xD0 */ Some(("ldtoken", OperandType::Token)),
+    /* 0xD1 */ Some(("conv.u2", OperandType::None)),
+    /* 0xD2 */ Some(("conv.u1", OperandType::None)),
+    /* 0xD3 */ Some(("conv.i", OperandType::None)),
+    /* 0xD4 */ Some(("conv.ovf.i", OperandType::None)),
+    /* 0xD5 */ Some(("conv.ovf.u", OperandType::None)),
+    /* 0xD6 */ Some(("add.ovf", OperandType::None)),
+    /* 0xD7 */ Some(("add.ovf.un", OperandType::None)),
+    /* 0xD8 */ Some(("mul.ovf", OperandType::None)),
+    /* 0xD9 */ Some(("mul.ovf.un", OperandType::None)),
+    /* 0xDA */ Some(("sub.ovf", OperandType::None)),
+    /* 0xDB */ Some(("sub.ovf.un", OperandType::None)),
+    /* 0xDC */ Some(("endfinally", OperandType::None)),
+    /* 0xDD */ Some(("leave", OperandType::Branch32)),
+    /* 0xDE */ Some(("leave.s", OperandType::Branch8)),
+    /* 0xDF */ Some(("stind.i", OperandType::None)),
+    /* 0xE0 */ Some(("conv.u", OperandType::None)),
+];
+
+/// Opcodes following the `0xFE` prefix.
+const TWO_BYTE: [Option<(&str, OperandType)>; 31] = [
+    /* 0x00 */ Some(("arglist", OperandType::None)),
+    /* 0x01 */ Some(("ceq", OperandType::None)),
+    /* 0x02 */ Some(("cgt", OperandType::None)),
+    /* 0x03 */ Some(("cgt.un", OperandType::None)),
+    /* 0x04 */ Some(("clt", OperandType::None)),
+    /* 0x05 */ Some(("clt.un", OperandType::None)),
+    /* 0x06 */ Some(("ldftn", OperandType::Token)),
+    /* 0x07 */ Some(("ldvirtftn", OperandType::Token)),
+    /* 0x08 */ None,
+    /* 0x09 */ Some(("ldarg", OperandType::Var16)),
+    /* 0x0A */ Some(("ldarga", OperandType::Var16)),
+    /* 0x0B */ Some(("starg", OperandType::Var16)),
+    /* 0x0C */ Some(("ldloc", OperandType::Var16)),
+    /* 0x0D */ Some(("ldloca", OperandType::Var16)),
+    /* 0x0E */ Some(("stloc", OperandType::Var16)),
+    /* 0x0F */ Some(("localloc", OperandType::None)),
+    /* 0x10 */ None,
+    /* 0x11 */ Some(("endfilter", OperandType::None)),
+    /* 0x12 */ Some(("unaligned.", OperandType::UInt8)),
+    /* 0x13 */ Some(("volatile.", OperandType::None)),
+    /* 0x14 */ Some(("tail.", OperandType::None)),
+    /* 0x15 */ Some(("initobj", OperandType::Token)),
+    /* 0x16 */ Some(("constrained.", OperandType::Token)),
+    /* 0x17 */ Some(("cpblk", OperandType::None)),
+    /* 0x18 */ Some(("initblk", OperandType::None)),
+    /* 0x19 */ Some(("no.", OperandType::UInt8)),
+    /* 0x1A */ Some(("rethrow", OperandType::None)),
+    /* 0x1B */ None,
+    /* 0x1C */ Some(("sizeof", OperandType::Token)),
+    /* 0x1D */ Some(("refanytype", OperandType::None)),
+    /* 0x1E */ Some(("readonly.", OperandType::None)),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_instructions() {
+        let code = [
+            0x72, 0x01, 0x00, 0x00, 0x70, // ldstr 0x70000001
+            0x28, 0x02, 0x00, 0x00, 0x0A, // call 0x0A000002
+            0x1F, 0xFE, // ldc.i4.s -2
+            0x2C, 0x02, // brfalse.s IL_0010
+            0xFE, 0x0C, 0x01, 0x00, // ldloc 1
+            0x45, 0x01, 0x00, 0x00, 0x00, 0xFB, 0xFF, 0xFF, 0xFF, // switch (IL_0016)
+            0x2A, // ret
+        ];
+
+        let instructions = decode_instructions(&code).unwrap();
+        assert_eq!(instructions.len(), 7);
+        assert_eq!(instructions[0].opcode.name, "ldstr");
+        assert_eq!(instructions[0].operand, Operand::Token(Token(0x7000_0001)));
+        assert_eq!(instructions[2].operand, Operand::Int(-2));
+        assert_eq!(instructions[3].operand, Operand::Target(0x10));
+        assert_eq!(instructions[4].opcode.value, 0xFE0C);
+        assert_eq!(instructions[4].operand, Operand::Variable(1));
+        assert_eq!(instructions[5].operand, Operand::Switch(vec![0x16]));
+        assert_eq!(instructions[6].offset, 0x1B);
+
+        assert_eq!(instructions[0].to_string(), "IL_0000: ldstr 0x70000001");
+        assert_eq!(instructions[3].to_string(), "IL_000c: brfalse.s IL_0010");
+        assert_eq!(instructions[5].to_string(), "IL_0012: switch (IL_0016)");
+    }
+
+    #[test]
+    fn rejects_invalid_code() {
+        assert!(decode_instructions(&[0x24]).is_err());
+        assert!(decode_instructions(&[0xFE]).is_err());
+        assert!(decode_instructions(&[0x72, 0x01]).is_err());
+        assert!(decode_instructions(&[0x45, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]).is_err());
+    }
+}
@@ -0,0 +1,377 @@
+use core::fmt;
+use std::collections::VecDeque;
+use super::{AssemblyIdentity, AssemblyVersion, Metadata};
+use crate::{error::ClrError, Result};
+
+/// Public key tokens the .NET Framework and .NET Core sign their own assemblies with.
+const FRAMEWORK_PUBLIC_KEY_TOKENS: [[u8; 8]; 6] = [
+    [0xB7, 0x7A, 0x5C, 0x56, 0x19, 0x34, 0xE0, 0x89], // ECMA (mscorlib, System)
+    [0xB0, 0x3F, 0x5F, 0x7F, 0x11, 0xD5, 0x0A, 0x3A], // Microsoft
+    [0x31, 0xBF, 0x38, 0x56, 0xAD, 0x36, 0x4E, 0x35], // Microsoft (WPF, ASP.NET)
+    [0xCC, 0x7B, 0x13, 0xFF, 0xCD, 0x2D, 0xDD, 0x51], // .NET Standard / Core
+    [0x7C, 0xEC, 0x85, 0xD7, 0xBE, 0xA7, 0x79, 0x8E], // Silverlight / Core
+    [0xAD, 0xB9, 0x79, 0x38, 0x29, 0xDD, 0xAE, 0x60], // .NET Core
+];
+
+/// How a referenced assembly would be satisfied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DependencyStatus {
+    /// Provided by the extra assembly at this index.
+    Resolved(usize),
+
+    /// Provided by the extra assembly at `index`, but a strong-named reference asks for another version.
+    VersionMismatch {
+        /// Index of the extra assembly with the same name.
+        index: usize,
+
+        /// Version of that assembly.
+        found: AssemblyVersion,
+    },
+
+    /// Signed with a framework key, so expected to come from the GAC or the shared framework.
+    Framework,
+
+    /// Neither provided nor part of the framework.
+    Unresolved,
+}
+
+/// A referenced assembly of the dependency closure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dependency {
+    /// The identity named by the `AssemblyRef` row.
+    pub identity: AssemblyIdentity,
+
+    /// Names of the assemblies referencing it.
+    pub referenced_by: Vec<String>,
+
+    /// How the reference would be satisfied.
+    pub status: DependencyStatus,
+}
+
+/// The dependency closure of an assembly over a set of extra assemblies.
+///
+/// References are followed through every extra assembly that resolves one, so the report
+/// covers indirect dependencies as well.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DependencyReport {
+    /// Identity of the assembly the closure starts from.
+    pub root: AssemblyIdentity,
+
+    /// Identities of the extra assemblies, in the order they were given.
+    pub extra: Vec<AssemblyIdentity>,
+
+    /// Every referenced assembly, in the order it was first reached.
+    pub dependencies: Vec<Dependency>,
+}
+
+impl DependencyReport {
+    /// Builds the dependency closure of `root`.
+    ///
+    /// # Arguments
+    ///
+    /// * `root` - The assembly to run.
+    /// * `extra` - Assemblies available to satisfy its references, e.g. ones that are not in the GAC.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(DependencyReport)` - The status of every assembly reachable from `root`.
+    /// * `Err(ClrError)` - If a buffer is not a .NET assembly or has no `Assembly` table.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// use rustclr::metadata::DependencyReport;
+    /// use std::fs;
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let buffer = fs::read("examples/sample.exe")?;
+    ///     let library = fs::read("examples/library.dll")?;
+    ///
+    ///     let report = DependencyReport::build(&buffer, &[&library])?;
+    ///     print!("{report}");
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn build(root: &[u8], extra: &[&[u8]]) -> Result<Self> {
+        let root = Metadata::parse(root)?;
+        let extra = extra.iter().map(|buffer| Metadata::parse(buffer)).collect::<Result<Vec<_>>>()?;
+        let identity = |metadata: &Metadata| {
+            metadata
+                .assembly()
+                .map(|assembly| assembly.identity())
+                .ok_or(ClrError::MetadataError("the image has no Assembly table"))
+        };
+
+        let mut report = Self {
+            root: identity(&root)?,
+            extra: extra.iter().map(identity).collect::<Result<_>>()?,
+            dependencies: Vec::new(),
+        };
+
+        let mut visited = vec![false; extra.len()];
+        let mut queue = VecDeque::from([(report.root.name.clone(), &root)]);
+        while let Some((parent, metadata)) = queue.pop_front() {
+            for reference in metadata.assembly_references() {
+                let identity = reference.identity();
+                if let Some(existing) = report.dependencies.iter_mut().find(|dependency| dependency.identity == identity) {
+                    if !existing.referenced_by.contains(&parent) {
+                        existing.referenced_by.push(parent.clone());
+                    }
+
+                    continue;
+                }
+
+                let status = report.status(&identity);
+                if let DependencyStatus::Resolved(index) = status {
+                    if !visited[index] {
+                        visited[index] = true;
+                        queue.push_back((report.extra[index].name.clone(), &extra[index]));
+                    }
+                }
+
+                report.dependencies.push(Dependency { identity, referenced_by: vec![parent.clone()], status });
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Returns the references nothing satisfies.
+    pub fn unresolved(&self) -> impl Iterator<Item = &Dependency> {
+        self.dependencies.iter().filter(|dependency| dependency.status == DependencyStatus::Unresolved)
+    }
+
+    /// Returns the references satisfied by an assembly of the wrong version.
+    pub fn mismatched(&self) -> impl Iterator<Item = &Dependency> {
+        self.dependencies
+            .iter()
+            .filter(|dependency| matches!(dependency.status, DependencyStatus::VersionMismatch { .. }))
+    }
+
+    /// Returns `true` if every reference is expected to load.
+    pub fn is_complete(&self) -> bool {
+        self.unresolved().next().is_none() && self.mismatched().next().is_none()
+    }
+
+    /// Decides how `identity` is satisfied by the extra assemblies or the framework.
+    ///
+    /// Names and cultures are matched case-insensitively. The version and public key token
+    /// only matter for strong-named references, since the loader ignores them for simple names.
+    fn status(&self, identity: &AssemblyIdentity) -> DependencyStatus {
+        let same_culture = |candidate: &AssemblyIdentity| match (&candidate.culture, &identity.culture) {
+            (Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+            (a, b) => a.is_none() && b.is_none(),
+        };
+
+        let candidates = self
+            .extra
+            .iter()
+            .enumerate()
+            .filter(|(_, candidate)| candidate.name.eq_ignore_ascii_case(&identity.name) && same_culture(candidate))
+            .collect::<Vec<_>>();
+
+        let exact = candidates.iter().find(|(_, candidate)| *candidate == identity);
+        match (exact, candidates.first()) {
+            (Some((index, _)), _) => DependencyStatus::Resolved(*index),
+            (None, Some((index, _))) if identity.public_key_token.is_none() => DependencyStatus::Resolved(*index),
+            (None, Some((index, candidate))) => DependencyStatus::VersionMismatch { index: *index, found: candidate.version },
+            (None, None) if identity.public_key_token.is_some_and(|token| FRAMEWORK_PUBLIC_KEY_TOKENS.contains(&token)) => {
+                DependencyStatus::Framework
+            }
+            (None, None) => DependencyStatus::Unresolved,
+        }
+    }
+}
+
+/// Assemblies kept in memory to satisfy references the runtime cannot resolve, keyed by identity.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AssemblyStore {
+    /// The identity of each assembly and its bytes, in registration order.
+    assemblies: Vec<(AssemblyIdentity, Vec<u8>)>,
+}
+
+impl AssemblyStore {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an assembly, replacing any previous one with the same identity.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - The bytes of the assembly.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(AssemblyIdentity)` - The identity parsed from the `Assembly` table.
+    /// * `Err(ClrError)` - If the buffer is not a .NET assembly or has no `Assembly` table.
+    pub fn insert(&mut self, buffer: &[u8]) -> Result<AssemblyIdentity> {
+        let identity = Metadata::parse(buffer)?
+            .assembly()
+            .map(|assembly| assembly.identity())
+            .ok_or(ClrError::MetadataError("the image has no Assembly table"))?;
+
+        self.assemblies.retain(|(existing, _)| *existing != identity);
+        self.assemblies.push((identity.clone(), buffer.to_vec()));
+        Ok(identity)
+    }
+
+    /// Finds the assembly the runtime asks for, as `ResolveEventArgs.Name` names it.
+    ///
+    /// An assembly matching the whole display name wins; otherwise, since the loader accepts
+    /// whatever the resolve handler returns, the one registered last with the same simple name
+    /// is used. Names are compared case-insensitively.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A simple or display name, e.g. `Lib, Version=1.0.0.0, Culture=neutral, PublicKeyToken=null`.
+    ///
+    /// # Returns
+    ///
+    /// * `Some((&AssemblyIdentity, &[u8]))` - The matching assembly.
+    /// * `None` - If no assembly has that name.
+    pub fn get(&self, name: &str) -> Option<(&AssemblyIdentity, &[u8])> {
+        let simple_name = name.split(',').next().unwrap_or_default().trim();
+        self.assemblies
+            .iter()
+            .rev()
+            .find(|(identity, _)| identity.matches(name))
+            .or_else(|| self.assemblies.iter().rev().find(|(identity, _)| identity.name.eq_ignore_ascii_case(simple_name)))
+            .map(|(identity, buffer)| (identity, buffer.as_slice()))
+    }
+
+    /// Iterates over the assemblies in registration order.
+    pub fn iter(&self) -> impl Iterator<Item = (&AssemblyIdentity, &[u8])> {
+        self.assemblies.iter().map(|(identity, buffer)| (identity, buffer.as_slice()))
+    }
+
+    /// Returns the number of assemblies.
+    pub fn len(&self) -> usize {
+        self.assemblies.len()
+    }
+
+    /// Returns `true` if no assembly was added.
+    pub fn is_empty(&self) -> bool {
+        self.assemblies.is_empty()
+    }
+}
+
+/// Prints one line per dependency, e.g. `  [unresolved] Lib, Version=1.0.0.0, ... (referenced by Sample)`.
+impl fmt::Display for DependencyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Dependencies of {}", self.root)?;
+        for dependency in &self.dependencies {
+            let status = match &dependency.status {
+                DependencyStatus::Resolved(_) => "resolved",
+                DependencyStatus::VersionMismatch { .. } => "mismatch",
+                DependencyStatus::Framework => "framework",
+                DependencyStatus::Unresolved => "unresolved",
+            };
+
+            write!(f, "  [{status}] {}", dependency.identity)?;
+            match &dependency.status {
+                DependencyStatus::VersionMismatch { found, .. } => write!(f, " (found {found})")?,
+                DependencyStatus::Unresolved => write!(f, " (referenced by {})", dependency.referenced_by.join(", "))?,
+                _ => {}
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::{builder::AssemblyBuilder, TableId};
+
+    /// Builds an assembly with the given version that references `references` as `(name, version, token)`.
+    fn assembly(name: &str, version: u32, references: &[(&str, u32, Option<&[u8]>)]) -> Vec<u8> {
+        let mut b = AssemblyBuilder::new();
+        b.characteristics |= 0x2000;
+        let name = b.string(name);
+        b.row(TableId::Assembly, &[0x8004, version, 0, 0, 0, 0, 0, name, 0]);
+        for (reference, version, token) in references {
+            let reference = b.string(reference);
+            let token = token.map_or(0, |token| b.blob(token));
+            b.row(TableId::AssemblyRef, &[*version, 0, 0, 0, 0, token, reference, 0, 0]);
+        }
+
+        b.build()
+    }
+
+    #[test]
+    fn builds_dependency_closure() {
+        let ecma: &[u8] = &[0xB7, 0x7A, 0x5C, 0x56, 0x19, 0x34, 0xE0, 0x89];
+        let signed: &[u8] = &[1, 2, 3, 4, 5, 6, 7, 8];
+        let root = assembly("Tool", 1, &[("mscorlib", 4, Some(ecma)), ("Core", 1, None), ("Signed", 2, Some(signed))]);
+        let core = assembly("core", 3, &[("mscorlib", 4, Some(ecma)), ("Missing", 1, None)]);
+        let signed = assembly("Signed", 1, &[]);
+        let unused = assembly("Unused", 1, &[("Other", 1, None)]);
+
+        let report = DependencyReport::build(&root, &[&unused, &core, &signed]).unwrap();
+        assert_eq!(report.root.name, "Tool");
+        assert_eq!(report.extra.len(), 3);
+
+        let statuses = report
+            .dependencies
+            .iter()
+            .map(|dependency| (dependency.identity.name.as_str(), dependency.status.clone()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(statuses, [
+            ("mscorlib", DependencyStatus::Framework),
+            ("Core", DependencyStatus::Resolved(1)),
+            ("Signed", DependencyStatus::VersionMismatch { index: 2, found: AssemblyVersion::new(1, 0, 0, 0) }),
+            ("Missing", DependencyStatus::Unresolved),
+        ]);
+
+        assert_eq!(report.dependencies[0].referenced_by, ["Tool", "core"]);
+        assert_eq!(report.unresolved().count(), 1);
+        assert_eq!(report.mismatched().count(), 1);
+        assert!(!report.is_complete());
+
+        let text = report.to_string();
+        assert!(text.starts_with("Dependencies of Tool, Version=1.0.0.0, Culture=neutral, PublicKeyToken=null\n"));
+        assert!(text.contains("  [unresolved] Missing, Version=1.0.0.0, Culture=neutral, PublicKeyToken=null (referenced by core)\n"));
+        assert!(text.contains("(found 1.0.0.0)"));
+    }
+
+    #[test]
+    fn stores_assemblies_by_identity() {
+        let first = assembly("Lib", 1, &[]);
+        let second = assembly("Lib", 2, &[]);
+        let other = assembly("Other", 1, &[]);
+
+        let mut store = AssemblyStore::new();
+        assert_eq!(store.insert(&first).unwrap().version, AssemblyVersion::new(1, 0, 0, 0));
+        store.insert(&other).unwrap();
+        store.insert(&second).unwrap();
+        store.insert(&first).unwrap();
+        assert_eq!(store.len(), 3);
+
+        let (identity, buffer) = store.get("lib, Version=2.0.0.0, Culture=neutral, PublicKeyToken=null").unwrap();
+        assert_eq!(identity.version, AssemblyVersion::new(2, 0, 0, 0));
+        assert_eq!(buffer, second.as_slice());
+
+        // Without a matching version, the one registered last is used
+        let (identity, buffer) = store.get("Lib, Version=3.0.0.0").unwrap();
+        assert_eq!(identity.version, AssemblyVersion::new(1, 0, 0, 0));
+        assert_eq!(buffer, first.as_slice());
+        assert_eq!(store.get("Other").unwrap().0.name, "Other");
+        assert!(store.get("Missing").is_none());
+        assert!(store.insert(&AssemblyBuilder::new().build()).is_err());
+    }
+
+    #[test]
+    fn rejects_modules_without_assembly_table() {
+        let root = assembly("Tool", 1, &[]);
+        let module = AssemblyBuilder::new().build();
+        assert!(DependencyReport::build(&root, &[]).unwrap().is_complete());
+        assert!(matches!(DependencyReport::build(&root, &[&module]), Err(ClrError::MetadataError(_))));
+    }
+}
@@ -0,0 +1,328 @@
+use super::{
+    body::CODE_TYPE_MASK,
+    signature::{SignatureDecoder, TypeSig},
+    CodedIndex, ExceptionHandler, Instruction, Metadata, MethodBody, MethodDefinition, Operand, TableId, Token,
+};
+use crate::{error::ClrError, Result};
+
+/// Calling convention of `FieldSig` blobs, telling field references from method references.
+const SIG_FIELD: u8 = 0x06;
+
+/// The parts of a method reference, printed as `Return Type::Name[Args](Params)`.
+struct MethodText {
+    /// The return type.
+    return_type: String,
+
+    /// The declaring type, or the module for global methods.
+    parent: String,
+
+    /// Name of the method.
+    name: String,
+
+    /// The formatted parameter list.
+    params: String,
+}
+
+impl MethodText {
+    /// Formats the method, with the generic arguments of a `MethodSpec` if any.
+    fn format(&self, generic_args: &[TypeSig]) -> String {
+        let args = match generic_args.is_empty() {
+            true => String::new(),
+            false => format!("[{}]", generic_args.iter().map(ToString::to_string).collect::<Vec<_>>().join(",")),
+        };
+
+        format!("{} {}::{}{args}({})", self.return_type, self.parent, self.name, self.params)
+    }
+}
+
+impl<'a> Metadata<'a> {
+    /// Decodes the types of the locals declared by a method body.
+    ///
+    /// # Arguments
+    ///
+    /// * `body` - The method body, whose `local_var_sig` names the locals signature.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<TypeSig>)` - The types of the locals, empty when the method has none.
+    /// * `Err(ClrError)` - If the token or the signature is malformed.
+    pub fn locals(&self, body: &MethodBody) -> Result<Vec<TypeSig>> {
+        if body.local_var_sig.is_null() {
+            return Ok(Vec::new());
+        }
+
+        let malformed = || ClrError::MetadataError("malformed local variable signature");
+        if body.local_var_sig.table() != Some(TableId::StandAloneSig) {
+            return Err(malformed());
+        }
+
+        let blob = self
+            .tables()
+            .table(TableId::StandAloneSig)
+            .get(body.local_var_sig.row(), 0)
+            .and_then(|blob| self.blobs().get(blob))
+            .ok_or_else(malformed)?;
+
+        SignatureDecoder::new(self, &[], &[]).locals(blob).ok_or_else(malformed)
+    }
+
+    /// Describes what a token used as an instruction operand refers to.
+    ///
+    /// Generic parameters of referenced members print as `!0` (type) and `!!0` (method).
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - A type, method, field, `MethodSpec`, `StandAloneSig` or `#US` token.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(String)` - E.g. `"Hello"`, `System.Exception`,
+    ///   `Void System.Console::WriteLine(System.String)` or `Int32 Sample.Program::counter`.
+    /// * `None` - If the token cannot be resolved.
+    pub fn describe_token(&self, token: Token) -> Option<String> {
+        if token.is_user_string() {
+            return self.user_strings().get(token.row()).map(|value| format!("{value:?}"));
+        }
+
+        let mut decoder = SignatureDecoder::new(self, &[], &[]);
+        match token.table()? {
+            TableId::TypeDef | TableId::TypeRef | TableId::TypeSpec => {
+                decoder.type_token(token, false).map(|ty| ty.to_string())
+            }
+            TableId::Field => {
+                let table = self.tables().table(TableId::Field);
+                let name = self.strings().get(table.get(token.row(), 1)?)?;
+                let field_type = decoder.field(self.blobs().get(table.get(token.row(), 2)?)?)?;
+                let parent = self.type_name(Token::new(TableId::TypeDef, self.field_declaring_type(token.row())?))?;
+                Some(format!("{field_type} {parent}::{name}"))
+            }
+            TableId::MemberRef => {
+                let table = self.tables().table(TableId::MemberRef);
+                let blob = self.blobs().get(table.get(token.row(), 2)?)?;
+                if blob.first()? & 0x0F != SIG_FIELD {
+                    return self.method_text(token).map(|method| method.format(&[]));
+                }
+
+                let name = self.strings().get(table.get(token.row(), 1)?)?;
+                let parent = self.member_ref_parent(token.row())?;
+                Some(format!("{} {parent}::{name}", decoder.field(blob)?))
+            }
+            TableId::MethodDef => self.method_text(token).map(|method| method.format(&[])),
+            TableId::MethodSpec => {
+                let table = self.tables().table(TableId::MethodSpec);
+                let (method_table, method_row) = CodedIndex::MethodDefOrRef.decode(table.get(token.row(), 0)?)?;
+                let args = decoder.method_spec(self.blobs().get(table.get(token.row(), 1)?)?)?;
+                Some(self.method_text(Token::new(method_table, method_row))?.format(&args))
+            }
+            TableId::StandAloneSig => {
+                let blob = self.blobs().get(self.tables().table(TableId::StandAloneSig).get(token.row(), 0)?)?;
+                let sig = decoder.method(blob)?;
+                Some(format!("{}({})", sig.return_type, sig.format_params()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Formats an instruction, replacing a token operand with what it refers to.
+    ///
+    /// # Returns
+    ///
+    /// * The instruction as `IL_0000: ldstr "Hello"`, keeping the raw token when it cannot be resolved.
+    pub fn format_instruction(&self, instruction: &Instruction) -> String {
+        let Operand::Token(token) = instruction.operand else {
+            return instruction.to_string();
+        };
+
+        match self.describe_token(token) {
+            Some(description) => format!("IL_{:04x}: {} {description}", instruction.offset, instruction.opcode.name),
+            None => instruction.to_string(),
+        }
+    }
+
+    /// Disassembles the body of a method into text.
+    ///
+    /// The listing starts with the method, its stack size, locals and exception clauses,
+    /// followed by one instruction per line with tokens resolved by `describe_token`.
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The method to disassemble, e.g. from `Metadata::find_type`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(String)` - The listing.
+    /// * `Err(ClrError)` - If the method has no IL body or the body cannot be decoded.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// use rustclr::metadata::Metadata;
+    /// use std::fs;
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let buffer = fs::read("examples/sample.exe")?;
+    ///     let metadata = Metadata::parse(&buffer)?;
+    ///
+    ///     let program = metadata.find_type("Sample.Program")?.ok_or("no such type")?;
+    ///     for method in program.methods.iter().filter(|method| method.name == "Main") {
+    ///         println!("{}", metadata.disassemble(method)?);
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn disassemble(&self, method: &MethodDefinition) -> Result<String> {
+        if method.rva == 0 || method.impl_flags & CODE_TYPE_MASK != 0 {
+            return Err(ClrError::MetadataError("the method has no IL body"));
+        }
+
+        let body = self.method_body(method.rva)?;
+        let describe = |token: Token| self.describe_token(token).unwrap_or_else(|| token.to_string());
+        let mut lines = vec![
+            format!(".method {} // {}", describe(method.token), method.token),
+            format!(".maxstack {}", body.max_stack),
+        ];
+
+        let locals = self.locals(&body)?;
+        if !locals.is_empty() {
+            let init = if body.init_locals { "init " } else { "" };
+            let locals = locals.iter().map(ToString::to_string).collect::<Vec<_>>();
+            lines.push(format!(".locals {init}({})", locals.join(", ")));
+        }
+
+        for clause in &body.exception_clauses {
+            let handler = match clause.handler {
+                ExceptionHandler::Catch(token) => format!("catch {}", describe(token)),
+                ExceptionHandler::Filter(offset) => format!("filter IL_{offset:04x}"),
+                ExceptionHandler::Finally => "finally".to_string(),
+                ExceptionHandler::Fault => "fault".to_string(),
+            };
+
+            lines.push(format!(
+                ".try IL_{:04x} to IL_{:04x} {handler} handler IL_{:04x} to IL_{:04x}",
+                clause.try_offset,
+                clause.try_offset + clause.try_length,
+                clause.handler_offset,
+                clause.handler_offset + clause.handler_length,
+            ));
+        }
+
+        lines.extend(body.instructions()?.iter().map(|instruction| self.format_instruction(instruction)));
+        Ok(lines.join("\n"))
+    }
+
+    /// Splits a `MethodDef` or `MemberRef` into the parts printed for it.
+    fn method_text(&self, token: Token) -> Option<MethodText> {
+        let mut decoder = SignatureDecoder::new(self, &[], &[]);
+        let (table, column, parent) = match token.table()? {
+            TableId::MethodDef => {
+                let parent = self.type_name(Token::new(TableId::TypeDef, self.declaring_type(token.row())?))?;
+                (TableId::MethodDef, 3, parent.to_string())
+            }
+            TableId::MemberRef => {
+                let parent = self.member_ref_parent(token.row())?;
+                (TableId::MemberRef, 1, parent)
+            }
+            _ => return None,
+        };
+
+        let table = self.tables().table(table);
+        let name = self.strings().get(table.get(token.row(), column)?)?.to_string();
+        let sig = decoder.method(self.blobs().get(table.get(token.row(), column + 1)?)?)?;
+        Some(MethodText {
+            return_type: sig.return_type.to_string(),
+            parent,
+            name,
+            params: sig.format_params(),
+        })
+    }
+
+    /// Names the parent of a `MemberRef` row: a type, a module or, for vararg call sites, a method.
+    fn member_ref_parent(&self, row: u32) -> Option<String> {
+        let class = self.tables().table(TableId::MemberRef).get(row, 0)?;
+        match CodedIndex::MemberRefParent.decode(class)? {
+            (TableId::ModuleRef, module) => {
+                let name = self.tables().table(TableId::ModuleRef).get(module, 0)?;
+                Some(format!("[{}]", self.strings().get(name)?))
+            }
+            (TableId::MethodDef, method) => {
+                let parent = self.declaring_type(method)?;
+                self.type_name(Token::new(TableId::TypeDef, parent)).map(|name| name.to_string())
+            }
+            (table, row) => SignatureDecoder::new(self, &[], &[])
+                .type_token(Token::new(table, row), false)
+                .map(|ty| ty.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::builder::AssemblyBuilder;
+
+    #[test]
+    fn disassembles_methods() {
+        let mut b = AssemblyBuilder::new();
+        let names = [
+            b.string("Program"), b.string("Sample"), b.string("Main"), b.string("counter"),
+            b.string("Console"), b.string("System"), b.string("WriteLine"), b.string("Exception"),
+            b.string("mscorlib"),
+        ];
+
+        let hello = b.user_string("Hello\n");
+        let main_sig = b.blob(&[0x00, 0x00, 0x01]);
+        let field_sig = b.blob(&[0x06, 0x08]);
+        let write_line_sig = b.blob(&[0x00, 0x01, 0x01, 0x0E]);
+        let locals_sig = b.blob(&[0x07, 0x02, 0x08, 0x0E]);
+
+        let mut code = vec![0x72];
+        code.extend(Token::user_string(hello).0.to_le_bytes());
+        code.push(0x28);
+        code.extend(Token::new(TableId::MemberRef, 1).0.to_le_bytes());
+        code.push(0x7E);
+        code.extend(Token::new(TableId::Field, 1).0.to_le_bytes());
+        code.extend([0x26, 0xDE, 0x03, 0x26, 0xDE, 0x00, 0x2A]);
+
+        let mut sections = vec![0x01, 16, 0, 0];
+        sections.extend([0x00, 0x00, 0x00, 0x00, 0x12, 0x12, 0x00, 0x03]);
+        sections.extend(Token::new(TableId::TypeRef, 2).0.to_le_bytes());
+        let rva = b.fat_body(&code, Token::new(TableId::StandAloneSig, 1).0, &sections);
+
+        let scope = CodedIndex::ResolutionScope.encode(TableId::AssemblyRef, 1).unwrap();
+        b.row(TableId::TypeDef, &[0x0010_0001, names[0], names[1], 0, 1, 1]);
+        b.row(TableId::Field, &[0x0016, names[3], field_sig]);
+        b.row(TableId::MethodDef, &[rva, 0, 0x0016, names[2], main_sig, 1]);
+        b.row(TableId::AssemblyRef, &[4, 0, 0, 0, 0, 0, names[8], 0, 0]);
+        b.row(TableId::TypeRef, &[scope, names[4], names[5]]);
+        b.row(TableId::TypeRef, &[scope, names[7], names[5]]);
+        b.row(TableId::MemberRef, &[CodedIndex::MemberRefParent.encode(TableId::TypeRef, 1).unwrap(), names[6], write_line_sig]);
+        b.row(TableId::StandAloneSig, &[locals_sig]);
+
+        let buffer = b.build();
+        let metadata = Metadata::parse(&buffer).unwrap();
+        let program = metadata.find_type("Sample.Program").unwrap().unwrap();
+        assert_eq!(metadata.disassemble(&program.methods[0]).unwrap(), [
+            ".method Void Sample.Program::Main() // 0x06000001",
+            ".maxstack 8",
+            ".locals init (Int32, System.String)",
+            ".try IL_0000 to IL_0012 catch System.Exception handler IL_0012 to IL_0015",
+            "IL_0000: ldstr \"Hello\\n\"",
+            "IL_0005: call Void System.Console::WriteLine(System.String)",
+            "IL_000a: ldsfld Int32 Sample.Program::counter",
+            "IL_000f: pop",
+            "IL_0010: leave.s IL_0015",
+            "IL_0012: pop",
+            "IL_0013: leave.s IL_0015",
+            "IL_0015: ret",
+        ].join("\n"));
+
+        assert_eq!(metadata.describe_token(Token(0x0A00_0002)), None);
+        let instruction = Instruction {
+            offset: 0,
+            opcode: crate::metadata::OpCode::from_value(0x28).unwrap(),
+            operand: Operand::Token(Token(0x0A00_0002)),
+        };
+        assert_eq!(metadata.format_instruction(&instruction), "IL_0000: call 0x0A000002");
+    }
+}
@@ -0,0 +1,281 @@
+use super::{Metadata, MethodDefinition, TableId, Token, TypeDefinition, TypeSig};
+use crate::{error::ClrError, Result};
+
+/// Name the C# compiler gives to the synchronous shim of an `async Main`.
+const ASYNC_MAIN_SHIM: &str = "<Main>";
+
+/// Return types the runtime accepts for an entry point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryPointReturn {
+    /// `void Main(...)`.
+    Void,
+
+    /// `int Main(...)`.
+    Int32,
+
+    /// `uint Main(...)`.
+    UInt32,
+}
+
+/// The managed entry point of an executable, resolved from the CLI header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryPoint {
+    /// Metadata token of the entry point method.
+    pub token: Token,
+
+    /// The type declaring the entry point.
+    pub declaring_type: TypeDefinition,
+
+    /// The entry point method.
+    pub method: MethodDefinition,
+
+    /// Whether the entry point is `Main(System.String[])` rather than `Main()`.
+    pub takes_args: bool,
+
+    /// What the entry point returns.
+    pub returns: EntryPointReturn,
+}
+
+impl<'a> Metadata<'a> {
+    /// Resolves and validates the entry point named by the CLI header's `EntryPointToken`.
+    ///
+    /// The entry point must be a static method taking either nothing or a `System.String[]`,
+    /// and returning `void`, `int` or `uint`. Compiler-generated shims of `async Task Main`
+    /// methods are rejected, as are methods returning a `Task`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(EntryPoint)` - The entry point, its argument and return kinds.
+    /// * `Err(ClrError)` - `ClrError::EntryPointNotFound` if there is no managed entry point,
+    ///   or `ClrError::InvalidEntryPoint` if it cannot be invoked.
+    pub fn entry_point(&self) -> Result<EntryPoint> {
+        let cli = self.cli_header();
+        let token = Token(cli.entry_point_token);
+        if cli.has_native_entry_point() || token.is_null() {
+            return Err(ClrError::EntryPointNotFound);
+        }
+
+        match token.table() {
+            Some(TableId::MethodDef) if token.row() <= self.tables().rows(TableId::MethodDef) => {}
+            Some(TableId::File) => {
+                return Err(ClrError::InvalidEntryPoint("the entry point is defined in another module".into()))
+            }
+            _ => return Err(ClrError::EntryPointNotFound),
+        }
+
+        let row = self.declaring_type(token.row()).ok_or(ClrError::EntryPointNotFound)?;
+        let declaring_type = self.type_definition(row)?;
+        let method = declaring_type
+            .methods
+            .iter()
+            .find(|method| method.token == token)
+            .cloned()
+            .ok_or(ClrError::EntryPointNotFound)?;
+
+        let invalid = |reason: &str| ClrError::InvalidEntryPoint(format!("{reason}: {declaring_type}::{method}"));
+        if method.name == ASYNC_MAIN_SHIM {
+            return Err(invalid("async entry points are not supported"));
+        }
+
+        if !method.is_static() {
+            return Err(invalid("the entry point must be static"));
+        }
+
+        let returns = match &method.signature.return_type {
+            TypeSig::Void => EntryPointReturn::Void,
+            TypeSig::Int32 => EntryPointReturn::Int32,
+            TypeSig::UInt32 => EntryPointReturn::UInt32,
+            ret if is_task(ret) => return Err(invalid("async entry points are not supported")),
+            _ => return Err(invalid("the entry point must return void or int")),
+        };
+
+        let takes_args = match method.signature.params.as_slice() {
+            [] => false,
+            [TypeSig::SzArray(element)] if **element == TypeSig::String => true,
+            _ => return Err(invalid("the entry point must take no arguments or System.String[]")),
+        };
+
+        Ok(EntryPoint { token, declaring_type, method, takes_args, returns })
+    }
+}
+
+impl<'a> Metadata<'a> {
+    /// Resolves a static method to invoke in place of the entry point, e.g. in a class library.
+    ///
+    /// # Arguments
+    ///
+    /// * `type_name` - Full name of the declaring type, e.g. `Namespace.Type`.
+    /// * `method` - Either a method name or a full signature as printed by
+    ///   `MethodInfo.ToString()`, e.g. `Void Run(System.String)`.
+    /// * `arity` - Number of arguments that will be passed, used to pick among overloads.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok((TypeDefinition, MethodDefinition))` - The declaring type and the method.
+    /// * `Err(ClrError)` - `ClrError::TypeNotFound` or `ClrError::MethodNotFound` if either is missing,
+    ///   or `ClrError::InvalidEntryPoint` if the method is not static, takes a different number of
+    ///   arguments, or the name matches several overloads.
+    pub fn find_static_method(&self, type_name: &str, method: &str, arity: usize) -> Result<(TypeDefinition, MethodDefinition)> {
+        let declaring_type = self
+            .find_type(type_name)?
+            .ok_or_else(|| ClrError::TypeNotFound(type_name.to_string()))?;
+
+        let by_signature = method.contains('(');
+        let named = declaring_type
+            .methods
+            .iter()
+            .filter(|candidate| match by_signature {
+                true => candidate.to_string() == method,
+                false => candidate.name == method,
+            })
+            .collect::<Vec<&MethodDefinition>>();
+
+        let candidates = named
+            .iter()
+            .filter(|candidate| candidate.signature.params.len() == arity)
+            .collect::<Vec<_>>();
+
+        let found = match (named.as_slice(), candidates.as_slice()) {
+            ([], _) => return Err(ClrError::MethodNotFound),
+            (_, [found]) => (**found).clone(),
+            ([found], []) => {
+                let count = found.signature.params.len();
+                return Err(ClrError::InvalidEntryPoint(format!(
+                    "{declaring_type}::{found} takes {count} argument(s), but {arity} were supplied"
+                )));
+            }
+            _ => {
+                return Err(ClrError::InvalidEntryPoint(format!(
+                    "{method} matches several overloads in {declaring_type}, pass a full signature instead"
+                )))
+            }
+        };
+
+        if !found.is_static() {
+            return Err(ClrError::InvalidEntryPoint(format!("{declaring_type}::{found} is not static")));
+        }
+
+        Ok((declaring_type, found))
+    }
+}
+
+/// Returns `true` for `System.Threading.Tasks.Task` and `Task<T>`.
+fn is_task(sig: &TypeSig) -> bool {
+    let name = match sig {
+        TypeSig::Class(name) => name,
+        TypeSig::GenericInst(base, _) => match &**base {
+            TypeSig::Class(name) => name,
+            _ => return false,
+        },
+        _ => return false,
+    };
+
+    name.namespace == "System.Threading.Tasks" && matches!(name.name.as_str(), "Task" | "Task`1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::{builder::AssemblyBuilder, CodedIndex};
+
+    /// Builds an executable whose only method has the given name, flags and signature.
+    fn assembly(name: &str, flags: u32, sig: &[u8]) -> Vec<u8> {
+        let mut b = AssemblyBuilder::new();
+        let mscorlib = b.string("mscorlib");
+        let tasks = b.string("System.Threading.Tasks");
+        let task = b.string("Task`1");
+        b.row(TableId::AssemblyRef, &[4, 0, 0, 0, 0, 0, mscorlib, 0, 0]);
+        b.row(TableId::TypeRef, &[CodedIndex::ResolutionScope.encode(TableId::AssemblyRef, 1).unwrap(), task, tasks]);
+
+        let module = b.string("<Module>");
+        let program = b.string("Program");
+        let method = b.string(name);
+        let sig = b.blob(sig);
+        b.row(TableId::TypeDef, &[0, module, 0, 0, 1, 1]);
+        b.row(TableId::TypeDef, &[0, program, 0, 0, 1, 1]);
+        b.row(TableId::MethodDef, &[0x2050, 0, flags, method, sig, 1]);
+        b.entry_point = 0x0600_0001;
+        b.build()
+    }
+
+    #[test]
+    fn resolves_valid_entry_points() {
+        let buffer = assembly("Main", 0x0016, &[0x00, 0x01, 0x01, 0x1D, 0x0E]);
+        let entry = Metadata::parse(&buffer).unwrap().entry_point().unwrap();
+        assert_eq!(entry.token, Token(0x0600_0001));
+        assert_eq!(entry.declaring_type.to_string(), "Program");
+        assert_eq!(entry.method.to_string(), "Void Main(System.String[])");
+        assert!(entry.takes_args);
+        assert_eq!(entry.returns, EntryPointReturn::Void);
+
+        let buffer = assembly("Main", 0x0016, &[0x00, 0x00, 0x08]);
+        let entry = Metadata::parse(&buffer).unwrap().entry_point().unwrap();
+        assert!(!entry.takes_args);
+        assert_eq!(entry.returns, EntryPointReturn::Int32);
+    }
+
+    #[test]
+    fn rejects_invalid_entry_points() {
+        let invalid = |name: &str, flags: u32, sig: &[u8]| {
+            let buffer = assembly(name, flags, sig);
+            matches!(Metadata::parse(&buffer).unwrap().entry_point(), Err(ClrError::InvalidEntryPoint(_)))
+        };
+
+        // Async shim, `Task<int> Main()`, instance method, `string Main()` and `void Main(int)`.
+        assert!(invalid("<Main>", 0x0016, &[0x00, 0x01, 0x01, 0x1D, 0x0E]));
+        assert!(invalid("Main", 0x0016, &[0x00, 0x00, 0x15, 0x12, 1 << 2 | 1, 0x01, 0x08]));
+        assert!(invalid("Main", 0x0006, &[0x20, 0x00, 0x01]));
+        assert!(invalid("Main", 0x0016, &[0x00, 0x00, 0x0E]));
+        assert!(invalid("Main", 0x0016, &[0x00, 0x01, 0x01, 0x08]));
+    }
+
+    #[test]
+    fn finds_static_methods_in_libraries() {
+        let mut b = AssemblyBuilder::new();
+        b.characteristics |= 0x2000;
+        let tools = b.string("Tools");
+        let runner = b.string("Runner");
+        let run = b.string("Run");
+        let stop = b.string("Stop");
+        let run_string = b.blob(&[0x00, 0x01, 0x01, 0x0E]);
+        let run_int_string = b.blob(&[0x00, 0x02, 0x0E, 0x08, 0x0E]);
+        let stop_sig = b.blob(&[0x20, 0x00, 0x01]);
+        b.row(TableId::TypeDef, &[0x0010_0001, runner, tools, 0, 1, 1]);
+        b.row(TableId::MethodDef, &[0x2050, 0, 0x0096, run, run_string, 1]);
+        b.row(TableId::MethodDef, &[0x2060, 0, 0x0096, run, run_int_string, 1]);
+        b.row(TableId::MethodDef, &[0x2070, 0, 0x0086, stop, stop_sig, 1]);
+
+        let buffer = b.build();
+        let metadata = Metadata::parse(&buffer).unwrap();
+        assert!(metadata.pe().is_dll());
+        assert!(matches!(metadata.entry_point(), Err(ClrError::EntryPointNotFound)));
+
+        let (ty, method) = metadata.find_static_method("Tools.Runner", "Run", 2).unwrap();
+        assert_eq!(ty.to_string(), "Tools.Runner");
+        assert_eq!(method.to_string(), "System.String Run(Int32, System.String)");
+
+        let (_, method) = metadata.find_static_method("Tools.Runner", "Void Run(System.String)", 1).unwrap();
+        assert_eq!(method.token, Token(0x0600_0001));
+
+        let find = |ty: &str, method: &str, arity: usize| metadata.find_static_method(ty, method, arity).err();
+        assert!(matches!(find("Tools.Missing", "Run", 1), Some(ClrError::TypeNotFound(_))));
+        assert!(matches!(find("Tools.Runner", "Walk", 0), Some(ClrError::MethodNotFound)));
+        assert!(matches!(find("Tools.Runner", "Void Run()", 0), Some(ClrError::MethodNotFound)));
+        assert!(matches!(find("Tools.Runner", "Run", 3), Some(ClrError::InvalidEntryPoint(_))));
+        assert!(matches!(find("Tools.Runner", "Stop", 0), Some(ClrError::InvalidEntryPoint(_))));
+        assert!(matches!(find("Tools.Runner", "Void Run(System.String)", 0), Some(ClrError::InvalidEntryPoint(_))));
+    }
+
+    #[test]
+    fn rejects_missing_entry_points() {
+        let mut b = AssemblyBuilder::new();
+        let buffer = b.build();
+        let metadata = Metadata::parse(&buffer).unwrap();
+        assert!(matches!(metadata.entry_point(), Err(ClrError::EntryPointNotFound)));
+
+        b.entry_point = 0x0600_0005;
+        let buffer = b.build();
+        let metadata = Metadata::parse(&buffer).unwrap();
+        assert!(matches!(metadata.entry_point(), Err(ClrError::EntryPointNotFound)));
+    }
+}
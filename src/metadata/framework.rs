@@ -0,0 +1,132 @@
+use core::fmt;
+use super::Metadata;
+
+/// The family of .NET runtimes an assembly was built for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameworkFamily {
+    /// The .NET Framework (`.NETFramework`, `mscorlib`), hosted through `mscoree.dll`.
+    NetFramework,
+
+    /// .NET Core and .NET 5+ (`.NETCoreApp`, `System.Runtime`, `System.Private.CoreLib`).
+    NetCore,
+
+    /// A .NET Standard library (`.NETStandard`, `netstandard`), which either runtime can load.
+    NetStandard,
+}
+
+impl FrameworkFamily {
+    /// Returns `true` if the .NET Framework CLR can host assemblies of this family.
+    pub fn runs_on_net_framework(&self) -> bool {
+        !matches!(self, FrameworkFamily::NetCore)
+    }
+}
+
+impl fmt::Display for FrameworkFamily {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            FrameworkFamily::NetFramework => ".NET Framework",
+            FrameworkFamily::NetCore => ".NET Core",
+            FrameworkFamily::NetStandard => ".NET Standard",
+        })
+    }
+}
+
+impl Metadata<'_> {
+    /// Detects the family of runtimes the assembly targets.
+    ///
+    /// The `TargetFrameworkAttribute` moniker is used when present. Otherwise the core library
+    /// the assembly references decides: `System.Private.CoreLib` or `System.Runtime` (without
+    /// `mscorlib`) mean .NET Core, `netstandard` means .NET Standard.
+    ///
+    /// # Returns
+    ///
+    /// * The detected family, `FrameworkFamily::NetFramework` when nothing points elsewhere.
+    pub fn framework_family(&self) -> FrameworkFamily {
+        if let Some(moniker) = self.target_framework() {
+            let identifier = moniker.split(',').next().unwrap_or_default().trim();
+            if identifier.eq_ignore_ascii_case(".NETCoreApp") {
+                return FrameworkFamily::NetCore;
+            }
+
+            if identifier.eq_ignore_ascii_case(".NETStandard") {
+                return FrameworkFamily::NetStandard;
+            }
+
+            if identifier.eq_ignore_ascii_case(".NETFramework") {
+                return FrameworkFamily::NetFramework;
+            }
+        }
+
+        // The core library itself references nothing
+        if self.assembly().is_some_and(|assembly| assembly.name == "System.Private.CoreLib") {
+            return FrameworkFamily::NetCore;
+        }
+
+        let references = self.assembly_references();
+        let references = |name: &str| references.iter().any(|reference| reference.name.eq_ignore_ascii_case(name));
+        if references("System.Private.CoreLib") || (references("System.Runtime") && !references("mscorlib")) {
+            FrameworkFamily::NetCore
+        } else if references("netstandard") {
+            FrameworkFamily::NetStandard
+        } else {
+            FrameworkFamily::NetFramework
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::{builder::AssemblyBuilder, TableId};
+
+    fn assembly(references: &[&str], moniker: Option<&str>) -> Vec<u8> {
+        let mut b = AssemblyBuilder::new();
+        let name = b.string("Sample");
+        b.row(TableId::Assembly, &[0x8004, 1, 0, 0, 0, 0, 0, name, 0]);
+        for reference in references {
+            let name = b.string(reference);
+            b.row(TableId::AssemblyRef, &[4, 0, 0, 0, 0, 0, name, 0, 0]);
+        }
+
+        if let Some(moniker) = moniker {
+            b.target_framework(moniker);
+        }
+
+        b.build()
+    }
+
+    #[test]
+    fn detects_framework_from_moniker() {
+        let cases = [
+            (".NETCoreApp,Version=v6.0", FrameworkFamily::NetCore),
+            (".NETStandard,Version=v2.0", FrameworkFamily::NetStandard),
+            (".NETFramework,Version=v4.7.2", FrameworkFamily::NetFramework),
+        ];
+
+        for (moniker, family) in cases {
+            let buffer = assembly(&["System.Runtime"], Some(moniker));
+            let metadata = Metadata::parse(&buffer).unwrap();
+            assert_eq!(metadata.framework_family(), family);
+        }
+    }
+
+    #[test]
+    fn detects_framework_from_references() {
+        let cases: [(&[&str], FrameworkFamily); 5] = [
+            (&["System.Runtime", "System.Console"], FrameworkFamily::NetCore),
+            (&["System.Private.CoreLib"], FrameworkFamily::NetCore),
+            (&["netstandard"], FrameworkFamily::NetStandard),
+            (&["mscorlib", "System.Runtime"], FrameworkFamily::NetFramework),
+            (&[], FrameworkFamily::NetFramework),
+        ];
+
+        for (references, family) in cases {
+            let buffer = assembly(references, None);
+            let metadata = Metadata::parse(&buffer).unwrap();
+            assert_eq!(metadata.framework_family(), family, "{references:?}");
+        }
+
+        assert!(!FrameworkFamily::NetCore.runs_on_net_framework());
+        assert!(FrameworkFamily::NetStandard.runs_on_net_framework());
+    }
+}
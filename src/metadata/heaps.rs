@@ -0,0 +1,165 @@
+use super::reader::Cursor;
+use uuid::Uuid;
+
+/// The `#Strings` heap, holding null-terminated UTF-8 identifiers.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StringsHeap<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> StringsHeap<'a> {
+    /// Wraps the raw bytes of the heap.
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    /// Returns the raw bytes of the heap.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Retrieves the string starting at `offset`.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(&str)` - The string at the given offset.
+    /// * `None` - If the offset is out of bounds or the string is not valid UTF-8.
+    pub fn get(&self, offset: u32) -> Option<&'a str> {
+        let mut cursor = Cursor::new(self.data);
+        cursor.seek(offset as usize);
+        cursor.c_str()
+    }
+
+    /// Iterates over the strings of the heap with their offsets, skipping the empty string at offset zero.
+    ///
+    /// Entries that are not valid UTF-8 are skipped.
+    pub fn iter(&self) -> impl Iterator<Item = (u32, &'a str)> + 'a {
+        let mut offset = 0;
+        self.data.split(|&b| b == 0).filter_map(move |bytes| {
+            let start = offset;
+            offset += bytes.len() as u32 + 1;
+            match bytes.is_empty() {
+                true => None,
+                false => core::str::from_utf8(bytes).ok().map(|value| (start, value)),
+            }
+        })
+    }
+}
+
+/// The `#US` heap, holding the UTF-16 string literals referenced by `ldstr`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UserStringsHeap<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> UserStringsHeap<'a> {
+    /// Wraps the raw bytes of the heap.
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    /// Returns the raw bytes of the heap.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Retrieves the string literal starting at `offset`.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(String)` - The decoded string literal.
+    /// * `None` - If the offset is out of bounds.
+    pub fn get(&self, offset: u32) -> Option<String> {
+        let mut cursor = Cursor::new(self.data);
+        cursor.seek(offset as usize);
+        let len = cursor.compressed_u32()? as usize;
+
+        // The blob ends with a terminal byte flagging non-ASCII content.
+        let bytes = cursor.bytes(len)?;
+        let units = bytes[..len & !1]
+            .as_chunks::<2>().0
+            .iter()
+            .map(|c| u16::from_le_bytes(*c))
+            .collect::<Vec<u16>>();
+
+        Some(String::from_utf16_lossy(&units))
+    }
+
+    /// Iterates over the string literals of the heap with their offsets.
+    ///
+    /// The empty entry at offset zero and the zero padding at the end of the heap are skipped.
+    pub fn iter(&self) -> impl Iterator<Item = (u32, String)> + 'a {
+        let heap = *self;
+        let mut cursor = Cursor::new(self.data);
+        cursor.seek(1);
+        core::iter::from_fn(move || loop {
+            let offset = cursor.position() as u32;
+            let len = cursor.compressed_u32()? as usize;
+            cursor.bytes(len)?;
+            if len != 0 {
+                return heap.get(offset).map(|value| (offset, value));
+            }
+        })
+    }
+}
+
+/// The `#Blob` heap, holding signatures, custom attribute values and public keys.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BlobHeap<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> BlobHeap<'a> {
+    /// Wraps the raw bytes of the heap.
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    /// Returns the raw bytes of the heap.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Retrieves the blob starting at `offset`, without its length prefix.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(&[u8])` - The contents of the blob.
+    /// * `None` - If the offset or the encoded length is out of bounds.
+    pub fn get(&self, offset: u32) -> Option<&'a [u8]> {
+        let mut cursor = Cursor::new(self.data);
+        cursor.seek(offset as usize);
+        let len = cursor.compressed_u32()? as usize;
+        cursor.bytes(len)
+    }
+}
+
+/// The `#GUID` heap, holding 16-byte GUIDs addressed by a 1-based index.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GuidHeap<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> GuidHeap<'a> {
+    /// Wraps the raw bytes of the heap.
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    /// Returns the raw bytes of the heap.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Retrieves the GUID at the given 1-based `index`.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(Uuid)` - The GUID at the given index.
+    /// * `None` - If the index is zero or out of bounds.
+    pub fn get(&self, index: u32) -> Option<Uuid> {
+        let start = (index.checked_sub(1)? as usize).checked_mul(16)?;
+        let bytes = self.data.get(start..start + 16)?;
+        Some(Uuid::from_bytes_le(bytes.try_into().ok()?))
+    }
+}
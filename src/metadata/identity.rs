@@ -0,0 +1,354 @@
+use core::{cmp::Ordering, fmt, hash::{Hash, Hasher}, str::FromStr};
+use super::sha1::sha1;
+use crate::error::ClrError;
+
+/// Length of a public key token in bytes.
+const PUBLIC_KEY_TOKEN_LEN: usize = 8;
+
+/// Derives the public key token of a strong-name public key.
+///
+/// The token is the last 8 bytes of the SHA-1 hash of the key, in reverse order.
+///
+/// # Arguments
+///
+/// * `public_key` - The full public key, as stored in the `Assembly` table.
+///
+/// # Returns
+///
+/// * The 8-byte public key token.
+pub fn public_key_token(public_key: &[u8]) -> [u8; PUBLIC_KEY_TOKEN_LEN] {
+    let hash = sha1(public_key);
+    let mut token = [0u8; PUBLIC_KEY_TOKEN_LEN];
+    token.copy_from_slice(&hash[hash.len() - PUBLIC_KEY_TOKEN_LEN..]);
+    token.reverse();
+    token
+}
+
+/// A four-part assembly version, ordered component by component.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct AssemblyVersion {
+    /// Major version.
+    pub major: u16,
+
+    /// Minor version.
+    pub minor: u16,
+
+    /// Build number.
+    pub build: u16,
+
+    /// Revision number.
+    pub revision: u16,
+}
+
+impl AssemblyVersion {
+    /// Creates a version out of its four parts.
+    pub fn new(major: u16, minor: u16, build: u16, revision: u16) -> Self {
+        Self { major, minor, build, revision }
+    }
+}
+
+/// Formats the version as `major.minor.build.revision`.
+impl fmt::Display for AssemblyVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}.{}", self.major, self.minor, self.build, self.revision)
+    }
+}
+
+/// Parses two to four dot-separated parts, e.g. `4.0` or `4.0.0.0`; missing parts are zero.
+impl FromStr for AssemblyVersion {
+    type Err = ClrError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ClrError::InvalidAssemblyName(format!("invalid version {s:?}"));
+        let parts = s
+            .trim()
+            .split('.')
+            .map(|part| part.parse::<u16>().map_err(|_| invalid()))
+            .collect::<Result<Vec<u16>, _>>()?;
+
+        match parts[..] {
+            [major, minor] => Ok(Self::new(major, minor, 0, 0)),
+            [major, minor, build] => Ok(Self::new(major, minor, build, 0)),
+            [major, minor, build, revision] => Ok(Self::new(major, minor, build, revision)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// The identity the loader binds against: name, version, culture and public key token.
+///
+/// Names and cultures compare case-insensitively, as they do for the loader. Identities
+/// are ordered by name, culture and public key token, then by version, so sorting groups
+/// the versions of an assembly from oldest to newest.
+#[derive(Debug, Clone)]
+pub struct AssemblyIdentity {
+    /// Simple name of the assembly, e.g. `System.Xml`.
+    pub name: String,
+
+    /// Version of the assembly.
+    pub version: AssemblyVersion,
+
+    /// Culture of satellite assemblies, `None` for neutral assemblies.
+    pub culture: Option<String>,
+
+    /// Public key token of strong-named assemblies.
+    pub public_key_token: Option<[u8; PUBLIC_KEY_TOKEN_LEN]>,
+}
+
+impl AssemblyIdentity {
+    /// Returns `true` if the identity carries a public key token.
+    pub fn is_strong_named(&self) -> bool {
+        self.public_key_token.is_some()
+    }
+
+    /// Checks the identity against a full or partial display name, as `Assembly.Load` would.
+    ///
+    /// Only the components present in `display_name` are compared, so `mscorlib` matches any
+    /// version of `mscorlib`, while `mscorlib, Version=4.0.0.0` only matches that version.
+    ///
+    /// # Arguments
+    ///
+    /// * `display_name` - The name to match, e.g. `System, Culture=neutral`.
+    ///
+    /// # Returns
+    ///
+    /// * `true` - If every component of `display_name` matches.
+    /// * `false` - If a component differs or `display_name` cannot be parsed.
+    pub fn matches(&self, display_name: &str) -> bool {
+        let Ok(name) = DisplayName::parse(display_name) else {
+            return false;
+        };
+
+        name.name.eq_ignore_ascii_case(&self.name)
+            && name.version.is_none_or(|version| version == self.version)
+            && name.culture.is_none_or(|culture| same_culture(&culture, &self.culture))
+            && name.public_key_token.is_none_or(|token| token == self.public_key_token)
+    }
+
+    /// Key used to compare and hash identities.
+    fn key(&self) -> (String, String, Option<[u8; PUBLIC_KEY_TOKEN_LEN]>, AssemblyVersion) {
+        (
+            self.name.to_ascii_lowercase(),
+            self.culture.as_deref().unwrap_or_default().to_ascii_lowercase(),
+            self.public_key_token,
+            self.version,
+        )
+    }
+}
+
+impl PartialEq for AssemblyIdentity {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for AssemblyIdentity {}
+
+impl PartialOrd for AssemblyIdentity {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for AssemblyIdentity {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl Hash for AssemblyIdentity {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+/// Formats the identity as a display name, e.g.
+/// `System, Version=4.0.0.0, Culture=neutral, PublicKeyToken=b77a5c561934e089`.
+impl fmt::Display for AssemblyIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.name.chars() {
+            if matches!(c, ',' | '=' | '"' | '\'' | '\\') {
+                write!(f, "\\")?;
+            }
+
+            write!(f, "{c}")?;
+        }
+
+        write!(f, ", Version={}, Culture={}, PublicKeyToken=", self.version, self.culture.as_deref().unwrap_or("neutral"))?;
+        match &self.public_key_token {
+            Some(token) => token.iter().try_for_each(|byte| write!(f, "{byte:02x}")),
+            None => write!(f, "null"),
+        }
+    }
+}
+
+/// Parses a display name such as `Lib, Version=1.0.0.0, Culture=neutral, PublicKeyToken=null`.
+///
+/// Components missing from a partial name take their defaults: version `0.0.0.0`, neutral
+/// culture and no public key token. A `PublicKey` component is turned into its token, and
+/// other components (e.g. `ProcessorArchitecture`) are ignored.
+impl FromStr for AssemblyIdentity {
+    type Err = ClrError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = DisplayName::parse(s)?;
+        Ok(Self {
+            name: name.name,
+            version: name.version.unwrap_or_default(),
+            culture: name.culture.flatten(),
+            public_key_token: name.public_key_token.flatten(),
+        })
+    }
+}
+
+/// The components of a display name, `None` where the name leaves them out.
+struct DisplayName {
+    name: String,
+    version: Option<AssemblyVersion>,
+    culture: Option<Option<String>>,
+    public_key_token: Option<Option<[u8; PUBLIC_KEY_TOKEN_LEN]>>,
+}
+
+impl DisplayName {
+    /// Splits a display name into its components, honouring quotes and `\` escapes in the simple name.
+    fn parse(s: &str) -> Result<Self, ClrError> {
+        let invalid = |reason: &str| ClrError::InvalidAssemblyName(format!("{reason} in {s:?}"));
+
+        let mut name = String::new();
+        let mut chars = s.trim_start().chars();
+        let mut quote = None;
+        while let Some(c) = chars.next() {
+            match (c, quote) {
+                ('\\', _) => name.push(chars.next().ok_or_else(|| invalid("dangling escape"))?),
+                ('"' | '\'', None) if name.is_empty() => quote = Some(c),
+                (c, Some(q)) if c == q => quote = None,
+                (',', None) => break,
+                (c, _) => name.push(c),
+            }
+        }
+
+        let name = name.trim().to_string();
+        if name.is_empty() || quote.is_some() {
+            return Err(invalid("missing or unterminated name"));
+        }
+
+        let mut display_name = Self { name, version: None, culture: None, public_key_token: None };
+        for component in chars.as_str().split(',').filter(|component| !component.trim().is_empty()) {
+            let (key, value) = component.split_once('=').ok_or_else(|| invalid("component without a value"))?;
+            let value = value.trim().trim_matches('"');
+            match key.trim().to_ascii_lowercase().as_str() {
+                "version" => display_name.version = Some(value.parse()?),
+                "culture" => {
+                    let neutral = value.is_empty() || value.eq_ignore_ascii_case("neutral");
+                    display_name.culture = Some((!neutral).then(|| value.to_string()));
+                }
+                "publickeytoken" if value.eq_ignore_ascii_case("null") => display_name.public_key_token = Some(None),
+                "publickeytoken" => {
+                    let token = parse_hex(value)
+                        .and_then(|bytes| <[u8; PUBLIC_KEY_TOKEN_LEN]>::try_from(bytes).ok())
+                        .ok_or_else(|| invalid("invalid public key token"))?;
+
+                    display_name.public_key_token = Some(Some(token));
+                }
+                "publickey" if value.eq_ignore_ascii_case("null") => display_name.public_key_token = Some(None),
+                "publickey" => {
+                    let key = parse_hex(value).ok_or_else(|| invalid("invalid public key"))?;
+                    display_name.public_key_token = Some(Some(public_key_token(&key)));
+                }
+                _ => {}
+            }
+        }
+
+        Ok(display_name)
+    }
+}
+
+/// Compares a requested culture with an identity's, treating `None` as neutral.
+fn same_culture(requested: &Option<String>, culture: &Option<String>) -> bool {
+    match (requested, culture) {
+        (Some(a), Some(b)) => a.eq_ignore_ascii_case(b),
+        (a, b) => a.is_none() && b.is_none(),
+    }
+}
+
+/// Decodes a string of hexadecimal digit pairs.
+fn parse_hex(value: &str) -> Option<Vec<u8>> {
+    if !value.len().is_multiple_of(2) || !value.is_ascii() {
+        return None;
+    }
+
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&value[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The ECMA standard public key, which `mscorlib` and `System` are signed with.
+    const ECMA_KEY: [u8; 16] = [0, 0, 0, 0, 0, 0, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0];
+
+    #[test]
+    fn computes_public_key_tokens() {
+        assert_eq!(public_key_token(&ECMA_KEY), [0xB7, 0x7A, 0x5C, 0x56, 0x19, 0x34, 0xE0, 0x89]);
+    }
+
+    #[test]
+    fn parses_and_formats_display_names() {
+        let full = "System, Version=4.0.0.0, Culture=neutral, PublicKeyToken=b77a5c561934e089";
+        let identity = full.parse::<AssemblyIdentity>().unwrap();
+        assert_eq!(identity.name, "System");
+        assert_eq!(identity.version, AssemblyVersion::new(4, 0, 0, 0));
+        assert_eq!(identity.culture, None);
+        assert!(identity.is_strong_named());
+        assert_eq!(identity.to_string(), full);
+
+        let satellite = "  \"My, Lib\" ,version=1.2 , culture=de-DE, PublicKeyToken=null, ProcessorArchitecture=MSIL"
+            .parse::<AssemblyIdentity>()
+            .unwrap();
+        assert_eq!(satellite.name, "My, Lib");
+        assert_eq!(satellite.version, AssemblyVersion::new(1, 2, 0, 0));
+        assert_eq!(satellite.to_string(), "My\\, Lib, Version=1.2.0.0, Culture=de-DE, PublicKeyToken=null");
+        assert_eq!(satellite.to_string().parse::<AssemblyIdentity>().unwrap(), satellite);
+
+        let from_key = "System, Version=4.0.0.0, PublicKey=00000000000000000400000000000000"
+            .parse::<AssemblyIdentity>()
+            .unwrap();
+        assert_eq!(from_key, identity);
+
+        for invalid in ["", ", Version=1.0", "Lib, Version=1", "Lib, Version", "Lib, PublicKeyToken=b77a", "\"Lib"] {
+            assert!(matches!(invalid.parse::<AssemblyIdentity>(), Err(ClrError::InvalidAssemblyName(_))), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn matches_partial_names() {
+        let identity = "mscorlib, Version=4.0.0.0, Culture=neutral, PublicKeyToken=b77a5c561934e089"
+            .parse::<AssemblyIdentity>()
+            .unwrap();
+
+        assert!(identity.matches("mscorlib"));
+        assert!(identity.matches("MSCORLIB, Culture=neutral"));
+        assert!(identity.matches("mscorlib, Version=4.0.0.0, PublicKeyToken=b77a5c561934e089"));
+        assert!(!identity.matches("mscorlib, Version=2.0.0.0"));
+        assert!(!identity.matches("mscorlib, PublicKeyToken=null"));
+        assert!(!identity.matches("mscorlib, Culture=en-US"));
+        assert!(!identity.matches("mscor"));
+        assert!(!identity.matches("System.mscorlib"));
+    }
+
+    #[test]
+    fn compares_case_insensitively_and_orders_by_version() {
+        let identity = |s: &str| s.parse::<AssemblyIdentity>().unwrap();
+        assert_eq!(identity("Lib, Version=1.0.0.0"), identity("lib, Version=1.0.0.0, Culture=NEUTRAL"));
+        assert_ne!(identity("Lib, Version=1.0.0.0"), identity("Lib, Version=1.0.0.1"));
+
+        let mut identities = [identity("Lib, Version=1.10"), identity("Lib, Version=1.9"), identity("Lib, Version=2.0")];
+        identities.sort();
+        let versions = identities.iter().map(|identity| identity.version.to_string()).collect::<Vec<_>>();
+        assert_eq!(versions, ["1.9.0.0", "1.10.0.0", "2.0.0.0"]);
+        assert!(AssemblyVersion::new(2, 0, 0, 0) > AssemblyVersion::new(1, 65535, 0, 0));
+    }
+}
@@ -0,0 +1,307 @@
+//! Raw DEFLATE (RFC 1951) decoder, used to unpack compressed manifest resources.
+
+use crate::{error::ClrError, Result};
+
+/// Base lengths of the length codes 257..=285.
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258,
+];
+
+/// Extra bits of the length codes 257..=285.
+const LENGTH_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+
+/// Base distances of the distance codes 0..=29.
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097,
+    6145, 8193, 12289, 16385, 24577,
+];
+
+/// Extra bits of the distance codes 0..=29.
+const DISTANCE_EXTRA: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+
+/// Order in which the code length code lengths of a dynamic block are stored.
+const CODE_LENGTH_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+/// Decompresses a raw DEFLATE stream, as written by .NET's `DeflateStream`.
+///
+/// # Arguments
+///
+/// * `data` - The compressed bytes, without a zlib or gzip header.
+///
+/// # Returns
+///
+/// * `Ok(Vec<u8>)` - The decompressed bytes.
+/// * `Err(ClrError)` - If the stream is truncated or malformed.
+pub(crate) fn inflate(data: &[u8]) -> Result<Vec<u8>> {
+    let mut bits = Bits { data, position: 0 };
+    let mut output = Vec::with_capacity(data.len() * 4);
+    loop {
+        let last = bits.read(1)? == 1;
+        match bits.read(2)? {
+            0 => stored(&mut bits, &mut output)?,
+            1 => {
+                let (literals, distances) = fixed();
+                compressed(&mut bits, &mut output, &literals, &distances)?;
+            }
+            2 => {
+                let (literals, distances) = dynamic(&mut bits)?;
+                compressed(&mut bits, &mut output, &literals, &distances)?;
+            }
+            _ => return Err(ClrError::DecompressionError("invalid block type")),
+        }
+
+        if last {
+            return Ok(output);
+        }
+    }
+}
+
+/// A little-endian bit reader over the compressed stream.
+struct Bits<'a> {
+    /// The compressed bytes.
+    data: &'a [u8],
+
+    /// Position of the next bit.
+    position: usize,
+}
+
+impl Bits<'_> {
+    /// Reads `count` bits, least significant first.
+    fn read(&mut self, count: u32) -> Result<u32> {
+        let mut value = 0;
+        for i in 0..count {
+            let byte = self.data.get(self.position / 8).ok_or(ClrError::DecompressionError("unexpected end of stream"))?;
+            value |= u32::from(byte >> (self.position % 8) & 1) << i;
+            self.position += 1;
+        }
+
+        Ok(value)
+    }
+
+    /// Skips to the next byte boundary.
+    fn align(&mut self) {
+        self.position = self.position.div_ceil(8) * 8;
+    }
+
+    /// Reads `count` whole bytes from a byte boundary.
+    fn bytes(&mut self, count: usize) -> Result<&[u8]> {
+        let start = self.position / 8;
+        let bytes = self
+            .data
+            .get(start..start + count)
+            .ok_or(ClrError::DecompressionError("unexpected end of stream"))?;
+
+        self.position += count * 8;
+        Ok(bytes)
+    }
+}
+
+/// A canonical Huffman code, stored as symbol counts per length and symbols sorted by code.
+struct Huffman {
+    /// Number of symbols for each code length.
+    counts: [u16; 16],
+
+    /// Symbols ordered by code.
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    /// Builds the code from the length of each symbol's code, zero meaning unused.
+    fn new(lengths: &[u8]) -> Result<Self> {
+        let mut counts = [0u16; 16];
+        for &length in lengths {
+            counts[length as usize] += 1;
+        }
+
+        counts[0] = 0;
+        let mut left = 1i32;
+        for &count in &counts[1..] {
+            left = (left << 1) - i32::from(count);
+            if left < 0 {
+                return Err(ClrError::DecompressionError("over-subscribed Huffman code"));
+            }
+        }
+
+        let mut offsets = [0u16; 16];
+        for length in 1..15 {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, &length) in lengths.iter().enumerate() {
+            if length != 0 {
+                symbols[offsets[length as usize] as usize] = symbol as u16;
+                offsets[length as usize] += 1;
+            }
+        }
+
+        Ok(Self { counts, symbols })
+    }
+
+    /// Decodes one symbol, reading the code one bit at a time.
+    fn decode(&self, bits: &mut Bits) -> Result<u16> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for &count in &self.counts[1..] {
+            code |= bits.read(1)? as i32;
+            let count = i32::from(count);
+            if code - count < first {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+
+        Err(ClrError::DecompressionError("invalid Huffman code"))
+    }
+}
+
+/// Copies a stored (uncompressed) block.
+fn stored(bits: &mut Bits, output: &mut Vec<u8>) -> Result<()> {
+    bits.align();
+    let header = bits.bytes(4)?;
+    let length = u16::from_le_bytes([header[0], header[1]]);
+    let complement = u16::from_le_bytes([header[2], header[3]]);
+    if length != !complement {
+        return Err(ClrError::DecompressionError("stored block length mismatch"));
+    }
+
+    output.extend_from_slice(bits.bytes(length as usize)?);
+    Ok(())
+}
+
+/// Returns the fixed literal/length and distance codes.
+fn fixed() -> (Huffman, Huffman) {
+    let mut lengths = [0u8; 288];
+    lengths[..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..].fill(8);
+
+    // Both codes are complete, so building them cannot fail
+    let literals = Huffman::new(&lengths).unwrap_or_else(|_| unreachable!());
+    let distances = Huffman::new(&[5; 30]).unwrap_or_else(|_| unreachable!());
+    (literals, distances)
+}
+
+/// Reads the literal/length and distance codes of a dynamic block.
+fn dynamic(bits: &mut Bits) -> Result<(Huffman, Huffman)> {
+    let literal_count = bits.read(5)? as usize + 257;
+    let distance_count = bits.read(5)? as usize + 1;
+    let code_count = bits.read(4)? as usize + 4;
+    if literal_count > 286 || distance_count > 30 {
+        return Err(ClrError::DecompressionError("too many length codes"));
+    }
+
+    let mut code_lengths = [0u8; 19];
+    for &index in &CODE_LENGTH_ORDER[..code_count] {
+        code_lengths[index] = bits.read(3)? as u8;
+    }
+
+    let code = Huffman::new(&code_lengths)?;
+    let mut lengths = Vec::with_capacity(literal_count + distance_count);
+    while lengths.len() < literal_count + distance_count {
+        let (value, repeat) = match code.decode(bits)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => {
+                let previous = *lengths.last().ok_or(ClrError::DecompressionError("repeat with no previous length"))?;
+                (previous, 3 + bits.read(2)?)
+            }
+            17 => (0, 3 + bits.read(3)?),
+            _ => (0, 11 + bits.read(7)?),
+        };
+
+        if lengths.len() + repeat as usize > literal_count + distance_count {
+            return Err(ClrError::DecompressionError("too many code lengths"));
+        }
+
+        lengths.extend(core::iter::repeat_n(value, repeat as usize));
+    }
+
+    if lengths[256] == 0 {
+        return Err(ClrError::DecompressionError("missing end-of-block code"));
+    }
+
+    let literals = Huffman::new(&lengths[..literal_count])?;
+    let distances = Huffman::new(&lengths[literal_count..])?;
+    Ok((literals, distances))
+}
+
+/// Decodes a Huffman-compressed block until its end-of-block code.
+fn compressed(bits: &mut Bits, output: &mut Vec<u8>, literals: &Huffman, distances: &Huffman) -> Result<()> {
+    loop {
+        let symbol = literals.decode(bits)? as usize;
+        match symbol {
+            0..=255 => output.push(symbol as u8),
+            256 => return Ok(()),
+            _ => {
+                let index = symbol - 257;
+                if index >= LENGTH_BASE.len() {
+                    return Err(ClrError::DecompressionError("invalid length code"));
+                }
+
+                let length = LENGTH_BASE[index] as usize + bits.read(u32::from(LENGTH_EXTRA[index]))? as usize;
+                let index = distances.decode(bits)? as usize;
+                if index >= DISTANCE_BASE.len() {
+                    return Err(ClrError::DecompressionError("invalid distance code"));
+                }
+
+                let distance = DISTANCE_BASE[index] as usize + bits.read(u32::from(DISTANCE_EXTRA[index]))? as usize;
+                if distance > output.len() {
+                    return Err(ClrError::DecompressionError("distance too far back"));
+                }
+
+                // The match may overlap the bytes it produces, so copy one byte at a time
+                let start = output.len() - distance;
+                for i in 0..length {
+                    output.push(output[start + i]);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inflates_stored_blocks() {
+        let data = [0x01, 0x05, 0x00, 0xFA, 0xFF, b'h', b'e', b'l', b'l', b'o'];
+        assert_eq!(inflate(&data).unwrap(), b"hello");
+    }
+
+    #[test]
+    fn inflates_fixed_blocks() {
+        // zlib.compress(b"hello hello hello", wbits=-15)
+        let data = [0xCB, 0x48, 0xCD, 0xC9, 0xC9, 0x57, 0xC8, 0x40, 0x90, 0x00];
+        assert_eq!(inflate(&data).unwrap(), b"hello hello hello");
+    }
+
+    #[test]
+    fn inflates_dynamic_blocks() {
+        // 40 pseudo-random letters from `abcd`, compressed with `wbits=-15`
+        let data = [
+            0x1D, 0x88, 0xC1, 0x0D, 0x00, 0x30, 0x10, 0x82, 0x66, 0x55, 0xD8, 0x7F, 0x86, 0xDA, 0x0B, 0x0F, 0x08, 0x50,
+            0x15, 0x33, 0x27, 0x75, 0x00, 0xB7, 0xCA, 0xC6, 0x6F, 0xDA, 0x3C,
+        ];
+
+        let mut state = 1u32;
+        let expected = (0..40)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                b"abcd"[(state >> 16 & 3) as usize]
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(inflate(&data).unwrap(), expected);
+    }
+
+    #[test]
+    fn rejects_malformed_streams() {
+        assert!(inflate(&[]).is_err());
+        assert!(inflate(&[0x07]).is_err());
+        assert!(inflate(&[0x01, 0x05, 0x00, 0x00, 0x00]).is_err());
+    }
+}
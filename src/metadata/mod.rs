@@ -0,0 +1,459 @@
+//! # Offline ECMA-335 Metadata Reader
+//!
+//! This module parses the CLI header, the metadata root, the heaps and the tables of a
+//! .NET assembly straight from its bytes, without starting a runtime. Everything here
+//! is pure Rust and behaves the same on every platform.
+
+mod assembly;
+mod attributes;
+mod body;
+mod bundle;
+mod cil;
+mod dependencies;
+mod disasm;
+mod entrypoint;
+mod framework;
+mod heaps;
+mod identity;
+mod inflate;
+mod platform;
+mod reader;
+mod resources;
+mod sha1;
+mod signature;
+mod strings;
+mod tables;
+mod types;
+
+pub use assembly::*;
+pub use attributes::*;
+pub use body::*;
+pub use bundle::*;
+pub use cil::*;
+pub use dependencies::*;
+pub use entrypoint::*;
+pub use framework::*;
+pub use heaps::*;
+pub use identity::*;
+pub use resources::*;
+pub use signature::*;
+pub use strings::*;
+pub use tables::*;
+pub use types::*;
+
+use reader::Cursor;
+use crate::{
+    error::ClrError, Result,
+    file::{DataDirectory, PeHeader},
+};
+
+/// Signature of the metadata root (`BSJB`).
+const METADATA_SIGNATURE: u32 = 0x424A_5342;
+
+/// The image contains only IL code.
+pub const COMIMAGE_FLAGS_ILONLY: u32 = 0x0000_0001;
+
+/// The image can only be loaded into a 32-bit process.
+pub const COMIMAGE_FLAGS_32BITREQUIRED: u32 = 0x0000_0002;
+
+/// The image is a library of IL code.
+pub const COMIMAGE_FLAGS_IL_LIBRARY: u32 = 0x0000_0004;
+
+/// The image is signed with a strong name.
+pub const COMIMAGE_FLAGS_STRONGNAMESIGNED: u32 = 0x0000_0008;
+
+/// The entry point is native code, referenced by RVA instead of a token.
+pub const COMIMAGE_FLAGS_NATIVE_ENTRYPOINT: u32 = 0x0000_0010;
+
+/// The image prefers to run in a 32-bit process (AnyCPU with "Prefer 32-bit").
+pub const COMIMAGE_FLAGS_32BITPREFERRED: u32 = 0x0002_0000;
+
+/// The CLI header (`IMAGE_COR20_HEADER`) pointed to by the COM descriptor directory.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CliHeader {
+    /// Size of the header in bytes.
+    pub cb: u32,
+
+    /// Minimum major version of the runtime required to run the image.
+    pub major_runtime_version: u16,
+
+    /// Minor version of the runtime required to run the image.
+    pub minor_runtime_version: u16,
+
+    /// Location of the metadata root.
+    pub metadata: DataDirectory,
+
+    /// Runtime flags (`COMIMAGE_FLAGS_*`).
+    pub flags: u32,
+
+    /// Token of the entry point method, or its RVA for native entry points.
+    pub entry_point_token: u32,
+
+    /// Location of the managed resources.
+    pub resources: DataDirectory,
+
+    /// Location of the strong name signature.
+    pub strong_name_signature: DataDirectory,
+
+    /// Location of the v-table fixups.
+    pub vtable_fixups: DataDirectory,
+}
+
+impl CliHeader {
+    /// Parses the CLI header from a byte slice.
+    fn parse(data: &[u8]) -> Option<Self> {
+        let mut cursor = Cursor::new(data);
+        let directory = |cursor: &mut Cursor| Some(DataDirectory {
+            virtual_address: cursor.u32()?,
+            size: cursor.u32()?,
+        });
+
+        let cb = cursor.u32()?;
+        let major_runtime_version = cursor.u16()?;
+        let minor_runtime_version = cursor.u16()?;
+        let metadata = directory(&mut cursor)?;
+        let flags = cursor.u32()?;
+        let entry_point_token = cursor.u32()?;
+        let resources = directory(&mut cursor)?;
+        let strong_name_signature = directory(&mut cursor)?;
+        directory(&mut cursor)?;
+        let vtable_fixups = directory(&mut cursor)?;
+
+        Some(Self {
+            cb,
+            major_runtime_version,
+            minor_runtime_version,
+            metadata,
+            flags,
+            entry_point_token,
+            resources,
+            strong_name_signature,
+            vtable_fixups,
+        })
+    }
+
+    /// Returns `true` if the image contains only IL code (`COMIMAGE_FLAGS_ILONLY`).
+    pub fn is_il_only(&self) -> bool {
+        self.flags & COMIMAGE_FLAGS_ILONLY != 0
+    }
+
+    /// Returns `true` if the image is marked as x86-only.
+    ///
+    /// `32BITREQUIRED` together with `32BITPREFERRED` describes an AnyCPU image that merely
+    /// prefers 32-bit, so this only holds when the preference flag is absent.
+    pub fn requires_32bit(&self) -> bool {
+        self.flags & COMIMAGE_FLAGS_32BITREQUIRED != 0 && !self.prefers_32bit()
+    }
+
+    /// Returns `true` if the image prefers a 32-bit process (`COMIMAGE_FLAGS_32BITPREFERRED`).
+    pub fn prefers_32bit(&self) -> bool {
+        self.flags & COMIMAGE_FLAGS_32BITPREFERRED != 0
+    }
+
+    /// Returns `true` if the entry point is native code (`COMIMAGE_FLAGS_NATIVE_ENTRYPOINT`).
+    pub fn has_native_entry_point(&self) -> bool {
+        self.flags & COMIMAGE_FLAGS_NATIVE_ENTRYPOINT != 0
+    }
+
+    /// Returns `true` if the image is strong-name signed (`COMIMAGE_FLAGS_STRONGNAMESIGNED`).
+    pub fn is_strong_name_signed(&self) -> bool {
+        self.flags & COMIMAGE_FLAGS_STRONGNAMESIGNED != 0
+    }
+
+    /// Returns `true` if the image mixes native and managed code, as C++/CLI assemblies do.
+    pub fn is_mixed_mode(&self) -> bool {
+        !self.is_il_only() || self.has_native_entry_point()
+    }
+
+    /// Returns the names of the flags that are set, e.g. `ILONLY | 32BITPREFERRED`.
+    pub fn flag_names(&self) -> String {
+        let names = [
+            (COMIMAGE_FLAGS_ILONLY, "ILONLY"),
+            (COMIMAGE_FLAGS_32BITREQUIRED, "32BITREQUIRED"),
+            (COMIMAGE_FLAGS_IL_LIBRARY, "IL_LIBRARY"),
+            (COMIMAGE_FLAGS_STRONGNAMESIGNED, "STRONGNAMESIGNED"),
+            (COMIMAGE_FLAGS_NATIVE_ENTRYPOINT, "NATIVE_ENTRYPOINT"),
+            (COMIMAGE_FLAGS_32BITPREFERRED, "32BITPREFERRED"),
+        ];
+
+        names
+            .iter()
+            .filter(|(flag, _)| self.flags & flag != 0)
+            .map(|(_, name)| *name)
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+}
+
+/// A stream header of the metadata root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamHeader {
+    /// Name of the stream (e.g. `#~`, `#Strings`).
+    pub name: String,
+
+    /// Offset of the stream relative to the metadata root.
+    pub offset: u32,
+
+    /// Size of the stream in bytes.
+    pub size: u32,
+}
+
+/// Parsed metadata of a .NET assembly.
+///
+/// # Examples
+///
+/// ```ignore
+/// use rustclr::metadata::{Metadata, TableId};
+/// use std::fs;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let buffer = fs::read("examples/sample.exe")?;
+///     let metadata = Metadata::parse(&buffer)?;
+///
+///     println!("Runtime version: {}", metadata.version());
+///     println!("Types: {}", metadata.tables().rows(TableId::TypeDef));
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Metadata<'a> {
+    /// The assembly buffer.
+    buffer: &'a [u8],
+
+    /// PE headers of the image.
+    pe: PeHeader,
+
+    /// The CLI header.
+    cli: CliHeader,
+
+    /// Version string stored in the metadata root (e.g. `v4.0.30319`).
+    version: &'a str,
+
+    /// Stream headers of the metadata root.
+    streams: Vec<StreamHeader>,
+
+    /// The `#Strings` heap.
+    strings: StringsHeap<'a>,
+
+    /// The `#US` heap.
+    user_strings: UserStringsHeap<'a>,
+
+    /// The `#Blob` heap.
+    blobs: BlobHeap<'a>,
+
+    /// The `#GUID` heap.
+    guids: GuidHeap<'a>,
+
+    /// The metadata tables.
+    tables: Tables<'a>,
+}
+
+impl<'a> Metadata<'a> {
+    /// Parses the metadata of the assembly contained in `buffer`.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - A reference to a byte slice representing the .NET assembly.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Metadata)` - If the CLI header and the metadata were parsed successfully.
+    /// * `Err(ClrError)` - If the buffer is not a .NET image or its metadata is malformed.
+    pub fn parse(buffer: &'a [u8]) -> Result<Self> {
+        let pe = PeHeader::parse(buffer).ok_or(ClrError::InvalidExecutable)?;
+        if !pe.cli_directory.is_present() {
+            return Err(ClrError::NotDotNet);
+        }
+
+        let cli = rva_slice(buffer, &pe, pe.cli_directory)
+            .and_then(CliHeader::parse)
+            .ok_or(ClrError::MetadataError("CLI header is out of bounds"))?;
+
+        let root = rva_slice(buffer, &pe, cli.metadata)
+            .ok_or(ClrError::MetadataError("metadata root is out of bounds"))?;
+
+        let malformed = || ClrError::MetadataError("metadata root is malformed");
+        let mut cursor = Cursor::new(root);
+        if cursor.u32().ok_or_else(malformed)? != METADATA_SIGNATURE {
+            return Err(ClrError::MetadataError("invalid metadata signature"));
+        }
+
+        // Major/minor version and the reserved field.
+        cursor.bytes(8).ok_or_else(malformed)?;
+        let length = cursor.u32().ok_or_else(malformed)? as usize;
+        let version = cursor.bytes(length).ok_or_else(malformed)?;
+        let version = version.split(|&b| b == 0).next().unwrap_or_default();
+        let version = core::str::from_utf8(version).map_err(|_| malformed())?;
+
+        cursor.u16().ok_or_else(malformed)?;
+        let count = cursor.u16().ok_or_else(malformed)?;
+        let mut streams = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let offset = cursor.u32().ok_or_else(malformed)?;
+            let size = cursor.u32().ok_or_else(malformed)?;
+            let name = cursor.c_str().ok_or_else(malformed)?.to_string();
+            cursor.align(4);
+            streams.push(StreamHeader { name, offset, size });
+        }
+
+        let stream = |name: &str| -> Result<Option<&'a [u8]>> {
+            match streams.iter().find(|s| s.name == name) {
+                Some(s) => (s.offset as usize)
+                    .checked_add(s.size as usize)
+                    .and_then(|end| root.get(s.offset as usize..end))
+                    .map(Some)
+                    .ok_or(ClrError::MetadataError("metadata stream is out of bounds")),
+                None => Ok(None),
+            }
+        };
+
+        let tables = match stream("#~")? {
+            Some(data) => data,
+            None => stream("#-")?.ok_or(ClrError::MetadataError("missing tables stream"))?,
+        };
+
+        Ok(Self {
+            buffer,
+            strings: StringsHeap::new(stream("#Strings")?.unwrap_or_default()),
+            user_strings: UserStringsHeap::new(stream("#US")?.unwrap_or_default()),
+            blobs: BlobHeap::new(stream("#Blob")?.unwrap_or_default()),
+            guids: GuidHeap::new(stream("#GUID")?.unwrap_or_default()),
+            tables: Tables::parse(tables)?,
+            version,
+            streams,
+            pe,
+            cli,
+        })
+    }
+
+    /// Returns the assembly buffer the metadata was read from.
+    pub fn buffer(&self) -> &'a [u8] {
+        self.buffer
+    }
+
+    /// Returns the PE headers of the image.
+    pub fn pe(&self) -> &PeHeader {
+        &self.pe
+    }
+
+    /// Returns the CLI header.
+    pub fn cli_header(&self) -> &CliHeader {
+        &self.cli
+    }
+
+    /// Returns the runtime version string stored in the metadata root.
+    pub fn version(&self) -> &'a str {
+        self.version
+    }
+
+    /// Returns the stream headers of the metadata root.
+    pub fn streams(&self) -> &[StreamHeader] {
+        &self.streams
+    }
+
+    /// Returns the `#Strings` heap.
+    pub fn strings(&self) -> &StringsHeap<'a> {
+        &self.strings
+    }
+
+    /// Returns the `#US` heap.
+    pub fn user_strings(&self) -> &UserStringsHeap<'a> {
+        &self.user_strings
+    }
+
+    /// Returns the `#Blob` heap.
+    pub fn blobs(&self) -> &BlobHeap<'a> {
+        &self.blobs
+    }
+
+    /// Returns the `#GUID` heap.
+    pub fn guids(&self) -> &GuidHeap<'a> {
+        &self.guids
+    }
+
+    /// Returns the metadata tables.
+    pub fn tables(&self) -> &Tables<'a> {
+        &self.tables
+    }
+
+    /// Returns the bytes starting at the given RVA, up to the end of its section.
+    pub fn rva(&self, rva: u32) -> Option<&'a [u8]> {
+        let offset = self.pe.rva_to_offset(rva)?;
+        self.buffer.get(offset..)
+    }
+}
+
+/// Resolves a data directory into the bytes it covers.
+fn rva_slice<'a>(buffer: &'a [u8], pe: &PeHeader, directory: DataDirectory) -> Option<&'a [u8]> {
+    let offset = pe.rva_to_offset(directory.virtual_address)?;
+    buffer.get(offset..offset.checked_add(directory.size as usize)?)
+}
+
+#[cfg(test)]
+pub(crate) mod builder;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use builder::AssemblyBuilder;
+
+    #[test]
+    fn parses_root_and_heaps() {
+        let mut builder = AssemblyBuilder::new();
+        let name = builder.string("Sample.exe");
+        let mvid = builder.guid([7; 16]);
+        let sig = builder.blob(&[0x00, 0x00, 0x01]);
+        let literal = builder.user_string("Hello, World!");
+        builder.row(TableId::Module, &[0, name, mvid, 0, 0]);
+
+        let buffer = builder.build();
+        let metadata = Metadata::parse(&buffer).unwrap();
+        assert_eq!(metadata.version(), "v4.0.30319");
+        assert_eq!(metadata.strings().get(name), Some("Sample.exe"));
+        assert_eq!(metadata.blobs().get(sig), Some(&[0x00, 0x00, 0x01][..]));
+        assert_eq!(metadata.user_strings().get(literal).as_deref(), Some("Hello, World!"));
+        assert_eq!(metadata.guids().get(mvid).map(|g| g.to_bytes_le()), Some([7; 16]));
+
+        let module = metadata.tables().table(TableId::Module);
+        assert_eq!(module.len(), 1);
+        assert_eq!(module.row(1), Some(vec![0, name, mvid, 0, 0]));
+        assert_eq!(module.get(2, 0), None);
+    }
+
+    #[test]
+    fn parses_tables_with_wide_indexes() {
+        let mut builder = AssemblyBuilder::new();
+        let name = builder.string("Method");
+
+        // Enough params to require 4-byte `Param` indexes and `HasCustomAttribute` coded indexes.
+        for i in 0..70_000u32 {
+            builder.row(TableId::Param, &[0, i % 3, 0]);
+        }
+
+        builder.row(TableId::MethodDef, &[0, 0, 0x16, name, 0, 69_999]);
+        let parent = CodedIndex::HasCustomAttribute.encode(TableId::Param, 65_537).unwrap();
+        builder.row(TableId::CustomAttribute, &[parent, 3 << 3 | 3, 0]);
+
+        let buffer = builder.build();
+        let metadata = Metadata::parse(&buffer).unwrap();
+        let tables = metadata.tables();
+        assert_eq!(tables.rows(TableId::Param), 70_000);
+        assert_eq!(tables.table(TableId::MethodDef).row_size(), 4 + 2 + 2 + 2 + 2 + 4);
+        assert_eq!(tables.table(TableId::MethodDef).get(1, 5), Some(69_999));
+        assert_eq!(tables.table(TableId::Param).get(70_000, 1), Some(69_999 % 3));
+
+        let raw = tables.table(TableId::CustomAttribute).get(1, 0).unwrap();
+        assert_eq!(CodedIndex::HasCustomAttribute.decode(raw), Some((TableId::Param, 65_537)));
+    }
+
+    #[test]
+    fn rejects_non_dotnet_images() {
+        let mut buffer = AssemblyBuilder::new().build();
+        assert!(Metadata::parse(&buffer[..0x40]).is_err());
+
+        // Corrupt the `BSJB` signature.
+        let root = buffer.windows(4).position(|w| w == b"BSJB").unwrap();
+        buffer[root] = 0;
+        assert!(matches!(Metadata::parse(&buffer), Err(ClrError::MetadataError(_))));
+    }
+}
@@ -0,0 +1,103 @@
+use super::Metadata;
+use crate::{error::ClrError, file::Bitness, Result};
+
+impl Metadata<'_> {
+    /// Returns the process bitness the image can be loaded into.
+    ///
+    /// PE32+ images are 64-bit only. PE32 images are 32-bit only when they are marked
+    /// `32BITREQUIRED` without `32BITPREFERRED`, or when they carry native x86 code.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(Bitness)` - The only bitness the image supports.
+    /// * `None` - If the image is AnyCPU and loads into either.
+    pub fn required_bitness(&self) -> Option<Bitness> {
+        let cli = self.cli_header();
+        match self.pe().bitness {
+            Bitness::Bits64 => Some(Bitness::Bits64),
+            Bitness::Bits32 if cli.requires_32bit() || !cli.is_il_only() => Some(Bitness::Bits32),
+            Bitness::Bits32 => None,
+        }
+    }
+
+    /// Checks that the image can be loaded from memory into a process of the given bitness.
+    ///
+    /// # Arguments
+    ///
+    /// * `host` - The bitness of the hosting process, usually `Bitness::host()`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the runtime can load the image.
+    /// * `Err(ClrError)` - `ClrError::MixedModeAssembly`, `ClrError::Requires32BitProcess` or
+    ///   `ClrError::Requires64BitProcess` otherwise.
+    pub fn check_host(&self, host: Bitness) -> Result<()> {
+        // `Assembly.Load(byte[])` cannot map native code, whatever the bitness
+        if self.cli_header().is_mixed_mode() {
+            return Err(ClrError::MixedModeAssembly);
+        }
+
+        match self.required_bitness() {
+            Some(Bitness::Bits32) if host == Bitness::Bits64 => Err(ClrError::Requires32BitProcess),
+            Some(Bitness::Bits64) if host == Bitness::Bits32 => Err(ClrError::Requires64BitProcess),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        file::{IMAGE_FILE_MACHINE_AMD64, IMAGE_NT_OPTIONAL_HDR64_MAGIC},
+        metadata::{builder::AssemblyBuilder, COMIMAGE_FLAGS_32BITPREFERRED, COMIMAGE_FLAGS_32BITREQUIRED},
+    };
+
+    fn image(flags: u32, pe32_plus: bool) -> Vec<u8> {
+        let mut b = AssemblyBuilder::new();
+        b.flags = flags;
+        if pe32_plus {
+            b.magic = IMAGE_NT_OPTIONAL_HDR64_MAGIC;
+            b.machine = IMAGE_FILE_MACHINE_AMD64;
+        }
+
+        b.build()
+    }
+
+    #[test]
+    fn accepts_anycpu_images_in_any_host() {
+        for flags in [0x1, 0x1 | COMIMAGE_FLAGS_32BITREQUIRED | COMIMAGE_FLAGS_32BITPREFERRED] {
+            let buffer = image(flags, false);
+            let metadata = Metadata::parse(&buffer).unwrap();
+            assert_eq!(metadata.required_bitness(), None);
+            assert!(metadata.check_host(Bitness::Bits32).is_ok());
+            assert!(metadata.check_host(Bitness::Bits64).is_ok());
+        }
+    }
+
+    #[test]
+    fn rejects_images_of_the_wrong_bitness() {
+        let buffer = image(0x1 | COMIMAGE_FLAGS_32BITREQUIRED, false);
+        let metadata = Metadata::parse(&buffer).unwrap();
+        assert!(metadata.cli_header().requires_32bit());
+        assert_eq!(metadata.cli_header().flag_names(), "ILONLY | 32BITREQUIRED");
+        assert!(metadata.check_host(Bitness::Bits32).is_ok());
+        assert!(matches!(metadata.check_host(Bitness::Bits64), Err(ClrError::Requires32BitProcess)));
+
+        let buffer = image(0x1, true);
+        let metadata = Metadata::parse(&buffer).unwrap();
+        assert_eq!(metadata.required_bitness(), Some(Bitness::Bits64));
+        assert!(metadata.check_host(Bitness::Bits64).is_ok());
+        assert!(matches!(metadata.check_host(Bitness::Bits32), Err(ClrError::Requires64BitProcess)));
+    }
+
+    #[test]
+    fn rejects_mixed_mode_images() {
+        for flags in [0x0, 0x1 | 0x10] {
+            let buffer = image(flags, false);
+            let metadata = Metadata::parse(&buffer).unwrap();
+            assert!(metadata.cli_header().is_mixed_mode());
+            assert!(matches!(metadata.check_host(Bitness::Bits32), Err(ClrError::MixedModeAssembly)));
+        }
+    }
+}
@@ -0,0 +1,158 @@
+/// Little-endian cursor over a byte slice used by the metadata parsers.
+///
+/// Every read is bounds-checked and returns `None` once the data runs out,
+/// so malformed metadata never causes a panic.
+#[derive(Debug, Clone)]
+pub(crate) struct Cursor<'a> {
+    /// The bytes being read.
+    data: &'a [u8],
+
+    /// Current position within `data`.
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    /// Creates a new cursor positioned at the start of `data`.
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    /// Moves the cursor to an absolute position.
+    pub(crate) fn seek(&mut self, pos: usize) {
+        self.pos = pos;
+    }
+
+    /// Returns the current position within the data.
+    pub(crate) fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Reads `len` raw bytes.
+    pub(crate) fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        let bytes = self.data.get(self.pos..self.pos.checked_add(len)?)?;
+        self.pos += len;
+        Some(bytes)
+    }
+
+    /// Returns the next byte without consuming it.
+    pub(crate) fn peek_u8(&self) -> Option<u8> {
+        self.data.get(self.pos).copied()
+    }
+
+    /// Reads a `u8`.
+    pub(crate) fn u8(&mut self) -> Option<u8> {
+        Some(self.bytes(1)?[0])
+    }
+
+    /// Reads a little-endian `u16`.
+    pub(crate) fn u16(&mut self) -> Option<u16> {
+        Some(u16::from_le_bytes(self.bytes(2)?.try_into().ok()?))
+    }
+
+    /// Reads a little-endian `u32`.
+    pub(crate) fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.bytes(4)?.try_into().ok()?))
+    }
+
+    /// Reads a little-endian `u64`.
+    pub(crate) fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.bytes(8)?.try_into().ok()?))
+    }
+
+    /// Reads an unsigned integer that is either 2 or 4 bytes wide.
+    pub(crate) fn index(&mut self, width: usize) -> Option<u32> {
+        match width {
+            2 => self.u16().map(u32::from),
+            _ => self.u32(),
+        }
+    }
+
+    /// Reads a compressed unsigned integer (ECMA-335 II.23.2).
+    pub(crate) fn compressed_u32(&mut self) -> Option<u32> {
+        let first = self.u8()? as u32;
+        if first & 0x80 == 0 {
+            Some(first)
+        } else if first & 0xC0 == 0x80 {
+            Some((first & 0x3F) << 8 | self.u8()? as u32)
+        } else if first & 0xE0 == 0xC0 {
+            let rest = self.bytes(3)?;
+            Some((first & 0x1F) << 24 | (rest[0] as u32) << 16 | (rest[1] as u32) << 8 | rest[2] as u32)
+        } else {
+            None
+        }
+    }
+
+    /// Reads a compressed signed integer (ECMA-335 II.23.2).
+    pub(crate) fn compressed_i32(&mut self) -> Option<i32> {
+        let start = self.pos;
+        let raw = self.compressed_u32()?;
+        let sign = match self.pos - start {
+            1 => 0xFFFF_FFC0u32,
+            2 => 0xFFFF_E000u32,
+            _ => 0xF000_0000u32,
+        };
+
+        // The sign bit is rotated into the least significant position.
+        let value = raw >> 1;
+        let value = if raw & 1 != 0 { value | sign } else { value };
+        Some(value as i32)
+    }
+
+    /// Reads a null-terminated UTF-8 string, consuming the terminator.
+    pub(crate) fn c_str(&mut self) -> Option<&'a str> {
+        let rest = self.data.get(self.pos..)?;
+        let len = rest.iter().position(|&b| b == 0)?;
+        let value = core::str::from_utf8(&rest[..len]).ok()?;
+        self.pos += len + 1;
+        Some(value)
+    }
+
+    /// Advances the cursor to the next multiple of `align` bytes.
+    pub(crate) fn align(&mut self, align: usize) {
+        self.pos = self.pos.div_ceil(align) * align;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_compressed_integers() {
+        // Examples from ECMA-335 II.23.2.
+        let unsigned = [
+            (&[0x03][..], 0x03),
+            (&[0x7F][..], 0x7F),
+            (&[0x80, 0x80][..], 0x80),
+            (&[0xAE, 0x57][..], 0x2E57),
+            (&[0xBF, 0xFF][..], 0x3FFF),
+            (&[0xC0, 0x00, 0x40, 0x00][..], 0x4000),
+            (&[0xDF, 0xFF, 0xFF, 0xFF][..], 0x1FFF_FFFF),
+        ];
+        for (bytes, value) in unsigned {
+            assert_eq!(Cursor::new(bytes).compressed_u32(), Some(value));
+        }
+
+        let signed = [
+            (&[0x06][..], 3),
+            (&[0x7B][..], -3),
+            (&[0x80, 0x80][..], 64),
+            (&[0x01][..], -64),
+            (&[0xC0, 0x00, 0x40, 0x00][..], 8192),
+            (&[0x80, 0x01][..], -8192),
+            (&[0xDF, 0xFF, 0xFF, 0xFE][..], 268_435_455),
+            (&[0xC0, 0x00, 0x00, 0x01][..], -268_435_456),
+        ];
+        for (bytes, value) in signed {
+            assert_eq!(Cursor::new(bytes).compressed_i32(), Some(value));
+        }
+    }
+
+    #[test]
+    fn stops_at_end_of_data() {
+        let mut cursor = Cursor::new(&[0x01, 0x02, 0x03]);
+        assert_eq!(cursor.u16(), Some(0x0201));
+        assert_eq!(cursor.u16(), None);
+        assert_eq!(cursor.u8(), Some(0x03));
+    }
+}
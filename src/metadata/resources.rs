@@ -0,0 +1,234 @@
+use std::borrow::Cow;
+use super::{inflate::inflate, CodedIndex, Metadata, TableId, Token};
+use crate::{error::ClrError, file::is_dotnet, Result};
+
+/// `ManifestResourceAttributes.VisibilityMask`.
+const VISIBILITY_MASK: u32 = 0x0007;
+
+/// `ManifestResourceAttributes.Public`.
+const VISIBILITY_PUBLIC: u32 = 0x0001;
+
+/// Suffix Costura and similar packers append to deflate-compressed resources.
+const COMPRESSED_SUFFIX: &str = ".compressed";
+
+/// Visibility of a manifest resource outside its assembly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceVisibility {
+    /// Exported, visible through `Assembly.GetManifestResourceStream` from other assemblies.
+    Public,
+
+    /// Private to the assembly.
+    Private,
+}
+
+/// Where the bytes of a manifest resource live.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResourceLocation {
+    /// Embedded in the managed resources of this image.
+    Embedded,
+
+    /// Stored in a separate file of a multi-file assembly.
+    File(String),
+
+    /// Forwarded to another assembly.
+    Assembly(String),
+}
+
+/// A row of the `ManifestResource` table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestResource<'a> {
+    /// Metadata token of the resource.
+    pub token: Token,
+
+    /// Name of the resource, e.g. `costura.library.dll.compressed`.
+    pub name: String,
+
+    /// Visibility of the resource.
+    pub visibility: ResourceVisibility,
+
+    /// Where the resource is stored.
+    pub location: ResourceLocation,
+
+    /// The raw bytes of embedded resources, `None` for resources stored elsewhere.
+    pub data: Option<&'a [u8]>,
+}
+
+impl<'a> ManifestResource<'a> {
+    /// Returns `true` if the resource name marks it as deflate-compressed (`*.compressed`).
+    pub fn is_compressed(&self) -> bool {
+        self.name.len() > COMPRESSED_SUFFIX.len()
+            && self.name.is_char_boundary(self.name.len() - COMPRESSED_SUFFIX.len())
+            && self.name[self.name.len() - COMPRESSED_SUFFIX.len()..].eq_ignore_ascii_case(COMPRESSED_SUFFIX)
+    }
+
+    /// Returns the resource name without the `.compressed` suffix, e.g. `costura.library.dll`.
+    pub fn file_name(&self) -> &str {
+        match self.is_compressed() {
+            true => &self.name[..self.name.len() - COMPRESSED_SUFFIX.len()],
+            false => &self.name,
+        }
+    }
+
+    /// Returns the contents of the resource, decompressing `.compressed` resources.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Cow<[u8]>)` - The stored bytes, or the inflated bytes of compressed resources.
+    /// * `Err(ClrError)` - If the resource is not embedded or fails to decompress.
+    pub fn contents(&self) -> Result<Cow<'a, [u8]>> {
+        let data = self.data.ok_or(ClrError::MetadataError("the resource is not embedded in the image"))?;
+        match self.is_compressed() {
+            true => inflate(data).map(Cow::Owned),
+            false => Ok(Cow::Borrowed(data)),
+        }
+    }
+}
+
+impl<'a> Metadata<'a> {
+    /// Lists the resources declared in the `ManifestResource` table.
+    ///
+    /// # Returns
+    ///
+    /// * The resources in table order. Embedded resources whose bytes fall outside the
+    ///   managed resources directory are listed with `data` set to `None`.
+    pub fn manifest_resources(&self) -> Vec<ManifestResource<'a>> {
+        let table = self.tables().table(TableId::ManifestResource);
+        (1..=table.len())
+            .filter_map(|index| {
+                let row = table.row(index)?;
+                let location = match CodedIndex::Implementation.decode(row[3]) {
+                    Some((_, 0)) | None => ResourceLocation::Embedded,
+                    Some((TableId::File, file)) => {
+                        let name = self.tables().table(TableId::File).get(file, 1)?;
+                        ResourceLocation::File(self.strings().get(name)?.to_string())
+                    }
+                    Some((TableId::AssemblyRef, assembly)) => {
+                        let name = self.tables().table(TableId::AssemblyRef).get(assembly, 6)?;
+                        ResourceLocation::Assembly(self.strings().get(name)?.to_string())
+                    }
+                    Some(_) => return None,
+                };
+
+                let data = match location {
+                    ResourceLocation::Embedded => self.resource_data(row[0]),
+                    _ => None,
+                };
+
+                Some(ManifestResource {
+                    token: Token::new(TableId::ManifestResource, index),
+                    name: self.strings().get(row[2])?.to_string(),
+                    visibility: match row[1] & VISIBILITY_MASK {
+                        VISIBILITY_PUBLIC => ResourceVisibility::Public,
+                        _ => ResourceVisibility::Private,
+                    },
+                    location,
+                    data,
+                })
+            })
+            .collect()
+    }
+
+    /// Extracts the .NET assemblies embedded as resources, decompressing them as needed.
+    ///
+    /// This covers tools packed with Costura.Fody and similar weavers, whose dependencies
+    /// can then be handed to `RustClr::with_dependency` instead of being loaded from disk.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<(String, Vec<u8>)>)` - The file name (see `ManifestResource::file_name`) and bytes of each assembly.
+    /// * `Err(ClrError)` - If a compressed resource fails to decompress.
+    pub fn embedded_assemblies(&self) -> Result<Vec<(String, Vec<u8>)>> {
+        let mut assemblies = Vec::new();
+        for resource in self.manifest_resources() {
+            if resource.data.is_none() {
+                continue;
+            }
+
+            let contents = resource.contents()?;
+            let is_assembly = is_dotnet(&contents)
+                && Metadata::parse(&contents).is_ok_and(|metadata| metadata.assembly().is_some());
+
+            if is_assembly {
+                assemblies.push((resource.file_name().to_string(), contents.into_owned()));
+            }
+        }
+
+        Ok(assemblies)
+    }
+
+    /// Reads the length-prefixed bytes of an embedded resource at `offset` in the resources directory.
+    fn resource_data(&self, offset: u32) -> Option<&'a [u8]> {
+        let directory = self.cli_header().resources;
+        if !directory.is_present() || offset.checked_add(4)? > directory.size {
+            return None;
+        }
+
+        let data = self.rva(directory.virtual_address)?.get(..directory.size as usize)?;
+        let start = offset as usize + 4;
+        let length = u32::from_le_bytes(data.get(offset as usize..start)?.try_into().ok()?) as usize;
+        data.get(start..start.checked_add(length)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::builder::AssemblyBuilder;
+
+    #[test]
+    fn reads_manifest_resources() {
+        let mut library = AssemblyBuilder::new();
+        let name = library.string("Library");
+        library.row(TableId::Assembly, &[0x8004, 1, 0, 0, 0, 0, 0, name, 0]);
+        let library = library.build();
+
+        // Stored deflate blocks, as `DeflateStream` would write them for incompressible data
+        let mut compressed = Vec::new();
+        let mut chunks = library.chunks(0xFFFF).peekable();
+        while let Some(chunk) = chunks.next() {
+            compressed.push(u8::from(chunks.peek().is_none()));
+            compressed.extend((chunk.len() as u16).to_le_bytes());
+            compressed.extend((!(chunk.len() as u16)).to_le_bytes());
+            compressed.extend(chunk);
+        }
+
+        let mut b = AssemblyBuilder::new();
+        let plain = b.resource(b"plain text");
+        let packed = b.resource(&compressed);
+        let names = [
+            b.string("Sample.Properties.Resources.resources"),
+            b.string("costura.library.dll.compressed"),
+            b.string("linked.txt"),
+            b.string("linked.bin"),
+        ];
+
+        let implementation = CodedIndex::Implementation.encode(TableId::File, 1).unwrap();
+        b.row(TableId::File, &[0, names[3], 0]);
+        b.row(TableId::ManifestResource, &[plain, 0x0001, names[0], 0]);
+        b.row(TableId::ManifestResource, &[packed, 0x0002, names[1], 0]);
+        b.row(TableId::ManifestResource, &[0, 0x0001, names[2], implementation]);
+
+        let buffer = b.build();
+        let metadata = Metadata::parse(&buffer).unwrap();
+        let resources = metadata.manifest_resources();
+        assert_eq!(resources.len(), 3);
+
+        assert_eq!(resources[0].visibility, ResourceVisibility::Public);
+        assert_eq!(resources[0].location, ResourceLocation::Embedded);
+        assert!(!resources[0].is_compressed());
+        assert_eq!(resources[0].data, Some(&b"plain text"[..]));
+        assert_eq!(resources[0].contents().unwrap().as_ref(), b"plain text");
+
+        assert_eq!(resources[1].visibility, ResourceVisibility::Private);
+        assert!(resources[1].is_compressed());
+        assert_eq!(resources[1].file_name(), "costura.library.dll");
+        assert_eq!(resources[1].contents().unwrap().as_ref(), library.as_slice());
+
+        assert_eq!(resources[2].location, ResourceLocation::File("linked.bin".to_string()));
+        assert_eq!(resources[2].data, None);
+        assert!(resources[2].contents().is_err());
+
+        let assemblies = metadata.embedded_assemblies().unwrap();
+        assert_eq!(assemblies, vec![("costura.library.dll".to_string(), library)]);
+    }
+}
@@ -0,0 +1,593 @@
+use core::fmt;
+use super::{reader::Cursor, Metadata, TableId, Token};
+
+/// Element types used in signature blobs (ECMA-335 II.23.1.16).
+const ELEMENT_TYPE_VOID: u8 = 0x01;
+const ELEMENT_TYPE_BOOLEAN: u8 = 0x02;
+const ELEMENT_TYPE_CHAR: u8 = 0x03;
+const ELEMENT_TYPE_I1: u8 = 0x04;
+const ELEMENT_TYPE_U1: u8 = 0x05;
+const ELEMENT_TYPE_I2: u8 = 0x06;
+const ELEMENT_TYPE_U2: u8 = 0x07;
+const ELEMENT_TYPE_I4: u8 = 0x08;
+const ELEMENT_TYPE_U4: u8 = 0x09;
+const ELEMENT_TYPE_I8: u8 = 0x0A;
+const ELEMENT_TYPE_U8: u8 = 0x0B;
+const ELEMENT_TYPE_R4: u8 = 0x0C;
+const ELEMENT_TYPE_R8: u8 = 0x0D;
+const ELEMENT_TYPE_STRING: u8 = 0x0E;
+const ELEMENT_TYPE_PTR: u8 = 0x0F;
+const ELEMENT_TYPE_BYREF: u8 = 0x10;
+const ELEMENT_TYPE_VALUETYPE: u8 = 0x11;
+const ELEMENT_TYPE_CLASS: u8 = 0x12;
+const ELEMENT_TYPE_VAR: u8 = 0x13;
+const ELEMENT_TYPE_ARRAY: u8 = 0x14;
+const ELEMENT_TYPE_GENERICINST: u8 = 0x15;
+const ELEMENT_TYPE_TYPEDBYREF: u8 = 0x16;
+const ELEMENT_TYPE_I: u8 = 0x18;
+const ELEMENT_TYPE_U: u8 = 0x19;
+const ELEMENT_TYPE_FNPTR: u8 = 0x1B;
+const ELEMENT_TYPE_OBJECT: u8 = 0x1C;
+const ELEMENT_TYPE_SZARRAY: u8 = 0x1D;
+const ELEMENT_TYPE_MVAR: u8 = 0x1E;
+const ELEMENT_TYPE_CMOD_REQD: u8 = 0x1F;
+const ELEMENT_TYPE_CMOD_OPT: u8 = 0x20;
+const ELEMENT_TYPE_SENTINEL: u8 = 0x41;
+const ELEMENT_TYPE_PINNED: u8 = 0x45;
+
+/// Calling convention flags of method signatures (ECMA-335 II.23.2.1).
+const SIG_GENERIC: u8 = 0x10;
+const SIG_HASTHIS: u8 = 0x20;
+const SIG_EXPLICITTHIS: u8 = 0x40;
+const SIG_VARARG: u8 = 0x05;
+const SIG_FIELD: u8 = 0x06;
+const SIG_PROPERTY: u8 = 0x08;
+const SIG_LOCAL: u8 = 0x07;
+const SIG_GENERIC_INST: u8 = 0x0A;
+
+/// Maximum nesting accepted while decoding a type, guarding against cyclic `TypeSpec`s.
+const MAX_DEPTH: usize = 64;
+
+/// The name of a type defined or referenced by an assembly.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TypeName {
+    /// Namespace of the type, empty for nested types.
+    pub namespace: String,
+
+    /// Simple name of the type (e.g. ``List`1``).
+    pub name: String,
+
+    /// The enclosing type, for nested types.
+    pub declaring: Option<Box<TypeName>>,
+}
+
+impl TypeName {
+    /// Returns `true` if the type is nested in another type.
+    pub fn is_nested(&self) -> bool {
+        self.declaring.is_some()
+    }
+}
+
+impl fmt::Display for TypeName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.declaring, self.namespace.is_empty()) {
+            (Some(declaring), _) => write!(f, "{declaring}+{}", self.name),
+            (None, true) => write!(f, "{}", self.name),
+            (None, false) => write!(f, "{}.{}", self.namespace, self.name),
+        }
+    }
+}
+
+/// A type decoded from a signature blob (ECMA-335 II.23.2.12).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TypeSig {
+    Void,
+    Boolean,
+    Char,
+    SByte,
+    Byte,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Int64,
+    UInt64,
+    Single,
+    Double,
+    String,
+    Object,
+    IntPtr,
+    UIntPtr,
+    TypedReference,
+
+    /// A reference type.
+    Class(TypeName),
+
+    /// A value type.
+    ValueType(TypeName),
+
+    /// A single-dimensional, zero-based array.
+    SzArray(Box<TypeSig>),
+
+    /// A general array with the given rank.
+    Array(Box<TypeSig>, u32),
+
+    /// An unmanaged pointer.
+    Ptr(Box<TypeSig>),
+
+    /// A managed reference (`ref`/`out`).
+    ByRef(Box<TypeSig>),
+
+    /// A generic type instantiated with type arguments.
+    GenericInst(Box<TypeSig>, Vec<TypeSig>),
+
+    /// A generic parameter of the enclosing type, by index and name.
+    Var(u32, String),
+
+    /// A generic parameter of the enclosing method, by index and name.
+    MVar(u32, String),
+
+    /// A function pointer.
+    FnPtr(Box<MethodSig>),
+}
+
+impl TypeSig {
+    /// Returns the full name of the type, as returned by `Type.ToString()`.
+    pub fn full_name(&self) -> String {
+        match self {
+            TypeSig::Class(name) | TypeSig::ValueType(name) => name.to_string(),
+            TypeSig::SzArray(element) => format!("{}[]", element.full_name()),
+            TypeSig::Array(element, rank) => format!("{}{}", element.full_name(), array_suffix(*rank)),
+            TypeSig::Ptr(element) => format!("{}*", element.full_name()),
+            TypeSig::ByRef(element) => format!("{}&", element.full_name()),
+            TypeSig::GenericInst(base, args) => {
+                let args = args.iter().map(TypeSig::full_name).collect::<Vec<_>>();
+                format!("{}[{}]", base.full_name(), args.join(","))
+            }
+            TypeSig::Var(_, name) | TypeSig::MVar(_, name) => name.clone(),
+            _ => format!("System.{}", self.primitive_name().unwrap_or("IntPtr")),
+        }
+    }
+
+    /// Returns the simple name of the type, as returned by `Type.Name`.
+    pub fn name(&self) -> String {
+        match self {
+            TypeSig::Class(name) | TypeSig::ValueType(name) => name.name.clone(),
+            TypeSig::SzArray(element) => format!("{}[]", element.name()),
+            TypeSig::Array(element, rank) => format!("{}{}", element.name(), array_suffix(*rank)),
+            TypeSig::Ptr(element) => format!("{}*", element.name()),
+            TypeSig::ByRef(element) => format!("{}&", element.name()),
+            TypeSig::GenericInst(base, _) => base.name(),
+            TypeSig::Var(_, name) | TypeSig::MVar(_, name) => name.clone(),
+            _ => self.primitive_name().unwrap_or("IntPtr").to_string(),
+        }
+    }
+
+    /// Returns the innermost element type of arrays, pointers and references.
+    pub fn root(&self) -> &TypeSig {
+        match self {
+            TypeSig::SzArray(element)
+            | TypeSig::Array(element, _)
+            | TypeSig::Ptr(element)
+            | TypeSig::ByRef(element) => element.root(),
+            _ => self,
+        }
+    }
+
+    /// Returns `true` for the types `Type.IsPrimitive` reports as primitive.
+    pub fn is_primitive(&self) -> bool {
+        !matches!(
+            self,
+            TypeSig::Void | TypeSig::String | TypeSig::Object | TypeSig::TypedReference
+        ) && self.primitive_name().is_some()
+    }
+
+    /// Returns the name within the `System` namespace of built-in types.
+    fn primitive_name(&self) -> Option<&'static str> {
+        Some(match self {
+            TypeSig::Void => "Void",
+            TypeSig::Boolean => "Boolean",
+            TypeSig::Char => "Char",
+            TypeSig::SByte => "SByte",
+            TypeSig::Byte => "Byte",
+            TypeSig::Int16 => "Int16",
+            TypeSig::UInt16 => "UInt16",
+            TypeSig::Int32 => "Int32",
+            TypeSig::UInt32 => "UInt32",
+            TypeSig::Int64 => "Int64",
+            TypeSig::UInt64 => "UInt64",
+            TypeSig::Single => "Single",
+            TypeSig::Double => "Double",
+            TypeSig::String => "String",
+            TypeSig::Object => "Object",
+            TypeSig::IntPtr => "IntPtr",
+            TypeSig::UIntPtr => "UIntPtr",
+            TypeSig::TypedReference => "TypedReference",
+            _ => return None,
+        })
+    }
+}
+
+/// Formats the type the way reflection prints it inside member signatures.
+///
+/// Primitive types, `Void` and `TypedReference` drop the `System.` prefix and
+/// nested types only print their simple name, so `MethodInfo.ToString()` shows
+/// `Void Main(System.String[])` and `Int32 Parse(System.String)`.
+impl fmt::Display for TypeSig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let root = self.root();
+        let nested = match root {
+            TypeSig::Class(name) | TypeSig::ValueType(name) => name.is_nested(),
+            TypeSig::GenericInst(base, _) => matches!(
+                &**base,
+                TypeSig::Class(name) | TypeSig::ValueType(name) if name.is_nested()
+            ),
+            TypeSig::Var(..) | TypeSig::MVar(..) => true,
+            _ => false,
+        };
+
+        if nested {
+            return f.write_str(&self.name());
+        }
+
+        let name = self.full_name();
+        if root.is_primitive() || matches!(root, TypeSig::Void | TypeSig::TypedReference | TypeSig::FnPtr(_)) {
+            return f.write_str(name.strip_prefix("System.").unwrap_or(&name));
+        }
+
+        f.write_str(&name)
+    }
+}
+
+/// A decoded method signature (ECMA-335 II.23.2.1).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MethodSig {
+    /// Whether the method takes an implicit `this` argument.
+    pub has_this: bool,
+
+    /// Whether `this` is passed explicitly as the first parameter.
+    pub explicit_this: bool,
+
+    /// Whether the method accepts a variable argument list.
+    pub varargs: bool,
+
+    /// Number of generic parameters declared by the method.
+    pub generic_count: u32,
+
+    /// The return type.
+    pub return_type: TypeSig,
+
+    /// The parameter types, in declaration order.
+    pub params: Vec<TypeSig>,
+}
+
+impl MethodSig {
+    /// Formats the parameter list as reflection does, without parentheses.
+    ///
+    /// `ref`/`out` parameters print as `Int32 ByRef`, and vararg methods end with `...`.
+    pub fn format_params(&self) -> String {
+        format_params(&self.params, self.varargs)
+    }
+}
+
+/// A decoded property signature (ECMA-335 II.23.2.5).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PropertySig {
+    /// Whether the property is an instance property.
+    pub has_this: bool,
+
+    /// The type of the property.
+    pub property_type: TypeSig,
+
+    /// The parameters of an indexed property.
+    pub params: Vec<TypeSig>,
+}
+
+/// Formats a parameter list the way `MethodBase.ConstructParameters` does.
+pub(crate) fn format_params(params: &[TypeSig], varargs: bool) -> String {
+    let mut params = params
+        .iter()
+        .map(|param| match param {
+            TypeSig::ByRef(_) => format!("{} ByRef", param.to_string().trim_end_matches('&')),
+            _ => param.to_string(),
+        })
+        .collect::<Vec<String>>();
+
+    if varargs {
+        params.push("...".to_string());
+    }
+
+    params.join(", ")
+}
+
+/// Returns the bracketed suffix of a general array of the given rank.
+fn array_suffix(rank: u32) -> String {
+    match rank {
+        1 => "[*]".to_string(),
+        _ => format!("[{}]", ",".repeat(rank.saturating_sub(1) as usize)),
+    }
+}
+
+/// Decodes signature blobs, resolving type tokens against the metadata.
+pub(crate) struct SignatureDecoder<'m, 'a> {
+    /// Metadata used to resolve `TypeDefOrRef` tokens.
+    metadata: &'m Metadata<'a>,
+
+    /// Names of the generic parameters of the enclosing type.
+    type_params: &'m [String],
+
+    /// Names of the generic parameters of the enclosing method.
+    method_params: &'m [String],
+
+    /// Current nesting of the decoder.
+    depth: usize,
+}
+
+impl<'m, 'a> SignatureDecoder<'m, 'a> {
+    /// Creates a decoder for signatures found in the given generic context.
+    pub(crate) fn new(metadata: &'m Metadata<'a>, type_params: &'m [String], method_params: &'m [String]) -> Self {
+        Self { metadata, type_params, method_params, depth: 0 }
+    }
+
+    /// Decodes a `MethodDefSig`, `MethodRefSig` or `StandAloneMethodSig` blob.
+    pub(crate) fn method(&mut self, blob: &[u8]) -> Option<MethodSig> {
+        self.method_sig(&mut Cursor::new(blob))
+    }
+
+    /// Decodes a `FieldSig` blob.
+    pub(crate) fn field(&mut self, blob: &[u8]) -> Option<TypeSig> {
+        let mut cursor = Cursor::new(blob);
+        if cursor.u8()? & 0x0F != SIG_FIELD {
+            return None;
+        }
+
+        self.skip_modifiers(&mut cursor)?;
+        self.type_sig(&mut cursor)
+    }
+
+    /// Decodes a `PropertySig` blob.
+    pub(crate) fn property(&mut self, blob: &[u8]) -> Option<PropertySig> {
+        let mut cursor = Cursor::new(blob);
+        let kind = cursor.u8()?;
+        if kind & 0x0F != SIG_PROPERTY {
+            return None;
+        }
+
+        let count = cursor.compressed_u32()?;
+        let property_type = self.param(&mut cursor)?;
+        let params = (0..count).map(|_| self.param(&mut cursor)).collect::<Option<Vec<_>>>()?;
+        Some(PropertySig { has_this: kind & SIG_HASTHIS != 0, property_type, params })
+    }
+
+    /// Decodes a `LocalVarSig` blob into the types of the locals.
+    pub(crate) fn locals(&mut self, blob: &[u8]) -> Option<Vec<TypeSig>> {
+        let mut cursor = Cursor::new(blob);
+        if cursor.u8()? != SIG_LOCAL {
+            return None;
+        }
+
+        let count = cursor.compressed_u32()?;
+        (0..count).map(|_| self.param(&mut cursor)).collect()
+    }
+
+    /// Decodes a `MethodSpec` instantiation blob into its generic arguments.
+    pub(crate) fn method_spec(&mut self, blob: &[u8]) -> Option<Vec<TypeSig>> {
+        let mut cursor = Cursor::new(blob);
+        if cursor.u8()? != SIG_GENERIC_INST {
+            return None;
+        }
+
+        let count = cursor.compressed_u32()?;
+        (0..count).map(|_| self.type_sig(&mut cursor)).collect()
+    }
+
+    /// Decodes a `TypeSpec` blob.
+    pub(crate) fn type_spec(&mut self, blob: &[u8]) -> Option<TypeSig> {
+        self.type_sig(&mut Cursor::new(blob))
+    }
+
+    /// Resolves a `TypeDef`, `TypeRef` or `TypeSpec` token into a type.
+    pub(crate) fn type_token(&mut self, token: Token, value_type: bool) -> Option<TypeSig> {
+        match token.table()? {
+            TableId::TypeSpec => {
+                self.enter()?;
+                let table = self.metadata.tables().table(TableId::TypeSpec);
+                let blob = self.metadata.blobs().get(table.get(token.row(), 0)?)?;
+                let sig = self.type_spec(blob);
+                self.depth -= 1;
+                sig
+            }
+            _ => {
+                let name = self.metadata.type_name(token)?;
+                Some(if value_type { TypeSig::ValueType(name) } else { TypeSig::Class(name) })
+            }
+        }
+    }
+
+    /// Increments the nesting depth, failing once it exceeds `MAX_DEPTH`.
+    fn enter(&mut self) -> Option<()> {
+        self.depth += 1;
+        (self.depth <= MAX_DEPTH).then_some(())
+    }
+
+    /// Reads a method signature from the cursor.
+    fn method_sig(&mut self, cursor: &mut Cursor) -> Option<MethodSig> {
+        let kind = cursor.u8()?;
+        let generic_count = if kind & SIG_GENERIC != 0 { cursor.compressed_u32()? } else { 0 };
+        let count = cursor.compressed_u32()?;
+        let return_type = self.param(cursor)?;
+
+        let mut params = Vec::with_capacity(count.min(0x100) as usize);
+        for _ in 0..count {
+            // Extra arguments of a vararg call site follow the sentinel.
+            if cursor.peek_u8()? == ELEMENT_TYPE_SENTINEL {
+                cursor.u8()?;
+            }
+
+            params.push(self.param(cursor)?);
+        }
+
+        Some(MethodSig {
+            has_this: kind & SIG_HASTHIS != 0,
+            explicit_this: kind & SIG_EXPLICITTHIS != 0,
+            varargs: kind & 0x0F == SIG_VARARG,
+            generic_count,
+            return_type,
+            params,
+        })
+    }
+
+    /// Reads a `RetType` or `Param`, including `BYREF`, `TYPEDBYREF` and `VOID`.
+    fn param(&mut self, cursor: &mut Cursor) -> Option<TypeSig> {
+        self.skip_modifiers(cursor)?;
+        self.type_sig(cursor)
+    }
+
+    /// Skips custom modifiers and the `PINNED` constraint.
+    fn skip_modifiers(&mut self, cursor: &mut Cursor) -> Option<()> {
+        loop {
+            match cursor.peek_u8()? {
+                ELEMENT_TYPE_CMOD_REQD | ELEMENT_TYPE_CMOD_OPT => {
+                    cursor.u8()?;
+                    cursor.compressed_u32()?;
+                }
+                ELEMENT_TYPE_PINNED => {
+                    cursor.u8()?;
+                }
+                _ => return Some(()),
+            }
+        }
+    }
+
+    /// Reads a `TypeDefOrRefEncoded` value and resolves it.
+    fn type_def_or_ref(&mut self, cursor: &mut Cursor, value_type: bool) -> Option<TypeSig> {
+        let encoded = cursor.compressed_u32()?;
+        let table = match encoded & 0x03 {
+            0 => TableId::TypeDef,
+            1 => TableId::TypeRef,
+            2 => TableId::TypeSpec,
+            _ => return None,
+        };
+
+        self.type_token(Token::new(table, encoded >> 2), value_type)
+    }
+
+    /// Reads a `Type` from the cursor.
+    fn type_sig(&mut self, cursor: &mut Cursor) -> Option<TypeSig> {
+        self.enter()?;
+        let sig = self.element(cursor);
+        self.depth -= 1;
+        sig
+    }
+
+    /// Reads the element type at the cursor and its operands.
+    fn element(&mut self, cursor: &mut Cursor) -> Option<TypeSig> {
+        Some(match cursor.u8()? {
+            ELEMENT_TYPE_VOID => TypeSig::Void,
+            ELEMENT_TYPE_BOOLEAN => TypeSig::Boolean,
+            ELEMENT_TYPE_CHAR => TypeSig::Char,
+            ELEMENT_TYPE_I1 => TypeSig::SByte,
+            ELEMENT_TYPE_U1 => TypeSig::Byte,
+            ELEMENT_TYPE_I2 => TypeSig::Int16,
+            ELEMENT_TYPE_U2 => TypeSig::UInt16,
+            ELEMENT_TYPE_I4 => TypeSig::Int32,
+            ELEMENT_TYPE_U4 => TypeSig::UInt32,
+            ELEMENT_TYPE_I8 => TypeSig::Int64,
+            ELEMENT_TYPE_U8 => TypeSig::UInt64,
+            ELEMENT_TYPE_R4 => TypeSig::Single,
+            ELEMENT_TYPE_R8 => TypeSig::Double,
+            ELEMENT_TYPE_STRING => TypeSig::String,
+            ELEMENT_TYPE_OBJECT => TypeSig::Object,
+            ELEMENT_TYPE_I => TypeSig::IntPtr,
+            ELEMENT_TYPE_U => TypeSig::UIntPtr,
+            ELEMENT_TYPE_TYPEDBYREF => TypeSig::TypedReference,
+            ELEMENT_TYPE_CLASS => self.type_def_or_ref(cursor, false)?,
+            ELEMENT_TYPE_VALUETYPE => self.type_def_or_ref(cursor, true)?,
+            ELEMENT_TYPE_PTR => TypeSig::Ptr(Box::new(self.param(cursor)?)),
+            ELEMENT_TYPE_BYREF => TypeSig::ByRef(Box::new(self.param(cursor)?)),
+            ELEMENT_TYPE_SZARRAY => TypeSig::SzArray(Box::new(self.param(cursor)?)),
+            ELEMENT_TYPE_ARRAY => {
+                let element = self.type_sig(cursor)?;
+                let rank = cursor.compressed_u32()?;
+                let sizes = cursor.compressed_u32()?;
+                for _ in 0..sizes {
+                    cursor.compressed_u32()?;
+                }
+
+                let bounds = cursor.compressed_u32()?;
+                for _ in 0..bounds {
+                    cursor.compressed_i32()?;
+                }
+
+                TypeSig::Array(Box::new(element), rank)
+            }
+            ELEMENT_TYPE_GENERICINST => {
+                let value_type = match cursor.u8()? {
+                    ELEMENT_TYPE_CLASS => false,
+                    ELEMENT_TYPE_VALUETYPE => true,
+                    _ => return None,
+                };
+
+                let base = self.type_def_or_ref(cursor, value_type)?;
+                let count = cursor.compressed_u32()?;
+                let args = (0..count).map(|_| self.type_sig(cursor)).collect::<Option<Vec<_>>>()?;
+                TypeSig::GenericInst(Box::new(base), args)
+            }
+            ELEMENT_TYPE_VAR => {
+                let index = cursor.compressed_u32()?;
+                let name = self.type_params.get(index as usize).cloned();
+                TypeSig::Var(index, name.unwrap_or_else(|| format!("!{index}")))
+            }
+            ELEMENT_TYPE_MVAR => {
+                let index = cursor.compressed_u32()?;
+                let name = self.method_params.get(index as usize).cloned();
+                TypeSig::MVar(index, name.unwrap_or_else(|| format!("!!{index}")))
+            }
+            ELEMENT_TYPE_FNPTR => TypeSig::FnPtr(Box::new(self.method_sig(cursor)?)),
+            _ => return None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn class(namespace: &str, name: &str) -> TypeName {
+        TypeName { namespace: namespace.to_string(), name: name.to_string(), declaring: None }
+    }
+
+    #[test]
+    fn formats_types_like_reflection() {
+        let string_array = TypeSig::SzArray(Box::new(TypeSig::String));
+        assert_eq!(string_array.to_string(), "System.String[]");
+        assert_eq!(TypeSig::Int32.to_string(), "Int32");
+        assert_eq!(TypeSig::Void.to_string(), "Void");
+        assert_eq!(TypeSig::Object.to_string(), "System.Object");
+        assert_eq!(TypeSig::Array(Box::new(TypeSig::Int32), 2).to_string(), "Int32[,]");
+        assert_eq!(TypeSig::Ptr(Box::new(TypeSig::Byte)).to_string(), "Byte*");
+
+        let list = TypeSig::GenericInst(
+            Box::new(TypeSig::Class(class("System.Collections.Generic", "List`1"))),
+            vec![TypeSig::Int32],
+        );
+        assert_eq!(list.to_string(), "System.Collections.Generic.List`1[System.Int32]");
+
+        let nested = TypeName { declaring: Some(Box::new(class("Ns", "Outer"))), ..class("", "Inner") };
+        assert_eq!(nested.to_string(), "Ns.Outer+Inner");
+        assert_eq!(TypeSig::ValueType(nested.clone()).full_name(), "Ns.Outer+Inner");
+        assert_eq!(TypeSig::ValueType(nested).to_string(), "Inner");
+    }
+
+    #[test]
+    fn formats_parameters_like_reflection() {
+        let params = [
+            TypeSig::ByRef(Box::new(TypeSig::Int32)),
+            TypeSig::ByRef(Box::new(TypeSig::String)),
+            TypeSig::MVar(0, "T".to_string()),
+        ];
+        assert_eq!(format_params(&params, false), "Int32 ByRef, System.String ByRef, T");
+        assert_eq!(format_params(&params[..1], true), "Int32 ByRef, ...");
+        assert_eq!(format_params(&[], true), "...");
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::BTreeMap;
+use super::{cil::{OpCode, Operand}, Metadata, Token};
+
+/// A string literal of the `#US` heap, with the methods that load it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StringLiteral {
+    /// Offset of the literal in the `#US` heap.
+    pub offset: u32,
+
+    /// The `ldstr` operand naming the literal (`0x70` followed by the offset).
+    pub token: Token,
+
+    /// The decoded UTF-16 value.
+    pub value: String,
+
+    /// `MethodDef` tokens of the methods that load the literal with `ldstr`, in table order.
+    pub references: Vec<Token>,
+}
+
+impl Metadata<'_> {
+    /// Lists the string literals of the `#US` heap along with the methods that reference them.
+    ///
+    /// Every IL method body is scanned for `ldstr`. Bodies that cannot be decoded (e.g. mangled
+    /// by an obfuscator) are skipped, so their literals may show no references.
+    ///
+    /// # Returns
+    ///
+    /// * The literals in heap order.
+    pub fn string_literals(&self) -> Vec<StringLiteral> {
+        let mut references = BTreeMap::<u32, Vec<Token>>::new();
+        for (method, rva) in self.il_methods() {
+            let Ok(instructions) = self.method_body(rva).and_then(|body| body.instructions()) else {
+                continue;
+            };
+
+            for instruction in instructions {
+                match instruction.operand {
+                    Operand::Token(token) if instruction.opcode.value == OpCode::LDSTR => {
+                        let methods = references.entry(token.row()).or_default();
+                        if methods.last() != Some(&method) {
+                            methods.push(method);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        self.user_strings()
+            .iter()
+            .map(|(offset, value)| StringLiteral {
+                offset,
+                token: Token::user_string(offset),
+                value,
+                references: references.remove(&offset).unwrap_or_default(),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::{builder::AssemblyBuilder, TableId};
+
+    #[test]
+    fn lists_literals_and_their_references() {
+        let mut b = AssemblyBuilder::new();
+        let hello = b.user_string("Hello");
+        let world = b.user_string("wörld");
+        let unused = b.user_string("unused");
+        let module = b.string("Sample.exe");
+        let name = b.string("Main");
+        let sig = b.blob(&[0x00, 0x00, 0x01]);
+
+        let ldstr = |offset: u32| [&[0x72][..], &Token::user_string(offset).0.to_le_bytes()].concat();
+        let first = b.body(&[ldstr(hello), ldstr(world), ldstr(hello), vec![0x2A]].concat());
+        let second = b.body(&[ldstr(world), vec![0x2A]].concat());
+        let broken = b.body(&[0x24]);
+        let (program, namespace) = (b.string("Program"), b.string("Sample"));
+        b.row(TableId::Module, &[0, module, 0, 0, 0]);
+        b.row(TableId::TypeDef, &[0x0010_0001, program, namespace, 0, 1, 1]);
+        b.row(TableId::MethodDef, &[first, 0, 0x16, name, sig, 1]);
+        b.row(TableId::MethodDef, &[second, 0, 0x16, name, sig, 1]);
+        b.row(TableId::MethodDef, &[broken, 0, 0x16, name, sig, 1]);
+
+        let buffer = b.build();
+        let metadata = Metadata::parse(&buffer).unwrap();
+        let literals = metadata.string_literals();
+        assert_eq!(literals.len(), 3);
+
+        let method = |row| Token::new(TableId::MethodDef, row);
+        assert_eq!(literals[0], StringLiteral {
+            offset: hello,
+            token: Token(0x7000_0000 | hello),
+            value: "Hello".to_string(),
+            references: vec![method(1)],
+        });
+
+        assert_eq!(literals[1].value, "wörld");
+        assert_eq!(literals[1].references, vec![method(1), method(2)]);
+        assert_eq!(literals[2].offset, unused);
+        assert!(literals[2].references.is_empty());
+        assert!(literals[2].token.is_user_string());
+
+        assert_eq!(metadata.method_name(method(2)).as_deref(), Some("Sample.Program::Main"));
+        assert_eq!(metadata.method_name(literals[0].token), None);
+
+        let identifiers = metadata.strings().iter().collect::<Vec<_>>();
+        assert_eq!(identifiers, vec![(module, "Sample.exe"), (name, "Main"), (program, "Program"), (namespace, "Sample")]);
+    }
+}
@@ -0,0 +1,566 @@
+use super::reader::Cursor;
+use crate::{error::ClrError, Result};
+
+/// Identifies a metadata table (ECMA-335 II.22).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum TableId {
+    Module = 0x00,
+    TypeRef = 0x01,
+    TypeDef = 0x02,
+    FieldPtr = 0x03,
+    Field = 0x04,
+    MethodPtr = 0x05,
+    MethodDef = 0x06,
+    ParamPtr = 0x07,
+    Param = 0x08,
+    InterfaceImpl = 0x09,
+    MemberRef = 0x0A,
+    Constant = 0x0B,
+    CustomAttribute = 0x0C,
+    FieldMarshal = 0x0D,
+    DeclSecurity = 0x0E,
+    ClassLayout = 0x0F,
+    FieldLayout = 0x10,
+    StandAloneSig = 0x11,
+    EventMap = 0x12,
+    EventPtr = 0x13,
+    Event = 0x14,
+    PropertyMap = 0x15,
+    PropertyPtr = 0x16,
+    Property = 0x17,
+    MethodSemantics = 0x18,
+    MethodImpl = 0x19,
+    ModuleRef = 0x1A,
+    TypeSpec = 0x1B,
+    ImplMap = 0x1C,
+    FieldRva = 0x1D,
+    EncLog = 0x1E,
+    EncMap = 0x1F,
+    Assembly = 0x20,
+    AssemblyProcessor = 0x21,
+    AssemblyOs = 0x22,
+    AssemblyRef = 0x23,
+    AssemblyRefProcessor = 0x24,
+    AssemblyRefOs = 0x25,
+    File = 0x26,
+    ExportedType = 0x27,
+    ManifestResource = 0x28,
+    NestedClass = 0x29,
+    GenericParam = 0x2A,
+    MethodSpec = 0x2B,
+    GenericParamConstraint = 0x2C,
+}
+
+impl TableId {
+    /// Every table, in the order they are stored in the `#~` stream.
+    pub const ALL: [TableId; 45] = [
+        TableId::Module, TableId::TypeRef, TableId::TypeDef, TableId::FieldPtr,
+        TableId::Field, TableId::MethodPtr, TableId::MethodDef, TableId::ParamPtr,
+        TableId::Param, TableId::InterfaceImpl, TableId::MemberRef, TableId::Constant,
+        TableId::CustomAttribute, TableId::FieldMarshal, TableId::DeclSecurity, TableId::ClassLayout,
+        TableId::FieldLayout, TableId::StandAloneSig, TableId::EventMap, TableId::EventPtr,
+        TableId::Event, TableId::PropertyMap, TableId::PropertyPtr, TableId::Property,
+        TableId::MethodSemantics, TableId::MethodImpl, TableId::ModuleRef, TableId::TypeSpec,
+        TableId::ImplMap, TableId::FieldRva, TableId::EncLog, TableId::EncMap,
+        TableId::Assembly, TableId::AssemblyProcessor, TableId::AssemblyOs, TableId::AssemblyRef,
+        TableId::AssemblyRefProcessor, TableId::AssemblyRefOs, TableId::File, TableId::ExportedType,
+        TableId::ManifestResource, TableId::NestedClass, TableId::GenericParam, TableId::MethodSpec,
+        TableId::GenericParamConstraint,
+    ];
+
+    /// Converts the raw table number into a `TableId`.
+    pub fn from_u8(value: u8) -> Option<Self> {
+        Self::ALL.get(value as usize).copied()
+    }
+
+    /// Returns the column layout of the table.
+    pub fn columns(self) -> &'static [Column] {
+        use Column::*;
+        use CodedIndex as C;
+        match self {
+            TableId::Module => &[U16, Strings, Guid, Guid, Guid],
+            TableId::TypeRef => &[Coded(C::ResolutionScope), Strings, Strings],
+            TableId::TypeDef => &[U32, Strings, Strings, Coded(C::TypeDefOrRef), Table(TableId::Field), Table(TableId::MethodDef)],
+            TableId::FieldPtr => &[Table(TableId::Field)],
+            TableId::Field => &[U16, Strings, Blob],
+            TableId::MethodPtr => &[Table(TableId::MethodDef)],
+            TableId::MethodDef => &[U32, U16, U16, Strings, Blob, Table(TableId::Param)],
+            TableId::ParamPtr => &[Table(TableId::Param)],
+            TableId::Param => &[U16, U16, Strings],
+            TableId::InterfaceImpl => &[Table(TableId::TypeDef), Coded(C::TypeDefOrRef)],
+            TableId::MemberRef => &[Coded(C::MemberRefParent), Strings, Blob],
+            TableId::Constant => &[U16, Coded(C::HasConstant), Blob],
+            TableId::CustomAttribute => &[Coded(C::HasCustomAttribute), Coded(C::CustomAttributeType), Blob],
+            TableId::FieldMarshal => &[Coded(C::HasFieldMarshal), Blob],
+            TableId::DeclSecurity => &[U16, Coded(C::HasDeclSecurity), Blob],
+            TableId::ClassLayout => &[U16, U32, Table(TableId::TypeDef)],
+            TableId::FieldLayout => &[U32, Table(TableId::Field)],
+            TableId::StandAloneSig => &[Blob],
+            TableId::EventMap => &[Table(TableId::TypeDef), Table(TableId::Event)],
+            TableId::EventPtr => &[Table(TableId::Event)],
+            TableId::Event => &[U16, Strings, Coded(C::TypeDefOrRef)],
+            TableId::PropertyMap => &[Table(TableId::TypeDef), Table(TableId::Property)],
+            TableId::PropertyPtr => &[Table(TableId::Property)],
+            TableId::Property => &[U16, Strings, Blob],
+            TableId::MethodSemantics => &[U16, Table(TableId::MethodDef), Coded(C::HasSemantics)],
+            TableId::MethodImpl => &[Table(TableId::TypeDef), Coded(C::MethodDefOrRef), Coded(C::MethodDefOrRef)],
+            TableId::ModuleRef => &[Strings],
+            TableId::TypeSpec => &[Blob],
+            TableId::ImplMap => &[U16, Coded(C::MemberForwarded), Strings, Table(TableId::ModuleRef)],
+            TableId::FieldRva => &[U32, Table(TableId::Field)],
+            TableId::EncLog => &[U32, U32],
+            TableId::EncMap => &[U32],
+            TableId::Assembly => &[U32, U16, U16, U16, U16, U32, Blob, Strings, Strings],
+            TableId::AssemblyProcessor => &[U32],
+            TableId::AssemblyOs => &[U32, U32, U32],
+            TableId::AssemblyRef => &[U16, U16, U16, U16, U32, Blob, Strings, Strings, Blob],
+            TableId::AssemblyRefProcessor => &[U32, Table(TableId::AssemblyRef)],
+            TableId::AssemblyRefOs => &[U32, U32, U32, Table(TableId::AssemblyRef)],
+            TableId::File => &[U32, Strings, Blob],
+            TableId::ExportedType => &[U32, U32, Strings, Strings, Coded(C::Implementation)],
+            TableId::ManifestResource => &[U32, U32, Strings, Coded(C::Implementation)],
+            TableId::NestedClass => &[Table(TableId::TypeDef), Table(TableId::TypeDef)],
+            TableId::GenericParam => &[U16, U16, Coded(C::TypeOrMethodDef), Strings],
+            TableId::MethodSpec => &[Coded(C::MethodDefOrRef), Blob],
+            TableId::GenericParamConstraint => &[Table(TableId::GenericParam), Coded(C::TypeDefOrRef)],
+        }
+    }
+}
+
+/// The kind of value stored in a table column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    /// A 2-byte constant.
+    U16,
+
+    /// A 4-byte constant.
+    U32,
+
+    /// An offset into the `#Strings` heap.
+    Strings,
+
+    /// An index into the `#GUID` heap.
+    Guid,
+
+    /// An offset into the `#Blob` heap.
+    Blob,
+
+    /// A 1-based row index into another table.
+    Table(TableId),
+
+    /// A coded index that may point into one of several tables.
+    Coded(CodedIndex),
+}
+
+/// Coded index kinds (ECMA-335 II.24.2.6).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CodedIndex {
+    TypeDefOrRef,
+    HasConstant,
+    HasCustomAttribute,
+    HasFieldMarshal,
+    HasDeclSecurity,
+    MemberRefParent,
+    HasSemantics,
+    MethodDefOrRef,
+    MemberForwarded,
+    Implementation,
+    CustomAttributeType,
+    ResolutionScope,
+    TypeOrMethodDef,
+}
+
+impl CodedIndex {
+    /// Returns the tables addressed by each tag value; `None` marks unused tags.
+    pub fn tables(self) -> &'static [Option<TableId>] {
+        use TableId as T;
+        match self {
+            CodedIndex::TypeDefOrRef => &[Some(T::TypeDef), Some(T::TypeRef), Some(T::TypeSpec)],
+            CodedIndex::HasConstant => &[Some(T::Field), Some(T::Param), Some(T::Property)],
+            CodedIndex::HasCustomAttribute => &[
+                Some(T::MethodDef), Some(T::Field), Some(T::TypeRef), Some(T::TypeDef),
+                Some(T::Param), Some(T::InterfaceImpl), Some(T::MemberRef), Some(T::Module),
+                Some(T::DeclSecurity), Some(T::Property), Some(T::Event), Some(T::StandAloneSig),
+                Some(T::ModuleRef), Some(T::TypeSpec), Some(T::Assembly), Some(T::AssemblyRef),
+                Some(T::File), Some(T::ExportedType), Some(T::ManifestResource), Some(T::GenericParam),
+                Some(T::GenericParamConstraint), Some(T::MethodSpec),
+            ],
+            CodedIndex::HasFieldMarshal => &[Some(T::Field), Some(T::Param)],
+            CodedIndex::HasDeclSecurity => &[Some(T::TypeDef), Some(T::MethodDef), Some(T::Assembly)],
+            CodedIndex::MemberRefParent => &[Some(T::TypeDef), Some(T::TypeRef), Some(T::ModuleRef), Some(T::MethodDef), Some(T::TypeSpec)],
+            CodedIndex::HasSemantics => &[Some(T::Event), Some(T::Property)],
+            CodedIndex::MethodDefOrRef => &[Some(T::MethodDef), Some(T::MemberRef)],
+            CodedIndex::MemberForwarded => &[Some(T::Field), Some(T::MethodDef)],
+            CodedIndex::Implementation => &[Some(T::File), Some(T::AssemblyRef), Some(T::ExportedType)],
+            CodedIndex::CustomAttributeType => &[None, None, Some(T::MethodDef), Some(T::MemberRef), None],
+            CodedIndex::ResolutionScope => &[Some(T::Module), Some(T::ModuleRef), Some(T::AssemblyRef), Some(T::TypeRef)],
+            CodedIndex::TypeOrMethodDef => &[Some(T::TypeDef), Some(T::MethodDef)],
+        }
+    }
+
+    /// Returns the number of low bits used to store the tag.
+    pub fn tag_bits(self) -> u32 {
+        let count = self.tables().len() as u32;
+        u32::BITS - (count - 1).leading_zeros()
+    }
+
+    /// Splits a raw coded index into the target table and its 1-based row.
+    ///
+    /// # Returns
+    ///
+    /// * `Some((TableId, u32))` - The addressed table and row (`0` for a null reference).
+    /// * `None` - If the tag does not map to any table.
+    pub fn decode(self, value: u32) -> Option<(TableId, u32)> {
+        let bits = self.tag_bits();
+        let tag = value & ((1 << bits) - 1);
+        let table = (*self.tables().get(tag as usize)?)?;
+        Some((table, value >> bits))
+    }
+
+    /// Builds a raw coded index from a table and row.
+    pub fn encode(self, table: TableId, row: u32) -> Option<u32> {
+        let tag = self.tables().iter().position(|&t| t == Some(table))? as u32;
+        Some(row << self.tag_bits() | tag)
+    }
+}
+
+/// Token type of `#US` offsets (`mdtString`), the operand of `ldstr`.
+const USER_STRING_TOKEN: u8 = 0x70;
+
+/// A metadata token, combining a table number in the high byte and a 1-based row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Token(pub u32);
+
+impl Token {
+    /// Builds a token from a table and a 1-based row.
+    pub fn new(table: TableId, row: u32) -> Self {
+        Self((table as u32) << 24 | (row & 0x00FF_FFFF))
+    }
+
+    /// Builds the `ldstr` token of the `#US` entry at `offset`.
+    pub fn user_string(offset: u32) -> Self {
+        Self(u32::from(USER_STRING_TOKEN) << 24 | (offset & 0x00FF_FFFF))
+    }
+
+    /// Returns `true` if the token addresses the `#US` heap.
+    pub fn is_user_string(&self) -> bool {
+        self.kind() == USER_STRING_TOKEN
+    }
+
+    /// Returns the raw table number stored in the high byte.
+    pub fn kind(&self) -> u8 {
+        (self.0 >> 24) as u8
+    }
+
+    /// Returns the table addressed by the token, if it is a table token.
+    pub fn table(&self) -> Option<TableId> {
+        TableId::from_u8(self.kind())
+    }
+
+    /// Returns the 1-based row (or heap offset for `#US` tokens).
+    pub fn row(&self) -> u32 {
+        self.0 & 0x00FF_FFFF
+    }
+
+    /// Returns `true` if the token has a null row.
+    pub fn is_null(&self) -> bool {
+        self.row() == 0
+    }
+}
+
+impl core::fmt::Display for Token {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "0x{:08X}", self.0)
+    }
+}
+
+/// Width information needed to decode table rows.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Widths {
+    /// Row count of every table.
+    pub(crate) rows: [u32; 64],
+
+    /// Width of `#Strings` offsets.
+    pub(crate) strings: usize,
+
+    /// Width of `#GUID` indexes.
+    pub(crate) guid: usize,
+
+    /// Width of `#Blob` offsets.
+    pub(crate) blob: usize,
+}
+
+impl Widths {
+    /// Computes the width in bytes of a single column.
+    pub(crate) fn column(&self, column: Column) -> usize {
+        match column {
+            Column::U16 => 2,
+            Column::U32 => 4,
+            Column::Strings => self.strings,
+            Column::Guid => self.guid,
+            Column::Blob => self.blob,
+            Column::Table(table) => if self.rows[table as usize] < 1 << 16 { 2 } else { 4 },
+            Column::Coded(coded) => {
+                let max = coded.tables()
+                    .iter()
+                    .flatten()
+                    .map(|t| self.rows[*t as usize])
+                    .max()
+                    .unwrap_or(0);
+
+                if max < 1 << (16 - coded.tag_bits()) { 2 } else { 4 }
+            }
+        }
+    }
+}
+
+/// A decoded metadata table.
+#[derive(Debug, Clone)]
+pub struct Table<'a> {
+    /// Which table this is.
+    id: TableId,
+
+    /// Number of rows.
+    rows: u32,
+
+    /// Size of a single row in bytes.
+    row_size: usize,
+
+    /// Byte offset and width of each column within a row.
+    columns: Vec<(usize, usize)>,
+
+    /// Raw row data.
+    data: &'a [u8],
+}
+
+impl<'a> Table<'a> {
+    /// Returns the identifier of the table.
+    pub fn id(&self) -> TableId {
+        self.id
+    }
+
+    /// Returns the number of rows in the table.
+    pub fn len(&self) -> u32 {
+        self.rows
+    }
+
+    /// Returns `true` if the table has no rows.
+    pub fn is_empty(&self) -> bool {
+        self.rows == 0
+    }
+
+    /// Returns the size of each row in bytes.
+    pub fn row_size(&self) -> usize {
+        self.row_size
+    }
+
+    /// Reads a single column of a row.
+    ///
+    /// # Arguments
+    ///
+    /// * `row` - The 1-based row index.
+    /// * `column` - The 0-based column index, following `TableId::columns`.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(u32)` - The raw column value (heap offset, row index or coded index).
+    /// * `None` - If the row or column is out of range.
+    pub fn get(&self, row: u32, column: usize) -> Option<u32> {
+        if row == 0 || row > self.rows {
+            return None;
+        }
+
+        let (offset, width) = *self.columns.get(column)?;
+        let mut cursor = Cursor::new(self.data);
+        cursor.seek((row as usize - 1) * self.row_size + offset);
+        cursor.index(width)
+    }
+
+    /// Reads every column of a row.
+    ///
+    /// # Arguments
+    ///
+    /// * `row` - The 1-based row index.
+    pub fn row(&self, row: u32) -> Option<Vec<u32>> {
+        (0..self.columns.len()).map(|column| self.get(row, column)).collect()
+    }
+
+    /// Finds the rows whose `column` equals `value`, using a binary search.
+    ///
+    /// Only meaningful for tables that ECMA-335 requires to be sorted on `column`
+    /// (e.g. `NestedClass`, `GenericParam`, `CustomAttribute`).
+    ///
+    /// # Returns
+    ///
+    /// * The 1-based range of matching rows, empty if none match.
+    pub fn equal_range(&self, column: usize, value: u32) -> core::ops::Range<u32> {
+        let lower_bound = |strict: bool| {
+            let (mut low, mut high) = (1, self.rows + 1);
+            while low < high {
+                let mid = low + (high - low) / 2;
+                let current = self.get(mid, column).unwrap_or(u32::MAX);
+                if current < value || (strict && current == value) {
+                    low = mid + 1;
+                } else {
+                    high = mid;
+                }
+            }
+            low
+        };
+
+        lower_bound(false)..lower_bound(true)
+    }
+}
+
+/// The decoded `#~` (or uncompressed `#-`) tables stream.
+#[derive(Debug, Clone)]
+pub struct Tables<'a> {
+    /// Major version of the table schema.
+    pub major_version: u8,
+
+    /// Minor version of the table schema.
+    pub minor_version: u8,
+
+    /// Bit vector of the tables that are sorted.
+    pub sorted: u64,
+
+    /// Every known table, indexed by `TableId`.
+    tables: Vec<Table<'a>>,
+}
+
+impl<'a> Tables<'a> {
+    /// Parses the tables stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The raw bytes of the `#~` or `#-` stream.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Tables)` - The decoded tables.
+    /// * `Err(ClrError)` - If the stream is truncated or references unknown tables.
+    pub fn parse(data: &'a [u8]) -> Result<Self> {
+        let truncated = || ClrError::MetadataError("tables stream is truncated");
+        let mut cursor = Cursor::new(data);
+        cursor.u32().ok_or_else(truncated)?;
+        let major_version = cursor.u8().ok_or_else(truncated)?;
+        let minor_version = cursor.u8().ok_or_else(truncated)?;
+        let heap_sizes = cursor.u8().ok_or_else(truncated)?;
+        cursor.u8().ok_or_else(truncated)?;
+        let valid = cursor.u64().ok_or_else(truncated)?;
+        let sorted = cursor.u64().ok_or_else(truncated)?;
+
+        let mut widths = Widths {
+            rows: [0; 64],
+            strings: if heap_sizes & 0x01 != 0 { 4 } else { 2 },
+            guid: if heap_sizes & 0x02 != 0 { 4 } else { 2 },
+            blob: if heap_sizes & 0x04 != 0 { 4 } else { 2 },
+        };
+
+        for i in 0..64 {
+            if valid & (1 << i) != 0 {
+                if TableId::from_u8(i as u8).is_none() {
+                    return Err(ClrError::MetadataError("tables stream references an unknown table"));
+                }
+
+                widths.rows[i] = cursor.u32().ok_or_else(truncated)?;
+            }
+        }
+
+        // Some uncompressed streams carry an extra 4 bytes of data after the row counts.
+        if heap_sizes & 0x40 != 0 {
+            cursor.u32().ok_or_else(truncated)?;
+        }
+
+        let mut tables = Vec::with_capacity(TableId::ALL.len());
+        for id in TableId::ALL {
+            let mut offset = 0;
+            let columns = id.columns()
+                .iter()
+                .map(|&column| {
+                    let width = widths.column(column);
+                    offset += width;
+                    (offset - width, width)
+                })
+                .collect::<Vec<_>>();
+
+            let rows = widths.rows[id as usize];
+            let size = (rows as usize).checked_mul(offset).ok_or_else(truncated)?;
+            let data = cursor.bytes(size).ok_or_else(truncated)?;
+            tables.push(Table { id, rows, row_size: offset, columns, data });
+        }
+
+        Ok(Self {
+            major_version,
+            minor_version,
+            sorted,
+            tables,
+        })
+    }
+
+    /// Returns the requested table (empty if not present in the stream).
+    pub fn table(&self, id: TableId) -> &Table<'a> {
+        &self.tables[id as usize]
+    }
+
+    /// Returns the number of rows of the requested table.
+    pub fn rows(&self, id: TableId) -> u32 {
+        self.tables[id as usize].rows
+    }
+
+    /// Returns every table, including empty ones, in stream order.
+    pub fn iter(&self) -> impl Iterator<Item = &Table<'a>> {
+        self.tables.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coded_index_tag_bits() {
+        assert_eq!(CodedIndex::TypeDefOrRef.tag_bits(), 2);
+        assert_eq!(CodedIndex::HasCustomAttribute.tag_bits(), 5);
+        assert_eq!(CodedIndex::MemberRefParent.tag_bits(), 3);
+        assert_eq!(CodedIndex::CustomAttributeType.tag_bits(), 3);
+        assert_eq!(CodedIndex::HasSemantics.tag_bits(), 1);
+        assert_eq!(CodedIndex::ResolutionScope.tag_bits(), 2);
+    }
+
+    #[test]
+    fn coded_index_round_trip() {
+        let raw = CodedIndex::TypeDefOrRef.encode(TableId::TypeRef, 5).unwrap();
+        assert_eq!(raw, 5 << 2 | 1);
+        assert_eq!(CodedIndex::TypeDefOrRef.decode(raw), Some((TableId::TypeRef, 5)));
+        assert_eq!(CodedIndex::CustomAttributeType.decode(0), None);
+        assert_eq!(CodedIndex::CustomAttributeType.decode(3 << 3 | 3), Some((TableId::MemberRef, 3)));
+    }
+
+    #[test]
+    fn column_widths_grow_with_row_counts() {
+        let mut widths = Widths { rows: [0; 64], strings: 2, guid: 2, blob: 4 };
+        assert_eq!(widths.column(Column::Table(TableId::Field)), 2);
+        assert_eq!(widths.column(Column::Coded(CodedIndex::HasCustomAttribute)), 2);
+        assert_eq!(widths.column(Column::Blob), 4);
+
+        // 2^11 rows no longer fit next to a 5-bit tag.
+        widths.rows[TableId::Param as usize] = 1 << 11;
+        assert_eq!(widths.column(Column::Coded(CodedIndex::HasCustomAttribute)), 4);
+        assert_eq!(widths.column(Column::Coded(CodedIndex::TypeDefOrRef)), 2);
+        assert_eq!(widths.column(Column::Table(TableId::Param)), 2);
+
+        widths.rows[TableId::Field as usize] = 1 << 16;
+        assert_eq!(widths.column(Column::Table(TableId::Field)), 4);
+    }
+
+    #[test]
+    fn tokens() {
+        let token = Token::new(TableId::MethodDef, 3);
+        assert_eq!(token.0, 0x0600_0003);
+        assert_eq!(token.table(), Some(TableId::MethodDef));
+        assert_eq!(token.row(), 3);
+        assert_eq!(token.to_string(), "0x06000003");
+    }
+}
@@ -0,0 +1,724 @@
+use core::fmt;
+use super::{
+    signature::{format_params, MethodSig, PropertySig, SignatureDecoder, TypeName, TypeSig},
+    CodedIndex, Metadata, TableId, Token,
+};
+use crate::{error::ClrError, Result};
+
+/// Maximum depth of enclosing types followed when resolving a type name.
+const MAX_NESTING: usize = 64;
+
+/// `TypeAttributes` visibility mask and the values of public types.
+const TYPE_VISIBILITY_MASK: u32 = 0x0000_0007;
+const TYPE_PUBLIC: u32 = 0x0000_0001;
+const TYPE_NESTED_PUBLIC: u32 = 0x0000_0002;
+const TYPE_INTERFACE: u32 = 0x0000_0020;
+
+/// `MethodAttributes` flags.
+const METHOD_ACCESS_MASK: u16 = 0x0007;
+const METHOD_PUBLIC: u16 = 0x0006;
+const METHOD_STATIC: u16 = 0x0010;
+const METHOD_ABSTRACT: u16 = 0x0400;
+const METHOD_RT_SPECIAL_NAME: u16 = 0x1000;
+
+/// `FieldAttributes` flags.
+const FIELD_STATIC: u16 = 0x0010;
+const FIELD_LITERAL: u16 = 0x0040;
+
+/// A type defined in the `TypeDef` table, mirroring what `_Assembly::types` lists.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeDefinition {
+    /// Metadata token of the type.
+    pub token: Token,
+
+    /// `TypeAttributes` of the type.
+    pub flags: u32,
+
+    /// Namespace, name and enclosing type.
+    pub name: TypeName,
+
+    /// Names of the generic parameters, including those inherited by nested types.
+    pub generic_params: Vec<String>,
+
+    /// The base type, if any.
+    pub base_type: Option<TypeSig>,
+
+    /// Fields declared by the type.
+    pub fields: Vec<FieldDefinition>,
+
+    /// Methods and constructors declared by the type.
+    pub methods: Vec<MethodDefinition>,
+
+    /// Properties declared by the type.
+    pub properties: Vec<PropertyDefinition>,
+}
+
+impl TypeDefinition {
+    /// Returns `true` if the type is visible outside the assembly (`public` or `nested public`).
+    pub fn is_public(&self) -> bool {
+        matches!(self.flags & TYPE_VISIBILITY_MASK, TYPE_PUBLIC | TYPE_NESTED_PUBLIC)
+    }
+
+    /// Returns `true` if the type is an interface.
+    pub fn is_interface(&self) -> bool {
+        self.flags & TYPE_INTERFACE != 0
+    }
+
+    /// Finds a method by its signature, mirroring `_Type::method_signature`.
+    ///
+    /// # Arguments
+    ///
+    /// * `signature` - The signature as printed by `MethodInfo.ToString()`, e.g. `Void Main(System.String[])`.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(&MethodDefinition)` - The method with a matching signature.
+    /// * `None` - If the type declares no such method.
+    pub fn method_signature(&self, signature: &str) -> Option<&MethodDefinition> {
+        self.methods.iter().find(|method| method.to_string() == signature)
+    }
+}
+
+/// Formats the type as `Type.ToString()` does, e.g. ``Ns.Outer+Inner`` or ``Ns.List`1[T]``.
+impl fmt::Display for TypeDefinition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if !self.generic_params.is_empty() {
+            write!(f, "[{}]", self.generic_params.join(","))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A method defined in the `MethodDef` table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MethodDefinition {
+    /// Metadata token of the method.
+    pub token: Token,
+
+    /// Name of the method.
+    pub name: String,
+
+    /// `MethodAttributes` of the method.
+    pub flags: u16,
+
+    /// `MethodImplAttributes` of the method.
+    pub impl_flags: u16,
+
+    /// RVA of the method body, zero for abstract and runtime-provided methods.
+    pub rva: u32,
+
+    /// Names of the generic parameters declared by the method.
+    pub generic_params: Vec<String>,
+
+    /// The decoded signature.
+    pub signature: MethodSig,
+
+    /// Rows of the `Param` table owned by the method.
+    pub params: Vec<ParamDefinition>,
+}
+
+impl MethodDefinition {
+    /// Returns `true` if the method is `public`.
+    pub fn is_public(&self) -> bool {
+        self.flags & METHOD_ACCESS_MASK == METHOD_PUBLIC
+    }
+
+    /// Returns `true` if the method is `static`.
+    pub fn is_static(&self) -> bool {
+        self.flags & METHOD_STATIC != 0
+    }
+
+    /// Returns `true` if the method is `abstract`.
+    pub fn is_abstract(&self) -> bool {
+        self.flags & METHOD_ABSTRACT != 0
+    }
+
+    /// Returns `true` for instance and type constructors (`.ctor` and `.cctor`).
+    pub fn is_constructor(&self) -> bool {
+        self.flags & METHOD_RT_SPECIAL_NAME != 0 && matches!(self.name.as_str(), ".ctor" | ".cctor")
+    }
+}
+
+/// Formats the method as `MethodInfo.ToString()` does, e.g. `Void Foo(System.String)`.
+impl fmt::Display for MethodDefinition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.signature.return_type, self.name)?;
+        if !self.generic_params.is_empty() {
+            write!(f, "[{}]", self.generic_params.join(","))?;
+        }
+
+        write!(f, "({})", self.signature.format_params())
+    }
+}
+
+/// A parameter defined in the `Param` table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParamDefinition {
+    /// Metadata token of the parameter.
+    pub token: Token,
+
+    /// `ParamAttributes` of the parameter.
+    pub flags: u16,
+
+    /// Position of the parameter; `0` describes the return value.
+    pub sequence: u16,
+
+    /// Name of the parameter.
+    pub name: String,
+}
+
+/// A field defined in the `Field` table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldDefinition {
+    /// Metadata token of the field.
+    pub token: Token,
+
+    /// `FieldAttributes` of the field.
+    pub flags: u16,
+
+    /// Name of the field.
+    pub name: String,
+
+    /// Type of the field.
+    pub field_type: TypeSig,
+}
+
+impl FieldDefinition {
+    /// Returns `true` if the field is `static`.
+    pub fn is_static(&self) -> bool {
+        self.flags & FIELD_STATIC != 0
+    }
+
+    /// Returns `true` if the field is a compile-time constant.
+    pub fn is_literal(&self) -> bool {
+        self.flags & FIELD_LITERAL != 0
+    }
+}
+
+/// Formats the field as `FieldInfo.ToString()` does, e.g. `System.String Name`.
+impl fmt::Display for FieldDefinition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.field_type, self.name)
+    }
+}
+
+/// A property defined in the `Property` table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PropertyDefinition {
+    /// Metadata token of the property.
+    pub token: Token,
+
+    /// `PropertyAttributes` of the property.
+    pub flags: u16,
+
+    /// Name of the property.
+    pub name: String,
+
+    /// The decoded signature.
+    pub signature: PropertySig,
+}
+
+/// Formats the property as `PropertyInfo.ToString()` does, e.g. `Int32 Item [System.String]`.
+impl fmt::Display for PropertyDefinition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.signature.property_type, self.name)?;
+        if !self.signature.params.is_empty() {
+            write!(f, " [{}]", format_params(&self.signature.params, false))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> Metadata<'a> {
+    /// Enumerates the types defined by the assembly, mirroring `_Assembly::types`.
+    ///
+    /// The `<Module>` pseudo-type is skipped, like reflection does. Unlike `_Type::methods`,
+    /// only the members declared by each type are listed; inherited ones are not.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<TypeDefinition>)` - The types with their fields, methods and properties.
+    /// * `Err(ClrError)` - If a name or a signature cannot be decoded.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// use rustclr::metadata::Metadata;
+    /// use std::fs;
+    ///
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let buffer = fs::read("examples/sample.exe")?;
+    ///     let metadata = Metadata::parse(&buffer)?;
+    ///
+    ///     for ty in metadata.types()? {
+    ///         for method in &ty.methods {
+    ///             println!("{ty}: {method}");
+    ///         }
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn types(&self) -> Result<Vec<TypeDefinition>> {
+        let table = self.tables().table(TableId::TypeDef);
+        let mut types = Vec::with_capacity(table.len() as usize);
+        for row in 1..=table.len() {
+            if row == 1 && self.string(table.get(row, 1)) == Some("<Module>") {
+                continue;
+            }
+
+            types.push(self.type_definition(row)?);
+        }
+
+        Ok(types)
+    }
+
+    /// Finds a type by the name `_Assembly::resolve_type` expects, e.g. `Ns.Outer+Inner`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(TypeDefinition))` - The type with its members.
+    /// * `Ok(None)` - If the assembly defines no such type.
+    /// * `Err(ClrError)` - If the members of the type cannot be decoded.
+    pub fn find_type(&self, name: &str) -> Result<Option<TypeDefinition>> {
+        for row in 1..=self.tables().rows(TableId::TypeDef) {
+            let token = Token::new(TableId::TypeDef, row);
+            if self.type_name(token).is_some_and(|type_name| type_name.to_string() == name) {
+                return self.type_definition(row).map(Some);
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Resolves the name of a `TypeDef` or `TypeRef` token.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(TypeName)` - The namespace, name and enclosing type.
+    /// * `None` - If the token does not reference an existing `TypeDef` or `TypeRef` row.
+    pub fn type_name(&self, token: Token) -> Option<TypeName> {
+        self.type_name_at(token, 0)
+    }
+
+    /// Formats a `MethodDef` token as `Namespace.Type::Method`.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(String)` - The declaring type and name of the method.
+    /// * `None` - If the token is not a `MethodDef` token or its row does not exist.
+    pub fn method_name(&self, token: Token) -> Option<String> {
+        if token.table()? != TableId::MethodDef {
+            return None;
+        }
+
+        let name = self.string(self.tables().table(TableId::MethodDef).get(token.row(), 3))?;
+        Some(format!("{}::{name}", self.member_parent(token)?))
+    }
+
+    /// Reads the `TypeDef` row at `row` along with its members.
+    pub(crate) fn type_definition(&self, row: u32) -> Result<TypeDefinition> {
+        let malformed = || ClrError::MetadataError("malformed TypeDef row");
+        let table = self.tables().table(TableId::TypeDef);
+        let token = Token::new(TableId::TypeDef, row);
+        let flags = table.get(row, 0).ok_or_else(malformed)?;
+        let name = self.type_name(token).ok_or_else(malformed)?;
+        let generic_params = self.generic_params(CodedIndex::TypeOrMethodDef.encode(TableId::TypeDef, row));
+
+        let extends = table.get(row, 3).ok_or_else(malformed)?;
+        let base_type = match CodedIndex::TypeDefOrRef.decode(extends) {
+            Some((table, row)) if row != 0 => Some(
+                SignatureDecoder::new(self, &generic_params, &[])
+                    .type_token(Token::new(table, row), false)
+                    .ok_or_else(malformed)?,
+            ),
+            _ => None,
+        };
+
+        let fields = self
+            .member_rows(TableId::TypeDef, row, 4, TableId::Field)
+            .into_iter()
+            .map(|field| self.field_definition(field, &generic_params))
+            .collect::<Result<Vec<_>>>()?;
+
+        let methods = self
+            .member_rows(TableId::TypeDef, row, 5, TableId::MethodDef)
+            .into_iter()
+            .map(|method| self.method_definition(method, &generic_params))
+            .collect::<Result<Vec<_>>>()?;
+
+        let property_map = self.tables().table(TableId::PropertyMap);
+        let properties = match (1..=property_map.len()).find(|&map| property_map.get(map, 0) == Some(row)) {
+            Some(map) => self
+                .member_rows(TableId::PropertyMap, map, 1, TableId::Property)
+                .into_iter()
+                .map(|property| self.property_definition(property, &generic_params))
+                .collect::<Result<Vec<_>>>()?,
+            None => Vec::new(),
+        };
+
+        Ok(TypeDefinition {
+            token,
+            flags,
+            name,
+            generic_params,
+            base_type,
+            fields,
+            methods,
+            properties,
+        })
+    }
+
+    /// Reads the `MethodDef` row at `row`.
+    ///
+    /// # Arguments
+    ///
+    /// * `row` - The 1-based row index.
+    /// * `type_params` - Generic parameters of the declaring type.
+    pub(crate) fn method_definition(&self, row: u32, type_params: &[String]) -> Result<MethodDefinition> {
+        let malformed = || ClrError::MetadataError("malformed MethodDef row");
+        let table = self.tables().table(TableId::MethodDef);
+        let values = table.row(row).ok_or_else(malformed)?;
+        let name = self.string(Some(values[3])).ok_or_else(malformed)?.to_string();
+        let generic_params = self.generic_params(CodedIndex::TypeOrMethodDef.encode(TableId::MethodDef, row));
+
+        let signature = self
+            .blobs()
+            .get(values[4])
+            .and_then(|blob| SignatureDecoder::new(self, type_params, &generic_params).method(blob))
+            .ok_or(ClrError::MetadataError("malformed method signature"))?;
+
+        let param_table = self.tables().table(TableId::Param);
+        let params = self
+            .member_rows(TableId::MethodDef, row, 5, TableId::Param)
+            .into_iter()
+            .map(|param| {
+                let values = param_table.row(param)?;
+                Some(ParamDefinition {
+                    token: Token::new(TableId::Param, param),
+                    flags: values[0] as u16,
+                    sequence: values[1] as u16,
+                    name: self.string(Some(values[2]))?.to_string(),
+                })
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or(ClrError::MetadataError("malformed Param row"))?;
+
+        Ok(MethodDefinition {
+            token: Token::new(TableId::MethodDef, row),
+            name,
+            flags: values[2] as u16,
+            impl_flags: values[1] as u16,
+            rva: values[0],
+            generic_params,
+            signature,
+            params,
+        })
+    }
+
+    /// Reads the `Field` row at `row`.
+    fn field_definition(&self, row: u32, type_params: &[String]) -> Result<FieldDefinition> {
+        let malformed = || ClrError::MetadataError("malformed Field row");
+        let values = self.tables().table(TableId::Field).row(row).ok_or_else(malformed)?;
+        let field_type = self
+            .blobs()
+            .get(values[2])
+            .and_then(|blob| SignatureDecoder::new(self, type_params, &[]).field(blob))
+            .ok_or(ClrError::MetadataError("malformed field signature"))?;
+
+        Ok(FieldDefinition {
+            token: Token::new(TableId::Field, row),
+            flags: values[0] as u16,
+            name: self.string(Some(values[1])).ok_or_else(malformed)?.to_string(),
+            field_type,
+        })
+    }
+
+    /// Reads the `Property` row at `row`.
+    fn property_definition(&self, row: u32, type_params: &[String]) -> Result<PropertyDefinition> {
+        let malformed = || ClrError::MetadataError("malformed Property row");
+        let values = self.tables().table(TableId::Property).row(row).ok_or_else(malformed)?;
+        let signature = self
+            .blobs()
+            .get(values[2])
+            .and_then(|blob| SignatureDecoder::new(self, type_params, &[]).property(blob))
+            .ok_or(ClrError::MetadataError("malformed property signature"))?;
+
+        Ok(PropertyDefinition {
+            token: Token::new(TableId::Property, row),
+            flags: values[0] as u16,
+            name: self.string(Some(values[1])).ok_or_else(malformed)?.to_string(),
+            signature,
+        })
+    }
+
+    /// Returns the names of the generic parameters owned by a `TypeOrMethodDef` coded index.
+    pub(crate) fn generic_params(&self, owner: Option<u32>) -> Vec<String> {
+        let Some(owner) = owner else {
+            return Vec::new();
+        };
+
+        let table = self.tables().table(TableId::GenericParam);
+        let mut params = table
+            .equal_range(2, owner)
+            .filter_map(|row| Some((table.get(row, 0)?, self.string(table.get(row, 3))?.to_string())))
+            .collect::<Vec<_>>();
+
+        params.sort_by_key(|(number, _)| *number);
+        params.into_iter().map(|(_, name)| name).collect()
+    }
+
+    /// Finds the `TypeDef` row that declares the `MethodDef` row `method`.
+    pub(crate) fn declaring_type(&self, method: u32) -> Option<u32> {
+        self.owner_row(TableId::TypeDef, 5, TableId::MethodDef, method)
+    }
+
+    /// Finds the `TypeDef` row that declares the `Field` row `field`.
+    pub(crate) fn field_declaring_type(&self, field: u32) -> Option<u32> {
+        self.owner_row(TableId::TypeDef, 4, TableId::Field, field)
+    }
+
+    /// Returns the `*Ptr` table that member lists of `target` go through, when the metadata uses one.
+    fn pointer_table(&self, target: TableId) -> Option<TableId> {
+        let pointers = match target {
+            TableId::Field => TableId::FieldPtr,
+            TableId::MethodDef => TableId::MethodPtr,
+            TableId::Param => TableId::ParamPtr,
+            TableId::Property => TableId::PropertyPtr,
+            _ => return None,
+        };
+
+        Some(pointers).filter(|&pointers| !self.tables().table(pointers).is_empty())
+    }
+
+    /// Expands a member list column (e.g. `TypeDef.MethodList`) into the rows it owns.
+    ///
+    /// The list runs up to the start of the next row's list, or to the end of the target
+    /// table, and goes through the matching `*Ptr` table when the metadata uses one.
+    fn member_rows(&self, owner: TableId, row: u32, column: usize, target: TableId) -> Vec<u32> {
+        let pointers = self.pointer_table(target);
+        let end_of_table = self.tables().rows(pointers.unwrap_or(target)) + 1;
+        let table = self.tables().table(owner);
+        let start = table.get(row, column).unwrap_or(end_of_table).min(end_of_table);
+        let end = table.get(row + 1, column).unwrap_or(end_of_table).min(end_of_table);
+
+        (start.max(1)..end)
+            .filter_map(|index| match pointers {
+                Some(pointers) => self.tables().table(pointers).get(index, 0),
+                None => Some(index),
+            })
+            .collect()
+    }
+
+    /// Finds the row of `owner` whose member list column (e.g. `TypeDef.MethodList`) holds `member`.
+    ///
+    /// The list columns are sorted, so this is a binary search for the last row whose list
+    /// starts at or before the member. Through a `*Ptr` table, the member is first looked up
+    /// in it.
+    fn owner_row(&self, owner: TableId, column: usize, target: TableId, member: u32) -> Option<u32> {
+        let index = match self.pointer_table(target) {
+            Some(pointers) => {
+                let pointers = self.tables().table(pointers);
+                (1..=pointers.len()).find(|&index| pointers.get(index, 0) == Some(member))?
+            }
+            None if member >= 1 && member <= self.tables().rows(target) => member,
+            None => return None,
+        };
+
+        let table = self.tables().table(owner);
+        let (mut low, mut high) = (1, table.len() + 1);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if table.get(mid, column)? <= index {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+
+        Some(low - 1).filter(|&row| row != 0)
+    }
+
+    /// Reads an optional `#Strings` offset.
+    fn string(&self, offset: Option<u32>) -> Option<&'a str> {
+        self.strings().get(offset?)
+    }
+
+    /// Resolves a type name, following at most `MAX_NESTING` enclosing types.
+    fn type_name_at(&self, token: Token, depth: usize) -> Option<TypeName> {
+        if depth > MAX_NESTING {
+            return None;
+        }
+
+        let row = token.row();
+        let id = token.table().filter(|id| matches!(id, TableId::TypeDef | TableId::TypeRef))?;
+        let table = self.tables().table(id);
+        let (name, namespace) = (table.get(row, 1), table.get(row, 2));
+
+        let declaring = match id {
+            TableId::TypeDef => {
+                let nested = self.tables().table(TableId::NestedClass);
+                nested
+                    .equal_range(0, row)
+                    .next()
+                    .and_then(|nested_row| nested.get(nested_row, 1))
+                    .map(|outer| Token::new(TableId::TypeDef, outer))
+            }
+            _ => match CodedIndex::ResolutionScope.decode(table.get(row, 0)?) {
+                Some((TableId::TypeRef, outer)) if outer != 0 => Some(Token::new(TableId::TypeRef, outer)),
+                _ => None,
+            },
+        };
+
+        let declaring = match declaring {
+            Some(outer) => Some(Box::new(self.type_name_at(outer, depth + 1)?)),
+            None => None,
+        };
+
+        Some(TypeName {
+            namespace: self.string(namespace)?.to_string(),
+            name: self.string(name)?.to_string(),
+            declaring,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::builder::AssemblyBuilder;
+
+    /// Builds an assembly with a generic type, a nested type and a handful of members.
+    fn sample() -> Vec<u8> {
+        let mut b = AssemblyBuilder::new();
+        let system = b.string("System");
+        let object = b.string("Object");
+        let mscorlib = b.string("mscorlib");
+        b.row(TableId::AssemblyRef, &[4, 0, 0, 0, 0, 0, mscorlib, 0, 0]);
+        b.row(TableId::TypeRef, &[CodedIndex::ResolutionScope.encode(TableId::AssemblyRef, 1).unwrap(), object, system]);
+        let extends = CodedIndex::TypeDefOrRef.encode(TableId::TypeRef, 1).unwrap();
+
+        let module = b.string("<Module>");
+        let sample = b.string("Sample");
+        let program = b.string("Program");
+        let cache = b.string("Cache`1");
+        let entry = b.string("Entry");
+
+        // <Module>, Sample.Program, Sample.Cache`1<T> and Sample.Program+Entry.
+        b.row(TableId::TypeDef, &[0, module, 0, 0, 1, 1]);
+        b.row(TableId::TypeDef, &[0x0010_0001, program, sample, extends, 1, 1]);
+        b.row(TableId::TypeDef, &[0x0010_0001, cache, sample, extends, 2, 4]);
+        b.row(TableId::TypeDef, &[0x0010_0002, entry, 0, extends, 3, 5]);
+        b.row(TableId::NestedClass, &[4, 2]);
+
+        // Fields: Program.Count (Int32), Cache`1.Items (T[]).
+        let count = b.string("Count");
+        let items = b.string("Items");
+        let int32 = b.blob(&[0x06, 0x08]);
+        let var_array = b.blob(&[0x06, 0x1D, 0x13, 0x00]);
+        b.row(TableId::Field, &[0x0016, count, int32]);
+        b.row(TableId::Field, &[0x0006, items, var_array]);
+
+        // Program: static void Main(string[] args), static int Run(ref int value, Entry entry),
+        // void .ctor(); Cache`1: T Get<U>(U key, List<T> list); Entry: no methods.
+        let main = b.string("Main");
+        let run = b.string("Run");
+        let ctor = b.string(".ctor");
+        let get = b.string("Get");
+        let args = b.string("args");
+        let value = b.string("value");
+        let generic = b.string("System.Collections.Generic");
+        let list = b.string("List`1");
+        b.row(TableId::TypeRef, &[CodedIndex::ResolutionScope.encode(TableId::AssemblyRef, 1).unwrap(), list, generic]);
+
+        let main_sig = b.blob(&[0x00, 0x01, 0x01, 0x1D, 0x0E]);
+        let run_sig = b.blob(&[0x00, 0x02, 0x08, 0x10, 0x08, 0x11, 4 << 2]);
+        let ctor_sig = b.blob(&[0x20, 0x00, 0x01]);
+        let get_sig = b.blob(&[0x30, 0x01, 0x02, 0x13, 0x00, 0x1E, 0x00, 0x15, 0x12, 2 << 2 | 1, 0x01, 0x13, 0x00]);
+        b.row(TableId::MethodDef, &[0x2050, 0, 0x0096, main, main_sig, 1]);
+        b.row(TableId::MethodDef, &[0x2060, 0, 0x0091, run, run_sig, 2]);
+        b.row(TableId::MethodDef, &[0x2070, 0, 0x1886, ctor, ctor_sig, 3]);
+        b.row(TableId::MethodDef, &[0x2080, 0, 0x0086, get, get_sig, 3]);
+        b.row(TableId::Param, &[0, 1, args]);
+        b.row(TableId::Param, &[0, 1, value]);
+
+        let t = b.string("T");
+        let u = b.string("U");
+        b.row(TableId::GenericParam, &[0, 0, CodedIndex::TypeOrMethodDef.encode(TableId::TypeDef, 3).unwrap(), t]);
+        b.row(TableId::GenericParam, &[0, 0, CodedIndex::TypeOrMethodDef.encode(TableId::MethodDef, 4).unwrap(), u]);
+
+        // Cache`1: indexed property `T Item[Int32]`.
+        let item = b.string("Item");
+        let item_sig = b.blob(&[0x28, 0x01, 0x13, 0x00, 0x08]);
+        b.row(TableId::PropertyMap, &[3, 1]);
+        b.row(TableId::Property, &[0, item, item_sig]);
+
+        b.build()
+    }
+
+    #[test]
+    fn enumerates_types_and_members() {
+        let buffer = sample();
+        let metadata = Metadata::parse(&buffer).unwrap();
+        let types = metadata.types().unwrap();
+
+        let names = types.iter().map(|ty| ty.to_string()).collect::<Vec<_>>();
+        assert_eq!(names, ["Sample.Program", "Sample.Cache`1[T]", "Sample.Program+Entry"]);
+        assert!(types.iter().all(|ty| ty.base_type.as_ref().map(TypeSig::full_name).as_deref() == Some("System.Object")));
+
+        let program = &types[0];
+        assert_eq!(program.token, Token(0x0200_0002));
+        assert_eq!(program.fields.len(), 1);
+        assert_eq!(program.fields[0].to_string(), "Int32 Count");
+        assert!(program.fields[0].is_static());
+
+        let methods = program.methods.iter().map(|m| m.to_string()).collect::<Vec<_>>();
+        assert_eq!(methods, ["Void Main(System.String[])", "Int32 Run(Int32 ByRef, Entry)", "Void .ctor()"]);
+        assert!(program.methods[0].is_static() && program.methods[0].is_public());
+        assert!(program.methods[2].is_constructor() && !program.methods[2].is_static());
+        assert_eq!(program.methods[0].params[0].name, "args");
+        assert_eq!(program.methods[1].params[0].name, "value");
+        assert_eq!(program.methods[0].rva, 0x2050);
+
+        let main = program.method_signature("Void Main(System.String[])").unwrap();
+        assert_eq!(main.token, Token(0x0600_0001));
+        assert!(program.method_signature("Void Main()").is_none());
+
+        let cache = &types[1];
+        assert_eq!(cache.fields[0].to_string(), "T[] Items");
+        assert_eq!(
+            cache.methods[0].to_string(),
+            "T Get[U](U, System.Collections.Generic.List`1[T])"
+        );
+        assert_eq!(cache.properties[0].to_string(), "T Item [Int32]");
+        assert!(cache.methods[0].params.is_empty());
+
+        let entry = &types[2];
+        assert!(entry.name.is_nested() && entry.is_public());
+        assert!(entry.methods.is_empty() && entry.fields.is_empty());
+
+        // <Module> has an empty method list that starts where the one of Program does.
+        let owners = (1..=5).map(|method| metadata.declaring_type(method)).collect::<Vec<_>>();
+        assert_eq!(owners, [Some(2), Some(2), Some(2), Some(3), None]);
+
+        let owners = (1..=3).map(|field| metadata.field_declaring_type(field)).collect::<Vec<_>>();
+        assert_eq!(owners, [Some(2), Some(3), None]);
+    }
+
+    #[test]
+    fn rejects_malformed_signatures() {
+        let mut b = AssemblyBuilder::new();
+        let program = b.string("Program");
+        let main = b.string("Main");
+        let sig = b.blob(&[0x00, 0x01, 0x01, 0x12, 0x7F]);
+        b.row(TableId::TypeDef, &[0, program, 0, 0, 1, 1]);
+        b.row(TableId::MethodDef, &[0, 0, 0x0016, main, sig, 1]);
+
+        let buffer = b.build();
+        let metadata = Metadata::parse(&buffer).unwrap();
+        assert!(matches!(metadata.types(), Err(ClrError::MetadataError(_))));
+    }
+}
@@ -0,0 +1,183 @@
+use windows_core::Interface;
+use windows_sys::Win32::System::Variant::VT_UI1;
+use crate::{
+    OwnedVariant, Result, Variant, WinStr,
+    create_safe_args, create_safe_array_buffer,
+    data::{BindingFlags, _AppDomain, _Type},
+    metadata::{AssemblyIdentity, AssemblyStore},
+};
+
+/// Display name of the assembly defining `System.Linq.Expressions.Expression`.
+const SYSTEM_CORE: &str = "System.Core, Version=4.0.0.0, Culture=neutral, PublicKeyToken=b77a5c561934e089";
+
+/// An `AppDomain.AssemblyResolve` handler that hands in-memory assemblies to the CLR.
+///
+/// The handler is compiled inside the AppDomain from an expression tree, so no helper
+/// assembly is written to disk. It looks the requested display name up in a `Hashtable`
+/// filled from Rust, falling back to the simple name for partial or differently versioned
+/// requests, loads the matching bytes with `Assembly.Load(byte[])` the first time they are
+/// asked for, and caches the resulting assembly for later requests. The handler is removed
+/// from the AppDomain when the resolver is dropped.
+pub struct AssemblyResolver {
+    /// The `Hashtable` mapping lower-case display names to `byte[]` or loaded `Assembly` objects,
+    /// and lower-case simple names to the display name they fall back to.
+    table: OwnedVariant,
+
+    /// The `System.Collections.Hashtable` type, used to add entries to `table`.
+    hashtable: _Type,
+
+    /// The compiled `ResolveEventHandler` delegate.
+    handler: OwnedVariant,
+
+    /// The AppDomain the handler is hooked to.
+    domain: OwnedVariant,
+
+    /// The `System.AppDomain` type, used to unhook the handler.
+    app_domain: _Type,
+}
+
+impl AssemblyResolver {
+    /// Installs the handler on `domain` and registers every assembly of `store`.
+    ///
+    /// # Arguments
+    ///
+    /// * `domain` - The AppDomain whose `AssemblyResolve` event is hooked.
+    /// * `store` - The assemblies to serve.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(AssemblyResolver)` - The installed handler, which accepts more assemblies later on.
+    /// * `Err(ClrError)` - If building or registering the handler fails.
+    pub fn install(domain: &_AppDomain, store: &AssemblyStore) -> Result<Self> {
+        let mscorlib = domain.get_assembly("mscorlib")?;
+        let system_core = domain.load_lib(SYSTEM_CORE)?;
+        let expression = system_core.resolve_type("System.Linq.Expressions.Expression")?;
+        let ty = |name: &str| mscorlib.resolve_type(name);
+        let build = |method: &str, args: Vec<OwnedVariant>| invoke(&expression, method, None, args);
+
+        let object = ty("System.Object")?;
+        let assembly = ty("System.Reflection.Assembly")?;
+        let bytes = ty("System.Byte[]")?;
+        let string = ty("System.String")?;
+        let table = mscorlib.create_instance("System.Collections.Hashtable")?;
+        let table_constant = || build("Constant", vec![table.clone()]);
+
+        // (object sender, ResolveEventArgs args)
+        let sender = build("Parameter", vec![object_variant(&object), "sender".to_variant()?])?;
+        let args = build("Parameter", vec![object_variant(&ty("System.ResolveEventArgs")?), "args".to_variant()?])?;
+
+        // args.Name.ToLowerInvariant() and args.Name.Split(",".ToCharArray())[0].ToLowerInvariant()
+        let name = build("Property", vec![args.clone(), "Name".to_variant()?])?;
+        let full_name = build("Call", vec![name.clone(), "ToLowerInvariant".to_variant()?, null()])?;
+        let comma = build("Call", vec![build("Constant", vec![",".to_variant()?])?, "ToCharArray".to_variant()?, null()])?;
+        let parts = build("Call", vec![name, "Split".to_variant()?, null(), comma])?;
+        let simple_name = build("ArrayIndex", vec![parts, build("Constant", vec![0.to_variant()?])?])?;
+        let simple_name = build("Call", vec![simple_name, "ToLowerInvariant".to_variant()?, null()])?;
+
+        // table.ContainsKey(full_name) ? full_name : (table[simple_name] as string ?? "")
+        let fallback = build("Property", vec![table_constant()?, "Item".to_variant()?, simple_name])?;
+        let fallback = build("Coalesce", vec![
+            build("TypeAs", vec![fallback, object_variant(&string)])?,
+            build("Constant", vec!["".to_variant()?])?,
+        ])?;
+        let key = build("Condition", vec![
+            build("Call", vec![table_constant()?, "ContainsKey".to_variant()?, null(), full_name.clone()])?,
+            full_name,
+            fallback,
+        ])?;
+        let entry = build("Property", vec![table_constant()?, "Item".to_variant()?, key])?;
+
+        // entry is byte[] ? (Assembly)(entry = Assembly.Load((byte[]) entry)) : entry as Assembly
+        let raw = build("Convert", vec![entry.clone(), object_variant(&bytes)])?;
+        let load = build("Call", vec![object_variant(&assembly), "Load".to_variant()?, null(), raw])?;
+        let cache = build("Assign", vec![entry.clone(), build("Convert", vec![load, object_variant(&object)])?])?;
+        let body = build("Condition", vec![
+            build("TypeIs", vec![entry.clone(), object_variant(&bytes)])?,
+            build("TypeAs", vec![cache, object_variant(&assembly)])?,
+            build("TypeAs", vec![entry, object_variant(&assembly)])?,
+        ])?;
+
+        let handler_type = ty("System.ResolveEventHandler")?;
+        let lambda = build("Lambda", vec![object_variant(&handler_type), body, sender, args])?;
+        let lambda_type = system_core.resolve_type("System.Linq.Expressions.LambdaExpression")?;
+        let handler = invoke(&lambda_type, "Compile", Some(&lambda), Vec::new())?;
+
+        // AppDomain.AssemblyResolve += handler
+        let app_domain = ty("System.AppDomain")?;
+        let domain = object_variant(domain);
+        invoke(&app_domain, "add_AssemblyResolve", Some(&domain), vec![handler.clone()])?;
+
+        let resolver = Self { table, hashtable: ty("System.Collections.Hashtable")?, handler, domain, app_domain };
+        for (identity, buffer) in store.iter() {
+            resolver.register(identity, buffer)?;
+        }
+
+        Ok(resolver)
+    }
+
+    /// Makes an assembly available to the handler under its display name.
+    ///
+    /// Requests whose display name differs, e.g. partial names or other versions, fall back to
+    /// the assembly registered last with the same simple name, whatever its version.
+    ///
+    /// # Arguments
+    ///
+    /// * `identity` - Identity of the assembly, as read from its `Assembly` table.
+    /// * `buffer` - The bytes of the assembly.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the assembly was registered.
+    /// * `Err(ClrError)` - If the bytes could not be copied into the AppDomain.
+    pub fn register(&self, identity: &AssemblyIdentity, buffer: &[u8]) -> Result<()> {
+        let full_name = identity.to_string().to_lowercase();
+        let bytes = OwnedVariant::from_array(VT_UI1, create_safe_array_buffer(buffer)?);
+        invoke(&self.hashtable, "set_Item", Some(&self.table), vec![full_name.to_variant()?, bytes])?;
+
+        let simple_name = identity.name.to_lowercase().to_variant()?;
+        invoke(&self.hashtable, "set_Item", Some(&self.table), vec![simple_name, full_name.to_variant()?])?;
+        Ok(())
+    }
+}
+
+impl Drop for AssemblyResolver {
+    fn drop(&mut self) {
+        // AppDomain.AssemblyResolve -= handler, so that a later resolver is the one answering
+        if let Err(e) = invoke(&self.app_domain, "remove_AssemblyResolve", Some(&self.domain), vec![self.handler.clone()]) {
+            eprintln!("Failed to remove the AssemblyResolve handler: {:?}", e);
+        }
+    }
+}
+
+impl core::fmt::Debug for AssemblyResolver {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("AssemblyResolver").finish_non_exhaustive()
+    }
+}
+
+/// Invokes a public method through `Type.InvokeMember`, static when `instance` is `None`.
+///
+/// Non-public members are left out, since `System.Linq.Expressions.Expression` has internal
+/// overloads that would make the binding ambiguous.
+fn invoke(ty: &_Type, name: &str, instance: Option<&OwnedVariant>, args: Vec<OwnedVariant>) -> Result<OwnedVariant> {
+    let flags = match instance {
+        Some(_) => BindingFlags::Public | BindingFlags::Instance | BindingFlags::InvokeMethod,
+        None => BindingFlags::Public | BindingFlags::Static | BindingFlags::InvokeMethod,
+    };
+
+    let args = create_safe_args(args)?;
+    match instance {
+        Some(instance) => ty.InvokeMember_3(name.to_bstr(), flags, instance, Some(&args)),
+        None => ty.InvokeMember_3(name.to_bstr(), flags, &OwnedVariant::default(), Some(&args)),
+    }
+}
+
+/// Wraps a COM interface pointer into a `VT_UNKNOWN` variant, which the CLR unwraps into the managed object.
+fn object_variant<T: Interface>(interface: &T) -> OwnedVariant {
+    OwnedVariant::from_object(interface)
+}
+
+/// An empty variant, passed as `null` (e.g. for the `Type[] typeArguments` of `Expression.Call`).
+fn null() -> OwnedVariant {
+    OwnedVariant::default()
+}
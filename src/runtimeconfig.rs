@@ -0,0 +1,578 @@
+use core::fmt;
+use std::{fs, path::{Path, PathBuf}};
+use crate::{
+    error::ClrError, Result,
+    metadata::{AssemblyVersion, FrameworkFamily, Metadata},
+    version::parse_version,
+};
+
+/// Name of the shared framework every .NET Core application runs on.
+pub(crate) const NETCORE_APP: &str = "Microsoft.NETCore.App";
+
+/// Name of the ASP.NET Core shared framework.
+const ASPNETCORE_APP: &str = "Microsoft.AspNetCore.App";
+
+/// Name of the Windows Forms and WPF shared framework.
+const WINDOWSDESKTOP_APP: &str = "Microsoft.WindowsDesktop.App";
+
+/// Assemblies that only ship with `Microsoft.WindowsDesktop.App`.
+const WINDOWSDESKTOP_ASSEMBLIES: [&str; 3] = ["System.Windows.Forms", "PresentationFramework", "PresentationCore"];
+
+/// How `hostfxr` picks a framework version when the requested one is not installed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RollForward {
+    /// Only newer patch versions of the requested `major.minor`.
+    LatestPatch,
+
+    /// The lowest newer minor version if the requested one is missing (the default).
+    Minor,
+
+    /// The lowest newer major version if the requested one is missing.
+    Major,
+
+    /// The newest minor version of the requested major version.
+    LatestMinor,
+
+    /// The newest installed version, even with a higher major version.
+    LatestMajor,
+
+    /// Exactly the requested version.
+    Disable,
+}
+
+impl RollForward {
+    /// Returns the value written to `rollForward`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RollForward::LatestPatch => "LatestPatch",
+            RollForward::Minor => "Minor",
+            RollForward::Major => "Major",
+            RollForward::LatestMinor => "LatestMinor",
+            RollForward::LatestMajor => "LatestMajor",
+            RollForward::Disable => "Disable",
+        }
+    }
+}
+
+impl fmt::Display for RollForward {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The value of a `configProperties` entry, such as `System.GC.Server`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigValue {
+    /// A switch, e.g. `"System.GC.Server": true`.
+    Bool(bool),
+
+    /// A number, e.g. `"System.GC.HeapHardLimit": 209715200`.
+    Number(i64),
+
+    /// A string, e.g. `"System.Globalization.Invariant": "true"`.
+    String(String),
+}
+
+impl From<bool> for ConfigValue {
+    fn from(value: bool) -> Self {
+        ConfigValue::Bool(value)
+    }
+}
+
+impl From<i64> for ConfigValue {
+    fn from(value: i64) -> Self {
+        ConfigValue::Number(value)
+    }
+}
+
+impl From<&str> for ConfigValue {
+    fn from(value: &str) -> Self {
+        ConfigValue::String(value.to_string())
+    }
+}
+
+impl From<String> for ConfigValue {
+    fn from(value: String) -> Self {
+        ConfigValue::String(value)
+    }
+}
+
+/// A shared framework the application runs on, e.g. `Microsoft.NETCore.App` `8.0.0`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FrameworkReference {
+    /// Name of the framework.
+    pub name: String,
+
+    /// Lowest version of the framework the application accepts.
+    pub version: String,
+}
+
+/// The `runtimeconfig.json` (and `deps.json`) of an assembly, derived from its metadata.
+///
+/// `hostfxr` starts a runtime from a `runtimeconfig.json`, which the SDK writes next to each
+/// build output. Assemblies received as bytes have none, so this rebuilds it: the target
+/// framework comes from the `TargetFrameworkAttribute`, or from the version of the referenced
+/// core library, and ASP.NET Core or Windows Desktop references add their shared framework.
+///
+/// # Examples
+///
+/// ```ignore
+/// use rustclr::{RollForward, RuntimeConfig};
+/// use std::fs;
+///
+/// fn main() -> Result<(), Box<dyn std::error::Error>> {
+///     let buffer = fs::read("bin/Library.dll")?;
+///     let config = RuntimeConfig::from_assembly(&buffer)?
+///         .with_roll_forward(RollForward::LatestMajor)
+///         .with_property("System.GC.Server", true);
+///
+///     println!("{}", config.to_json());
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeConfig {
+    /// The `.NETCoreApp` moniker the assembly runs under, e.g. `.NETCoreApp,Version=v8.0`.
+    pub target_framework: String,
+
+    /// Simple name of the assembly.
+    pub assembly_name: String,
+
+    /// Version of the assembly.
+    pub assembly_version: AssemblyVersion,
+
+    /// The shared frameworks to load, `Microsoft.NETCore.App` first.
+    pub frameworks: Vec<FrameworkReference>,
+
+    /// Roll-forward policy, `hostfxr` using `Minor` when unset.
+    pub roll_forward: Option<RollForward>,
+
+    /// The `configProperties` (`AppContext` switches), in insertion order.
+    pub properties: Vec<(String, ConfigValue)>,
+}
+
+impl RuntimeConfig {
+    /// Derives the configuration of an assembly from its bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - A reference to a byte slice representing the .NET assembly.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(RuntimeConfig)` - The configuration, see `RuntimeConfig::from_metadata`.
+    /// * `Err(ClrError)` - If the metadata is malformed or the assembly does not run on .NET Core.
+    pub fn from_assembly(buffer: &[u8]) -> Result<Self> {
+        Self::from_metadata(&Metadata::parse(buffer)?)
+    }
+
+    /// Derives the configuration of an assembly from its metadata.
+    ///
+    /// .NET Standard libraries, and .NET Core assemblies whose exact version cannot be told
+    /// from their references (`System.Runtime` 4.x), get the lowest matching .NET Core
+    /// version with `RollForward::Major`, so any newer installed runtime can host them.
+    ///
+    /// # Arguments
+    ///
+    /// * `metadata` - The parsed metadata of the assembly.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(RuntimeConfig)` - The configuration, without any `configProperties`.
+    /// * `Err(ClrError)` - `ClrError::NetFrameworkAssembly` for .NET Framework assemblies,
+    ///   or `ClrError::MetadataError` if the image is a module without an `Assembly` row.
+    pub fn from_metadata(metadata: &Metadata) -> Result<Self> {
+        let assembly = metadata
+            .assembly()
+            .ok_or(ClrError::MetadataError("the module does not define an assembly"))?;
+
+        let moniker = metadata.target_framework();
+        let references = metadata.assembly_references();
+        let ((major, minor), exact) = match metadata.framework_family() {
+            FrameworkFamily::NetCore => {
+                match moniker.as_deref().and_then(moniker_version) {
+                    Some(version) => (version, true),
+                    None => {
+                        // `System.Runtime` is versioned like the runtime since .NET 5
+                        let core = references
+                            .iter()
+                            .find(|reference| ["System.Runtime", "System.Private.CoreLib"].contains(&reference.name.as_str()))
+                            .map(|reference| reference.version);
+
+                        match core {
+                            Some(version) if version.major >= 5 => ((u32::from(version.major), u32::from(version.minor)), true),
+                            _ => ((3, 1), false),
+                        }
+                    }
+                }
+            }
+            FrameworkFamily::NetStandard => {
+                // .NET Standard 2.1 needs .NET Core 3.0, earlier versions run on 2.0
+                match moniker.as_deref().and_then(moniker_version) {
+                    Some((2, 1..)) => ((3, 0), false),
+                    _ => ((2, 0), false),
+                }
+            }
+            FrameworkFamily::NetFramework => {
+                let framework = moniker.unwrap_or_else(|| FrameworkFamily::NetFramework.to_string());
+                return Err(ClrError::NetFrameworkAssembly(framework));
+            }
+        };
+
+        let version = format!("{major}.{minor}.0");
+        let mut frameworks = vec![FrameworkReference { name: NETCORE_APP.to_string(), version: version.clone() }];
+        let references = |predicate: &dyn Fn(&str) -> bool| references.iter().any(|reference| predicate(&reference.name));
+        if references(&|name| name.starts_with("Microsoft.AspNetCore.")) {
+            frameworks.push(FrameworkReference { name: ASPNETCORE_APP.to_string(), version: version.clone() });
+        }
+
+        if major >= 3 && references(&|name| WINDOWSDESKTOP_ASSEMBLIES.contains(&name)) {
+            frameworks.push(FrameworkReference { name: WINDOWSDESKTOP_APP.to_string(), version });
+        }
+
+        Ok(Self {
+            target_framework: format!(".NETCoreApp,Version=v{major}.{minor}"),
+            assembly_name: assembly.name,
+            assembly_version: assembly.version,
+            frameworks,
+            roll_forward: (!exact).then_some(RollForward::Major),
+            properties: Vec::new(),
+        })
+    }
+
+    /// Sets the roll-forward policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `roll_forward` - The policy applied to every framework.
+    ///
+    /// # Returns
+    ///
+    /// * Returns the modified `RuntimeConfig` instance.
+    pub fn with_roll_forward(mut self, roll_forward: RollForward) -> Self {
+        self.roll_forward = Some(roll_forward);
+        self
+    }
+
+    /// Sets a `configProperties` entry, replacing any previous value.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the property, e.g. `System.GC.Server`.
+    /// * `value` - Its value, e.g. `true`, `209715200i64` or `"value"`.
+    ///
+    /// # Returns
+    ///
+    /// * Returns the modified `RuntimeConfig` instance.
+    pub fn with_property(mut self, name: &str, value: impl Into<ConfigValue>) -> Self {
+        let value = value.into();
+        match self.properties.iter_mut().find(|(existing, _)| existing == name) {
+            Some((_, existing)) => *existing = value,
+            None => self.properties.push((name.to_string(), value)),
+        }
+
+        self
+    }
+
+    /// Adds a shared framework, or changes the version of one already listed.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the framework, e.g. `Microsoft.AspNetCore.App`.
+    /// * `version` - The lowest accepted version, e.g. `8.0.0`.
+    ///
+    /// # Returns
+    ///
+    /// * Returns the modified `RuntimeConfig` instance.
+    pub fn with_framework(mut self, name: &str, version: &str) -> Self {
+        match self.frameworks.iter_mut().find(|framework| framework.name == name) {
+            Some(framework) => framework.version = version.to_string(),
+            None => self.frameworks.push(FrameworkReference { name: name.to_string(), version: version.to_string() }),
+        }
+
+        self
+    }
+
+    /// Returns the short target framework moniker, e.g. `net8.0` or `netcoreapp3.1`.
+    pub fn tfm(&self) -> String {
+        let (major, minor) = moniker_version(&self.target_framework).unwrap_or_default();
+        match major >= 5 {
+            true => format!("net{major}.{minor}"),
+            false => format!("netcoreapp{major}.{minor}"),
+        }
+    }
+
+    /// Renders the `runtimeconfig.json`.
+    ///
+    /// A single framework is written as `framework`, several as `frameworks`.
+    pub fn to_json(&self) -> String {
+        let framework = |framework: &FrameworkReference| Json::Object(vec![
+            ("name", Json::String(framework.name.clone())),
+            ("version", Json::String(framework.version.clone())),
+        ]);
+
+        let mut options = vec![("tfm", Json::String(self.tfm()))];
+        if let Some(roll_forward) = self.roll_forward {
+            options.push(("rollForward", Json::String(roll_forward.to_string())));
+        }
+
+        match &self.frameworks[..] {
+            [single] => options.push(("framework", framework(single))),
+            frameworks => options.push(("frameworks", Json::Array(frameworks.iter().map(framework).collect()))),
+        }
+
+        if !self.properties.is_empty() {
+            let properties = self.properties.iter().map(|(name, value)| {
+                let value = match value {
+                    ConfigValue::Bool(value) => Json::Bool(*value),
+                    ConfigValue::Number(value) => Json::Number(*value),
+                    ConfigValue::String(value) => Json::String(value.clone()),
+                };
+
+                (name.as_str(), value)
+            });
+
+            options.push(("configProperties", Json::Object(properties.collect())));
+        }
+
+        Json::Object(vec![("runtimeOptions", Json::Object(options))]).to_string()
+    }
+
+    /// Renders a minimal `deps.json`, listing the assembly as the only project of the application.
+    pub fn deps_json(&self) -> String {
+        let version = &self.assembly_version;
+        let library = format!("{}/{}.{}.{}", self.assembly_name, version.major, version.minor, version.build);
+        let file_name = self.file_name();
+        let runtime = Json::Object(vec![(file_name.as_str(), Json::Object(Vec::new()))]);
+
+        Json::Object(vec![
+            ("runtimeTarget", Json::Object(vec![
+                ("name", Json::String(self.target_framework.clone())),
+                ("signature", Json::String(String::new())),
+            ])),
+            ("compilationOptions", Json::Object(Vec::new())),
+            ("targets", Json::Object(vec![
+                (self.target_framework.as_str(), Json::Object(vec![
+                    (library.as_str(), Json::Object(vec![("runtime", runtime)])),
+                ])),
+            ])),
+            ("libraries", Json::Object(vec![
+                (library.as_str(), Json::Object(vec![
+                    ("type", Json::String("project".to_string())),
+                    ("serviceable", Json::Bool(false)),
+                    ("sha512", Json::String(String::new())),
+                ])),
+            ])),
+        ])
+        .to_string()
+    }
+
+    /// Writes `<assembly>.runtimeconfig.json` and `<assembly>.deps.json` into a directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory of the assembly.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(PathBuf)` - The path of the `runtimeconfig.json`, to hand to `RustHostFxr::with_runtime_config`.
+    /// * `Err(ClrError)` - If the files cannot be written.
+    pub fn write(&self, dir: &Path) -> Result<PathBuf> {
+        let write = |extension: &str, contents: String| {
+            let path = dir.join(format!("{}.{extension}", self.assembly_name));
+            fs::write(&path, contents).map(|_| path).map_err(|_| ClrError::ErrorClr("Failed to write the runtime configuration"))
+        };
+
+        write("deps.json", self.deps_json())?;
+        write("runtimeconfig.json", self.to_json())
+    }
+
+    /// Returns the file name of the assembly, e.g. `Library.dll`.
+    pub(crate) fn file_name(&self) -> String {
+        format!("{}.dll", self.assembly_name)
+    }
+}
+
+/// Reads `major.minor` out of a moniker such as `.NETCoreApp,Version=v8.0`.
+fn moniker_version(moniker: &str) -> Option<(u32, u32)> {
+    let version = moniker.split(',').find_map(|part| part.trim().strip_prefix("Version="))?;
+    match parse_version(version)?[..] {
+        [major] => Some((major, 0)),
+        [major, minor, ..] => Some((major, minor)),
+        [] => None,
+    }
+}
+
+/// The subset of JSON the configuration files need, rendered with two-space indentation.
+enum Json<'a> {
+    Object(Vec<(&'a str, Json<'a>)>),
+    Array(Vec<Json<'a>>),
+    String(String),
+    Number(i64),
+    Bool(bool),
+}
+
+impl Json<'_> {
+    /// Writes the value, indenting nested lines by `indent` levels.
+    fn write(&self, f: &mut fmt::Formatter<'_>, indent: usize) -> fmt::Result {
+        let pad = "  ".repeat(indent + 1);
+        match self {
+            Json::Object(members) if members.is_empty() => f.write_str("{}"),
+            Json::Array(items) if items.is_empty() => f.write_str("[]"),
+            Json::Object(members) => {
+                f.write_str("{\n")?;
+                for (index, (name, value)) in members.iter().enumerate() {
+                    write!(f, "{pad}{}: ", JsonString(name))?;
+                    value.write(f, indent + 1)?;
+                    f.write_str(if index + 1 < members.len() { ",\n" } else { "\n" })?;
+                }
+
+                write!(f, "{}}}", &pad[2..])
+            }
+            Json::Array(items) => {
+                f.write_str("[\n")?;
+                for (index, item) in items.iter().enumerate() {
+                    f.write_str(&pad)?;
+                    item.write(f, indent + 1)?;
+                    f.write_str(if index + 1 < items.len() { ",\n" } else { "\n" })?;
+                }
+
+                write!(f, "{}]", &pad[2..])
+            }
+            Json::String(value) => write!(f, "{}", JsonString(value)),
+            Json::Number(value) => write!(f, "{value}"),
+            Json::Bool(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+impl fmt::Display for Json<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, 0)
+    }
+}
+
+/// A string written as a quoted, escaped JSON string.
+struct JsonString<'a>(&'a str);
+
+impl fmt::Display for JsonString<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("\"")?;
+        for c in self.0.chars() {
+            match c {
+                '"' => f.write_str("\\\"")?,
+                '\\' => f.write_str("\\\\")?,
+                '\n' => f.write_str("\\n")?,
+                '\r' => f.write_str("\\r")?,
+                '\t' => f.write_str("\\t")?,
+                c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+                c => write!(f, "{c}")?,
+            }
+        }
+
+        f.write_str("\"")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::{builder::AssemblyBuilder, TableId};
+
+    /// Builds `Sample` 1.2.3.0, referencing the given assemblies and versions.
+    fn assembly(references: &[(&str, u32)], moniker: Option<&str>) -> Vec<u8> {
+        let mut b = AssemblyBuilder::new();
+        let name = b.string("Sample");
+        b.row(TableId::Assembly, &[0x8004, 1, 2, 3, 0, 0, 0, name, 0]);
+        for (reference, major) in references {
+            let name = b.string(reference);
+            b.row(TableId::AssemblyRef, &[*major, 0, 0, 0, 0, 0, name, 0, 0]);
+        }
+
+        if let Some(moniker) = moniker {
+            b.target_framework(moniker);
+        }
+
+        b.build()
+    }
+
+    #[test]
+    fn renders_runtime_config_from_moniker() {
+        let buffer = assembly(&[("System.Runtime", 8)], Some(".NETCoreApp,Version=v8.0"));
+        let config = RuntimeConfig::from_assembly(&buffer).unwrap()
+            .with_roll_forward(RollForward::LatestMinor)
+            .with_property("System.GC.Server", true)
+            .with_property("System.GC.HeapHardLimit", 209_715_200i64)
+            .with_property("Custom \"switch\"", "on")
+            .with_property("System.GC.Server", false);
+
+        assert_eq!(config.tfm(), "net8.0");
+        assert_eq!(config.to_json(), r#"{
+  "runtimeOptions": {
+    "tfm": "net8.0",
+    "rollForward": "LatestMinor",
+    "framework": {
+      "name": "Microsoft.NETCore.App",
+      "version": "8.0.0"
+    },
+    "configProperties": {
+      "System.GC.Server": false,
+      "System.GC.HeapHardLimit": 209715200,
+      "Custom \"switch\"": "on"
+    }
+  }
+}"#);
+
+        assert_eq!(config.deps_json(), r#"{
+  "runtimeTarget": {
+    "name": ".NETCoreApp,Version=v8.0",
+    "signature": ""
+  },
+  "compilationOptions": {},
+  "targets": {
+    ".NETCoreApp,Version=v8.0": {
+      "Sample/1.2.3": {
+        "runtime": {
+          "Sample.dll": {}
+        }
+      }
+    }
+  },
+  "libraries": {
+    "Sample/1.2.3": {
+      "type": "project",
+      "serviceable": false,
+      "sha512": ""
+    }
+  }
+}"#);
+    }
+
+    #[test]
+    fn infers_frameworks_from_references() {
+        let buffer = assembly(&[("System.Runtime", 6), ("Microsoft.AspNetCore.Http", 6)], None);
+        let config = RuntimeConfig::from_assembly(&buffer).unwrap();
+        assert_eq!(config.target_framework, ".NETCoreApp,Version=v6.0");
+        assert_eq!(config.roll_forward, None);
+        assert_eq!(config.frameworks.iter().map(|f| f.name.as_str()).collect::<Vec<_>>(), [NETCORE_APP, ASPNETCORE_APP]);
+        assert!(config.to_json().contains("\"frameworks\": [\n      {\n        \"name\": \"Microsoft.NETCore.App\""));
+
+        // .NET Core 3.x references `System.Runtime` 4.2, which does not tell the exact version
+        let buffer = assembly(&[("System.Runtime", 4), ("System.Windows.Forms", 4)], None);
+        let config = RuntimeConfig::from_assembly(&buffer).unwrap();
+        assert_eq!((config.tfm().as_str(), config.roll_forward), ("netcoreapp3.1", Some(RollForward::Major)));
+        assert_eq!(config.frameworks[1].name, WINDOWSDESKTOP_APP);
+
+        let buffer = assembly(&[("netstandard", 2)], Some(".NETStandard,Version=v2.1"));
+        let config = RuntimeConfig::from_assembly(&buffer).unwrap();
+        assert_eq!((config.frameworks[0].version.as_str(), config.roll_forward), ("3.0.0", Some(RollForward::Major)));
+    }
+
+    #[test]
+    fn rejects_net_framework_assemblies() {
+        let buffer = assembly(&[("mscorlib", 4)], Some(".NETFramework,Version=v4.7.2"));
+        let error = RuntimeConfig::from_assembly(&buffer).unwrap_err();
+        assert!(matches!(&error, ClrError::NetFrameworkAssembly(framework) if framework == ".NETFramework,Version=v4.7.2"));
+        assert!(!error.to_string().contains("only .NET Framework"));
+    }
+}
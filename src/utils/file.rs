@@ -1,92 +1,281 @@
 use crate::{error::ClrError, Result};
-use windows_sys::Win32::System::{
-    Diagnostics::Debug::{
-        IMAGE_DIRECTORY_ENTRY_COM_DESCRIPTOR, IMAGE_FILE_DLL, 
-        IMAGE_FILE_EXECUTABLE_IMAGE, IMAGE_NT_HEADERS64, 
-        IMAGE_SUBSYSTEM_NATIVE
-    }, 
-    SystemServices::{
-        IMAGE_DOS_HEADER, 
-        IMAGE_DOS_SIGNATURE, 
-        IMAGE_NT_SIGNATURE
-    }
-};
-use std::mem::size_of;
-/// Extracts the NT header from the given buffer if it represents a valid PE file.
-/// 
-/// # Arguments
-/// 
-/// * `buffer` - A reference to a byte slice representing the potential PE file.
-/// 
-/// # Returns
-/// 
-/// * `Some(*const IMAGE_NT_HEADERS64)` - If the buffer contains a valid NT header.
-/// * `None` - If the buffer does not represent a valid NT header.
-unsafe fn get_nt_header(buffer: &[u8]) -> Option<*const IMAGE_NT_HEADERS64> {
-    if buffer.len() < size_of::<IMAGE_DOS_HEADER>() {
-        return None;
+
+/// DOS header signature (`MZ`).
+const IMAGE_DOS_SIGNATURE: u16 = 0x5A4D;
+
+/// NT header signature (`PE\0\0`).
+const IMAGE_NT_SIGNATURE: u32 = 0x0000_4550;
+
+/// Optional header magic for PE32 images.
+pub const IMAGE_NT_OPTIONAL_HDR32_MAGIC: u16 = 0x10B;
+
+/// Optional header magic for PE32+ images.
+pub const IMAGE_NT_OPTIONAL_HDR64_MAGIC: u16 = 0x20B;
+
+/// The file is executable (there are no unresolved external references).
+pub const IMAGE_FILE_EXECUTABLE_IMAGE: u16 = 0x0002;
+
+/// The image file is a dynamic-link library (DLL).
+pub const IMAGE_FILE_DLL: u16 = 0x2000;
+
+/// The image does not require a subsystem (device drivers and native processes).
+pub const IMAGE_SUBSYSTEM_NATIVE: u16 = 1;
+
+/// Index of the COM descriptor (CLI header) in the data directory.
+pub const IMAGE_DIRECTORY_ENTRY_COM_DESCRIPTOR: usize = 14;
+
+/// Machine type for x86 images.
+pub const IMAGE_FILE_MACHINE_I386: u16 = 0x014C;
+
+/// Machine type for x64 images.
+pub const IMAGE_FILE_MACHINE_AMD64: u16 = 0x8664;
+
+/// Machine type for ARM64 images.
+pub const IMAGE_FILE_MACHINE_ARM64: u16 = 0xAA64;
+
+/// Size of `IMAGE_FILE_HEADER` in bytes.
+const FILE_HEADER_SIZE: usize = 20;
+
+/// Size of `IMAGE_SECTION_HEADER` in bytes.
+const SECTION_HEADER_SIZE: usize = 40;
+
+/// Reads a little-endian `u16` at `offset`, returning `None` when out of bounds.
+#[inline]
+pub(crate) fn read_u16(buffer: &[u8], offset: usize) -> Option<u16> {
+    let bytes = buffer.get(offset..offset.checked_add(2)?)?;
+    Some(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+/// Reads a little-endian `u32` at `offset`, returning `None` when out of bounds.
+#[inline]
+pub(crate) fn read_u32(buffer: &[u8], offset: usize) -> Option<u32> {
+    let bytes = buffer.get(offset..offset.checked_add(4)?)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// Bitness of a PE image, as declared by the optional header magic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bitness {
+    /// PE32 image (`0x10B`), used by x86 and most AnyCPU assemblies.
+    Bits32,
+
+    /// PE32+ image (`0x20B`), used by x64 and ARM64 assemblies.
+    Bits64,
+}
+
+impl Bitness {
+    /// Returns the bitness of the current process.
+    pub const fn host() -> Self {
+        if cfg!(target_pointer_width = "64") {
+            Self::Bits64
+        } else {
+            Self::Bits32
+        }
     }
+}
 
-    let dos_header = buffer.as_ptr() as *const IMAGE_DOS_HEADER;
-    if (*dos_header).e_magic != IMAGE_DOS_SIGNATURE {
-        return None;
+/// A single entry of the optional header's data directory.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DataDirectory {
+    /// Relative virtual address of the table.
+    pub virtual_address: u32,
+
+    /// Size of the table in bytes.
+    pub size: u32,
+}
+
+impl DataDirectory {
+    /// Returns `true` if the directory points to something.
+    pub fn is_present(&self) -> bool {
+        self.virtual_address != 0 && self.size != 0
     }
+}
+
+/// A section header of the PE image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SectionHeader {
+    /// Section name, padded with zeros.
+    pub name: [u8; 8],
+
+    /// Size of the section when loaded into memory.
+    pub virtual_size: u32,
+
+    /// Address of the section relative to the image base.
+    pub virtual_address: u32,
+
+    /// Size of the initialized data on disk.
+    pub size_of_raw_data: u32,
+
+    /// File offset of the section data.
+    pub pointer_to_raw_data: u32,
+
+    /// Section flags.
+    pub characteristics: u32,
+}
+
+/// PE header fields relevant to hosting .NET assemblies.
+///
+/// The header is parsed directly from the raw bytes and handles both PE32 and PE32+
+/// optional headers, so it has no dependency on the Windows API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeHeader {
+    /// Target machine (`IMAGE_FILE_MACHINE_*`).
+    pub machine: u16,
+
+    /// File header characteristics (`IMAGE_FILE_*`).
+    pub characteristics: u16,
+
+    /// Subsystem required to run the image (`IMAGE_SUBSYSTEM_*`).
+    pub subsystem: u16,
+
+    /// Bitness declared by the optional header magic.
+    pub bitness: Bitness,
+
+    /// COM descriptor directory, pointing at the CLI header.
+    pub cli_directory: DataDirectory,
+
+    /// Section headers of the image.
+    pub sections: Vec<SectionHeader>,
+}
+
+impl PeHeader {
+    /// Parses the PE headers from the given buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `buffer` - A reference to a byte slice representing the potential PE file.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(PeHeader)` - If the buffer contains valid DOS, NT and optional headers.
+    /// * `None` - If the buffer does not represent a valid PE file.
+    pub fn parse(buffer: &[u8]) -> Option<Self> {
+        if read_u16(buffer, 0)? != IMAGE_DOS_SIGNATURE {
+            return None;
+        }
+
+        let nt_offset = read_u32(buffer, 0x3C)? as usize;
+        if read_u32(buffer, nt_offset)? != IMAGE_NT_SIGNATURE {
+            return None;
+        }
+
+        let file_header = nt_offset + 4;
+        let machine = read_u16(buffer, file_header)?;
+        let number_of_sections = read_u16(buffer, file_header + 2)? as usize;
+        let size_of_optional_header = read_u16(buffer, file_header + 16)? as usize;
+        let characteristics = read_u16(buffer, file_header + 18)?;
+
+        // The data directory starts at a different offset depending on the magic,
+        // since PE32+ widens `ImageBase` and the stack/heap sizes to 64 bits.
+        let optional_header = file_header + FILE_HEADER_SIZE;
+        let (bitness, rva_count_offset) = match read_u16(buffer, optional_header)? {
+            IMAGE_NT_OPTIONAL_HDR32_MAGIC => (Bitness::Bits32, 92),
+            IMAGE_NT_OPTIONAL_HDR64_MAGIC => (Bitness::Bits64, 108),
+            _ => return None,
+        };
+
+        let subsystem = read_u16(buffer, optional_header + 68)?;
+        let number_of_rva_and_sizes = read_u32(buffer, optional_header + rva_count_offset)? as usize;
+        let data_directory = optional_header + rva_count_offset + 4;
+        if rva_count_offset + 4 + number_of_rva_and_sizes.min(16) * 8 > size_of_optional_header {
+            return None;
+        }
 
-    if (*dos_header).e_lfanew >= (buffer.len() - size_of::<IMAGE_NT_HEADERS64>()) as i32 {
-        return None;
+        let cli_directory = if number_of_rva_and_sizes > IMAGE_DIRECTORY_ENTRY_COM_DESCRIPTOR {
+            let entry = data_directory + IMAGE_DIRECTORY_ENTRY_COM_DESCRIPTOR * 8;
+            DataDirectory {
+                virtual_address: read_u32(buffer, entry)?,
+                size: read_u32(buffer, entry + 4)?,
+            }
+        } else {
+            DataDirectory::default()
+        };
+
+        let section_table = optional_header + size_of_optional_header;
+        let sections = (0..number_of_sections)
+            .map(|i| {
+                let offset = section_table + i * SECTION_HEADER_SIZE;
+                let name = buffer.get(offset..offset + 8)?.try_into().ok()?;
+                Some(SectionHeader {
+                    name,
+                    virtual_size: read_u32(buffer, offset + 8)?,
+                    virtual_address: read_u32(buffer, offset + 12)?,
+                    size_of_raw_data: read_u32(buffer, offset + 16)?,
+                    pointer_to_raw_data: read_u32(buffer, offset + 20)?,
+                    characteristics: read_u32(buffer, offset + 36)?,
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Self {
+            machine,
+            characteristics,
+            subsystem,
+            bitness,
+            cli_directory,
+            sections,
+        })
     }
 
-    let nt_header = (buffer.as_ptr() as usize + (*dos_header).e_lfanew as usize) as *const IMAGE_NT_HEADERS64;
-    if (*nt_header).Signature != IMAGE_NT_SIGNATURE {
-        return None;
+    /// Returns `true` if the image is marked as a DLL.
+    pub fn is_dll(&self) -> bool {
+        self.characteristics & IMAGE_FILE_DLL != 0
     }
 
-    Some(nt_header)
+    /// Converts a relative virtual address into an offset in the file buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `rva` - The relative virtual address to translate.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(usize)` - The file offset backing the address.
+    /// * `None` - If no section contains the address.
+    pub fn rva_to_offset(&self, rva: u32) -> Option<usize> {
+        self.sections.iter().find_map(|section| {
+            let delta = rva.checked_sub(section.virtual_address)?;
+            if delta < section.size_of_raw_data {
+                Some(section.pointer_to_raw_data as usize + delta as usize)
+            } else {
+                None
+            }
+        })
+    }
 }
 
 /// Checks if the given buffer represents a valid PE executable (non-DLL, non-Native).
-/// 
+///
 /// # Arguments
-/// 
+///
 /// * `buffer` - A reference to a byte slice representing the potential PE file.
-/// 
+///
 /// # Returns
-/// 
+///
 /// * `true` - If the buffer represents a valid PE executable.
 /// * `false` - If the buffer is not a valid PE executable.
 pub(crate) fn is_exe(buffer: &[u8]) -> bool {
-    unsafe {
-        if let Some(nt_header) = get_nt_header(buffer) {
-            let characteristics = (*nt_header).FileHeader.Characteristics;
-
-            return characteristics & IMAGE_FILE_EXECUTABLE_IMAGE != 0
-                && characteristics & IMAGE_FILE_DLL == 0
-                && characteristics & IMAGE_SUBSYSTEM_NATIVE == 0;
-        }
-
-        false
+    if let Some(header) = PeHeader::parse(buffer) {
+        return header.characteristics & IMAGE_FILE_EXECUTABLE_IMAGE != 0
+            && !header.is_dll()
+            && header.subsystem != IMAGE_SUBSYSTEM_NATIVE;
     }
+
+    false
 }
 
 /// Checks if the given buffer represents a valid .NET executable.
-/// 
+///
 /// # Arguments
-/// 
+///
 /// * `buffer` - A reference to a byte slice representing the potential .NET assembly.
-/// 
+///
 /// # Returns
-/// 
+///
 /// * `true` - If the buffer represents a valid .NET executable.
 /// * `false` - If the buffer is not a .NET executable.
 pub(crate) fn is_dotnet(buffer: &[u8]) -> bool {
-    unsafe {
-        if let Some(nt_header) = get_nt_header(buffer) {
-            let com_directory = (*nt_header).OptionalHeader.DataDirectory[IMAGE_DIRECTORY_ENTRY_COM_DESCRIPTOR as usize];
-            return com_directory.VirtualAddress != 0 && com_directory.Size != 0;
-        }
-
-        false
-    }
+    PeHeader::parse(buffer).is_some_and(|header| header.cli_directory.is_present())
 }
 
 /// Validates if the given buffer represents a valid .NET executable.
@@ -96,7 +285,7 @@ pub(crate) fn is_dotnet(buffer: &[u8]) -> bool {
 /// * `buffer` - A reference to a byte slice representing the potential .NET assembly.
 ///
 /// # Returns
-/// 
+///
 /// * `Ok(())` - If the environment is successfully prepared.
 /// * `Err(ClrError)` - If any error occurs during the preparation process.
 pub(crate) fn validate_file(buffer: &[u8]) -> Result<()> {
@@ -110,3 +299,110 @@ pub(crate) fn validate_file(buffer: &[u8]) -> Result<()> {
 
     Ok(())
 }
+
+/// Validates if the given buffer represents a .NET executable or class library.
+///
+/// Unlike `validate_file`, DLLs are accepted, since their methods can be invoked
+/// directly through `RustClr::with_entry`.
+///
+/// # Arguments
+///
+/// * `buffer` - A reference to a byte slice representing the potential .NET assembly.
+///
+/// # Returns
+///
+/// * `Ok(())` - If the buffer is a .NET executable or DLL.
+/// * `Err(ClrError)` - `ClrError::InvalidExecutable` or `ClrError::NotDotNet` otherwise.
+pub(crate) fn validate_assembly(buffer: &[u8]) -> Result<()> {
+    let is_library = PeHeader::parse(buffer).is_some_and(|header| header.is_dll());
+    if !is_library {
+        return validate_file(buffer);
+    }
+
+    if !is_dotnet(buffer) {
+        return Err(ClrError::NotDotNet);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Builds a minimal PE image with a single `.text` section holding `text`,
+    /// mapped at RVA `0x2000` and stored at file offset `0x200`.
+    pub(crate) fn build_pe(magic: u16, machine: u16, characteristics: u16, cli: DataDirectory, text: &[u8]) -> Vec<u8> {
+        let optional_size: usize = if magic == IMAGE_NT_OPTIONAL_HDR64_MAGIC { 240 } else { 224 };
+        let text_size = text.len().max(1).div_ceil(0x200) * 0x200;
+        let mut pe = vec![0u8; 0x200 + text_size];
+        pe[0x200..0x200 + text.len()].copy_from_slice(text);
+        pe[0..2].copy_from_slice(&IMAGE_DOS_SIGNATURE.to_le_bytes());
+        pe[0x3C..0x40].copy_from_slice(&0x80u32.to_le_bytes());
+        pe[0x80..0x84].copy_from_slice(&IMAGE_NT_SIGNATURE.to_le_bytes());
+
+        let fh = 0x84;
+        pe[fh..fh + 2].copy_from_slice(&machine.to_le_bytes());
+        pe[fh + 2..fh + 4].copy_from_slice(&1u16.to_le_bytes());
+        pe[fh + 16..fh + 18].copy_from_slice(&(optional_size as u16).to_le_bytes());
+        pe[fh + 18..fh + 20].copy_from_slice(&characteristics.to_le_bytes());
+
+        let oh = fh + FILE_HEADER_SIZE;
+        let rva_count = if magic == IMAGE_NT_OPTIONAL_HDR64_MAGIC { 108 } else { 92 };
+        pe[oh..oh + 2].copy_from_slice(&magic.to_le_bytes());
+        pe[oh + 68..oh + 70].copy_from_slice(&3u16.to_le_bytes());
+        pe[oh + rva_count..oh + rva_count + 4].copy_from_slice(&16u32.to_le_bytes());
+        let com = oh + rva_count + 4 + IMAGE_DIRECTORY_ENTRY_COM_DESCRIPTOR * 8;
+        pe[com..com + 4].copy_from_slice(&cli.virtual_address.to_le_bytes());
+        pe[com + 4..com + 8].copy_from_slice(&cli.size.to_le_bytes());
+
+        let sh = oh + optional_size;
+        pe[sh..sh + 5].copy_from_slice(b".text");
+        pe[sh + 8..sh + 12].copy_from_slice(&(text.len() as u32).to_le_bytes());
+        pe[sh + 12..sh + 16].copy_from_slice(&0x2000u32.to_le_bytes());
+        pe[sh + 16..sh + 20].copy_from_slice(&(text_size as u32).to_le_bytes());
+        pe[sh + 20..sh + 24].copy_from_slice(&0x200u32.to_le_bytes());
+        pe
+    }
+
+    const CLI: DataDirectory = DataDirectory { virtual_address: 0x2008, size: 0x48 };
+
+    #[test]
+    fn parses_pe32() {
+        let pe = build_pe(IMAGE_NT_OPTIONAL_HDR32_MAGIC, IMAGE_FILE_MACHINE_I386, 0x0102, CLI, &[]);
+        let header = PeHeader::parse(&pe).unwrap();
+        assert_eq!(header.bitness, Bitness::Bits32);
+        assert_eq!(header.machine, IMAGE_FILE_MACHINE_I386);
+        assert_eq!(header.cli_directory, CLI);
+        assert_eq!(header.rva_to_offset(0x2008), Some(0x208));
+        assert!(is_dotnet(&pe) && is_exe(&pe));
+    }
+
+    #[test]
+    fn parses_pe32_plus() {
+        let pe = build_pe(IMAGE_NT_OPTIONAL_HDR64_MAGIC, IMAGE_FILE_MACHINE_AMD64, 0x0022, CLI, &[]);
+        let header = PeHeader::parse(&pe).unwrap();
+        assert_eq!(header.bitness, Bitness::Bits64);
+        assert_eq!(header.machine, IMAGE_FILE_MACHINE_AMD64);
+        assert_eq!(header.cli_directory, CLI);
+        assert!(validate_file(&pe).is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_images() {
+        let dll = build_pe(IMAGE_NT_OPTIONAL_HDR32_MAGIC, IMAGE_FILE_MACHINE_I386, 0x2102, CLI, &[]);
+        assert!(matches!(validate_file(&dll), Err(ClrError::InvalidExecutable)));
+        assert!(validate_assembly(&dll).is_ok());
+
+        let native = build_pe(IMAGE_NT_OPTIONAL_HDR32_MAGIC, IMAGE_FILE_MACHINE_I386, 0x0102, DataDirectory::default(), &[]);
+        assert!(matches!(validate_file(&native), Err(ClrError::NotDotNet)));
+
+        let native_dll = build_pe(IMAGE_NT_OPTIONAL_HDR32_MAGIC, IMAGE_FILE_MACHINE_I386, 0x2102, DataDirectory::default(), &[]);
+        assert!(matches!(validate_assembly(&native_dll), Err(ClrError::NotDotNet)));
+
+        let mut truncated = build_pe(IMAGE_NT_OPTIONAL_HDR32_MAGIC, IMAGE_FILE_MACHINE_I386, 0x0102, CLI, &[]);
+        truncated.truncate(0x100);
+        assert!(PeHeader::parse(&truncated).is_none());
+        assert!(PeHeader::parse(&[]).is_none());
+    }
+}
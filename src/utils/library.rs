@@ -0,0 +1,97 @@
+use std::{ffi::c_void, path::Path};
+use crate::{error::ClrError, Result};
+
+#[cfg(windows)]
+use dinvk::{GetProcAddress, LoadLibraryA};
+
+#[cfg(unix)]
+mod dl {
+    use std::ffi::{c_char, c_int, c_void};
+
+    /// Resolves every symbol when the library is loaded.
+    pub const RTLD_NOW: c_int = 2;
+
+    #[cfg_attr(target_os = "linux", link(name = "dl"))]
+    extern "C" {
+        pub fn dlopen(filename: *const c_char, flag: c_int) -> *mut c_void;
+        pub fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+    }
+}
+
+/// A native library loaded at runtime, such as `hostfxr` or `coreclr`.
+///
+/// The library is never unloaded: a CoreCLR runtime cannot be shut down and
+/// restarted within a process, so its host libraries stay mapped until exit.
+#[derive(Debug)]
+pub(crate) struct Library {
+    /// Handle returned by `LoadLibraryA` or `dlopen`.
+    handle: *mut c_void,
+}
+
+impl Library {
+    /// Loads the library at `path`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Full path of the shared library.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Library)` - The loaded library.
+    /// * `Err(ClrError)` - `ClrError::HostNotFound` if the library cannot be loaded.
+    pub(crate) fn load(path: &Path) -> Result<Self> {
+        let not_found = || ClrError::HostNotFound(path.display().to_string());
+        let name = path.to_str().ok_or_else(not_found)?;
+
+        #[cfg(windows)]
+        let handle = LoadLibraryA(name);
+
+        #[cfg(unix)]
+        let handle = {
+            let name = std::ffi::CString::new(name).map_err(|_| not_found())?;
+            unsafe { dl::dlopen(name.as_ptr(), dl::RTLD_NOW) }
+        };
+
+        match handle.is_null() {
+            true => Err(not_found()),
+            false => Ok(Self { handle }),
+        }
+    }
+
+    /// Resolves an exported function.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the export.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(T)` - The export, cast to the function pointer type `T`.
+    /// * `Err(ClrError)` - `ClrError::HostNotFound` if the library does not export `name`.
+    ///
+    /// # Safety
+    ///
+    /// `T` must be a function pointer type matching the signature of the export.
+    pub(crate) unsafe fn symbol<T: Copy>(&self, name: &'static str) -> Result<T> {
+        assert_eq!(size_of::<T>(), size_of::<*mut c_void>(), "symbols must be cast to function pointers");
+
+        #[cfg(windows)]
+        let address = GetProcAddress(self.handle, name, None);
+
+        #[cfg(unix)]
+        let address = {
+            let symbol = std::ffi::CString::new(name).map_err(|_| ClrError::HostNotFound(name.to_string()))?;
+            dl::dlsym(self.handle, symbol.as_ptr())
+        };
+
+        if address.is_null() {
+            return Err(ClrError::HostNotFound(name.to_string()));
+        }
+
+        Ok(core::mem::transmute_copy::<*mut c_void, T>(&address))
+    }
+}
+
+// The handle is only used to resolve symbols, which the loaders allow from any thread
+unsafe impl Send for Library {}
+unsafe impl Sync for Library {}
@@ -1,11 +1,23 @@
+#[cfg(windows)]
 use windows_sys::Win32::Foundation::{SysAllocString, SysStringLen};
 
 /// Module related to safearray creation
+#[cfg(windows)]
 mod safearray;
+#[cfg(windows)]
 pub use safearray::*;
+
+/// Module converting `VARIANT` results back to Rust types
+#[cfg(windows)]
+mod variant;
+#[cfg(windows)]
+pub use variant::*;
  
-/// Module used to validate that the file corresponds to what is expected
-pub(crate) mod file;
+/// Module used to parse PE headers and validate that the file corresponds to what is expected
+pub mod file;
+
+/// Module used to load the native libraries of the CoreCLR hosts at runtime
+pub(crate) mod library;
 
 /// The `WinStr` trait provides methods for working with BSTRs (Binary String),
 /// a format commonly used in Windows API. BSTRs are wide strings (UTF-16) 
@@ -15,6 +27,7 @@ pub(crate) mod file;
 /// The trait is implemented for `&str`, `String`, and `*const u16`, each with specific 
 /// behavior in converting to BSTR format. Additionally, the `*const u16` implementation 
 /// provides a `to_string` method for converting the BSTR back to a `String`.
+#[cfg(windows)]
 pub trait WinStr {
     /// Converts a Rust string into a BSTR.
     ///
@@ -57,6 +70,7 @@ pub trait WinStr {
     }
 }
 
+#[cfg(windows)]
 impl WinStr for &str {
     /// Converts a `&str` to a BSTR.
     ///
@@ -69,6 +83,7 @@ impl WinStr for &str {
     }
 }
 
+#[cfg(windows)]
 impl WinStr for String {
     /// Converts a `String` to a BSTR.
     ///
@@ -81,6 +96,7 @@ impl WinStr for String {
     }
 }
 
+#[cfg(windows)]
 impl WinStr for *const u16 {
     /// Passes through the BSTR pointer without modification.
     ///
@@ -1,196 +1,155 @@
-use super::WinStr;
+use super::OwnedVariant;
+use crate::Variant;
 use crate::error::ClrError;
 use crate::Result;
 use std::{
     ffi::c_void, 
-    ptr::{copy_nonoverlapping, null_mut}
+    ptr::{copy_nonoverlapping, null_mut, NonNull}
 };
-use windows_sys::Win32::{
-    Foundation::{
-        SysFreeString, VARIANT_FALSE, 
-        VARIANT_TRUE
+use windows_sys::Win32::System::{
+    Com::{SAFEARRAY, SAFEARRAYBOUND}, 
+    Ole::{
+        SafeArrayAccessData, SafeArrayCreate, 
+        SafeArrayCreateVector, SafeArrayDestroy,
+        SafeArrayGetLBound, SafeArrayGetUBound,
+        SafeArrayPutElement, SafeArrayUnaccessData
     }, 
-    System::{
-        Com::{SAFEARRAY, SAFEARRAYBOUND}, 
-        Ole::{
-            SafeArrayAccessData, SafeArrayCreate, 
-            SafeArrayCreateVector, SafeArrayPutElement, 
-            SafeArrayUnaccessData
-        }, 
-        Variant::{
-            VARIANT, VT_ARRAY, VT_BSTR, VT_BOOL, 
-            VT_I4, VT_UI1, VT_VARIANT,
-        } 
-    }
+    Variant::{VT_UI1, VT_VARIANT} 
 };
 
-
-/// Trait to convert various Rust types to Windows COM-compatible `VARIANT` types.
-/// 
-/// This trait is implemented for common Rust types like `String`, `&str`, `bool`, and `i32`.
-pub trait Variant {
-    /// Converts the Rust type to a `VARIANT`.
+/// An owned `SAFEARRAY`, destroyed with `SafeArrayDestroy` when dropped.
+///
+/// Destroying the array also frees its elements: the strings of a `VT_BSTR` array,
+/// the references of a `VT_UNKNOWN` array and the values of a `VT_VARIANT` array.
+/// Arrays passed to COM methods are only borrowed by them, so they are dropped once
+/// the call returns; `into_raw` hands one over to code that destroys it itself.
+#[derive(Debug)]
+pub struct SafeArray(NonNull<SAFEARRAY>);
+
+impl SafeArray {
+    /// Takes ownership of a raw `SAFEARRAY`.
     ///
-    /// # Returns
+    /// # Arguments
     ///
-    /// * The corresponding `VARIANT` structure for the implementing type.
-    fn to_variant(&self) -> VARIANT;
-    
-    /// Returns the `u16` representing the VARIANT type.
+    /// * `array` - The array, e.g. one returned by a COM method.
     ///
     /// # Returns
     ///
-    /// * The type ID for the VARIANT.
-    fn var_type() -> u16;
-}
-
-impl Variant for String {
-    /// Converts a `String` to a BSTR-based `VARIANT`.
-    fn to_variant(&self) -> VARIANT {
-        let bstr = self.to_bstr();
-        let mut variant = unsafe { std::mem::zeroed::<VARIANT>() }; 
-        variant.Anonymous.Anonymous.vt = Self::var_type();
-        variant.Anonymous.Anonymous.Anonymous.bstrVal = bstr;
-
-        variant
+    /// * `Some(SafeArray)` - The owned array.
+    /// * `None` - If `array` is null.
+    ///
+    /// # Safety
+    ///
+    /// `array` must be a valid `SAFEARRAY` that is not destroyed anywhere else.
+    pub unsafe fn from_raw(array: *mut SAFEARRAY) -> Option<Self> {
+        NonNull::new(array).map(Self)
     }
 
-    /// Returns the VARIANT type ID for BSTRs.
-    fn var_type() -> u16 {
-        VT_BSTR
+    /// Returns the raw `SAFEARRAY`, which is still owned by this `SafeArray`.
+    ///
+    /// # Returns
+    ///
+    /// * A pointer valid for as long as this `SafeArray` lives.
+    pub fn as_ptr(&self) -> *mut SAFEARRAY {
+        self.0.as_ptr()
     }
-}
-
-impl Variant for &str {
-    /// Converts a `&str` to a BSTR-based `VARIANT`.
-    fn to_variant(&self) -> VARIANT {
-        let bstr = self.to_bstr();
-        let mut variant = unsafe { std::mem::zeroed::<VARIANT>() }; 
-        variant.Anonymous.Anonymous.vt = Self::var_type();
-        variant.Anonymous.Anonymous.Anonymous.bstrVal = bstr;
 
-        variant
+    /// Releases ownership of the `SAFEARRAY` without destroying it.
+    ///
+    /// # Returns
+    ///
+    /// * The raw `SAFEARRAY`, which the caller must destroy.
+    pub fn into_raw(self) -> *mut SAFEARRAY {
+        let array = self.as_ptr();
+        std::mem::forget(self);
+        array
     }
 
-    /// Returns the VARIANT type ID for BSTRs.
-    fn var_type() -> u16 {
-        VT_BSTR
+    /// Returns the bounds of the first dimension of the array.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok((i32, i32))` - The lower and upper bounds, inclusive.
+    /// * `Err(ClrError)` - If the bounds cannot be read.
+    pub fn bounds(&self) -> Result<(i32, i32)> {
+        bounds(self.as_ptr())
     }
 }
 
-impl Variant for bool {
-    /// Converts a `bool` to a boolean `VARIANT`.
-    fn to_variant(&self) -> VARIANT {
-        let mut variant = unsafe { std::mem::zeroed::<VARIANT>() };
-        variant.Anonymous.Anonymous.vt = Self::var_type();
-        variant.Anonymous.Anonymous.Anonymous.boolVal = if *self {
-            VARIANT_TRUE
-        } else {
-            VARIANT_FALSE
-        };
-
-        variant
-    }
-
-    /// Returns the VARIANT type ID for booleans.
-    fn var_type() -> u16 {
-        VT_BOOL
+impl Drop for SafeArray {
+    fn drop(&mut self) {
+        unsafe { SafeArrayDestroy(self.as_ptr()) };
     }
 }
 
-impl Variant for i32 {
-    /// Converts an `i32` to an integer `VARIANT`.
-    fn to_variant(&self) -> VARIANT {
-        let mut variant = unsafe { std::mem::zeroed::<VARIANT>() };
-        variant.Anonymous.Anonymous.vt = Self::var_type();
-        variant.Anonymous.Anonymous.Anonymous.lVal = *self;
-
-        variant
+/// Returns the bounds of the first dimension of a borrowed `SAFEARRAY`.
+///
+/// # Arguments
+///
+/// * `array` - A valid `SAFEARRAY`.
+///
+/// # Returns
+///
+/// * `Ok((i32, i32))` - The lower and upper bounds, inclusive.
+/// * `Err(ClrError)` - If the bounds cannot be read.
+pub(crate) fn bounds(array: *mut SAFEARRAY) -> Result<(i32, i32)> {
+    let (mut lower, mut upper) = (0, 0);
+    let hr = unsafe { SafeArrayGetLBound(array, 1, &mut lower) };
+    if hr != 0 {
+        return Err(ClrError::ApiError("SafeArrayGetLBound", hr));
     }
 
-    /// Returns the VARIANT type ID for integers.
-    fn var_type() -> u16 {
-        VT_I4
+    let hr = unsafe { SafeArrayGetUBound(array, 1, &mut upper) };
+    if hr != 0 {
+        return Err(ClrError::ApiError("SafeArrayGetUBound", hr));
     }
+
+    Ok((lower, upper))
 }
 
 /// Creates a `SAFEARRAY` from a vector of elements implementing the `Variant` trait.
 ///
+/// The elements are wrapped into a single `VT_ARRAY` argument, as expected by
+/// `Main(System.String[])`.
+///
 /// # Arguments
 ///
 /// * `args` - A vector of elements implementing the `Variant` trait.
 ///
 /// # Returns
 ///
-/// * `Ok(*mut SAFEARRAY)` - The created `SAFEARRAY`.
+/// * `Ok(SafeArray)` - The created `SAFEARRAY`.
 /// * `Err(ClrError)` - If the creation or element insertion into the `SAFEARRAY` fails.
-pub fn create_safe_array_args<T: Variant>(args: Vec<T>) -> Result<*mut SAFEARRAY> {
-    unsafe {
-        let vartype = T::var_type();
-        let psa = SafeArrayCreateVector(vartype, 0, args.len() as u32);
-        if psa.is_null() {
-            return Err(ClrError::NullPointerError("SafeArrayCreateVector"));
-        }
-        
-        for (i, arg) in args.iter().enumerate() {
-            let variant = arg.to_variant();
-            let index = i as i32;
-            let value_ptr = match vartype {
-                VT_BOOL => &variant.Anonymous.Anonymous.Anonymous.boolVal as *const _ as *const c_void,
-                VT_I4 => &variant.Anonymous.Anonymous.Anonymous.lVal as *const _ as *const c_void,
-                VT_BSTR => variant.Anonymous.Anonymous.Anonymous.bstrVal as *const c_void,
-                _ => return Err(ClrError::VariantUnsupported),
-            };
-
-            let hr = SafeArrayPutElement(psa, &index, value_ptr);
-            if hr != 0 {
-                return Err(ClrError::ApiError("SafeArrayPutElement", hr));
-            }
+pub fn create_safe_array_args<T: Variant>(args: Vec<T>) -> Result<SafeArray> {
+    let array = OwnedVariant::try_from(&T::array_value(&args))?;
 
-            if vartype == VT_BSTR {
-                SysFreeString(variant.Anonymous.Anonymous.Anonymous.bstrVal);
-            }
-        }
-        
-        let args = SafeArrayCreateVector(VT_VARIANT, 0, 1);
-        let mut var_array = std::mem::zeroed::<VARIANT>(); 
-        var_array.Anonymous.Anonymous.vt = VT_ARRAY | vartype;
-        var_array.Anonymous.Anonymous.Anonymous.parray = psa;
-
-        let index = 0;
-        let hr = SafeArrayPutElement(
-            args, 
-            &index, 
-            &mut var_array as *const VARIANT as *const c_void
-        );
-        if hr != 0 {
-            return Err(ClrError::ApiError("SafeArrayPutElement (2)", hr));
-        }
-    
-        Ok(args)
-    }
+    // The outer array stores a deep copy of the inner one
+    create_safe_args(vec![array])
 }
 
 /// Creates a `SAFEARRAY` from a vector of `VARIANT` elements.
 ///
+/// The array holds copies of the elements, which are cleared when `args` is dropped.
+///
 /// # Arguments
 ///
 /// * `args` - A vector of `VARIANT` elements.
 ///
 /// # Returns
 ///
-/// * `Ok(*mut SAFEARRAY)` - The created `SAFEARRAY`.
+/// * `Ok(SafeArray)` - The created `SAFEARRAY`.
 /// * `Err(ClrError)` - If the creation or element insertion into the `SAFEARRAY` fails.
-pub fn create_safe_args(args: Vec<VARIANT>) -> Result<*mut SAFEARRAY> {
+pub fn create_safe_args(args: Vec<OwnedVariant>) -> Result<SafeArray> {
     unsafe {       
-        let arg = SafeArrayCreateVector(VT_VARIANT, 0, args.len() as u32);
+        let arg = SafeArray::from_raw(SafeArrayCreateVector(VT_VARIANT, 0, args.len() as u32))
+            .ok_or(ClrError::NullPointerError("SafeArrayCreateVector"))?;
+
         for (i, var) in args.iter().enumerate() {
             let index = i as i32;
-            let mut variant = *var;
             let hr = SafeArrayPutElement(
-                arg, 
+                arg.as_ptr(), 
                 &index, 
-                &mut variant as *const VARIANT as *const c_void
+                var.as_ptr() as *const c_void
             );
             if hr != 0 {
                 return Err(ClrError::ApiError("SafeArrayPutElement", hr));
@@ -209,9 +168,9 @@ pub fn create_safe_args(args: Vec<VARIANT>) -> Result<*mut SAFEARRAY> {
 ///
 /// # Returns
 ///
-/// * `Ok(*mut SAFEARRAY)` - The created `SAFEARRAY`.
+/// * `Ok(SafeArray)` - The created `SAFEARRAY`.
 /// * `Err(ClrError)` - If the creation or data copying into the `SAFEARRAY` fails.
-pub fn create_safe_array_buffer(data: &[u8]) -> Result<*mut SAFEARRAY> {
+pub fn create_safe_array_buffer(data: &[u8]) -> Result<SafeArray> {
     let len: u32 = data.len() as u32;
     let bounds = SAFEARRAYBOUND {
         cElements: data.len() as _,
@@ -219,19 +178,17 @@ pub fn create_safe_array_buffer(data: &[u8]) -> Result<*mut SAFEARRAY> {
     };
 
     unsafe {
-        let sa = SafeArrayCreate(VT_UI1, 1, &bounds);
-        if sa.is_null() {
-            return Err(ClrError::NullPointerError("SafeArrayCreate"));
-        }
+        let sa = SafeArray::from_raw(SafeArrayCreate(VT_UI1, 1, &bounds))
+            .ok_or(ClrError::NullPointerError("SafeArrayCreate"))?;
     
         let mut p_data = null_mut();
-        let mut hr = SafeArrayAccessData(sa, &mut p_data);
+        let mut hr = SafeArrayAccessData(sa.as_ptr(), &mut p_data);
         if hr != 0 {
             return Err(ClrError::ApiError("SafeArrayAccessData", hr));
         }
     
         copy_nonoverlapping(data.as_ptr(), p_data as *mut u8, len as usize);
-        hr = SafeArrayUnaccessData(sa);
+        hr = SafeArrayUnaccessData(sa.as_ptr());
         if hr != 0 {
             return Err(ClrError::ApiError("SafeArrayUnaccessData", hr));
         }
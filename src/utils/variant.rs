@@ -0,0 +1,557 @@
+use std::{ffi::c_void, fmt, ops::Deref, ptr::{null_mut, NonNull}};
+use windows_core::{Interface, GUID};
+use windows_sys::Win32::Foundation::SysAllocStringLen;
+use windows_sys::Win32::System::{
+    Com::SAFEARRAY,
+    Ole::{
+        SafeArrayAccessData, SafeArrayCreateVector, SafeArrayCreateVectorEx,
+        SafeArrayGetDim, SafeArrayUnaccessData,
+    },
+    Variant::{
+        VariantClear, VariantCopy, VARIANT, VT_ARRAY, VT_BOOL, VT_BSTR, VT_EMPTY,
+        VT_I1, VT_I2, VT_I4, VT_I8, VT_INT, VT_NULL, VT_R4, VT_R8, VT_RECORD,
+        VT_UI1, VT_UI2, VT_UI4, VT_UI8, VT_UINT, VT_UNKNOWN, VT_VARIANT,
+    },
+};
+use super::{safearray::bounds, SafeArray, WinStr};
+use crate::{
+    data::IRecordInfo, error::ClrError, marshal::{vt_name, SYSTEM_GUID_TYPE},
+    decode_variant, encode_variant, ObjectRef, RawSafeArray, RawVariant,
+    Result, Value, Variant, VariantAllocator,
+};
+
+/// An owned `VARIANT`, cleared with `VariantClear` when dropped.
+///
+/// Clearing frees whatever the `VARIANT` holds: its `BSTR`, its `SAFEARRAY` or its
+/// reference to a COM object. It dereferences to the raw `VARIANT`, so it can be read
+/// in place or with `FromVariant`, and cloning it copies the value with `VariantCopy`.
+pub struct OwnedVariant(VARIANT);
+
+impl OwnedVariant {
+    /// Takes ownership of a raw `VARIANT`.
+    ///
+    /// # Arguments
+    ///
+    /// * `variant` - The `VARIANT`, e.g. one returned by a COM method.
+    ///
+    /// # Returns
+    ///
+    /// * The owned `VARIANT`.
+    ///
+    /// # Safety
+    ///
+    /// `variant` must be valid for its `vt`, and what it holds must not be freed anywhere else.
+    pub unsafe fn from_raw(variant: VARIANT) -> Self {
+        Self(variant)
+    }
+
+    /// Wraps a COM object into a `VT_UNKNOWN` variant, adding a reference to it.
+    ///
+    /// The CLR unwraps such a variant into the managed object, e.g. a `System.Type`
+    /// passed as an argument.
+    ///
+    /// # Arguments
+    ///
+    /// * `object` - The COM interface to wrap.
+    ///
+    /// # Returns
+    ///
+    /// * The `VT_UNKNOWN` variant.
+    pub fn from_object<T: Interface>(object: &T) -> Self {
+        let mut variant = Self::default();
+        variant.0.Anonymous.Anonymous.vt = VT_UNKNOWN;
+        variant.0.Anonymous.Anonymous.Anonymous.punkVal = object.clone().into_raw();
+        variant
+    }
+
+    /// Wraps an array into a `VT_ARRAY` variant, which takes ownership of it.
+    ///
+    /// # Arguments
+    ///
+    /// * `vt` - The type of the elements, e.g. `VT_UI1` for a `byte[]`.
+    /// * `array` - The array.
+    ///
+    /// # Returns
+    ///
+    /// * The `VT_ARRAY | vt` variant.
+    pub fn from_array(vt: u16, array: SafeArray) -> Self {
+        let mut variant = Self::default();
+        variant.0.Anonymous.Anonymous.vt = VT_ARRAY | vt;
+        variant.0.Anonymous.Anonymous.Anonymous.parray = array.into_raw();
+        variant
+    }
+
+    /// Returns the `vt` of the `VARIANT`, telling which type it holds.
+    pub fn vt(&self) -> u16 {
+        vt(&self.0)
+    }
+
+    /// Returns a pointer to the `VARIANT`, which is still owned by this `OwnedVariant`.
+    pub fn as_ptr(&self) -> *const VARIANT {
+        &self.0
+    }
+
+    /// Returns a mutable pointer to the `VARIANT`, e.g. to receive the result of a COM method.
+    ///
+    /// Anything written through it is cleared when this `OwnedVariant` is dropped.
+    pub fn as_mut_ptr(&mut self) -> *mut VARIANT {
+        &mut self.0
+    }
+
+    /// Releases ownership of the `VARIANT` without clearing it.
+    ///
+    /// # Returns
+    ///
+    /// * The raw `VARIANT`, which the caller must clear.
+    pub fn into_raw(self) -> VARIANT {
+        let variant = self.0;
+        std::mem::forget(self);
+        variant
+    }
+}
+
+impl Default for OwnedVariant {
+    /// Creates a `VT_EMPTY` variant, passed as `null` to COM methods.
+    fn default() -> Self {
+        Self(unsafe { std::mem::zeroed() })
+    }
+}
+
+impl Clone for OwnedVariant {
+    /// Copies the value with `VariantCopy`, duplicating strings and arrays and adding a
+    /// reference to objects. A failed copy leaves the clone `VT_EMPTY`.
+    fn clone(&self) -> Self {
+        let mut copy = Self::default();
+        unsafe { VariantCopy(copy.as_mut_ptr(), self.as_ptr()) };
+        copy
+    }
+}
+
+impl Deref for OwnedVariant {
+    type Target = VARIANT;
+
+    fn deref(&self) -> &VARIANT {
+        &self.0
+    }
+}
+
+impl Drop for OwnedVariant {
+    fn drop(&mut self) {
+        unsafe { VariantClear(&mut self.0) };
+    }
+}
+
+impl fmt::Debug for OwnedVariant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("OwnedVariant").field(&vt_name(self.vt())).finish()
+    }
+}
+
+impl PartialEq for OwnedVariant {
+    /// Compares the raw `VARIANT`s: objects are equal when they are the same object,
+    /// strings and arrays when they are the same allocation.
+    fn eq(&self, other: &Self) -> bool {
+        let bytes = |variant: &Self| unsafe {
+            std::slice::from_raw_parts(variant.as_ptr() as *const u8, size_of::<VARIANT>())
+        };
+
+        bytes(self) == bytes(other)
+    }
+}
+
+/// The type library of `mscorlib` (`mscorlib.tlb`), which describes `System.Guid`.
+const LIBID_MSCORLIB: GUID = GUID::from_u128(0xbed7f4ea_1a96_11d2_8f08_00a0c9a6186d);
+
+/// The GUID of the `System.Guid` record in `mscorlib.tlb`.
+const GUID_SYSTEM_GUID: GUID = GUID::from_u128(SYSTEM_GUID_TYPE);
+
+/// Retrieves the `IRecordInfo` describing `System.Guid`, used to pass GUIDs as `VT_RECORD`.
+///
+/// # Returns
+///
+/// * `Ok(IRecordInfo)` - The record description.
+/// * `Err(ClrError)` - If `mscorlib.tlb` is not registered.
+pub(crate) fn guid_record_info() -> Result<IRecordInfo> {
+    IRecordInfo::from_guids(&LIBID_MSCORLIB, 2, 4, &GUID_SYSTEM_GUID)
+}
+
+// The OLE functions read and write the layout `encode_variant` and `decode_variant` work on
+const _: () = assert!(size_of::<RawVariant>() == size_of::<VARIANT>());
+
+/// Allocates encoded `VARIANT`s with the OLE functions, so that `VariantClear` frees them.
+struct OleAllocator;
+
+unsafe impl VariantAllocator for OleAllocator {
+    fn alloc_string(&mut self, text: &[u16]) -> Result<*mut u16> {
+        let bstr = unsafe { SysAllocStringLen(text.as_ptr(), text.len() as u32) };
+        if bstr.is_null() {
+            return Err(ClrError::NullPointerError("SysAllocStringLen"));
+        }
+
+        Ok(bstr as *mut u16)
+    }
+
+    fn alloc_array(&mut self, vt: u16, len: u32) -> Result<NonNull<RawSafeArray>> {
+        let array = if vt == VT_RECORD {
+            // Record arrays keep the `IRecordInfo` of their type, which is `System.Guid`
+            let record_info = guid_record_info()?;
+            unsafe { SafeArrayCreateVectorEx(vt, 0, len, Interface::as_raw(&record_info)) }
+        } else {
+            unsafe { SafeArrayCreateVector(vt, 0, len) }
+        };
+
+        NonNull::new(array.cast()).ok_or(ClrError::NullPointerError("SafeArrayCreateVector"))
+    }
+
+    fn alloc_guid(&mut self, guid: &[u8; 16]) -> Result<[*mut c_void; 2]> {
+        let record_info = guid_record_info()?;
+        let record = record_info.RecordCreateCopy(guid.as_ptr().cast())?;
+        Ok([record, record_info.into_raw()])
+    }
+
+    fn retain(&mut self, object: &ObjectRef) -> *mut c_void {
+        object.clone().into_raw()
+    }
+
+    fn copy_variant(&mut self, source: &OwnedVariant, variant: &mut RawVariant) -> Result<()> {
+        let hr = unsafe { VariantCopy(variant as *mut RawVariant as *mut VARIANT, source.as_ptr()) };
+        if hr != 0 {
+            return Err(ClrError::ApiError("VariantCopy", hr));
+        }
+
+        Ok(())
+    }
+}
+
+impl TryFrom<&Value> for OwnedVariant {
+    type Error = ClrError;
+
+    /// Converts a `Value` to the `VARIANT` passed to the CLR.
+    fn try_from(value: &Value) -> Result<Self> {
+        // On failure, dropping the variant frees whatever was allocated for it
+        let mut variant = Self::default();
+        encode_variant(value, unsafe { &mut *(variant.as_mut_ptr() as *mut RawVariant) }, &mut OleAllocator)?;
+        Ok(variant)
+    }
+}
+
+impl Variant for OwnedVariant {
+    /// Passes the `VARIANT` as is, through a copy.
+    fn to_value(&self) -> Value {
+        Value::Variant(self.clone())
+    }
+
+    fn to_variant(&self) -> Result<OwnedVariant> {
+        Ok(self.clone())
+    }
+
+    /// Returns the VARIANT type ID for values of any type.
+    fn var_type() -> u16 {
+        VT_VARIANT
+    }
+}
+
+/// Trait to convert a `VARIANT`, such as the result of `_MethodInfo::invoke`, back to a Rust type.
+///
+/// This is the inverse of `Variant`. The `vt` of the `VARIANT` is checked before any field of
+/// its union is read, so a mismatch fails with `ClrError::VariantTypeMismatch` instead of
+/// returning garbage.
+///
+/// # Examples
+///
+/// ```ignore
+/// use rustclr::FromVariant;
+///
+/// let result = method.invoke(None, None)?;
+/// let text = String::from_variant(&result)?;
+/// let count = Option::<i64>::from_variant(&result)?;
+/// ```
+pub trait FromVariant: Sized {
+    /// Converts the `VARIANT` to the Rust type.
+    ///
+    /// # Arguments
+    ///
+    /// * `variant` - The `VARIANT` to read. It is not cleared.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Self)` - The converted value.
+    /// * `Err(ClrError)` - `ClrError::VariantTypeMismatch` if `vt` does not hold this type,
+    ///   or an error from reading a `SAFEARRAY`.
+    fn from_variant(variant: &VARIANT) -> Result<Self>;
+}
+
+/// Returns the `vt` of a `VARIANT`.
+fn vt(variant: &VARIANT) -> u16 {
+    unsafe { variant.Anonymous.Anonymous.vt }
+}
+
+/// Builds the error for a `VARIANT` that does not hold the `expected` type.
+fn mismatch(expected: &'static str, variant: &VARIANT) -> ClrError {
+    ClrError::VariantTypeMismatch(expected, vt_name(vt(variant)))
+}
+
+/// Reads any integer `VARIANT` as an `i128`, wide enough for every integer type.
+fn integer(variant: &VARIANT) -> Option<i128> {
+    let value = unsafe { &variant.Anonymous.Anonymous.Anonymous };
+    unsafe {
+        match vt(variant) {
+            VT_I1 => Some(value.cVal.into()),
+            VT_I2 => Some(value.iVal.into()),
+            VT_I4 => Some(value.lVal.into()),
+            VT_INT => Some(value.intVal.into()),
+            VT_I8 => Some(value.llVal.into()),
+            VT_UI1 => Some(value.bVal.into()),
+            VT_UI2 => Some(value.uiVal.into()),
+            VT_UI4 => Some(value.ulVal.into()),
+            VT_UINT => Some(value.uintVal.into()),
+            VT_UI8 => Some(value.ullVal.into()),
+            _ => None,
+        }
+    }
+}
+
+/// Implements `FromVariant` for integer types, accepting any integer `vt` whose value fits.
+macro_rules! impl_from_variant_integer {
+    ($($ty:ty => $name:literal),* $(,)?) => {
+        $(
+            impl FromVariant for $ty {
+                fn from_variant(variant: &VARIANT) -> Result<Self> {
+                    integer(variant)
+                        .and_then(|value| <$ty>::try_from(value).ok())
+                        .ok_or_else(|| mismatch($name, variant))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_variant_integer! {
+    i8 => "i8",
+    i16 => "i16",
+    i32 => "i32",
+    i64 => "i64",
+    u8 => "u8",
+    u16 => "u16",
+    u32 => "u32",
+    u64 => "u64",
+}
+
+impl FromVariant for f32 {
+    fn from_variant(variant: &VARIANT) -> Result<Self> {
+        match vt(variant) {
+            VT_R4 => Ok(unsafe { variant.Anonymous.Anonymous.Anonymous.fltVal }),
+            _ => Err(mismatch("f32", variant)),
+        }
+    }
+}
+
+impl FromVariant for f64 {
+    fn from_variant(variant: &VARIANT) -> Result<Self> {
+        match vt(variant) {
+            VT_R8 => Ok(unsafe { variant.Anonymous.Anonymous.Anonymous.dblVal }),
+            VT_R4 => Ok(unsafe { variant.Anonymous.Anonymous.Anonymous.fltVal }.into()),
+            _ => Err(mismatch("f64", variant)),
+        }
+    }
+}
+
+impl FromVariant for bool {
+    fn from_variant(variant: &VARIANT) -> Result<Self> {
+        match vt(variant) {
+            VT_BOOL => Ok(unsafe { variant.Anonymous.Anonymous.Anonymous.boolVal } != 0),
+            _ => Err(mismatch("bool", variant)),
+        }
+    }
+}
+
+impl FromVariant for String {
+    fn from_variant(variant: &VARIANT) -> Result<Self> {
+        match vt(variant) {
+            VT_BSTR => Ok(unsafe { variant.Anonymous.Anonymous.Anonymous.bstrVal }.to_string()),
+            _ => Err(mismatch("String", variant)),
+        }
+    }
+}
+
+impl FromVariant for Value {
+    /// Decodes any `VARIANT` `decode_variant` supports, copying what it points to.
+    fn from_variant(variant: &VARIANT) -> Result<Self> {
+        unsafe { decode_variant(&*(variant as *const VARIANT as *const RawVariant)) }
+    }
+}
+
+impl FromVariant for () {
+    /// Accepts the `VT_EMPTY` (or `VT_NULL`) returned by `void` methods.
+    fn from_variant(variant: &VARIANT) -> Result<Self> {
+        match vt(variant) {
+            VT_EMPTY | VT_NULL => Ok(()),
+            _ => Err(mismatch("()", variant)),
+        }
+    }
+}
+
+impl<T: FromVariant> FromVariant for Option<T> {
+    /// Maps `VT_EMPTY` and `VT_NULL` (a `null` reference) to `None`.
+    fn from_variant(variant: &VARIANT) -> Result<Self> {
+        match vt(variant) {
+            VT_EMPTY | VT_NULL => Ok(None),
+            _ => T::from_variant(variant).map(Some),
+        }
+    }
+}
+
+impl FromVariant for Vec<u8> {
+    /// Reads a `byte[]` (`VT_ARRAY | VT_UI1`).
+    fn from_variant(variant: &VARIANT) -> Result<Self> {
+        if vt(variant) != VT_ARRAY | VT_UI1 {
+            return Err(mismatch("Vec<u8>", variant));
+        }
+
+        let array = unsafe { variant.Anonymous.Anonymous.Anonymous.parray };
+        with_elements::<u8, _>(array, |bytes| bytes.to_vec())
+    }
+}
+
+impl FromVariant for Vec<String> {
+    /// Reads a `string[]` (`VT_ARRAY | VT_BSTR`), or an `object[]` holding only strings.
+    fn from_variant(variant: &VARIANT) -> Result<Self> {
+        let array = unsafe { variant.Anonymous.Anonymous.Anonymous.parray };
+        match vt(variant) {
+            vt if vt == VT_ARRAY | VT_BSTR => {
+                with_elements::<*const u16, _>(array, |strings| strings.iter().map(|bstr| bstr.to_string()).collect())
+            }
+            vt if vt == VT_ARRAY | VT_VARIANT => {
+                with_elements::<VARIANT, _>(array, |items| items.iter().map(String::from_variant).collect())?
+            }
+            _ => Err(mismatch("Vec<String>", variant)),
+        }
+    }
+}
+
+/// Gives `read` the elements of a one-dimensional `SAFEARRAY`, while its data is locked.
+///
+/// # Arguments
+///
+/// * `array` - The `SAFEARRAY`, whose elements must be of type `T`.
+/// * `read` - Reads the elements.
+///
+/// # Returns
+///
+/// * `Ok(R)` - What `read` returned.
+/// * `Err(ClrError)` - If the array is null, not one-dimensional, or cannot be locked.
+fn with_elements<T, R>(array: *mut SAFEARRAY, read: impl FnOnce(&[T]) -> R) -> Result<R> {
+    if array.is_null() {
+        return Err(ClrError::NullPointerError("SAFEARRAY"));
+    }
+
+    unsafe {
+        if SafeArrayGetDim(array) != 1 {
+            return Err(ClrError::SafeArrayError("expected a one-dimensional array".to_string()));
+        }
+
+        let (lower, upper) = bounds(array)?;
+        let mut data: *mut c_void = null_mut();
+        let hr = SafeArrayAccessData(array, &mut data);
+        if hr != 0 {
+            return Err(ClrError::ApiError("SafeArrayAccessData", hr));
+        }
+
+        let len = usize::try_from(upper - lower + 1).unwrap_or(0);
+        let elements = match len {
+            0 => &[][..],
+            _ => std::slice::from_raw_parts(data as *const T, len),
+        };
+
+        let result = read(elements);
+        SafeArrayUnaccessData(array);
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use windows_sys::Win32::System::Variant::VT_DECIMAL;
+    use crate::{create_safe_array_buffer, Variant};
+
+    /// Builds a scalar `VARIANT` with the given `vt`, filling its union through `set`.
+    fn variant(vt: u16, set: impl FnOnce(&mut VARIANT)) -> VARIANT {
+        let mut variant = unsafe { std::mem::zeroed::<VARIANT>() };
+        variant.Anonymous.Anonymous.vt = vt;
+        set(&mut variant);
+        variant
+    }
+
+    #[test]
+    fn converts_scalars() {
+        let long = variant(VT_I8, |v| v.Anonymous.Anonymous.Anonymous.llVal = -5);
+        assert_eq!(i64::from_variant(&long).unwrap(), -5);
+        assert_eq!(i8::from_variant(&long).unwrap(), -5);
+        assert!(matches!(u32::from_variant(&long), Err(ClrError::VariantTypeMismatch("u32", _))));
+
+        let double = variant(VT_R4, |v| v.Anonymous.Anonymous.Anonymous.fltVal = 1.5);
+        assert_eq!(f64::from_variant(&double).unwrap(), 1.5);
+        assert_eq!(f32::from_variant(&double).unwrap(), 1.5);
+
+        assert!(bool::from_variant(&true.to_variant().unwrap()).unwrap());
+        assert_eq!(String::from_variant(&"text".to_variant().unwrap()).unwrap(), "text");
+        assert_eq!(i32::from_variant(&7.to_variant().unwrap()).unwrap(), 7);
+    }
+
+    #[test]
+    fn converts_empty_and_null() {
+        let empty = variant(VT_EMPTY, |_| {});
+        assert!(<()>::from_variant(&empty).is_ok());
+        assert_eq!(Option::<String>::from_variant(&empty).unwrap(), None);
+        assert_eq!(Option::<i32>::from_variant(&7.to_variant().unwrap()).unwrap(), Some(7));
+
+        let error = String::from_variant(&empty).unwrap_err();
+        assert_eq!(error.to_string(), "Expected a VARIANT holding String, found VT_EMPTY");
+    }
+
+    #[test]
+    fn converts_byte_arrays() {
+        let bytes = OwnedVariant::from_array(VT_UI1, create_safe_array_buffer(&[1, 2, 3]).unwrap());
+        assert_eq!(Vec::<u8>::from_variant(&bytes).unwrap(), [1, 2, 3]);
+        assert!(matches!(Vec::<String>::from_variant(&bytes), Err(ClrError::VariantTypeMismatch(_, found)) if found == "VT_ARRAY | VT_UI1"));
+    }
+
+    #[test]
+    fn owned_variants_copy_their_values() {
+        let text = "text".to_variant().unwrap();
+        let copy = text.clone();
+        assert_ne!(unsafe { text.Anonymous.Anonymous.Anonymous.bstrVal }, unsafe { copy.Anonymous.Anonymous.Anonymous.bstrVal });
+        drop(text);
+        assert_eq!(String::from_variant(&copy).unwrap(), "text");
+
+        let raw = copy.into_raw();
+        let copy = unsafe { OwnedVariant::from_raw(raw) };
+        assert_eq!(format!("{copy:?}"), "OwnedVariant(\"VT_BSTR\")");
+        assert_eq!(OwnedVariant::default().vt(), VT_EMPTY);
+    }
+
+    #[test]
+    fn converts_values_to_variants() {
+        let decimal = "-1.5".parse::<crate::Decimal>().unwrap().to_variant().unwrap();
+        assert_eq!(decimal.vt(), VT_DECIMAL);
+        unsafe {
+            assert_eq!(decimal.Anonymous.decVal.Anonymous1.Anonymous.scale, 1);
+            assert_eq!(decimal.Anonymous.decVal.Anonymous1.Anonymous.sign, 0x80);
+            assert_eq!(decimal.Anonymous.decVal.Anonymous2.Lo64, 15);
+        }
+
+        let date = std::time::UNIX_EPOCH.to_variant().unwrap();
+        assert_eq!(f64::from_variant(&date).unwrap_err().to_string(), "Expected a VARIANT holding f64, found VT_DATE");
+        assert_eq!(unsafe { date.Anonymous.Anonymous.Anonymous.date }, 25569.0);
+
+        assert_eq!(u16::from_variant(&'\u{1F600}'.to_variant().unwrap()).unwrap(), 0xfffd);
+        assert_eq!(u64::from_variant(&u64::MAX.to_variant().unwrap()).unwrap(), u64::MAX);
+        assert_eq!(Vec::<u8>::from_variant(&vec![1u8, 2].to_variant().unwrap()).unwrap(), [1, 2]);
+        assert_eq!(Vec::<String>::from_variant(&vec!["a", "b"].to_variant().unwrap()).unwrap(), ["a", "b"]);
+        assert_eq!(None::<i32>.to_variant().unwrap().vt(), VT_NULL);
+
+        let value = Value::Array(VT_VARIANT, vec![Value::I64(-1), Value::String("text".to_string()), Value::Null]);
+        assert_eq!(Value::from_variant(&value.to_variant().unwrap()).unwrap(), value);
+    }
+}
@@ -0,0 +1,635 @@
+use std::{fmt, str::FromStr};
+use std::time::SystemTime;
+use uuid::Uuid;
+use crate::{error::ClrError, ObjectRef, Result};
+#[cfg(windows)]
+use crate::{create_safe_args, OwnedVariant, SafeArray};
+
+/// The `VARENUM` type IDs of the `VARIANT`s exchanged with the CLR.
+pub mod vt {
+    pub const EMPTY: u16 = 0;
+    pub const NULL: u16 = 1;
+    pub const I2: u16 = 2;
+    pub const I4: u16 = 3;
+    pub const R4: u16 = 4;
+    pub const R8: u16 = 5;
+    pub const DATE: u16 = 7;
+    pub const BSTR: u16 = 8;
+    pub const DISPATCH: u16 = 9;
+    pub const BOOL: u16 = 11;
+    pub const VARIANT: u16 = 12;
+    pub const UNKNOWN: u16 = 13;
+    pub const DECIMAL: u16 = 14;
+    pub const I1: u16 = 16;
+    pub const UI1: u16 = 17;
+    pub const UI2: u16 = 18;
+    pub const UI4: u16 = 19;
+    pub const I8: u16 = 20;
+    pub const UI8: u16 = 21;
+    pub const INT: u16 = 22;
+    pub const UINT: u16 = 23;
+    pub const RECORD: u16 = 36;
+    pub const ARRAY: u16 = 0x2000;
+    pub const BYREF: u16 = 0x4000;
+}
+
+/// A .NET argument, independent of its COM representation.
+///
+/// Arguments are converted to `Value`s first, which is plain Rust and can be checked on
+/// any platform, and only then to the `VARIANT`s passed to the CLR.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    /// A `null` reference (`VT_EMPTY`).
+    Empty,
+
+    /// A `System.DBNull` (`VT_NULL`), which is what `None` is passed as.
+    Null,
+
+    /// A `System.Boolean` (`VT_BOOL`).
+    Bool(bool),
+
+    /// A `System.SByte` (`VT_I1`).
+    I8(i8),
+
+    /// A `System.Int16` (`VT_I2`).
+    I16(i16),
+
+    /// A `System.Int32` (`VT_I4`).
+    I32(i32),
+
+    /// A `System.Int64` (`VT_I8`).
+    I64(i64),
+
+    /// A `System.Byte` (`VT_UI1`).
+    U8(u8),
+
+    /// A `System.UInt16` (`VT_UI2`).
+    U16(u16),
+
+    /// A `System.UInt32` (`VT_UI4`).
+    U32(u32),
+
+    /// A `System.UInt64` (`VT_UI8`).
+    U64(u64),
+
+    /// A `System.Single` (`VT_R4`).
+    F32(f32),
+
+    /// A `System.Double` (`VT_R8`).
+    F64(f64),
+
+    /// A `System.Char` (`VT_UI2`). Characters outside the Basic Multilingual Plane do not
+    /// fit in one UTF-16 code unit and are passed as U+FFFD.
+    Char(char),
+
+    /// A `System.String` (`VT_BSTR`).
+    String(String),
+
+    /// A `System.Decimal` (`VT_DECIMAL`).
+    Decimal(Decimal),
+
+    /// A `System.DateTime` (`VT_DATE`).
+    Date(SystemTime),
+
+    /// A `System.Guid` (`VT_RECORD`).
+    Guid(Uuid),
+
+    /// A `System.Byte[]` (`VT_ARRAY | VT_UI1`).
+    Bytes(Vec<u8>),
+
+    /// A one-dimensional array (`VT_ARRAY | vt`) of elements of type `vt`, which are
+    /// `VARIANT`s themselves when `vt` is `VT_VARIANT`.
+    Array(u16, Vec<Value>),
+
+    /// A reference to a COM object (`VT_UNKNOWN`), which the CLR unwraps into the managed object.
+    Object(ObjectRef),
+
+    /// A `VARIANT` passed as is, such as an object returned by a previous invocation.
+    #[cfg(windows)]
+    Variant(OwnedVariant),
+}
+
+impl Value {
+    /// Returns the `VARENUM` type the value is encoded as.
+    ///
+    /// # Returns
+    ///
+    /// * The `vt` of the `VARIANT`, e.g. `VT_ARRAY | VT_BSTR` for an array of strings.
+    pub fn vt(&self) -> u16 {
+        match self {
+            Value::Empty => vt::EMPTY,
+            Value::Null => vt::NULL,
+            Value::Bool(_) => vt::BOOL,
+            Value::I8(_) => vt::I1,
+            Value::I16(_) => vt::I2,
+            Value::I32(_) => vt::I4,
+            Value::I64(_) => vt::I8,
+            Value::U8(_) => vt::UI1,
+            Value::U16(_) | Value::Char(_) => vt::UI2,
+            Value::U32(_) => vt::UI4,
+            Value::U64(_) => vt::UI8,
+            Value::F32(_) => vt::R4,
+            Value::F64(_) => vt::R8,
+            Value::String(_) => vt::BSTR,
+            Value::Decimal(_) => vt::DECIMAL,
+            Value::Date(_) => vt::DATE,
+            Value::Guid(_) => vt::RECORD,
+            Value::Bytes(_) => vt::ARRAY | vt::UI1,
+            Value::Array(element_vt, _) => vt::ARRAY | element_vt,
+            Value::Object(_) => vt::UNKNOWN,
+            #[cfg(windows)]
+            Value::Variant(variant) => variant.vt(),
+        }
+    }
+}
+
+/// A `System.Decimal`: a 96-bit integer scaled down by a power of ten.
+///
+/// # Examples
+///
+/// ```ignore
+/// use rustclr::Decimal;
+///
+/// let price: Decimal = "-12.50".parse()?;
+/// assert_eq!((price.mantissa(), price.scale()), (-1250, 2));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Decimal {
+    /// The unscaled value, at most 96 bits in magnitude.
+    mantissa: i128,
+
+    /// The number of decimal places.
+    scale: u8,
+}
+
+impl Decimal {
+    /// The largest magnitude of the mantissa, 2^96 - 1.
+    pub const MAX_MANTISSA: i128 = (1 << 96) - 1;
+
+    /// The largest number of decimal places.
+    pub const MAX_SCALE: u8 = 28;
+
+    /// Creates the decimal `mantissa / 10^scale`.
+    ///
+    /// # Arguments
+    ///
+    /// * `mantissa` - The unscaled value.
+    /// * `scale` - The number of decimal places.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Decimal)` - The decimal.
+    /// * `Err(ClrError)` - If the mantissa exceeds 96 bits or the scale exceeds 28.
+    pub fn new(mantissa: i128, scale: u8) -> Result<Self> {
+        if mantissa.unsigned_abs() > Self::MAX_MANTISSA as u128 || scale > Self::MAX_SCALE {
+            return Err(ClrError::InvalidDecimal(format!("{mantissa}e-{scale}")));
+        }
+
+        Ok(Self { mantissa, scale })
+    }
+
+    /// Returns the unscaled value.
+    pub fn mantissa(&self) -> i128 {
+        self.mantissa
+    }
+
+    /// Returns the number of decimal places.
+    pub fn scale(&self) -> u8 {
+        self.scale
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = ClrError;
+
+    /// Parses a decimal such as `-12.50`, keeping the number of decimal places.
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || ClrError::InvalidDecimal(s.to_string());
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+
+        let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        if digits.is_empty() || digits == "." || !(integer.bytes().chain(fraction.bytes())).all(|b| b.is_ascii_digit()) {
+            return Err(invalid());
+        }
+
+        let mut mantissa = 0i128;
+        for digit in integer.bytes().chain(fraction.bytes()) {
+            mantissa = mantissa.checked_mul(10)
+                .and_then(|m| m.checked_add(i128::from(digit - b'0')))
+                .ok_or_else(invalid)?;
+        }
+
+        let scale = u8::try_from(fraction.len()).map_err(|_| invalid())?;
+        Self::new(if negative { -mantissa } else { mantissa }, scale).map_err(|_| invalid())
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let scale = usize::from(self.scale);
+        let digits = format!("{:0>width$}", self.mantissa.unsigned_abs(), width = scale + 1);
+        let (integer, fraction) = digits.split_at(digits.len() - scale);
+        let sign = if self.mantissa < 0 { "-" } else { "" };
+
+        if fraction.is_empty() {
+            write!(f, "{sign}{integer}")
+        } else {
+            write!(f, "{sign}{integer}.{fraction}")
+        }
+    }
+}
+
+/// Returns the type of the elements of an array of values of type `vt`: arrays are
+/// nested as `VARIANT`s.
+fn element_type(vt: u16) -> u16 {
+    if vt & vt::ARRAY != 0 { vt::VARIANT } else { vt }
+}
+
+/// Trait to convert various Rust types to .NET arguments.
+///
+/// This trait is implemented for strings, `bool`, `char`, the integer and floating point types,
+/// `Decimal`, `SystemTime`, GUIDs, `Option`s (with `None` passed as `DBNull`), and vectors and
+/// slices of any of them.
+pub trait Variant {
+    /// Converts the Rust type to a `Value`.
+    ///
+    /// # Returns
+    ///
+    /// * The `Value` the type is passed as.
+    fn to_value(&self) -> Value;
+
+    /// Converts the Rust type to a `VARIANT`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(OwnedVariant)` - The corresponding `VARIANT`, cleared when it is dropped.
+    /// * `Err(ClrError)` - If an array or record for the value cannot be created.
+    #[cfg(windows)]
+    fn to_variant(&self) -> Result<OwnedVariant> {
+        OwnedVariant::try_from(&self.to_value())
+    }
+
+    /// Returns the `u16` representing the VARIANT type.
+    ///
+    /// # Returns
+    ///
+    /// * The type ID for the VARIANT.
+    fn var_type() -> u16
+    where
+        Self: Sized;
+
+    /// Converts a slice of the Rust type to an array `Value`.
+    ///
+    /// # Arguments
+    ///
+    /// * `items` - The elements of the array.
+    ///
+    /// # Returns
+    ///
+    /// * The `Value::Array` holding the elements.
+    fn array_value(items: &[Self]) -> Value
+    where
+        Self: Sized,
+    {
+        Value::Array(element_type(Self::var_type()), items.iter().map(Variant::to_value).collect())
+    }
+}
+
+impl Variant for String {
+    fn to_value(&self) -> Value {
+        Value::String(self.clone())
+    }
+
+    /// Returns the VARIANT type ID for BSTRs.
+    fn var_type() -> u16 {
+        vt::BSTR
+    }
+}
+
+impl Variant for &str {
+    fn to_value(&self) -> Value {
+        Value::String(self.to_string())
+    }
+
+    /// Returns the VARIANT type ID for BSTRs.
+    fn var_type() -> u16 {
+        vt::BSTR
+    }
+}
+
+impl Variant for char {
+    fn to_value(&self) -> Value {
+        Value::Char(*self)
+    }
+
+    /// Returns the VARIANT type ID for UTF-16 code units.
+    fn var_type() -> u16 {
+        vt::UI2
+    }
+}
+
+impl Variant for u8 {
+    fn to_value(&self) -> Value {
+        Value::U8(*self)
+    }
+
+    /// Returns the VARIANT type ID for bytes.
+    fn var_type() -> u16 {
+        vt::UI1
+    }
+
+    /// Byte arrays are kept as one buffer rather than a `Value` per byte.
+    fn array_value(items: &[Self]) -> Value {
+        Value::Bytes(items.to_vec())
+    }
+}
+
+/// Implements `Variant` for types that are copied into a `Value` variant as is.
+macro_rules! impl_variant {
+    ($($ty:ty => $variant:ident($vt:ident)),+ $(,)?) => {
+        $(
+            impl Variant for $ty {
+                fn to_value(&self) -> Value {
+                    Value::$variant(*self)
+                }
+
+                fn var_type() -> u16 {
+                    vt::$vt
+                }
+            }
+        )+
+    };
+}
+
+impl_variant! {
+    bool => Bool(BOOL),
+    i8 => I8(I1),
+    i16 => I16(I2),
+    i32 => I32(I4),
+    i64 => I64(I8),
+    u16 => U16(UI2),
+    u32 => U32(UI4),
+    u64 => U64(UI8),
+    f32 => F32(R4),
+    f64 => F64(R8),
+    Decimal => Decimal(DECIMAL),
+    SystemTime => Date(DATE),
+    Uuid => Guid(RECORD),
+}
+
+#[cfg(windows)]
+impl Variant for windows_core::GUID {
+    fn to_value(&self) -> Value {
+        Value::Guid(Uuid::from_u128(self.to_u128()))
+    }
+
+    /// Returns the VARIANT type ID for records, which is how `System.Guid` is passed.
+    fn var_type() -> u16 {
+        vt::RECORD
+    }
+}
+
+impl Variant for ObjectRef {
+    fn to_value(&self) -> Value {
+        Value::Object(self.clone())
+    }
+
+    /// Returns the VARIANT type ID for COM objects.
+    fn var_type() -> u16 {
+        vt::UNKNOWN
+    }
+}
+
+impl<T: Variant> Variant for Option<T> {
+    fn to_value(&self) -> Value {
+        self.as_ref().map_or(Value::Null, Variant::to_value)
+    }
+
+    /// Returns the VARIANT type ID for values of any type, since `None` is `VT_NULL`.
+    fn var_type() -> u16 {
+        vt::VARIANT
+    }
+}
+
+impl<T: Variant> Variant for Vec<T> {
+    fn to_value(&self) -> Value {
+        T::array_value(self)
+    }
+
+    /// Returns the VARIANT type ID for arrays of `T`.
+    fn var_type() -> u16 {
+        vt::ARRAY | element_type(T::var_type())
+    }
+}
+
+impl<T: Variant> Variant for &[T] {
+    fn to_value(&self) -> Value {
+        T::array_value(self)
+    }
+
+    /// Returns the VARIANT type ID for arrays of `T`.
+    fn var_type() -> u16 {
+        vt::ARRAY | element_type(T::var_type())
+    }
+}
+
+impl Variant for Value {
+    fn to_value(&self) -> Value {
+        self.clone()
+    }
+
+    /// Returns the VARIANT type ID for values of any type.
+    fn var_type() -> u16 {
+        vt::VARIANT
+    }
+}
+
+impl Variant for Box<dyn Variant> {
+    fn to_value(&self) -> Value {
+        self.as_ref().to_value()
+    }
+
+    /// Returns the VARIANT type ID for values of any type.
+    fn var_type() -> u16 {
+        vt::VARIANT
+    }
+}
+
+/// Trait for the argument lists accepted by `_Type::invoke` and `_MethodInfo::invoke`.
+///
+/// It is implemented for `()` (no arguments), for tuples of up to 12 elements implementing
+/// `Variant`, which may all have different types, and for vectors such as `Vec<Box<dyn Variant>>`.
+/// The `args!` macro builds a list of any length.
+pub trait IntoArgs {
+    /// Converts the arguments to `Value`s, in order.
+    ///
+    /// # Returns
+    ///
+    /// * The arguments.
+    fn into_args(self) -> Vec<Value>;
+
+    /// Packs the arguments into a `SAFEARRAY` of `VARIANT`s.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(SafeArray))` - The arguments.
+    /// * `Ok(None)` - If there are no arguments, which is passed as a null array.
+    /// * `Err(ClrError)` - If the `SAFEARRAY` or one of its elements cannot be created.
+    #[cfg(windows)]
+    fn into_safe_array(self) -> Result<Option<SafeArray>>
+    where
+        Self: Sized,
+    {
+        let args = self.into_args();
+        if args.is_empty() {
+            return Ok(None);
+        }
+
+        let variants = args.iter().map(OwnedVariant::try_from).collect::<Result<Vec<_>>>()?;
+        create_safe_args(variants).map(Some)
+    }
+}
+
+impl IntoArgs for () {
+    fn into_args(self) -> Vec<Value> {
+        Vec::new()
+    }
+}
+
+impl<T: Variant> IntoArgs for Vec<T> {
+    fn into_args(self) -> Vec<Value> {
+        self.iter().map(Variant::to_value).collect()
+    }
+}
+
+/// Implements `IntoArgs` for tuples whose elements all implement `Variant`.
+macro_rules! impl_into_args_tuple {
+    ($($name:ident),+) => {
+        impl<$($name: Variant),+> IntoArgs for ($($name,)+) {
+            fn into_args(self) -> Vec<Value> {
+                let ($($name,)+) = self;
+                vec![$($name.to_value()),+]
+            }
+        }
+    };
+}
+
+impl_into_args_tuple!(A);
+impl_into_args_tuple!(A, B);
+impl_into_args_tuple!(A, B, C);
+impl_into_args_tuple!(A, B, C, D);
+impl_into_args_tuple!(A, B, C, D, E);
+impl_into_args_tuple!(A, B, C, D, E, F);
+impl_into_args_tuple!(A, B, C, D, E, F, G);
+impl_into_args_tuple!(A, B, C, D, E, F, G, H);
+impl_into_args_tuple!(A, B, C, D, E, F, G, H, I);
+impl_into_args_tuple!(A, B, C, D, E, F, G, H, I, J);
+impl_into_args_tuple!(A, B, C, D, E, F, G, H, I, J, K);
+impl_into_args_tuple!(A, B, C, D, E, F, G, H, I, J, K, L);
+
+/// Builds an argument list of any length from values implementing `Variant`.
+///
+/// The values may have different types and are only borrowed.
+///
+/// # Examples
+///
+/// ```ignore
+/// use rustclr::{args, Invocation};
+///
+/// // static string Format(string format, object arg0, object arg1)
+/// let text = string.invoke("Format", None, args!["{0} {1}", "answer", 42], Invocation::Static)?;
+/// ```
+#[macro_export]
+macro_rules! args {
+    ($($arg:expr),* $(,)?) => {
+        ::std::vec![$($crate::Variant::to_value(&$arg)),*] as ::std::vec::Vec<$crate::Value>
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_tuples_in_order() {
+        assert_eq!(().into_args(), []);
+        assert_eq!(("text",).into_args(), [Value::String("text".to_string())]);
+        assert_eq!(
+            ("text".to_string(), 5, true).into_args(),
+            [Value::String("text".to_string()), Value::I32(5), Value::Bool(true)]
+        );
+
+        let twelve = (1, 2, 3, 4, 5, 6, 7, 8, 9, 10, "eleven", false).into_args();
+        assert_eq!(twelve.len(), 12);
+        assert_eq!(twelve[10], Value::String("eleven".to_string()));
+    }
+
+    #[test]
+    fn converts_lists() {
+        let boxed: Vec<Box<dyn Variant>> = vec![Box::new(1), Box::new("two"), Box::new(Value::Empty)];
+        assert_eq!(boxed.into_args(), [Value::I32(1), Value::String("two".to_string()), Value::Empty]);
+        assert_eq!(vec![true, false].into_args(), [Value::Bool(true), Value::Bool(false)]);
+
+        let name = String::from("name");
+        assert_eq!(args![name, 7, true,], [Value::String(name.clone()), Value::I32(7), Value::Bool(true)]);
+        assert!(args![].into_args().is_empty());
+    }
+
+    #[test]
+    fn converts_primitives() {
+        let guid = Uuid::from_u128(0x9c5923e9_de52_33ea_88de_7ebc8633b9cc);
+        assert_eq!(
+            (-1i8, 2u16, 3i64, u64::MAX, 1.5f32, 2.5f64, 'x', guid).into_args(),
+            [
+                Value::I8(-1), Value::U16(2), Value::I64(3), Value::U64(u64::MAX),
+                Value::F32(1.5), Value::F64(2.5), Value::Char('x'), Value::Guid(guid),
+            ]
+        );
+
+        assert_eq!((None::<i32>, Some(4u32)).into_args(), [Value::Null, Value::U32(4)]);
+        assert_eq!(Option::<i32>::var_type(), vt::VARIANT);
+        assert_eq!(SystemTime::var_type(), vt::DATE);
+    }
+
+    #[test]
+    fn converts_arrays() {
+        assert_eq!(vec![1u8, 2].to_value(), Value::Bytes(vec![1, 2]));
+        assert_eq!(Vec::<u8>::var_type(), vt::ARRAY | vt::UI1);
+
+        let strings = ["a", "b"];
+        assert_eq!(
+            strings.as_slice().to_value(),
+            Value::Array(vt::BSTR, vec![Value::String("a".to_string()), Value::String("b".to_string())])
+        );
+
+        assert_eq!(Vec::<Vec<i32>>::var_type(), vt::ARRAY | vt::VARIANT);
+        assert_eq!(
+            vec![vec![1], vec![]].to_value(),
+            Value::Array(vt::VARIANT, vec![Value::Array(vt::I4, vec![Value::I32(1)]), Value::Array(vt::I4, vec![])])
+        );
+
+        assert_eq!(
+            vec![Some(true), None].to_value(),
+            Value::Array(vt::VARIANT, vec![Value::Bool(true), Value::Null])
+        );
+    }
+
+    #[test]
+    fn parses_decimals() {
+        let decimal: Decimal = "-123.450".parse().unwrap();
+        assert_eq!((decimal.mantissa(), decimal.scale()), (-123450, 3));
+        assert_eq!(decimal.to_string(), "-123.450");
+
+        assert_eq!("+.5".parse::<Decimal>().unwrap().to_string(), "0.5");
+        assert_eq!("7".parse::<Decimal>().unwrap(), Decimal::new(7, 0).unwrap());
+        assert_eq!(Decimal::new(-5, 3).unwrap().to_string(), "-0.005");
+        assert_eq!("79228162514264337593543950335".parse::<Decimal>().unwrap().mantissa(), Decimal::MAX_MANTISSA);
+
+        for invalid in ["", ".", "-", "1.2.3", "1e5", "79228162514264337593543950336", "0.00000000000000000000000000001"] {
+            assert!(invalid.parse::<Decimal>().is_err(), "{invalid}");
+        }
+    }
+}
@@ -0,0 +1,419 @@
+use core::{cmp::Ordering, fmt, hash::{Hash, Hasher}, str::FromStr};
+use std::sync::Mutex;
+use crate::error::ClrError;
+
+/// Version strings parsed at runtime, allocated once for the lifetime of the process.
+static INTERNED_VERSIONS: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+/// Represents the .NET runtime versions supported by RustClr.
+///
+/// Versions are ordered by their numeric components, so hosts can require a minimum
+/// version with `version >= RuntimeVersion::V4`. `RuntimeVersion::Auto` sorts after
+/// every concrete version.
+///
+/// # Examples
+///
+/// ```ignore
+/// use rustclr::RuntimeVersion;
+///
+/// let version = "4.0".parse::<RuntimeVersion>()?;
+/// assert_eq!(version, RuntimeVersion::V4);
+///
+/// let version = "v1.1.4322".parse::<RuntimeVersion>()?;
+/// assert_eq!(version, RuntimeVersion::Custom("v1.1.4322"));
+/// assert!(version < RuntimeVersion::V2);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub enum RuntimeVersion {
+    /// .NET Framework 2.0, identified by version `v2.0.50727`.
+    V2,
+
+    /// .NET Framework 3.0, identified by version `v3.0`.
+    V3,
+
+    /// .NET Framework 4.0, identified by version `v4.0.30319`.
+    V4,
+
+    /// Any other runtime, identified by the version string passed to `ICLRMetaHost::GetRuntime`.
+    ///
+    /// Versions parsed at runtime are interned, so each distinct string is allocated once.
+    Custom(&'static str),
+
+    /// Picks the best installed runtime from the assembly metadata (see `select_runtime`).
+    Auto,
+
+    /// Represents an unknown or unsupported .NET runtime version.
+    #[deprecated(note = "use `RuntimeVersion::Auto`, or `RuntimeVersion::Custom` for other versions")]
+    UNKNOWN,
+}
+
+impl RuntimeVersion {
+    /// Returns the version string passed to `ICLRMetaHost::GetRuntime`.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(&str)` - The version string, e.g. `v4.0.30319`.
+    /// * `None` - For `RuntimeVersion::Auto`, which is resolved against the installed runtimes.
+    #[allow(deprecated)]
+    pub fn as_str(&self) -> Option<&'static str> {
+        match self {
+            RuntimeVersion::V2 => Some("v2.0.50727"),
+            RuntimeVersion::V3 => Some("v3.0"),
+            RuntimeVersion::V4 => Some("v4.0.30319"),
+            RuntimeVersion::Custom(version) => Some(version),
+            RuntimeVersion::Auto => None,
+            RuntimeVersion::UNKNOWN => Some("UNKNOWN"),
+        }
+    }
+
+    /// Creates a `RuntimeVersion::Custom` from a version string known only at runtime, e.g.
+    /// one listed by `ICLRMetaHost::runtimes`.
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - The version string, e.g. `v4.0.30319`.
+    ///
+    /// # Returns
+    ///
+    /// * The `RuntimeVersion::Custom` holding the interned string.
+    pub fn custom(version: &str) -> Self {
+        let mut interned = INTERNED_VERSIONS.lock().unwrap_or_else(|e| e.into_inner());
+        match interned.iter().find(|interned| **interned == version) {
+            Some(interned) => RuntimeVersion::Custom(interned),
+            None => {
+                let version: &'static str = Box::leak(version.into());
+                interned.push(version);
+                RuntimeVersion::Custom(version)
+            }
+        }
+    }
+
+    /// Converts the `RuntimeVersion` to a wide string representation as a `Vec<u16>`.
+    ///
+    /// # Returns
+    ///
+    /// A `Vec<u16>` containing the .NET runtime version as a null-terminated wide string.
+    /// `RuntimeVersion::Auto` falls back to `v4.0.30319`.
+    #[cfg(windows)]
+    pub(crate) fn to_vec(self) -> Vec<u16> {
+        let runtime_version = self.as_str().unwrap_or("v4.0.30319");
+        runtime_version.encode_utf16().chain(Some(0)).collect::<Vec<u16>>()
+    }
+
+    /// Returns the key used to compare versions.
+    fn key(&self) -> (bool, Vec<u32>, &str) {
+        match self.as_str() {
+            Some(version) => match parse_version(version) {
+                Some(parts) => (false, parts, ""),
+                None => (false, Vec::new(), version),
+            },
+            None => (true, Vec::new(), ""),
+        }
+    }
+}
+
+impl FromStr for RuntimeVersion {
+    type Err = ClrError;
+
+    /// Parses and normalizes a runtime version.
+    ///
+    /// `v2`, `2.0` and `v2.0.50727` (and likewise for `v3` and `v4`) map to the known
+    /// versions, as does `3.5`, which runs on the v2 CLR; `auto` maps to `RuntimeVersion::Auto`;
+    /// any other dotted version becomes `RuntimeVersion::Custom` with a leading `v`,
+    /// e.g. `1.1.4322` → `v1.1.4322`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().eq_ignore_ascii_case("auto") {
+            return Ok(RuntimeVersion::Auto);
+        }
+
+        let parts = parse_version(s).ok_or_else(|| ClrError::InvalidRuntimeVersion(s.to_string()))?;
+        if parts.len() >= 2 && parts[..2] == [3, 5] && parts[2..].iter().all(|&part| part == 0) {
+            return Ok(RuntimeVersion::V2);
+        }
+
+        for known in [RuntimeVersion::V2, RuntimeVersion::V3, RuntimeVersion::V4] {
+            let canonical = known.key().1;
+            let shorthand = parts.len() <= 2 && parts.iter().skip(1).all(|&part| part == 0);
+            if parts == canonical || (shorthand && parts[0] == canonical[0]) {
+                return Ok(known);
+            }
+        }
+
+        let parts = parts.iter().map(u32::to_string).collect::<Vec<String>>();
+        Ok(RuntimeVersion::custom(&format!("v{}", parts.join("."))))
+    }
+}
+
+impl fmt::Display for RuntimeVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str().unwrap_or("auto"))
+    }
+}
+
+impl PartialEq for RuntimeVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for RuntimeVersion {}
+
+impl PartialOrd for RuntimeVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for RuntimeVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl Hash for RuntimeVersion {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+/// The rule `select_runtime` used to pick a runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectionRule {
+    /// The metadata version string is installed as-is.
+    Exact,
+
+    /// The CLR implied by the `TargetFrameworkAttribute` is installed.
+    TargetFramework,
+
+    /// The newest installed runtime with the same major version as the metadata.
+    SameMajor,
+
+    /// No compatible runtime is installed, so the newest later one is used (e.g. v2 assemblies on v4).
+    RollForward,
+
+    /// Nothing was requested, so the newest installed runtime is used.
+    Latest,
+}
+
+impl fmt::Display for SelectionRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SelectionRule::Exact => "metadata version is installed",
+            SelectionRule::TargetFramework => "matches the target framework",
+            SelectionRule::SameMajor => "newest runtime with the same major version",
+            SelectionRule::RollForward => "rolled forward to a newer runtime",
+            SelectionRule::Latest => "newest installed runtime",
+        })
+    }
+}
+
+/// The runtime picked by `select_runtime`, along with the reason.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeSelection {
+    /// The installed version string to pass to `ICLRMetaHost::GetRuntime`.
+    pub version: String,
+
+    /// The rule that chose it.
+    pub rule: SelectionRule,
+}
+
+impl fmt::Display for RuntimeSelection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.version, self.rule)
+    }
+}
+
+/// Picks the installed runtime best suited to an assembly.
+///
+/// The rules are tried in order: an exact match of the metadata version string, the CLR
+/// implied by the `TargetFrameworkAttribute` (`v2.0`-`v3.5` run on CLR 2, `v4.x` on CLR 4),
+/// the newest runtime sharing the metadata's major version, and finally the newest runtime
+/// that is later than it. Without any requested version the newest runtime is picked.
+///
+/// # Arguments
+///
+/// * `metadata_version` - Version string of the metadata root, e.g. `v2.0.50727`.
+/// * `target_framework` - Value of the `TargetFrameworkAttribute`, e.g. `.NETFramework,Version=v4.7.2`.
+/// * `installed` - Version strings of the installed runtimes, as listed by `ICLRMetaHost::runtimes`.
+///
+/// # Returns
+///
+/// * `Some(RuntimeSelection)` - The chosen runtime and the rule that chose it.
+/// * `None` - If no installed runtime can run the assembly.
+///
+/// # Examples
+///
+/// ```ignore
+/// use rustclr::{select_runtime, SelectionRule};
+///
+/// let installed = ["v2.0.50727", "v4.0.30319"];
+/// let selection = select_runtime(Some("v2.0.50727"), None, &installed).unwrap();
+/// assert_eq!(selection.version, "v2.0.50727");
+/// assert_eq!(selection.rule, SelectionRule::Exact);
+/// ```
+pub fn select_runtime<S: AsRef<str>>(
+    metadata_version: Option<&str>,
+    target_framework: Option<&str>,
+    installed: &[S],
+) -> Option<RuntimeSelection> {
+    let mut runtimes = installed
+        .iter()
+        .filter_map(|version| Some((parse_version(version.as_ref())?, version.as_ref())))
+        .collect::<Vec<_>>();
+
+    // Newest first, so every rule below picks the newest candidate.
+    runtimes.sort_by(|a, b| b.0.cmp(&a.0));
+    let pick = |rule: SelectionRule, matches: &dyn Fn(&[u32]) -> bool| {
+        runtimes
+            .iter()
+            .find(|(version, _)| matches(version))
+            .map(|(_, name)| RuntimeSelection { version: name.to_string(), rule })
+    };
+
+    let requested = metadata_version.and_then(parse_version);
+    if let Some(metadata_version) = metadata_version {
+        let exact = runtimes.iter().find(|(_, name)| name.eq_ignore_ascii_case(metadata_version.trim()));
+        if let Some((_, name)) = exact {
+            return Some(RuntimeSelection { version: name.to_string(), rule: SelectionRule::Exact });
+        }
+    }
+
+    if let Some(major) = target_framework.and_then(framework_clr_major) {
+        if let Some(selection) = pick(SelectionRule::TargetFramework, &|version| version[0] == major) {
+            return Some(selection);
+        }
+    }
+
+    match requested {
+        Some(requested) => pick(SelectionRule::SameMajor, &|version| version[0] == requested[0])
+            .or_else(|| pick(SelectionRule::RollForward, &|version| version[0] > requested[0])),
+        None => pick(SelectionRule::Latest, &|_| true),
+    }
+}
+
+/// Parses the numeric components of a version string such as `v4.0.30319` or `4.0`.
+pub(crate) fn parse_version(version: &str) -> Option<Vec<u32>> {
+    let version = version.trim();
+    let version = version.strip_prefix(['v', 'V']).unwrap_or(version);
+    version
+        .split('.')
+        .map(|part| part.parse::<u32>().ok())
+        .collect::<Option<Vec<u32>>>()
+        .filter(|parts| !parts.is_empty())
+}
+
+/// Maps a `.NETFramework` moniker to the major version of the CLR that runs it.
+fn framework_clr_major(moniker: &str) -> Option<u32> {
+    let mut parts = moniker.split(',').map(str::trim);
+    if !parts.next()?.eq_ignore_ascii_case(".NETFramework") {
+        return None;
+    }
+
+    let version = parts.find_map(|part| part.strip_prefix("Version="))?;
+    match parse_version(version)?[0] {
+        major if major >= 4 => Some(4),
+        _ => Some(2),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INSTALLED: [&str; 3] = ["v2.0.50727", "v4.0.30319", "v1.1.4322"];
+
+    #[test]
+    fn parses_versions() {
+        assert_eq!(parse_version("v4.0.30319"), Some(vec![4, 0, 30319]));
+        assert_eq!(parse_version("2.0"), Some(vec![2, 0]));
+        assert_eq!(parse_version("v4"), Some(vec![4]));
+        assert_eq!(parse_version("v4.x"), None);
+        assert_eq!(parse_version(""), None);
+    }
+
+    #[test]
+    fn parses_runtime_versions() {
+        for input in ["v4", "4", "4.0", "v4.0", "v4.0.30319", " V4.0.30319 "] {
+            assert_eq!(input.parse::<RuntimeVersion>().unwrap(), RuntimeVersion::V4, "{input}");
+        }
+
+        assert_eq!("v2.0.50727".parse::<RuntimeVersion>().unwrap(), RuntimeVersion::V2);
+        assert_eq!("3.0".parse::<RuntimeVersion>().unwrap(), RuntimeVersion::V3);
+        assert_eq!("v3.5".parse::<RuntimeVersion>().unwrap(), RuntimeVersion::V2);
+        assert_eq!("3.5.0".parse::<RuntimeVersion>().unwrap(), RuntimeVersion::V2);
+        assert_eq!("Auto".parse::<RuntimeVersion>().unwrap(), RuntimeVersion::Auto);
+
+        let custom = "1.1.4322".parse::<RuntimeVersion>().unwrap();
+        assert_eq!(custom, RuntimeVersion::Custom("v1.1.4322"));
+        assert!(matches!(("v1.1.4322".parse().unwrap(), custom), (RuntimeVersion::Custom(a), RuntimeVersion::Custom(b)) if a.as_ptr() == b.as_ptr()));
+        assert_eq!(custom.to_string(), "v1.1.4322");
+        assert_eq!("v4.5".parse::<RuntimeVersion>().unwrap().as_str(), Some("v4.5"));
+
+        for input in ["", "v", "four", "v4.x", "4..0", "UNKNOWN"] {
+            assert!(matches!(input.parse::<RuntimeVersion>(), Err(ClrError::InvalidRuntimeVersion(_))), "{input}");
+        }
+    }
+
+    #[test]
+    fn orders_runtime_versions() {
+        let custom = RuntimeVersion::Custom;
+        assert!(RuntimeVersion::V2 < RuntimeVersion::V3);
+        assert!(RuntimeVersion::V3 < RuntimeVersion::V4);
+        assert!(custom("v1.1.4322") < RuntimeVersion::V2);
+        assert!(custom("v4.0.30319") >= RuntimeVersion::V4);
+        assert_eq!(custom("v4.0.30319"), RuntimeVersion::V4);
+        assert!(RuntimeVersion::Auto > custom("v9.0"));
+
+        let mut versions = vec![RuntimeVersion::V4, custom("v1.0.3705"), RuntimeVersion::V2];
+        versions.sort();
+        assert_eq!(versions, [custom("v1.0.3705"), RuntimeVersion::V2, RuntimeVersion::V4]);
+    }
+
+    #[test]
+    fn selects_exact_match() {
+        let selection = select_runtime(Some("v2.0.50727"), None, &INSTALLED).unwrap();
+        assert_eq!(selection.version, "v2.0.50727");
+        assert_eq!(selection.rule, SelectionRule::Exact);
+
+        let selection = select_runtime(Some("v4.0.30319"), Some(".NETFramework,Version=v4.8"), &INSTALLED).unwrap();
+        assert_eq!(selection.rule, SelectionRule::Exact);
+    }
+
+    #[test]
+    fn selects_by_target_framework() {
+        // A v3.5 assembly carrying a non-standard metadata version still needs CLR 2.
+        let selection = select_runtime(Some("v2.0.50727-custom"), Some(".NETFramework,Version=v3.5"), &INSTALLED).unwrap();
+        assert_eq!(selection.version, "v2.0.50727");
+        assert_eq!(selection.rule, SelectionRule::TargetFramework);
+
+        let selection = select_runtime(None, Some(".NETFramework,Version=v4.7.2"), &INSTALLED).unwrap();
+        assert_eq!(selection.version, "v4.0.30319");
+        assert_eq!(selection.rule, SelectionRule::TargetFramework);
+
+        // Monikers for other frameworks are ignored.
+        let selection = select_runtime(None, Some(".NETCoreApp,Version=v8.0"), &INSTALLED).unwrap();
+        assert_eq!(selection.rule, SelectionRule::Latest);
+    }
+
+    #[test]
+    fn selects_same_major_then_rolls_forward() {
+        let selection = select_runtime(Some("v4.0"), None, &["v4.0.30128", "v4.0.30319"]).unwrap();
+        assert_eq!(selection.version, "v4.0.30319");
+        assert_eq!(selection.rule, SelectionRule::SameMajor);
+
+        let selection = select_runtime(Some("v2.0.50727"), None, &["v4.0.30319"]).unwrap();
+        assert_eq!(selection.version, "v4.0.30319");
+        assert_eq!(selection.rule, SelectionRule::RollForward);
+
+        assert_eq!(select_runtime(Some("v4.0.30319"), None, &["v2.0.50727"]), None);
+        assert_eq!(select_runtime::<&str>(Some("v4.0.30319"), None, &[]), None);
+    }
+
+    #[test]
+    fn selects_latest_without_request() {
+        let selection = select_runtime(None, None, &INSTALLED).unwrap();
+        assert_eq!(selection.version, "v4.0.30319");
+        assert_eq!(selection.rule, SelectionRule::Latest);
+        assert_eq!(selection.to_string(), "v4.0.30319 (newest installed runtime)");
+    }
+}
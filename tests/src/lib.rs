@@ -19,7 +19,7 @@ fn test_create_domain() -> Result<(), Box<dyn std::error::Error>> {
 fn test_with_args() -> Result<(), Box<dyn std::error::Error>> {
     let buffer = std::fs::read("file").expect("Error reading file");
     let output = RustClr::new(&buffer)?
-        .with_args(vec!["test"])
+        .with_args(vec!["test".to_string()])
         .with_output_redirection(true)
         .run()?;
 